@@ -27,7 +27,13 @@ use crate::{
     DxfError,
     DxfResult,
     ExtensionGroup,
+    HatchBoundaryPath,
+    HatchGradientColor,
+    HatchPatternDefinitionLine,
     LwPolylineVertex,
+    MLeaderArrowhead,
+    MLeaderBlockAttribute,
+    MLeaderContextData,
     Point,
     Vector,
     XData,
@@ -227,6 +233,13 @@ fn generate_entity_types(fun: &mut String, element: &Element) {
         }
     }
 
+    fun.push_str(
+        "    /// An entity type not otherwise supported; its code pairs are preserved verbatim.\n",
+    );
+    fun.push_str("    Unknown {\n");
+    fun.push_str("        type_string: String,\n");
+    fun.push_str("        pairs: Vec<CodePair>,\n");
+    fun.push_str("    },\n");
     fun.push_str("}\n");
     fun.push_str("\n");
 
@@ -424,6 +437,7 @@ fn generate_is_supported_on_version(fun: &mut String, element: &Element) {
             ));
         }
     }
+    fun.push_str("            &EntityType::Unknown { .. } => { true },\n");
     fun.push_str("        }\n");
     fun.push_str("    }\n");
 }
@@ -463,6 +477,7 @@ fn generate_type_string(fun: &mut String, element: &Element) {
             ));
         }
     }
+    fun.push_str("            &EntityType::Unknown { ref type_string, .. } => { type_string },\n");
     fun.push_str("        }\n");
     fun.push_str("    }\n");
 }
@@ -546,6 +561,7 @@ fn generate_try_apply_code_pair(fun: &mut String, element: &Element) {
         }
     }
 
+    fun.push_str("            &mut EntityType::Unknown { ref mut pairs, .. } => { pairs.push(pair.clone()); },\n");
     fun.push_str("        }\n");
     fun.push_str("        Ok(true)\n");
     fun.push_str("    }\n");
@@ -579,6 +595,11 @@ fn generate_write(fun: &mut String, element: &Element) {
             }
         }
     }
+    fun.push_str("            &EntityType::Unknown { ref pairs, .. } => {\n");
+    fun.push_str("                for pair in pairs {\n");
+    fun.push_str("                    writer.write_code_pair(pair)?;\n");
+    fun.push_str("                }\n");
+    fun.push_str("            },\n");
     fun.push_str("        }\n");
     fun.push_str("\n");
     fun.push_str("        Ok(())\n");
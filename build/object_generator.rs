@@ -233,6 +233,13 @@ fn generate_object_types(fun: &mut String, element: &Element) {
         }
     }
 
+    fun.push_str(
+        "    /// An object type not otherwise supported; its code pairs are preserved verbatim.\n",
+    );
+    fun.push_str("    Unknown {\n");
+    fun.push_str("        type_string: String,\n");
+    fun.push_str("        pairs: Vec<CodePair>,\n");
+    fun.push_str("    },\n");
     fun.push_str("}\n");
     fun.push_str("\n");
 
@@ -409,6 +416,7 @@ fn generate_is_supported_on_version(fun: &mut String, element: &Element) {
             ));
         }
     }
+    fun.push_str("            ObjectType::Unknown { .. } => { true },\n");
     fun.push_str("        }\n");
     fun.push_str("    }\n");
 }
@@ -444,6 +452,7 @@ fn generate_type_string(fun: &mut String, element: &Element) {
             ));
         }
     }
+    fun.push_str("            ObjectType::Unknown { ref type_string, .. } => { type_string },\n");
     fun.push_str("        }\n");
     fun.push_str("    }\n");
 }
@@ -539,6 +548,9 @@ fn generate_try_apply_code_pair(fun: &mut String, element: &Element) {
         }
     }
 
+    fun.push_str(
+        "            ObjectType::Unknown { ref mut pairs, .. } => { pairs.push(pair.clone()); },\n",
+    );
     fun.push_str("        }\n");
     fun.push_str("        Ok(true)\n");
     fun.push_str("    }\n");
@@ -584,6 +596,11 @@ fn generate_write(fun: &mut String, element: &Element) {
             }
         }
     }
+    fun.push_str("            ObjectType::Unknown { ref pairs, .. } => {\n");
+    fun.push_str("                for pair in pairs {\n");
+    fun.push_str("                    writer.write_code_pair(pair)?;\n");
+    fun.push_str("                }\n");
+    fun.push_str("            },\n");
     fun.push_str("        }\n");
     fun.push_str("\n");
     fun.push_str("        Ok(())\n");
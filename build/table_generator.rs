@@ -57,7 +57,7 @@ fn generate_table_items(fun: &mut String, element: &Element) {
     for table in &element.children {
         let mut seen_fields = HashSet::new();
         let table_item = &table.children[0];
-        fun.push_str("#[derive(Debug)]\n");
+        fun.push_str("#[derive(Clone, Debug)]\n");
         fun.push_str("#[cfg_attr(feature = \"serialize\", derive(Serialize, Deserialize))]\n");
         fun.push_str(&format!("pub struct {name} {{\n", name = name(&table_item)));
         fun.push_str("    pub name: String,\n");
@@ -139,6 +139,47 @@ fn generate_table_items(fun: &mut String, element: &Element) {
         fun.push_str("    pub fn set_owner<'a>(&mut self, item: &'a mut DrawingItemMut, drawing: &'a mut Drawing) {\n");
         fun.push_str("        self.__owner_handle = drawing.assign_and_get_handle(item);\n");
         fun.push_str("    }\n");
+
+        // generate flags methods
+        for field in &table_item.children {
+            if field.name == "Field" {
+                for flag in &field.children {
+                    if flag.name == "Flag" {
+                        let flag_name = name(flag);
+                        let mask = attr(flag, "Mask");
+                        fun.push_str(&format!(
+                            "    pub fn get_{name}(&self) -> bool {{\n",
+                            name = flag_name
+                        ));
+                        fun.push_str(&format!(
+                            "        self.{name} & {mask} != 0\n",
+                            name = name(field),
+                            mask = mask
+                        ));
+                        fun.push_str("    }\n");
+                        fun.push_str(&format!(
+                            "    pub fn set_{name}(&mut self, val: bool) {{\n",
+                            name = flag_name
+                        ));
+                        fun.push_str("        if val {\n");
+                        fun.push_str(&format!(
+                            "            self.{name} |= {mask};\n",
+                            name = name(field),
+                            mask = mask
+                        ));
+                        fun.push_str("        }\n");
+                        fun.push_str("        else {\n");
+                        fun.push_str(&format!(
+                            "            self.{name} &= !{mask};\n",
+                            name = name(field),
+                            mask = mask
+                        ));
+                        fun.push_str("        }\n");
+                        fun.push_str("    }\n");
+                    }
+                }
+            }
+        }
         fun.push_str("}\n");
         fun.push_str("\n");
     }
@@ -203,38 +244,76 @@ fn generate_table_reader(fun: &mut String, element: &Element) {
         fun.push_str("                    }\n");
         fun.push_str("\n");
         fun.push_str(&format!(
-            "                    let mut item = {typ}::default();\n",
-            typ = attr(&table_item, "Name")
+            "                    match read_{item_type}(iter) {{\n",
+            item_type = item_type
         ));
-        fun.push_str("                    loop {\n");
-        fun.push_str("                        match iter.next() {\n");
-        fun.push_str(
-            "                            Some(Ok(pair @ CodePair { code: 0, .. })) => {\n",
-        );
-        fun.push_str("                                iter.put_back(Ok(pair));\n");
-        fun.push_str("                                break;\n");
-        fun.push_str("                            },\n");
-        fun.push_str("                            Some(Ok(pair)) => {\n");
-        fun.push_str("                                match pair.code {\n");
-        fun.push_str(
-            "                                    2 => item.name = pair.assert_string()?,\n",
-        );
-        fun.push_str("                                    5 => item.handle = pair.as_handle()?,\n");
-        fun.push_str(
-            "                                    extension_data::EXTENSION_DATA_GROUP => {\n",
-        );
-        fun.push_str("                                        let group = ExtensionGroup::read_group(pair.assert_string()?, iter, pair.offset)?;\n");
+        fun.push_str("                        Ok(item) => {\n");
         fun.push_str(
-            "                                        item.extension_data_groups.push(group);\n",
+            "                            if item.handle == 0 && iter.options().assign_handles {\n",
         );
-        fun.push_str("                                    },\n");
-        fun.push_str("                                    x_data::XDATA_APPLICATIONNAME => {\n");
-        fun.push_str("                                        let x = XData::read_item(pair.assert_string()?, iter)?;\n");
-        fun.push_str("                                        item.x_data.push(x);\n");
-        fun.push_str("                                    },\n");
+        fun.push_str(&format!(
+            "                                drawing.add_{item_type}(item);\n",
+            item_type = item_type
+        ));
+        fun.push_str("                            }\n");
+        fun.push_str("                            else {\n");
+        fun.push_str(&format!(
+            "                                drawing.add_{item_type}_no_handle_set(item);\n",
+            item_type = item_type
+        ));
+        fun.push_str("                            }\n");
+        fun.push_str("                        },\n");
+        fun.push_str(&format!(
+            "                        Err(e) => iter.recover(e, \"{table_type} table entry\", None)?,\n",
+            table_type = attr(table, "TypeString")
+        ));
+        fun.push_str("                    }\n");
+        fun.push_str("                }\n");
+        fun.push_str("                else {\n");
+        fun.push_str("                    // do nothing, probably the table's handle or flags\n");
+        fun.push_str("                }\n");
+        fun.push_str("            },\n");
+        fun.push_str("            Some(Err(e)) => return Err(e),\n");
+        fun.push_str("            None => return Err(DxfError::UnexpectedEndOfInput),\n");
+        fun.push_str("        }\n");
+        fun.push_str("    }\n");
+        fun.push_str("\n");
+        fun.push_str("    Ok(())\n");
+        fun.push_str("}\n");
+        fun.push_str("\n");
+
+        fun.push_str(&format!(
+            "fn read_{item_type}<I>(iter: &mut CodePairPutBack<I>) -> DxfResult<{typ}>\n",
+            item_type = item_type,
+            typ = attr(table_item, "Name")
+        ));
+        fun.push_str("    where I: Read {\n");
+        fun.push_str("\n");
+        fun.push_str(&format!(
+            "    let mut item = {typ}::default();\n",
+            typ = attr(&table_item, "Name")
+        ));
+        fun.push_str("    loop {\n");
+        fun.push_str("        match iter.next() {\n");
+        fun.push_str("            Some(Ok(pair @ CodePair { code: 0, .. })) => {\n");
+        fun.push_str("                iter.put_back(Ok(pair));\n");
+        fun.push_str("                break;\n");
+        fun.push_str("            },\n");
+        fun.push_str("            Some(Ok(pair)) => {\n");
+        fun.push_str("                match pair.code {\n");
+        fun.push_str("                    2 => item.name = pair.assert_string()?,\n");
+        fun.push_str("                    5 => item.handle = pair.as_handle()?,\n");
+        fun.push_str("                    extension_data::EXTENSION_DATA_GROUP => {\n");
+        fun.push_str("                        let group = ExtensionGroup::read_group(pair.assert_string()?, iter, pair.offset)?;\n");
+        fun.push_str("                        item.extension_data_groups.push(group);\n");
+        fun.push_str("                    },\n");
+        fun.push_str("                    x_data::XDATA_APPLICATIONNAME => {\n");
         fun.push_str(
-            "                                    330 => item.__owner_handle = pair.as_handle()?,\n",
+            "                        let x = XData::read_item(pair.assert_string()?, iter)?;\n",
         );
+        fun.push_str("                        item.x_data.push(x);\n");
+        fun.push_str("                    },\n");
+        fun.push_str("                    330 => item.__owner_handle = pair.as_handle()?,\n");
         for field in &table_item.children {
             if generate_reader(&field) {
                 for (i, &cd) in codes(&field).iter().enumerate() {
@@ -274,7 +353,7 @@ fn generate_table_reader(fun: &mut String, element: &Element) {
                         }
                     };
                     fun.push_str(&format!(
-                        "                                    {code} => {{ {cmd}; }},\n",
+                        "                    {code} => {{ {cmd}; }},\n",
                         code = cd,
                         cmd = write_cmd
                     ));
@@ -282,31 +361,7 @@ fn generate_table_reader(fun: &mut String, element: &Element) {
             }
         }
 
-        fun.push_str("                                    _ => (), // unsupported code\n");
-        fun.push_str("                                }\n");
-        fun.push_str("                            },\n");
-        fun.push_str("                            Some(Err(e)) => return Err(e),\n");
-        fun.push_str(
-            "                            None => return Err(DxfError::UnexpectedEndOfInput),\n",
-        );
-        fun.push_str("                        }\n");
-        fun.push_str("                    }\n");
-        fun.push_str("\n");
-        fun.push_str("                    if item.handle == 0 {\n");
-        fun.push_str(&format!(
-            "                        drawing.add_{item_type}(item);\n",
-            item_type = item_type
-        ));
-        fun.push_str("                    }\n");
-        fun.push_str("                    else {\n");
-        fun.push_str(&format!(
-            "                        drawing.add_{item_type}_no_handle_set(item);\n",
-            item_type = item_type
-        ));
-        fun.push_str("                    }\n");
-        fun.push_str("                }\n");
-        fun.push_str("                else {\n");
-        fun.push_str("                    // do nothing, probably the table's handle or flags\n");
+        fun.push_str("                    _ => (), // unsupported code\n");
         fun.push_str("                }\n");
         fun.push_str("            },\n");
         fun.push_str("            Some(Err(e)) => return Err(e),\n");
@@ -314,7 +369,7 @@ fn generate_table_reader(fun: &mut String, element: &Element) {
         fun.push_str("        }\n");
         fun.push_str("    }\n");
         fun.push_str("\n");
-        fun.push_str("    Ok(())\n");
+        fun.push_str("    Ok(item)\n");
         fun.push_str("}\n");
         fun.push_str("\n");
     }
@@ -379,7 +434,7 @@ fn generate_table_writer(fun: &mut String, element: &Element) {
             item_type=item_type));
         fun.push_str("        }\n");
         fun.push_str("\n");
-        fun.push_str("        if drawing.header.version >= AcadVersion::R14 {\n");
+        fun.push_str("        if writer.version() >= AcadVersion::R14 {\n");
         fun.push_str("            for group in &item.extension_data_groups {\n");
         fun.push_str("                group.write(writer)?;\n");
         fun.push_str("            }\n");
@@ -391,19 +446,29 @@ fn generate_table_writer(fun: &mut String, element: &Element) {
             class_name = attr(&table_item, "ClassName")
         ));
         fun.push_str("        writer.write_code_pair(&CodePair::new_string(2, &item.name))?;\n");
-        fun.push_str("        writer.write_code_pair(&CodePair::new_i16(70, 0))?;\n"); // TODO: flags
+        let has_flags = table_item
+            .children
+            .iter()
+            .any(|field| field.name == "Field" && name(field) == "flags");
+        if has_flags {
+            fun.push_str(
+                "        writer.write_code_pair(&CodePair::new_i16(70, item.flags as i16))?;\n",
+            );
+        } else {
+            fun.push_str("        writer.write_code_pair(&CodePair::new_i16(70, 0))?;\n");
+        }
         for field in &table_item.children {
             if generate_writer(&field) {
                 let mut predicates = vec![];
                 if !min_version(&field).is_empty() {
                     predicates.push(format!(
-                        "drawing.header.version >= AcadVersion::{}",
+                        "writer.version() >= AcadVersion::{}",
                         min_version(&field)
                     ));
                 }
                 if !max_version(&field).is_empty() {
                     predicates.push(format!(
-                        "drawing.header.version <= AcadVersion::{}",
+                        "writer.version() <= AcadVersion::{}",
                         max_version(&field)
                     ));
                 }
@@ -489,7 +554,7 @@ fn generate_table_writer(fun: &mut String, element: &Element) {
         }
 
         fun.push_str("        for x in &item.x_data {\n");
-        fun.push_str("            x.write(drawing.header.version, writer)?;\n");
+        fun.push_str("            x.write(writer.version(), writer)?;\n");
         fun.push_str("        }\n");
 
         fun.push_str("    }\n");
@@ -0,0 +1,703 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+use crate::entities::*;
+use crate::tables::*;
+use crate::{Block, Color, Drawing};
+
+/// The lineweights, in hundredths of a millimeter, that AutoCAD accepts.
+const VALID_LINE_WEIGHTS: [i16; 24] = [
+    0, 5, 9, 13, 15, 18, 20, 25, 30, 35, 40, 50, 53, 60, 70, 80, 90, 100, 106, 120, 140, 158, 200,
+    211,
+];
+
+/// How serious an `AuditIssue` is.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+pub enum AuditSeverity {
+    /// The drawing can be used, but other applications may display it differently.
+    Warning,
+    /// The drawing is corrupt and may be rejected by other applications.
+    Error,
+}
+
+/// A problem found by `Drawing::audit()`.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+pub struct AuditIssue {
+    /// How serious the problem is.
+    pub severity: AuditSeverity,
+    /// The handle of the item with the problem, or `0` if the item doesn't have a handle.
+    pub handle: u32,
+    /// A description of the problem.
+    pub description: String,
+    /// `true` if the problem was fixed by `Drawing::audit_and_fix()`.
+    pub is_fixed: bool,
+}
+
+impl fmt::Display for AuditIssue {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        let severity = match self.severity {
+            AuditSeverity::Warning => "warning",
+            AuditSeverity::Error => "error",
+        };
+        write!(formatter, "{}", severity)?;
+        if self.handle != 0 {
+            write!(formatter, " [{:X}]", self.handle)?;
+        }
+        write!(formatter, ": {}", self.description)?;
+        if self.is_fixed {
+            write!(formatter, " (fixed)")?;
+        }
+        Ok(())
+    }
+}
+
+/// The change that fixes an issue.  Each fix is safe to apply more than once.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+enum AuditFix {
+    ReassignDuplicateHandles,
+    RaiseHandleSeed,
+    ClearDanglingPointers,
+    ResetInvalidValues,
+    AddLayer(String),
+    AddLineType(String),
+    AddStyle(String),
+    AddDimStyle(String),
+    AddBlock(String),
+}
+
+struct Auditor<'a> {
+    drawing: &'a Drawing,
+    handles: HashSet<u32>,
+    issues: Vec<(AuditIssue, Option<AuditFix>)>,
+}
+
+impl<'a> Auditor<'a> {
+    fn report(
+        &mut self,
+        severity: AuditSeverity,
+        handle: u32,
+        description: String,
+        fix: Option<AuditFix>,
+    ) {
+        self.issues.push((
+            AuditIssue {
+                severity,
+                handle,
+                description,
+                is_fixed: false,
+            },
+            fix,
+        ));
+    }
+    fn check_handles(&mut self) {
+        let drawing = self.drawing;
+        let mut counts = HashMap::new();
+        let mut order = vec![];
+        let mut add = |handle: u32| {
+            if handle != 0 {
+                let count = counts.entry(handle).or_insert(0);
+                if *count == 0 {
+                    order.push(handle);
+                }
+                *count += 1;
+            }
+        };
+        for handle in table_item_handles(drawing) {
+            add(handle);
+        }
+        for block in drawing.blocks() {
+            add(block.handle);
+            for entity in &block.entities {
+                add(entity.common.handle);
+            }
+        }
+        for entity in drawing.entities() {
+            add(entity.common.handle);
+        }
+        for object in drawing.objects() {
+            add(object.common.handle);
+        }
+
+        for handle in order {
+            if counts[&handle] > 1 {
+                self.report(
+                    AuditSeverity::Error,
+                    handle,
+                    format!("handle {:X} is used by {} items", handle, counts[&handle]),
+                    Some(AuditFix::ReassignDuplicateHandles),
+                );
+            }
+            if handle >= drawing.header.next_available_handle {
+                self.report(
+                    AuditSeverity::Error,
+                    handle,
+                    format!(
+                        "handle {:X} is not below the handle seed {:X}",
+                        handle, drawing.header.next_available_handle
+                    ),
+                    Some(AuditFix::RaiseHandleSeed),
+                );
+            }
+            self.handles.insert(handle);
+        }
+    }
+    fn check_pointer(&mut self, handle: u32, pointer_name: &str, pointer: u32) {
+        if pointer != 0 && !self.handles.contains(&pointer) {
+            self.report(
+                AuditSeverity::Warning,
+                handle,
+                format!(
+                    "{} pointer {:X} doesn't refer to an item in the drawing",
+                    pointer_name, pointer
+                ),
+                Some(AuditFix::ClearDanglingPointers),
+            );
+        }
+    }
+    fn check_layer(&mut self, handle: u32, layer_name: &str) {
+        if !self
+            .drawing
+            .layers()
+            .any(|l| l.name.eq_ignore_ascii_case(layer_name))
+        {
+            self.report(
+                AuditSeverity::Warning,
+                handle,
+                format!("layer `{}` doesn't exist", layer_name),
+                Some(AuditFix::AddLayer(String::from(layer_name))),
+            );
+        }
+    }
+    fn check_line_type(&mut self, handle: u32, line_type_name: &str) {
+        let is_special = line_type_name.eq_ignore_ascii_case("BYLAYER")
+            || line_type_name.eq_ignore_ascii_case("BYBLOCK");
+        if !line_type_name.is_empty()
+            && !is_special
+            && !self
+                .drawing
+                .line_types()
+                .any(|lt| lt.name.eq_ignore_ascii_case(line_type_name))
+        {
+            self.report(
+                AuditSeverity::Warning,
+                handle,
+                format!("line type `{}` doesn't exist", line_type_name),
+                Some(AuditFix::AddLineType(String::from(line_type_name))),
+            );
+        }
+    }
+    fn check_entity(&mut self, entity: &Entity) {
+        let common = &entity.common;
+        let handle = common.handle;
+        self.check_pointer(handle, "owner", common.__owner_handle);
+        self.check_pointer(handle, "material", common.__material_handle);
+        self.check_pointer(handle, "plot style", common.__plot_style_handle);
+        self.check_layer(handle, &common.layer);
+        self.check_line_type(handle, &common.line_type_name);
+
+        let color = common.color.get_raw_value();
+        if !(-255..=257).contains(&color) {
+            self.report(
+                AuditSeverity::Warning,
+                handle,
+                format!("color {} is out of range", color),
+                Some(AuditFix::ResetInvalidValues),
+            );
+        }
+        let line_weight = common.lineweight_enum_value;
+        if !is_valid_line_weight(line_weight) {
+            self.report(
+                AuditSeverity::Warning,
+                handle,
+                format!("lineweight {} is not a valid lineweight", line_weight),
+                Some(AuditFix::ResetInvalidValues),
+            );
+        }
+
+        let text_style_name = match entity.specific {
+            EntityType::ArcAlignedText(ref e) => Some(&e.text_style_name),
+            EntityType::Attribute(ref e) => Some(&e.text_style_name),
+            EntityType::AttributeDefinition(ref e) => Some(&e.text_style_name),
+            EntityType::MText(ref e) => Some(&e.text_style_name),
+            EntityType::Text(ref e) => Some(&e.text_style_name),
+            _ => None,
+        };
+        if let Some(name) = text_style_name {
+            if !self
+                .drawing
+                .styles()
+                .any(|s| s.name.eq_ignore_ascii_case(name))
+            {
+                self.report(
+                    AuditSeverity::Warning,
+                    handle,
+                    format!("text style `{}` doesn't exist", name),
+                    Some(AuditFix::AddStyle(name.clone())),
+                );
+            }
+        }
+
+        let dimension_base = match entity.specific {
+            EntityType::RotatedDimension(ref d) => Some(&d.dimension_base),
+            EntityType::RadialDimension(ref d) => Some(&d.dimension_base),
+            EntityType::DiameterDimension(ref d) => Some(&d.dimension_base),
+            EntityType::AngularThreePointDimension(ref d) => Some(&d.dimension_base),
+            EntityType::OrdinateDimension(ref d) => Some(&d.dimension_base),
+            _ => None,
+        };
+        let dimension_style_name = match entity.specific {
+            EntityType::Leader(ref l) => Some(&l.dimension_style_name),
+            EntityType::Tolerance(ref t) => Some(&t.dimension_style_name),
+            _ => dimension_base.map(|d| &d.dimension_style_name),
+        };
+        if let Some(name) = dimension_style_name {
+            if !self
+                .drawing
+                .dim_styles()
+                .any(|d| d.name.eq_ignore_ascii_case(name))
+            {
+                self.report(
+                    AuditSeverity::Warning,
+                    handle,
+                    format!("dimension style `{}` doesn't exist", name),
+                    Some(AuditFix::AddDimStyle(name.clone())),
+                );
+            }
+        }
+        if let Some(dimension_base) = dimension_base {
+            if !dimension_base.block_name.is_empty() && !self.has_block(&dimension_base.block_name)
+            {
+                // other applications regenerate the block from the dimension's values
+                self.report(
+                    AuditSeverity::Warning,
+                    handle,
+                    format!(
+                        "dimension block `{}` doesn't exist",
+                        dimension_base.block_name
+                    ),
+                    None,
+                );
+            }
+        }
+        if let EntityType::Insert(ref ins) = entity.specific {
+            if !self.has_block(&ins.name) {
+                self.report(
+                    AuditSeverity::Error,
+                    handle,
+                    format!("block `{}` doesn't exist", ins.name),
+                    Some(AuditFix::AddBlock(ins.name.clone())),
+                );
+            }
+        }
+    }
+    fn has_block(&self, name: &str) -> bool {
+        self.drawing
+            .blocks()
+            .any(|b| b.name.eq_ignore_ascii_case(name))
+    }
+}
+
+impl Drawing {
+    /// Checks the drawing for problems that other applications may reject, e.g., duplicate
+    /// handles, pointers to items that don't exist, references to missing layers, styles, or
+    /// blocks, and out of range values.  The drawing isn't modified.  See `audit_and_fix()`.
+    pub fn audit(&self) -> Vec<AuditIssue> {
+        self.audit_internal()
+            .into_iter()
+            .map(|(issue, _)| issue)
+            .collect()
+    }
+    /// Checks the drawing for problems like `audit()` and fixes what it can.  Duplicate handles
+    /// are reassigned, dangling pointers are cleared, missing layers, line types, styles, and
+    /// blocks are added, and invalid colors and lineweights are reset to BYLAYER.  The returned
+    /// issues describe the drawing before it was fixed; see `AuditIssue::is_fixed`.
+    pub fn audit_and_fix(&mut self) -> Vec<AuditIssue> {
+        let mut issues = self.audit_internal();
+        let mut applied = HashSet::new();
+        // handles are fixed first so new items get unique handles, and the seed is raised before
+        // duplicates are reassigned so they don't collide with handles already in use
+        let mut fixes = issues
+            .iter()
+            .filter_map(|(_, fix)| fix.clone())
+            .collect::<Vec<_>>();
+        fixes.sort_by_key(|fix| match fix {
+            AuditFix::RaiseHandleSeed => 0,
+            AuditFix::ReassignDuplicateHandles => 1,
+            _ => 2,
+        });
+        for fix in fixes {
+            if applied.insert(fix.clone()) {
+                self.apply_audit_fix(&fix);
+            }
+        }
+        for (issue, fix) in &mut issues {
+            issue.is_fixed = fix.is_some();
+        }
+        issues.into_iter().map(|(issue, _)| issue).collect()
+    }
+    fn audit_internal(&self) -> Vec<(AuditIssue, Option<AuditFix>)> {
+        let mut auditor = Auditor {
+            drawing: self,
+            handles: HashSet::new(),
+            issues: vec![],
+        };
+        auditor.check_handles();
+
+        for layer in self.layers() {
+            auditor.check_pointer(layer.handle, "plot style", layer.__plot_style_handle);
+            auditor.check_pointer(layer.handle, "material", layer.__material_handle);
+            auditor.check_line_type(layer.handle, &layer.line_type_name);
+        }
+        for block in self.blocks() {
+            auditor.check_pointer(block.handle, "owner", block.__owner_handle);
+            for entity in &block.entities {
+                auditor.check_entity(entity);
+            }
+        }
+        for entity in self.entities() {
+            auditor.check_entity(entity);
+        }
+        for object in self.objects() {
+            auditor.check_pointer(object.common.handle, "owner", object.common.__owner_handle);
+        }
+
+        auditor.issues
+    }
+    fn apply_audit_fix(&mut self, fix: &AuditFix) {
+        match fix {
+            AuditFix::ReassignDuplicateHandles => self.reassign_duplicate_handles(),
+            AuditFix::RaiseHandleSeed => {
+                let max_handle = max_handle(self);
+                if max_handle >= self.header.next_available_handle {
+                    self.header.next_available_handle = max_handle + 1;
+                }
+            }
+            AuditFix::ClearDanglingPointers => self.clear_dangling_pointers(),
+            AuditFix::ResetInvalidValues => {
+                let reset = |entity: &mut Entity| {
+                    let color = entity.common.color.get_raw_value();
+                    if !(-255..=257).contains(&color) {
+                        entity.common.color = Color::by_layer();
+                    }
+                    if !is_valid_line_weight(entity.common.lineweight_enum_value) {
+                        entity.common.lineweight_enum_value = -2; // BYLAYER
+                    }
+                };
+                self.entities_mut().for_each(reset);
+                self.blocks_mut()
+                    .flat_map(|b| b.entities.iter_mut())
+                    .for_each(reset);
+            }
+            AuditFix::AddLayer(name) => {
+                if !self.layers().any(|l| l.name.eq_ignore_ascii_case(name)) {
+                    self.add_layer(Layer {
+                        name: name.clone(),
+                        ..Default::default()
+                    });
+                }
+            }
+            AuditFix::AddLineType(name) => {
+                if !self
+                    .line_types()
+                    .any(|lt| lt.name.eq_ignore_ascii_case(name))
+                {
+                    self.add_line_type(LineType {
+                        name: name.clone(),
+                        ..Default::default()
+                    });
+                }
+            }
+            AuditFix::AddStyle(name) => {
+                if !self.styles().any(|s| s.name.eq_ignore_ascii_case(name)) {
+                    self.add_style(Style {
+                        name: name.clone(),
+                        ..Default::default()
+                    });
+                }
+            }
+            AuditFix::AddDimStyle(name) => {
+                if !self.dim_styles().any(|d| d.name.eq_ignore_ascii_case(name)) {
+                    self.add_dim_style(DimStyle {
+                        name: name.clone(),
+                        ..Default::default()
+                    });
+                }
+            }
+            AuditFix::AddBlock(name) => {
+                if !self.blocks().any(|b| b.name.eq_ignore_ascii_case(name)) {
+                    self.add_block(Block {
+                        name: name.clone(),
+                        ..Default::default()
+                    });
+                }
+            }
+        }
+    }
+    /// Gives every item after the first that uses a handle a new handle.
+    fn reassign_duplicate_handles(&mut self) {
+        let mut seen = HashSet::new();
+        let mut next_handle = self.header.next_available_handle.max(max_handle(self) + 1);
+        let mut reassign = |handle: &mut u32| {
+            if *handle != 0 && !seen.insert(*handle) {
+                *handle = next_handle;
+                next_handle += 1;
+            }
+        };
+        self.app_ids_mut().for_each(|i| reassign(&mut i.handle));
+        self.block_records_mut()
+            .for_each(|i| reassign(&mut i.handle));
+        self.dim_styles_mut().for_each(|i| reassign(&mut i.handle));
+        self.layers_mut().for_each(|i| reassign(&mut i.handle));
+        self.line_types_mut().for_each(|i| reassign(&mut i.handle));
+        self.styles_mut().for_each(|i| reassign(&mut i.handle));
+        self.ucss_mut().for_each(|i| reassign(&mut i.handle));
+        self.views_mut().for_each(|i| reassign(&mut i.handle));
+        self.view_ports_mut().for_each(|i| reassign(&mut i.handle));
+        for block in self.blocks_mut() {
+            reassign(&mut block.handle);
+            for entity in block.entities.iter_mut() {
+                reassign(&mut entity.common.handle);
+            }
+        }
+        self.entities_mut()
+            .for_each(|e| reassign(&mut e.common.handle));
+        self.objects_mut()
+            .for_each(|o| reassign(&mut o.common.handle));
+        self.header.next_available_handle = next_handle;
+    }
+    fn clear_dangling_pointers(&mut self) {
+        let mut handles = table_item_handles(self).into_iter().collect::<HashSet<_>>();
+        for block in self.blocks() {
+            handles.insert(block.handle);
+            handles.extend(block.entities.iter().map(|e| e.common.handle));
+        }
+        handles.extend(self.entities().map(|e| e.common.handle));
+        handles.extend(self.objects().map(|o| o.common.handle));
+        let clear = |pointer: &mut u32| {
+            if !handles.contains(pointer) {
+                *pointer = 0;
+            }
+        };
+        let clear_entity = |entity: &mut Entity| {
+            clear(&mut entity.common.__owner_handle);
+            clear(&mut entity.common.__material_handle);
+            clear(&mut entity.common.__plot_style_handle);
+        };
+        for layer in self.layers_mut() {
+            clear(&mut layer.__plot_style_handle);
+            clear(&mut layer.__material_handle);
+        }
+        for block in self.blocks_mut() {
+            clear(&mut block.__owner_handle);
+            block.entities.iter_mut().for_each(clear_entity);
+        }
+        self.entities_mut().for_each(clear_entity);
+        self.objects_mut()
+            .for_each(|o| clear(&mut o.common.__owner_handle));
+    }
+}
+
+/// Returns the handles of all table items, e.g., layers and styles.
+fn table_item_handles(drawing: &Drawing) -> Vec<u32> {
+    let mut handles = vec![];
+    handles.extend(drawing.app_ids().map(|i| i.handle));
+    handles.extend(drawing.block_records().map(|i| i.handle));
+    handles.extend(drawing.dim_styles().map(|i| i.handle));
+    handles.extend(drawing.layers().map(|i| i.handle));
+    handles.extend(drawing.line_types().map(|i| i.handle));
+    handles.extend(drawing.styles().map(|i| i.handle));
+    handles.extend(drawing.ucss().map(|i| i.handle));
+    handles.extend(drawing.views().map(|i| i.handle));
+    handles.extend(drawing.view_ports().map(|i| i.handle));
+    handles
+}
+
+/// Returns the largest handle of any item in the drawing.
+fn max_handle(drawing: &Drawing) -> u32 {
+    table_item_handles(drawing)
+        .into_iter()
+        .chain(drawing.blocks().flat_map(|b| {
+            std::iter::once(b.handle).chain(b.entities.iter().map(|e| e.common.handle))
+        }))
+        .chain(drawing.entities().map(|e| e.common.handle))
+        .chain(drawing.objects().map(|o| o.common.handle))
+        .max()
+        .unwrap_or(0)
+}
+
+fn is_valid_line_weight(value: i16) -> bool {
+    (-3..=-1).contains(&value) || VALID_LINE_WEIGHTS.contains(&value)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::entities::*;
+    use crate::enums::*;
+    use crate::helper_functions::tests::*;
+    use crate::objects::*;
+    use crate::tables::*;
+    use crate::*;
+
+    fn line() -> Entity {
+        Entity::new(EntityType::Line(Line::default()))
+    }
+
+    #[test]
+    fn no_issues_in_new_drawing() {
+        let mut drawing = Drawing::new();
+        drawing.add_entity(line());
+        assert_eq!(Vec::<AuditIssue>::new(), drawing.audit());
+    }
+
+    #[test]
+    fn no_issues_after_round_trip() {
+        let mut drawing = Drawing::new();
+        drawing.header.version = AcadVersion::R2018;
+        drawing.add_entity(line());
+        drawing.add_object(Object::new(ObjectType::DictionaryVariable(
+            Default::default(),
+        )));
+        let drawing = parse_drawing(&to_test_string(&drawing));
+        assert_eq!(Vec::<AuditIssue>::new(), drawing.audit());
+    }
+
+    #[test]
+    fn duplicate_handles_are_reassigned() {
+        let mut drawing = Drawing::new();
+        let first = drawing.add_entity(line()).common.handle;
+        drawing.add_entity(line());
+        let seed = drawing.header.next_available_handle;
+        drawing.entities_mut().for_each(|e| e.common.handle = first);
+
+        let issues = drawing.audit();
+        assert_eq!(1, issues.len());
+        assert_eq!(AuditSeverity::Error, issues[0].severity);
+        assert_eq!(first, issues[0].handle);
+
+        let issues = drawing.audit_and_fix();
+        assert!(issues[0].is_fixed);
+        let handles = drawing
+            .entities()
+            .map(|e| e.common.handle)
+            .collect::<Vec<_>>();
+        assert_eq!(vec![first, seed], handles);
+        assert_eq!(seed + 1, drawing.header.next_available_handle);
+        assert!(drawing.audit().is_empty());
+    }
+
+    #[test]
+    fn handle_seed_is_raised() {
+        let mut drawing = Drawing::new();
+        drawing.add_entity(line());
+        drawing.entities_mut().for_each(|e| e.common.handle = 0x500);
+        assert_eq!(1, drawing.audit().len());
+        drawing.audit_and_fix();
+        assert_eq!(0x501, drawing.header.next_available_handle);
+    }
+
+    #[test]
+    fn duplicate_handles_are_reassigned_above_stale_seed() {
+        let mut drawing = Drawing::new();
+        drawing.add_entity(line());
+        drawing.add_entity(line());
+        drawing.add_entity(line());
+        let handles = [0x500, 0x500, 0x501];
+        for (entity, handle) in drawing.entities_mut().zip(handles.iter()) {
+            entity.common.handle = *handle;
+        }
+        // the seed is stale, and 0x501 is already in use
+        drawing.header.next_available_handle = 0x501;
+
+        drawing.audit_and_fix();
+        let handles = drawing
+            .entities()
+            .map(|e| e.common.handle)
+            .collect::<Vec<_>>();
+        assert_eq!(vec![0x500, 0x502, 0x501], handles);
+        assert_eq!(0x503, drawing.header.next_available_handle);
+        assert!(drawing.audit().is_empty());
+    }
+
+    #[test]
+    fn layer_names_are_case_insensitive() {
+        let mut drawing = Drawing::new();
+        drawing.add_layer(Layer {
+            name: String::from("WALLS"),
+            ..Default::default()
+        });
+        drawing.add_entity(line());
+        drawing
+            .entities_mut()
+            .for_each(|e| e.common.layer = String::from("Walls"));
+        assert!(drawing.audit().is_empty());
+        let layer_count = drawing.layers().count();
+        drawing.audit_and_fix();
+        assert_eq!(layer_count, drawing.layers().count());
+    }
+
+    #[test]
+    fn dangling_pointers_and_missing_tables_are_fixed() {
+        let mut drawing = Drawing::new();
+        drawing.add_entity(line());
+        drawing.entities_mut().for_each(|e| {
+            e.common.__owner_handle = 0x4242;
+            e.common.layer = String::from("missing-layer");
+            e.common.line_type_name = String::from("missing-line-type");
+        });
+        drawing.add_entity(Entity::new(EntityType::Insert(Insert {
+            name: String::from("missing-block"),
+            ..Default::default()
+        })));
+
+        let issues = drawing.audit();
+        let descriptions = issues
+            .iter()
+            .map(|i| i.description.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            vec![
+                "owner pointer 4242 doesn't refer to an item in the drawing",
+                "layer `missing-layer` doesn't exist",
+                "line type `missing-line-type` doesn't exist",
+                "block `missing-block` doesn't exist",
+            ],
+            descriptions
+        );
+
+        let issues = drawing.audit_and_fix();
+        assert!(issues.iter().all(|i| i.is_fixed));
+        assert!(drawing.audit().is_empty());
+        assert_eq!(0, drawing.entities().next().unwrap().common.__owner_handle);
+        assert!(drawing.layers().any(|l| l.name == "missing-layer"));
+        assert!(drawing.blocks().any(|b| b.name == "missing-block"));
+    }
+
+    #[test]
+    fn invalid_values_are_reset() {
+        let mut drawing = Drawing::new();
+        drawing.add_entity(line());
+        drawing.entities_mut().for_each(|e| {
+            e.common.color = Color::from_raw_value(300);
+            e.common.lineweight_enum_value = 17;
+        });
+        assert_eq!(2, drawing.audit().len());
+        drawing.audit_and_fix();
+        let entity = drawing.entities().next().unwrap();
+        assert!(entity.common.color.is_by_layer());
+        assert_eq!(-2, entity.common.lineweight_enum_value);
+        assert!(drawing.audit().is_empty());
+    }
+
+    #[test]
+    fn display_issue() {
+        let issue = AuditIssue {
+            severity: AuditSeverity::Error,
+            handle: 0x2A,
+            description: String::from("something is wrong"),
+            is_fixed: true,
+        };
+        assert_eq!("error [2A]: something is wrong (fixed)", issue.to_string());
+    }
+}
@@ -156,7 +156,7 @@ impl Block {
                                 }
                             }
 
-                            if current.handle == 0 {
+                            if current.handle == 0 && iter.options().assign_handles {
                                 drawing.add_block(current);
                             } else {
                                 drawing.add_block_no_handle_set(current);
@@ -439,8 +439,12 @@ mod tests {
     #[test]
     fn read_block_with_unsupported_entity_first() {
         let block = read_single_block(vec!["0", "UNSUPPORTED_ENTITY", "0", "LINE"]);
-        assert_eq!(1, block.entities.len());
+        assert_eq!(2, block.entities.len());
         match block.entities[0].specific {
+            EntityType::Unknown { .. } => (),
+            _ => panic!("expected an unknown entity"),
+        }
+        match block.entities[1].specific {
             EntityType::Line(_) => (),
             _ => panic!("expected a line"),
         }
@@ -449,22 +453,30 @@ mod tests {
     #[test]
     fn read_block_with_unsupported_entity_last() {
         let block = read_single_block(vec!["0", "LINE", "0", "UNSUPPORTED_ENTITY"]);
-        assert_eq!(1, block.entities.len());
+        assert_eq!(2, block.entities.len());
         match block.entities[0].specific {
             EntityType::Line(_) => (),
             _ => panic!("expected a line"),
         }
+        match block.entities[1].specific {
+            EntityType::Unknown { .. } => (),
+            _ => panic!("expected an unknown entity"),
+        }
     }
 
     #[test]
     fn read_block_with_unsupported_entity_in_the_middle() {
         let block = read_single_block(vec!["0", "LINE", "0", "UNSUPPORTED_ENTITY", "0", "CIRCLE"]);
-        assert_eq!(2, block.entities.len());
+        assert_eq!(3, block.entities.len());
         match block.entities[0].specific {
             EntityType::Line(_) => (),
             _ => panic!("expected a line"),
         }
         match block.entities[1].specific {
+            EntityType::Unknown { .. } => (),
+            _ => panic!("expected an unknown entity"),
+        }
+        match block.entities[2].specific {
             EntityType::Circle(_) => (),
             _ => panic!("expected a circle"),
         }
@@ -0,0 +1,1214 @@
+use std::f64::consts::PI;
+
+use crate::entities::*;
+use crate::enums::*;
+use crate::helper_functions::strip_m_text_formatting;
+use crate::{
+    Block, Drawing, HatchBoundaryEdge, HatchBoundaryPath, LwPolylineVertex, Point, PolylineSegment,
+    TransformationMatrix, Vector,
+};
+
+/// Represents an axis-aligned box in world coordinates.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+pub struct BoundingBox {
+    /// The corner with the smallest X, Y, and Z values.
+    pub min: Point,
+    /// The corner with the largest X, Y, and Z values.
+    pub max: Point,
+}
+
+// public implementation
+impl BoundingBox {
+    /// Creates a new `BoundingBox` that contains both of the specified points.
+    pub fn new(p1: Point, p2: Point) -> Self {
+        let mut bbox = BoundingBox::from_point(&p1);
+        bbox.add_point(&p2);
+        bbox
+    }
+    /// Creates a new `BoundingBox` that contains only the specified point.
+    pub fn from_point(point: &Point) -> Self {
+        BoundingBox {
+            min: point.clone(),
+            max: point.clone(),
+        }
+    }
+    /// Creates a new `BoundingBox` that contains all of the specified points, or `None` if there
+    /// are no points.
+    pub fn from_points<'a, I>(points: I) -> Option<Self>
+    where
+        I: IntoIterator<Item = &'a Point>,
+    {
+        let mut bbox: Option<BoundingBox> = None;
+        for p in points {
+            include_point(&mut bbox, p);
+        }
+
+        bbox
+    }
+    /// Grows the box to include the specified point.
+    pub fn add_point(&mut self, point: &Point) {
+        self.min.x = self.min.x.min(point.x);
+        self.min.y = self.min.y.min(point.y);
+        self.min.z = self.min.z.min(point.z);
+        self.max.x = self.max.x.max(point.x);
+        self.max.y = self.max.y.max(point.y);
+        self.max.z = self.max.z.max(point.z);
+    }
+    /// Grows the box to include another box.
+    pub fn add_box(&mut self, other: &BoundingBox) {
+        self.add_point(&other.min);
+        self.add_point(&other.max);
+    }
+    /// Returns the smallest box containing both this and another box.
+    pub fn union(&self, other: &BoundingBox) -> BoundingBox {
+        let mut bbox = self.clone();
+        bbox.add_box(other);
+        bbox
+    }
+    /// Returns the width, height, and depth of the box.
+    pub fn size(&self) -> Vector {
+        Vector::new(
+            self.max.x - self.min.x,
+            self.max.y - self.min.y,
+            self.max.z - self.min.z,
+        )
+    }
+    /// Returns the center of the box.
+    pub fn center(&self) -> Point {
+        Point::new(
+            (self.min.x + self.max.x) / 2.0,
+            (self.min.y + self.max.y) / 2.0,
+            (self.min.z + self.max.z) / 2.0,
+        )
+    }
+    /// Returns `true` if the point is inside or on the edge of the box.
+    pub fn contains(&self, point: &Point) -> bool {
+        point.x >= self.min.x
+            && point.x <= self.max.x
+            && point.y >= self.min.y
+            && point.y <= self.max.y
+            && point.z >= self.min.z
+            && point.z <= self.max.z
+    }
+    /// Returns the eight corners of the box.
+    pub fn corners(&self) -> Vec<Point> {
+        let mut corners = vec![];
+        for &x in &[self.min.x, self.max.x] {
+            for &y in &[self.min.y, self.max.y] {
+                for &z in &[self.min.z, self.max.z] {
+                    corners.push(Point::new(x, y, z));
+                }
+            }
+        }
+
+        corners
+    }
+}
+
+//------------------------------------------------------------------------------
+//                                                                        Entity
+//------------------------------------------------------------------------------
+impl Entity {
+    /// Returns the extents of the entity in world coordinates, or `None` if the entity has no
+    /// geometry that can be bounded, e.g., `XLine`, `Ray`, or entities stored as proprietary data.
+    ///
+    /// The `drawing` is used to find the `Block` referenced by an `Insert` or dimension.  Curves
+    /// are bounded exactly except for splines, which use the extents of their control points.
+    /// Text is approximated by assuming every character is as wide as it is tall.
+    pub fn bounding_box(&self, drawing: &Drawing) -> Option<BoundingBox> {
+        self.bounding_box_internal(drawing, &mut vec![])
+    }
+}
+
+// internal visibility only
+impl Entity {
+    fn bounding_box_internal(
+        &self,
+        drawing: &Drawing,
+        visited_blocks: &mut Vec<String>,
+    ) -> Option<BoundingBox> {
+        let mut bbox = None;
+        match self.specific {
+            EntityType::Arc(ref arc) => {
+                let ocs = arc.normal.ocs_matrix();
+                add_elliptical_arc(
+                    &mut bbox,
+                    &ocs.transform_point(&arc.center),
+                    &ocs.transform_vector(&Vector::x_axis()).scale(arc.radius),
+                    &ocs.transform_vector(&Vector::y_axis()).scale(arc.radius),
+                    arc.start_angle.to_radians(),
+                    arc.end_angle.to_radians(),
+                );
+                add_thickness(&mut bbox, &arc.normal, arc.thickness);
+            }
+            EntityType::ArcAlignedText(ref text) => {
+                let ocs = text.extrusion_direction.ocs_matrix();
+                let radius = text.arc_radius + text.offset_from_arc + text.text_height;
+                add_elliptical_arc(
+                    &mut bbox,
+                    &ocs.transform_point(&text.center_point),
+                    &ocs.transform_vector(&Vector::x_axis()).scale(radius),
+                    &ocs.transform_vector(&Vector::y_axis()).scale(radius),
+                    0.0,
+                    2.0 * PI,
+                );
+            }
+            EntityType::Attribute(ref att) => {
+                add_text(
+                    &mut bbox,
+                    &att.normal.ocs_matrix(),
+                    &att.location,
+                    &att.second_alignment_point,
+                    &att.value,
+                    att.text_height,
+                    att.relative_x_scale_factor,
+                    att.rotation,
+                    att.horizontal_text_justification,
+                    att.vertical_text_justification,
+                );
+                add_thickness(&mut bbox, &att.normal, att.thickness);
+            }
+            EntityType::AttributeDefinition(ref att) => {
+                add_text(
+                    &mut bbox,
+                    &att.normal.ocs_matrix(),
+                    &att.location,
+                    &att.second_alignment_point,
+                    &att.value,
+                    att.text_height,
+                    att.relative_x_scale_factor,
+                    att.rotation,
+                    att.horizontal_text_justification,
+                    att.vertical_text_justification,
+                );
+                add_thickness(&mut bbox, &att.normal, att.thickness);
+            }
+            EntityType::Circle(ref circle) => {
+                let ocs = circle.normal.ocs_matrix();
+                add_elliptical_arc(
+                    &mut bbox,
+                    &ocs.transform_point(&circle.center),
+                    &ocs.transform_vector(&Vector::x_axis()).scale(circle.radius),
+                    &ocs.transform_vector(&Vector::y_axis()).scale(circle.radius),
+                    0.0,
+                    2.0 * PI,
+                );
+                add_thickness(&mut bbox, &circle.normal, circle.thickness);
+            }
+            EntityType::RotatedDimension(ref dim) => {
+                add_dimension(&mut bbox, &dim.dimension_base, drawing, visited_blocks);
+                include_point(&mut bbox, &dim.definition_point_2);
+                include_point(&mut bbox, &dim.definition_point_3);
+            }
+            EntityType::RadialDimension(ref dim) => {
+                add_dimension(&mut bbox, &dim.dimension_base, drawing, visited_blocks);
+                include_point(&mut bbox, &dim.definition_point_2);
+            }
+            EntityType::DiameterDimension(ref dim) => {
+                add_dimension(&mut bbox, &dim.dimension_base, drawing, visited_blocks);
+                include_point(&mut bbox, &dim.definition_point_2);
+            }
+            EntityType::AngularThreePointDimension(ref dim) => {
+                add_dimension(&mut bbox, &dim.dimension_base, drawing, visited_blocks);
+                include_point(&mut bbox, &dim.definition_point_2);
+                include_point(&mut bbox, &dim.definition_point_3);
+                include_point(&mut bbox, &dim.definition_point_4);
+            }
+            EntityType::OrdinateDimension(ref dim) => {
+                add_dimension(&mut bbox, &dim.dimension_base, drawing, visited_blocks);
+                include_point(&mut bbox, &dim.definition_point_2);
+                include_point(&mut bbox, &dim.definition_point_3);
+            }
+            EntityType::Ellipse(ref ellipse) => {
+                let minor_axis = ellipse
+                    .normal
+                    .normalize()
+                    .cross(&ellipse.major_axis)
+                    .scale(ellipse.minor_axis_ratio);
+                add_elliptical_arc(
+                    &mut bbox,
+                    &ellipse.center,
+                    &ellipse.major_axis,
+                    &minor_axis,
+                    ellipse.start_parameter,
+                    ellipse.end_parameter,
+                );
+            }
+            EntityType::Face3D(ref face) => {
+                include_point(&mut bbox, &face.first_corner);
+                include_point(&mut bbox, &face.second_corner);
+                include_point(&mut bbox, &face.third_corner);
+                include_point(&mut bbox, &face.fourth_corner);
+            }
+            EntityType::Hatch(ref hatch) => {
+                let ocs = hatch.extrusion_direction.ocs_matrix();
+                for path in &hatch.boundary_paths {
+                    add_hatch_boundary_path(&mut bbox, &ocs, hatch.elevation_point.z, path);
+                }
+            }
+            EntityType::Helix(ref helix) => {
+                let axis = helix.axis_vector.normalize();
+                let (x_axis, y_axis, _) = axis.ocs_axes();
+                let height = helix.number_of_turns * helix.turn_height;
+                for h in &[0.0, height] {
+                    add_elliptical_arc(
+                        &mut bbox,
+                        &helix.axis_base_point.offset(&axis.scale(*h)),
+                        &x_axis.scale(helix.radius),
+                        &y_axis.scale(helix.radius),
+                        0.0,
+                        2.0 * PI,
+                    );
+                }
+            }
+            EntityType::Image(ref image) => {
+                add_parallelogram(
+                    &mut bbox,
+                    &image.location,
+                    &image.u_vector.scale(image.image_size.x),
+                    &image.v_vector.scale(image.image_size.y),
+                );
+            }
+            EntityType::Insert(ref ins) => {
+                add_insert(&mut bbox, ins, drawing, visited_blocks);
+            }
+            EntityType::Leader(ref leader) => {
+                for v in &leader.vertices {
+                    include_point(&mut bbox, v);
+                }
+            }
+            EntityType::Line(ref line) => {
+                include_point(&mut bbox, &line.p1);
+                include_point(&mut bbox, &line.p2);
+                add_thickness(&mut bbox, &line.extrusion_direction, line.thickness);
+            }
+            EntityType::LwPolyline(ref poly) => {
+                add_bulged_vertices(
+                    &mut bbox,
+                    &poly.extrusion_direction.ocs_matrix(),
+                    self.common.elevation,
+                    &poly.vertices,
+                    poly.get_is_closed(),
+                );
+                add_thickness(&mut bbox, &poly.extrusion_direction, poly.thickness);
+            }
+            EntityType::MLine(ref mline) => {
+                for v in &mline.vertices {
+                    include_point(&mut bbox, v);
+                }
+            }
+            EntityType::MLeader(ref mleader) => {
+                let context = &mleader.context_data;
+                include_point(&mut bbox, &context.content_base_point);
+                for root in &context.roots {
+                    include_point(&mut bbox, &root.last_leader_line_point);
+                    for line in &root.lines {
+                        for v in &line.vertices {
+                            include_point(&mut bbox, v);
+                        }
+                    }
+                }
+            }
+            EntityType::MText(ref mtext) => {
+                add_m_text(&mut bbox, mtext);
+            }
+            EntityType::ModelPoint(ref point) => {
+                include_point(&mut bbox, &point.location);
+                add_thickness(&mut bbox, &point.extrusion_direction, point.thickness);
+            }
+            EntityType::Ole2Frame(ref frame) => {
+                include_point(&mut bbox, &frame.upper_left_corner);
+                include_point(&mut bbox, &frame.lower_right_corner);
+            }
+            EntityType::Polyline(ref poly) => {
+                if poly.get_is_3d_polyline() || poly.get_is_3d_polygon_mesh() {
+                    for v in poly.vertices() {
+                        include_point(&mut bbox, &v.location);
+                    }
+                } else if poly.get_is_polyface_mesh() {
+                    // face records don't contain locations
+                    for v in poly.vertices().filter(|v| v.get_is_3d_polygon_mesh()) {
+                        include_point(&mut bbox, &v.location);
+                    }
+                } else {
+                    let vertices = poly
+                        .vertices()
+                        .map(|v| LwPolylineVertex {
+                            x: v.location.x,
+                            y: v.location.y,
+                            bulge: v.bulge,
+                            ..Default::default()
+                        })
+                        .collect::<Vec<_>>();
+                    add_bulged_vertices(
+                        &mut bbox,
+                        &poly.normal.ocs_matrix(),
+                        poly.location.z,
+                        &vertices,
+                        poly.get_is_closed(),
+                    );
+                    add_thickness(&mut bbox, &poly.normal, poly.thickness);
+                }
+            }
+            EntityType::RText(ref text) => {
+                let ocs = text.extrusion_direction.ocs_matrix();
+                let (x_dir, y_dir) = rotated_axes(&ocs, text.rotation_angle);
+                add_rectangle(
+                    &mut bbox,
+                    &ocs.transform_point(&text.insertion_point),
+                    &x_dir,
+                    &y_dir,
+                    (0.0, text.text_height * text.contents.chars().count() as f64),
+                    (0.0, text.text_height),
+                );
+            }
+            EntityType::Shape(ref shape) => {
+                let ocs = shape.extrusion_direction.ocs_matrix();
+                let (x_dir, y_dir) = rotated_axes(&ocs, shape.rotation_angle);
+                let width = shape.size * positive_or_one(shape.relative_x_scale_factor);
+                add_rectangle(
+                    &mut bbox,
+                    &ocs.transform_point(&shape.location),
+                    &x_dir,
+                    &y_dir,
+                    (0.0, width),
+                    (0.0, shape.size),
+                );
+                add_thickness(&mut bbox, &shape.extrusion_direction, shape.thickness);
+            }
+            EntityType::Solid(ref solid) => {
+                let ocs = solid.extrusion_direction.ocs_matrix();
+                include_point(&mut bbox, &ocs.transform_point(&solid.first_corner));
+                include_point(&mut bbox, &ocs.transform_point(&solid.second_corner));
+                include_point(&mut bbox, &ocs.transform_point(&solid.third_corner));
+                include_point(&mut bbox, &ocs.transform_point(&solid.fourth_corner));
+                add_thickness(&mut bbox, &solid.extrusion_direction, solid.thickness);
+            }
+            EntityType::Spline(ref spline) => {
+                // a spline always lies within the convex hull of its control points
+                let points = if spline.control_points.is_empty() {
+                    &spline.fit_points
+                } else {
+                    &spline.control_points
+                };
+                for p in points {
+                    include_point(&mut bbox, p);
+                }
+            }
+            EntityType::Text(ref text) => {
+                add_text(
+                    &mut bbox,
+                    &text.normal.ocs_matrix(),
+                    &text.location,
+                    &text.second_alignment_point,
+                    &text.value,
+                    text.text_height,
+                    text.relative_x_scale_factor,
+                    text.rotation,
+                    text.horizontal_text_justification,
+                    text.vertical_text_justification,
+                );
+                add_thickness(&mut bbox, &text.normal, text.thickness);
+            }
+            EntityType::Trace(ref trace) => {
+                let ocs = trace.extrusion_direction.ocs_matrix();
+                include_point(&mut bbox, &ocs.transform_point(&trace.first_corner));
+                include_point(&mut bbox, &ocs.transform_point(&trace.second_corner));
+                include_point(&mut bbox, &ocs.transform_point(&trace.third_corner));
+                include_point(&mut bbox, &ocs.transform_point(&trace.fourth_corner));
+                add_thickness(&mut bbox, &trace.extrusion_direction, trace.thickness);
+            }
+            EntityType::Vertex(ref vertex) => {
+                include_point(&mut bbox, &vertex.location);
+            }
+            EntityType::Wipeout(ref wipeout) => {
+                add_parallelogram(
+                    &mut bbox,
+                    &wipeout.location,
+                    &wipeout.u_vector.scale(wipeout.image_size.x),
+                    &wipeout.v_vector.scale(wipeout.image_size.y),
+                );
+            }
+            // infinite, unbounded, or only described by proprietary data
+            EntityType::Body(_)
+            | EntityType::DgnUnderlay(_)
+            | EntityType::DwfUnderlay(_)
+            | EntityType::Light(_)
+            | EntityType::OleFrame(_)
+            | EntityType::PdfUnderlay(_)
+            | EntityType::ProxyEntity(_)
+            | EntityType::Ray(_)
+            | EntityType::Region(_)
+            | EntityType::Section(_)
+            | EntityType::Seqend(_)
+            | EntityType::Solid3D(_)
+            | EntityType::Tolerance(_)
+            | EntityType::XLine(_)
+            | EntityType::Unknown { .. } => (),
+        }
+
+        bbox
+    }
+}
+
+//------------------------------------------------------------------------------
+//                                                                         Block
+//------------------------------------------------------------------------------
+impl Block {
+    /// Returns the extents of the block's entities in the block's coordinate system, or `None` if
+    /// none of the entities can be bounded.  The `drawing` is used to resolve nested `Insert`s.
+    pub fn bounding_box(&self, drawing: &Drawing) -> Option<BoundingBox> {
+        self.bounding_box_internal(drawing, &mut vec![self.name.clone()])
+    }
+    fn bounding_box_internal(
+        &self,
+        drawing: &Drawing,
+        visited_blocks: &mut Vec<String>,
+    ) -> Option<BoundingBox> {
+        let mut bbox = None;
+        for e in &self.entities {
+            if let Some(ref b) = e.bounding_box_internal(drawing, visited_blocks) {
+                include_box(&mut bbox, b);
+            }
+        }
+
+        bbox
+    }
+}
+
+//------------------------------------------------------------------------------
+//                                                                       Drawing
+//------------------------------------------------------------------------------
+impl Drawing {
+    /// Returns the extents of all model space entities in world coordinates, or `None` if no entity
+    /// can be bounded.
+    pub fn extents(&self) -> Option<BoundingBox> {
+        let mut bbox = None;
+        for e in self.entities().filter(|e| !e.common.is_in_paper_space) {
+            if let Some(ref b) = e.bounding_box(self) {
+                include_box(&mut bbox, b);
+            }
+        }
+
+        bbox
+    }
+    /// Sets the header's `$EXTMIN` and `$EXTMAX` values to the extents of the model space entities.
+    /// The values are left unchanged if no entity can be bounded.
+    pub fn update_extents(&mut self) {
+        if let Some(bbox) = self.extents() {
+            self.header.minimum_drawing_extents = bbox.min;
+            self.header.maximum_drawing_extents = bbox.max;
+        }
+    }
+}
+
+//------------------------------------------------------------------------------
+//                                                               private helpers
+//------------------------------------------------------------------------------
+
+/// Returns the world coordinate directions of the X and Y axes of `ocs` after rotating them in the
+/// OCS plane by the specified angle in degrees.
+fn rotated_axes(ocs: &TransformationMatrix, rotation: f64) -> (Vector, Vector) {
+    let (sin, cos) = rotation.to_radians().sin_cos();
+    let x_dir = ocs.transform_vector(&Vector::new(cos, sin, 0.0));
+    let y_dir = ocs.transform_vector(&Vector::new(-sin, cos, 0.0));
+    (x_dir, y_dir)
+}
+
+fn include_point(bbox: &mut Option<BoundingBox>, p: &Point) {
+    match bbox {
+        Some(ref mut b) => b.add_point(p),
+        None => *bbox = Some(BoundingBox::from_point(p)),
+    }
+}
+
+fn include_box(bbox: &mut Option<BoundingBox>, other: &BoundingBox) {
+    include_point(bbox, &other.min);
+    include_point(bbox, &other.max);
+}
+
+fn positive_or_one(val: f64) -> f64 {
+    if val > 0.0 {
+        val
+    } else {
+        1.0
+    }
+}
+
+/// Grows the box by the entity's extrusion.
+fn add_thickness(bbox: &mut Option<BoundingBox>, normal: &Vector, thickness: f64) {
+    if thickness != 0.0 {
+        if let Some(ref mut b) = bbox {
+            let v = normal.normalize().scale(thickness);
+            let min = b.min.offset(&v);
+            let max = b.max.offset(&v);
+            b.add_point(&min);
+            b.add_point(&max);
+        }
+    }
+}
+
+/// Adds the elliptical arc `center + u * cos(t) + v * sin(t)` where `t` runs counter-clockwise from
+/// `start` to `end` (in radians).  Circular arcs have perpendicular `u` and `v` of equal length.
+fn add_elliptical_arc(
+    bbox: &mut Option<BoundingBox>,
+    center: &Point,
+    u: &Vector,
+    v: &Vector,
+    start: f64,
+    end: f64,
+) {
+    let point_at = |t: f64| {
+        let (sin, cos) = t.sin_cos();
+        Point::new(
+            center.x + u.x * cos + v.x * sin,
+            center.y + u.y * cos + v.y * sin,
+            center.z + u.z * cos + v.z * sin,
+        )
+    };
+    let two_pi = 2.0 * PI;
+    let start = start.rem_euclid(two_pi);
+    let mut sweep = (end - start).rem_euclid(two_pi);
+    if sweep == 0.0 {
+        sweep = two_pi;
+    }
+
+    include_point(bbox, &point_at(start));
+    include_point(bbox, &point_at(start + sweep));
+
+    // extremes along each world axis
+    for &(u_i, v_i) in &[(u.x, v.x), (u.y, v.y), (u.z, v.z)] {
+        let t = v_i.atan2(u_i);
+        for &candidate in &[t, t + PI] {
+            if (candidate - start).rem_euclid(two_pi) <= sweep {
+                include_point(bbox, &point_at(candidate));
+            }
+        }
+    }
+}
+
+fn add_rectangle(
+    bbox: &mut Option<BoundingBox>,
+    origin: &Point,
+    x_dir: &Vector,
+    y_dir: &Vector,
+    x_range: (f64, f64),
+    y_range: (f64, f64),
+) {
+    for &x in &[x_range.0, x_range.1] {
+        for &y in &[y_range.0, y_range.1] {
+            include_point(bbox, &origin.offset(&x_dir.scale(x).add(&y_dir.scale(y))));
+        }
+    }
+}
+
+fn add_parallelogram(bbox: &mut Option<BoundingBox>, origin: &Point, u: &Vector, v: &Vector) {
+    add_rectangle(bbox, origin, u, v, (0.0, 1.0), (0.0, 1.0));
+}
+
+/// Adds the segment from `p1` to `p2` in the OCS, where `bulge` is the tangent of a quarter of the
+/// included angle of the arc, or `0.0` for a straight segment.
+fn add_bulged_segment(
+    bbox: &mut Option<BoundingBox>,
+    ocs: &TransformationMatrix,
+    elevation: f64,
+    p1: &LwPolylineVertex,
+    p2: &LwPolylineVertex,
+) {
+    let start = Point::new(p1.x, p1.y, elevation);
+    let end = Point::new(p2.x, p2.y, elevation);
+    include_point(bbox, &ocs.transform_point(&start));
+    include_point(bbox, &ocs.transform_point(&end));
+    if let PolylineSegment::Arc {
+        center,
+        radius,
+        start_angle,
+        sweep_angle,
+        ..
+    } = PolylineSegment::from_bulge(start, end, p1.bulge)
+    {
+        let (start, sweep) = (start_angle.to_radians(), sweep_angle.to_radians());
+        let (start, end) = if sweep > 0.0 {
+            (start, start + sweep)
+        } else {
+            (start + sweep, start)
+        };
+        add_elliptical_arc(
+            bbox,
+            &ocs.transform_point(&center),
+            &ocs.transform_vector(&Vector::x_axis()).scale(radius),
+            &ocs.transform_vector(&Vector::y_axis()).scale(radius),
+            start,
+            end,
+        );
+    }
+}
+
+fn add_bulged_vertices(
+    bbox: &mut Option<BoundingBox>,
+    ocs: &TransformationMatrix,
+    elevation: f64,
+    vertices: &[LwPolylineVertex],
+    is_closed: bool,
+) {
+    for v in vertices {
+        include_point(bbox, &ocs.transform_point(&Point::new(v.x, v.y, elevation)));
+    }
+
+    for pair in vertices.windows(2) {
+        add_bulged_segment(bbox, ocs, elevation, &pair[0], &pair[1]);
+    }
+
+    if is_closed && vertices.len() > 1 {
+        add_bulged_segment(
+            bbox,
+            ocs,
+            elevation,
+            &vertices[vertices.len() - 1],
+            &vertices[0],
+        );
+    }
+}
+
+fn add_hatch_boundary_path(
+    bbox: &mut Option<BoundingBox>,
+    ocs: &TransformationMatrix,
+    elevation: f64,
+    path: &HatchBoundaryPath,
+) {
+    add_bulged_vertices(bbox, ocs, elevation, &path.vertices, true);
+    let to_wcs = |p: &Point| ocs.transform_point(&Point::new(p.x, p.y, elevation));
+    for edge in &path.edges {
+        match edge {
+            HatchBoundaryEdge::Line { start, end } => {
+                include_point(bbox, &to_wcs(start));
+                include_point(bbox, &to_wcs(end));
+            }
+            HatchBoundaryEdge::CircularArc {
+                center,
+                radius,
+                start_angle,
+                end_angle,
+                is_counter_clockwise,
+            } => {
+                let (start, end) =
+                    hatch_edge_angles(*start_angle, *end_angle, *is_counter_clockwise);
+                let mut y_axis = ocs.transform_vector(&Vector::y_axis()).scale(*radius);
+                if !is_counter_clockwise {
+                    y_axis = y_axis.scale(-1.0);
+                }
+                add_elliptical_arc(
+                    bbox,
+                    &to_wcs(center),
+                    &ocs.transform_vector(&Vector::x_axis()).scale(*radius),
+                    &y_axis,
+                    start,
+                    end,
+                );
+            }
+            HatchBoundaryEdge::EllipticArc {
+                center,
+                major_axis,
+                minor_axis_ratio,
+                start_angle,
+                end_angle,
+                is_counter_clockwise,
+            } => {
+                let (start, end) =
+                    hatch_edge_angles(*start_angle, *end_angle, *is_counter_clockwise);
+                let major = ocs.transform_vector(&Vector::new(major_axis.x, major_axis.y, 0.0));
+                let mut minor = ocs
+                    .transform_vector(&Vector::z_axis())
+                    .cross(&major)
+                    .scale(*minor_axis_ratio);
+                if !is_counter_clockwise {
+                    minor = minor.scale(-1.0);
+                }
+                add_elliptical_arc(bbox, &to_wcs(center), &major, &minor, start, end);
+            }
+            HatchBoundaryEdge::Spline {
+                control_points,
+                fit_points,
+                ..
+            } => {
+                let points = if control_points.is_empty() {
+                    fit_points
+                } else {
+                    control_points
+                };
+                for p in points {
+                    include_point(bbox, &to_wcs(p));
+                }
+            }
+        }
+    }
+}
+
+/// Converts the start and end angles (in degrees) of a hatch edge to radians.  Clockwise edges are
+/// evaluated in a mirrored coordinate system where they run counter-clockwise.
+fn hatch_edge_angles(start: f64, end: f64, is_counter_clockwise: bool) -> (f64, f64) {
+    if is_counter_clockwise {
+        (start.to_radians(), end.to_radians())
+    } else {
+        ((-start).to_radians(), (-end).to_radians())
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn add_text(
+    bbox: &mut Option<BoundingBox>,
+    ocs: &TransformationMatrix,
+    location: &Point,
+    second_alignment_point: &Point,
+    value: &str,
+    text_height: f64,
+    relative_x_scale_factor: f64,
+    rotation: f64,
+    horizontal_justification: HorizontalTextJustification,
+    vertical_justification: VerticalTextJustification,
+) {
+    let height = text_height;
+    let mut width =
+        height * positive_or_one(relative_x_scale_factor) * value.chars().count() as f64;
+    let mut rotation = rotation;
+    let mut anchor = location;
+    let is_default_justification = horizontal_justification == HorizontalTextJustification::Left
+        && vertical_justification == VerticalTextJustification::Baseline;
+    if !is_default_justification {
+        anchor = second_alignment_point;
+    }
+
+    let x_offset = match horizontal_justification {
+        HorizontalTextJustification::Left => 0.0,
+        HorizontalTextJustification::Center | HorizontalTextJustification::Middle => -width / 2.0,
+        HorizontalTextJustification::Right => -width,
+        HorizontalTextJustification::Aligned | HorizontalTextJustification::Fit => {
+            // the text is stretched between the two points
+            let (dx, dy) = (
+                second_alignment_point.x - location.x,
+                second_alignment_point.y - location.y,
+            );
+            width = (dx * dx + dy * dy).sqrt();
+            rotation = dy.atan2(dx).to_degrees();
+            anchor = location;
+            0.0
+        }
+    };
+    let y_offset = match vertical_justification {
+        VerticalTextJustification::Baseline
+            if horizontal_justification == HorizontalTextJustification::Middle =>
+        {
+            -height / 2.0
+        }
+        VerticalTextJustification::Baseline | VerticalTextJustification::Bottom => 0.0,
+        VerticalTextJustification::Middle => -height / 2.0,
+        VerticalTextJustification::Top => -height,
+    };
+    let (x_dir, y_dir) = rotated_axes(ocs, rotation);
+    add_rectangle(
+        bbox,
+        &ocs.transform_point(anchor),
+        &x_dir,
+        &y_dir,
+        (x_offset, x_offset + width),
+        (y_offset, y_offset + height),
+    );
+}
+
+fn add_m_text(bbox: &mut Option<BoundingBox>, mtext: &MText) {
+    let mut text = mtext.extended_text.join("");
+    text.push_str(&mtext.text);
+    let text = strip_m_text_formatting(&text);
+    let lines = text.split('\n').collect::<Vec<_>>();
+    let height = mtext.initial_text_height;
+    let width = if mtext.reference_rectangle_width > 0.0 {
+        mtext.reference_rectangle_width
+    } else {
+        let longest_line = lines.iter().map(|l| l.chars().count()).max().unwrap_or(0);
+        height * longest_line as f64
+    };
+    let total_height = if mtext.vertical_height > 0.0 {
+        mtext.vertical_height
+    } else {
+        // successive lines are spaced 5/3 of the text height apart
+        let spacing = height * 5.0 / 3.0 * positive_or_one(mtext.line_spacing_factor);
+        height + spacing * (lines.len() - 1) as f64
+    };
+
+    let normal = mtext.extrusion_direction.normalize();
+    let (x_dir, y_dir) = if mtext.x_axis_direction.length() > 0.0 {
+        let x_dir = mtext.x_axis_direction.normalize();
+        let y_dir = normal.cross(&x_dir).normalize();
+        (x_dir, y_dir)
+    } else {
+        rotated_axes(&normal.ocs_matrix(), mtext.rotation_angle.to_degrees())
+    };
+
+    let x_offset = match mtext.attachment_point {
+        AttachmentPoint::TopLeft | AttachmentPoint::MiddleLeft | AttachmentPoint::BottomLeft => 0.0,
+        AttachmentPoint::TopCenter
+        | AttachmentPoint::MiddleCenter
+        | AttachmentPoint::BottomCenter => -width / 2.0,
+        AttachmentPoint::TopRight | AttachmentPoint::MiddleRight | AttachmentPoint::BottomRight => {
+            -width
+        }
+    };
+    let y_offset = match mtext.attachment_point {
+        AttachmentPoint::TopLeft | AttachmentPoint::TopCenter | AttachmentPoint::TopRight => {
+            -total_height
+        }
+        AttachmentPoint::MiddleLeft
+        | AttachmentPoint::MiddleCenter
+        | AttachmentPoint::MiddleRight => -total_height / 2.0,
+        AttachmentPoint::BottomLeft
+        | AttachmentPoint::BottomCenter
+        | AttachmentPoint::BottomRight => 0.0,
+    };
+    add_rectangle(
+        bbox,
+        &mtext.insertion_point,
+        &x_dir,
+        &y_dir,
+        (x_offset, x_offset + width),
+        (y_offset, y_offset + total_height),
+    );
+}
+
+fn add_block_by_name<F>(
+    bbox: &mut Option<BoundingBox>,
+    name: &str,
+    drawing: &Drawing,
+    visited_blocks: &mut Vec<String>,
+    transform: F,
+) where
+    F: Fn(&Block, &Point) -> Vec<Point>,
+{
+    if visited_blocks.iter().any(|b| b == name) {
+        // self-referencing block
+        return;
+    }
+
+    if let Some(block) = drawing.blocks().find(|b| b.name == name) {
+        visited_blocks.push(String::from(name));
+        let block_box = block.bounding_box_internal(drawing, visited_blocks);
+        visited_blocks.pop();
+        if let Some(block_box) = block_box {
+            for corner in block_box.corners() {
+                for p in transform(block, &corner) {
+                    include_point(bbox, &p);
+                }
+            }
+        }
+    }
+}
+
+fn add_dimension(
+    bbox: &mut Option<BoundingBox>,
+    dim: &DimensionBase,
+    drawing: &Drawing,
+    visited_blocks: &mut Vec<String>,
+) {
+    // the dimension's anonymous block contains the rendered geometry in world coordinates
+    include_point(bbox, &dim.definition_point_1);
+    add_block_by_name(
+        bbox,
+        &dim.block_name,
+        drawing,
+        visited_blocks,
+        |_block, p| vec![p.clone()],
+    );
+}
+
+fn add_insert(
+    bbox: &mut Option<BoundingBox>,
+    ins: &Insert,
+    drawing: &Drawing,
+    visited_blocks: &mut Vec<String>,
+) {
+    let ocs = ins.extrusion_direction.ocs_matrix();
+    let (sin, cos) = ins.rotation.to_radians().sin_cos();
+    let last_column = f64::from(ins.column_count.max(1) - 1);
+    let last_row = f64::from(ins.row_count.max(1) - 1);
+    let transform = |block: &Block, p: &Point| {
+        let x = (p.x - block.base_point.x) * ins.x_scale_factor;
+        let y = (p.y - block.base_point.y) * ins.y_scale_factor;
+        let z = (p.z - block.base_point.z) * ins.z_scale_factor;
+        let mut points = vec![];
+        // only the outermost instances of an array are needed
+        for &column in &[0.0, last_column] {
+            for &row in &[0.0, last_row] {
+                let x = x + column * ins.column_spacing;
+                let y = y + row * ins.row_spacing;
+                let ocs_point = Point::new(
+                    ins.location.x + x * cos - y * sin,
+                    ins.location.y + x * sin + y * cos,
+                    ins.location.z + z,
+                );
+                points.push(ocs.transform_point(&ocs_point));
+            }
+        }
+
+        points
+    };
+    add_block_by_name(bbox, &ins.name, drawing, visited_blocks, transform);
+
+    for att in ins.attributes() {
+        let att = Entity::new(EntityType::Attribute(att.clone()));
+        if let Some(ref b) = att.bounding_box_internal(drawing, visited_blocks) {
+            include_box(bbox, b);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::entities::*;
+    use crate::*;
+
+    fn assert_box(expected_min: Point, expected_max: Point, actual: Option<BoundingBox>) {
+        let actual = actual.expect("expected a bounding box");
+        let close = |a: &Point, b: &Point| {
+            (a.x - b.x).abs() < 1e-9 && (a.y - b.y).abs() < 1e-9 && (a.z - b.z).abs() < 1e-9
+        };
+        assert!(
+            close(&expected_min, &actual.min) && close(&expected_max, &actual.max),
+            "expected {:?} - {:?} but was {:?} - {:?}",
+            expected_min,
+            expected_max,
+            actual.min,
+            actual.max
+        );
+    }
+
+    fn bounding_box(specific: EntityType) -> Option<BoundingBox> {
+        Entity::new(specific).bounding_box(&Drawing::new())
+    }
+
+    #[test]
+    fn line_bounding_box() {
+        assert_box(
+            Point::new(-1.0, 2.0, 0.0),
+            Point::new(3.0, 4.0, 5.0),
+            bounding_box(EntityType::Line(Line::new(
+                Point::new(3.0, 2.0, 0.0),
+                Point::new(-1.0, 4.0, 5.0),
+            ))),
+        );
+    }
+
+    #[test]
+    fn circle_bounding_box() {
+        assert_box(
+            Point::new(0.0, 1.0, 0.0),
+            Point::new(2.0, 3.0, 0.0),
+            bounding_box(EntityType::Circle(Circle::new(
+                Point::new(1.0, 2.0, 0.0),
+                1.0,
+            ))),
+        );
+    }
+
+    #[test]
+    fn arc_bounding_box_crossing_quadrants() {
+        // from 45 to 135 degrees only crosses the positive Y axis
+        let s = 0.5f64.sqrt();
+        assert_box(
+            Point::new(-s, s, 0.0),
+            Point::new(s, 1.0, 0.0),
+            bounding_box(EntityType::Arc(Arc::new(Point::origin(), 1.0, 45.0, 135.0))),
+        );
+
+        // from 315 to 45 degrees wraps through 0
+        assert_box(
+            Point::new(s, -s, 0.0),
+            Point::new(1.0, s, 0.0),
+            bounding_box(EntityType::Arc(Arc::new(Point::origin(), 1.0, 315.0, 45.0))),
+        );
+    }
+
+    #[test]
+    fn arc_bounding_box_with_negative_normal() {
+        // a -Z normal mirrors the X axis
+        let mut arc = Arc::new(Point::new(1.0, 0.0, 0.0), 1.0, 0.0, 90.0);
+        arc.normal = Vector::new(0.0, 0.0, -1.0);
+        assert_box(
+            Point::new(-2.0, 0.0, 0.0),
+            Point::new(-1.0, 1.0, 0.0),
+            bounding_box(EntityType::Arc(arc)),
+        );
+    }
+
+    #[test]
+    fn ellipse_bounding_box() {
+        let ellipse = Ellipse {
+            center: Point::new(1.0, 1.0, 0.0),
+            major_axis: Vector::new(2.0, 0.0, 0.0),
+            minor_axis_ratio: 0.5,
+            ..Default::default()
+        };
+        assert_box(
+            Point::new(-1.0, 0.0, 0.0),
+            Point::new(3.0, 2.0, 0.0),
+            bounding_box(EntityType::Ellipse(ellipse)),
+        );
+    }
+
+    #[test]
+    fn bulged_lw_polyline_bounding_box() {
+        // a half circle from (0,0) to (2,0) bulging below the X axis
+        let mut poly = LwPolyline::default();
+        poly.vertices.push(LwPolylineVertex {
+            x: 0.0,
+            y: 0.0,
+            bulge: 1.0,
+            ..Default::default()
+        });
+        poly.vertices.push(LwPolylineVertex {
+            x: 2.0,
+            y: 0.0,
+            ..Default::default()
+        });
+        assert_box(
+            Point::new(0.0, -1.0, 0.0),
+            Point::new(2.0, 0.0, 0.0),
+            bounding_box(EntityType::LwPolyline(poly)),
+        );
+    }
+
+    #[test]
+    fn line_with_thickness_bounding_box() {
+        let mut line = Line::new(Point::origin(), Point::new(1.0, 0.0, 0.0));
+        line.thickness = 2.0;
+        assert_box(
+            Point::origin(),
+            Point::new(1.0, 0.0, 2.0),
+            bounding_box(EntityType::Line(line)),
+        );
+    }
+
+    #[test]
+    fn text_bounding_box() {
+        let text = Text {
+            location: Point::new(1.0, 1.0, 0.0),
+            text_height: 2.0,
+            value: String::from("abc"),
+            ..Default::default()
+        };
+        assert_box(
+            Point::new(1.0, 1.0, 0.0),
+            Point::new(7.0, 3.0, 0.0),
+            bounding_box(EntityType::Text(text)),
+        );
+    }
+
+    #[test]
+    fn m_text_bounding_box_ignores_formatting_codes() {
+        let mtext = MText {
+            initial_text_height: 1.0,
+            line_spacing_factor: 1.0,
+            reference_rectangle_width: 0.0,
+            vertical_height: 0.0,
+            text: String::from("{\\fArial|b1;ab}\\Pc"),
+            ..Default::default()
+        };
+        assert_box(
+            Point::new(0.0, -8.0 / 3.0, 0.0),
+            Point::new(2.0, 0.0, 0.0),
+            bounding_box(EntityType::MText(mtext)),
+        );
+    }
+
+    #[test]
+    fn unbounded_entity_has_no_bounding_box() {
+        assert_eq!(None, bounding_box(EntityType::XLine(Default::default())));
+    }
+
+    #[test]
+    fn insert_bounding_box_through_block() {
+        let mut drawing = Drawing::new();
+        let mut block = Block {
+            name: String::from("b"),
+            base_point: Point::new(1.0, 0.0, 0.0),
+            ..Default::default()
+        };
+        block.entities.push(Entity::new(EntityType::Line(Line::new(
+            Point::new(1.0, 0.0, 0.0),
+            Point::new(2.0, 1.0, 0.0),
+        ))));
+        drawing.add_block(block);
+
+        let ins = Insert {
+            name: String::from("b"),
+            location: Point::new(10.0, 10.0, 0.0),
+            x_scale_factor: 2.0,
+            rotation: 90.0,
+            column_count: 2,
+            column_spacing: 5.0,
+            ..Default::default()
+        };
+        let ent = Entity::new(EntityType::Insert(ins));
+
+        // the block is (0,0)-(2,1) after scaling, rotated 90 degrees, with a second column 5 units
+        // further along the rotated X axis
+        assert_box(
+            Point::new(9.0, 10.0, 0.0),
+            Point::new(10.0, 17.0, 0.0),
+            ent.bounding_box(&drawing),
+        );
+    }
+
+    #[test]
+    fn self_referencing_insert_terminates() {
+        let mut drawing = Drawing::new();
+        let mut block = Block {
+            name: String::from("b"),
+            ..Default::default()
+        };
+        block.entities.push(Entity::new(EntityType::Line(Line::new(
+            Point::origin(),
+            Point::new(1.0, 1.0, 0.0),
+        ))));
+        block.entities.push(Entity::new(EntityType::Insert(Insert {
+            name: String::from("b"),
+            location: Point::new(5.0, 5.0, 0.0),
+            ..Default::default()
+        })));
+        drawing.add_block(block);
+        let block = drawing.blocks().next().unwrap();
+        assert_box(
+            Point::origin(),
+            Point::new(1.0, 1.0, 0.0),
+            block.bounding_box(&drawing),
+        );
+    }
+
+    #[test]
+    fn drawing_extents() {
+        let mut drawing = Drawing::new();
+        assert_eq!(None, drawing.extents());
+        drawing.add_entity(Entity::new(EntityType::Line(Line::new(
+            Point::new(-1.0, -2.0, 0.0),
+            Point::new(0.0, 0.0, 0.0),
+        ))));
+        drawing.add_entity(Entity::new(EntityType::Circle(Circle::new(
+            Point::new(5.0, 5.0, 0.0),
+            1.0,
+        ))));
+        assert_box(
+            Point::new(-1.0, -2.0, 0.0),
+            Point::new(6.0, 6.0, 0.0),
+            drawing.extents(),
+        );
+
+        drawing.update_extents();
+        assert_eq!(
+            Point::new(-1.0, -2.0, 0.0),
+            drawing.header.minimum_drawing_extents
+        );
+        assert_eq!(
+            Point::new(6.0, 6.0, 0.0),
+            drawing.header.maximum_drawing_extents
+        );
+    }
+}
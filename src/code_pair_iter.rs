@@ -1,4 +1,4 @@
-use crate::{CodePair, CodePairValue, DxfError, DxfResult, ExpectedType};
+use crate::{CodePair, CodePairValue, DxfError, DxfResult, ExpectedType, FilePosition};
 
 use crate::code_pair_value::un_escape_ascii_to_unicode;
 use crate::helper_functions::*;
@@ -15,6 +15,8 @@ pub(crate) struct CodePairIter<T: Read> {
     returned_binary_pair: bool,
     binary_detection_complete: bool,
     offset: usize,
+    last_code: Option<i32>,
+    read_doubles_as_text: bool,
 }
 
 impl<T: Read> CodePairIter<T> {
@@ -29,11 +31,29 @@ impl<T: Read> CodePairIter<T> {
             returned_binary_pair: false,
             binary_detection_complete: false,
             offset: 0,
+            last_code: None,
+            read_doubles_as_text: false,
         }
     }
     pub fn read_as_utf8(&mut self) {
         self.string_encoding = encoding_rs::UTF_8;
     }
+    /// When set, doubles are returned as strings; from a text file that is the original text so
+    /// they can be written back exactly as they appeared.
+    pub fn set_read_doubles_as_text(&mut self, value: bool) {
+        self.read_doubles_as_text = value;
+    }
+    pub fn position(&self) -> FilePosition {
+        if self.read_as_text {
+            FilePosition::Line(self.offset)
+        } else {
+            FilePosition::ByteOffset(self.offset)
+        }
+    }
+    /// The code of the most recently read code pair, even if its value couldn't be read.
+    pub fn last_code(&self) -> Option<i32> {
+        self.last_code
+    }
     fn detect_binary_or_text_file(&mut self) -> DxfResult<()> {
         match &*self.first_line {
             "AutoCAD Binary DXF" => {
@@ -82,7 +102,16 @@ impl<T: Read> CodePairIter<T> {
         }
 
         let code_offset = self.offset;
-        let code = try_into_option!(parse_i32(String::from(code_line), code_offset));
+        let code = match parse_i32(String::from(code_line), code_offset) {
+            Ok(code) => code,
+            Err(e) => {
+                // swallow the value so the next read starts at a code
+                self.offset += 1;
+                let _ = read_line(&mut self.reader, false, self.string_encoding);
+                return Some(Err(e));
+            }
+        };
+        self.last_code = Some(code);
 
         // Read value.  If no line is available die horribly.
         self.offset += 1;
@@ -110,6 +139,10 @@ impl<T: Read> CodePairIter<T> {
             ExpectedType::Short => {
                 CodePairValue::Short(try_into_option!(parse_i16(value_line, self.offset)))
             }
+            ExpectedType::Double if self.read_doubles_as_text => {
+                try_into_option!(parse_f64(value_line.clone(), self.offset));
+                CodePairValue::Str(value_line)
+            }
             ExpectedType::Double => {
                 CodePairValue::Double(try_into_option!(parse_f64(value_line, self.offset)))
             }
@@ -148,6 +181,7 @@ impl<T: Read> CodePairIter<T> {
         }
 
         // Read value.  If no data is available die horribly.
+        self.last_code = Some(code);
         let expected_type = match ExpectedType::get_expected_type(code) {
             Some(t) => t,
             None => return Some(Err(DxfError::UnexpectedEnumValue(self.offset))),
@@ -177,6 +211,10 @@ impl<T: Read> CodePairIter<T> {
                 CodePairValue::Short(try_from_dxf_result!(read_i16(&mut self.reader))),
                 2,
             ),
+            ExpectedType::Double if self.read_doubles_as_text => (
+                CodePairValue::Str(try_from_dxf_result!(read_f64(&mut self.reader)).to_string()),
+                8,
+            ),
             ExpectedType::Double => (
                 CodePairValue::Double(try_from_dxf_result!(read_f64(&mut self.reader))),
                 8,
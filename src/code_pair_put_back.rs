@@ -2,11 +2,29 @@ use std::io::Read;
 
 use crate::code_pair_iter::CodePairIter;
 use crate::dxf_result::DxfResult;
-use crate::CodePair;
+use crate::{
+    CodePair, CodePairValue, ContextFrame, DxfError, ErrorContext, FilePosition, LoadOptions,
+    LoadWarning,
+};
 
 pub(crate) struct CodePairPutBack<T: Read> {
     top: Vec<DxfResult<CodePair>>,
     iter: CodePairIter<T>,
+    options: LoadOptions,
+    warnings: Vec<LoadWarning>,
+    /// The name of the section being read, used to describe errors.
+    current_section: Option<String>,
+    /// The type string and handle of the item being read, used to describe errors.
+    current_item: ItemState,
+    /// The item that was being read before `current_item`, restored if its `0` code pair is put
+    /// back.
+    previous_item: ItemState,
+}
+
+#[derive(Clone, Default)]
+struct ItemState {
+    name: Option<String>,
+    handle: Option<u32>,
 }
 
 impl<T: Read> CodePairPutBack<T> {
@@ -14,24 +32,157 @@ impl<T: Read> CodePairPutBack<T> {
     where
         T: Read,
     {
-        CodePairPutBack { top: vec![], iter }
+        CodePairPutBack {
+            top: vec![],
+            iter,
+            options: LoadOptions::default(),
+            warnings: vec![],
+            current_section: None,
+            current_item: ItemState::default(),
+            previous_item: ItemState::default(),
+        }
     }
     pub fn put_back(&mut self, item: DxfResult<CodePair>) {
+        if let Ok(CodePair { code: 0, .. }) = item {
+            self.current_item = self.previous_item.clone();
+        }
         self.top.push(item);
     }
     pub fn read_as_utf8(&mut self) {
         self.iter.read_as_utf8()
     }
+    /// Reads code pairs with `read`, keeping the text of any doubles as it appears in the file.
+    pub fn read_verbatim<R, F>(&mut self, read: F) -> R
+    where
+        F: FnOnce(&mut Self) -> R,
+    {
+        self.iter.set_read_doubles_as_text(true);
+        let result = read(self);
+        self.iter.set_read_doubles_as_text(false);
+        result
+    }
+    pub fn options(&self) -> &LoadOptions {
+        &self.options
+    }
+    pub fn set_options(&mut self, options: &LoadOptions) {
+        self.options = options.clone();
+    }
+    pub fn take_warnings(&mut self) -> Vec<LoadWarning> {
+        std::mem::take(&mut self.warnings)
+    }
+    pub fn file_position(&self) -> FilePosition {
+        self.iter.position()
+    }
+    /// Describes what was being read when `error` occurred, unless it's already described.
+    pub fn add_context(&self, error: DxfError) -> DxfError {
+        if error.context().is_some() {
+            return error;
+        }
+
+        let mut frames = vec![];
+        if let Some(ref section) = self.current_section {
+            frames.push(ContextFrame::Section(section.clone()));
+        }
+        if let Some(ref name) = self.current_item.name {
+            frames.push(ContextFrame::Item(name.clone()));
+        }
+        if let Some(handle) = self.current_item.handle {
+            frames.push(ContextFrame::Handle(handle));
+        }
+        if let Some(code) = self.iter.last_code() {
+            frames.push(ContextFrame::Code(code));
+        }
+        let context = ErrorContext {
+            position: Some(self.iter.position()),
+            frames,
+        };
+        DxfError::Context(Box::new(error), context)
+    }
+    fn track_item(&mut self, pair: &CodePair) {
+        match pair.code {
+            // a new entity, object, table entry, etc., or a header variable
+            0 | 9 => {
+                let name = pair.assert_string().ok();
+                if pair.code == 0 && matches!(name.as_deref(), Some("SECTION") | Some("EOF")) {
+                    self.current_section = None;
+                }
+                self.previous_item = self.current_item.clone();
+                self.current_item = ItemState { name, handle: None };
+            }
+            2 if self.current_item.name.as_deref() == Some("SECTION") => {
+                self.current_section = pair.assert_string().ok();
+            }
+            5 | 105 => self.current_item.handle = pair.as_handle().ok(),
+            _ => (),
+        }
+    }
+    /// Called when reading an item fails with `error`.  If `LoadOptions::lenient()` is set, the
+    /// error is recorded and the rest of the item is skipped with `skip_to(stop_at)`.  Otherwise
+    /// `error` is returned.
+    pub fn recover(
+        &mut self,
+        error: DxfError,
+        skipped: &str,
+        stop_at: Option<&str>,
+    ) -> DxfResult<()> {
+        let position = self.file_position();
+        let error = self.add_context(error);
+        if !self.options.is_lenient || !is_recoverable(&error) {
+            return Err(error);
+        }
+
+        self.warnings.push(LoadWarning {
+            position,
+            skipped: String::from(skipped),
+            error,
+        });
+        self.skip_to(stop_at)
+    }
+    /// Swallows code pairs until the next `0/<stop_at>`, `0/ENDSEC`, or `0/EOF` pair, or the next
+    /// `0` pair of any kind if `stop_at` is `None`.  The `0` pair is left to be read next.
+    pub fn skip_to(&mut self, stop_at: Option<&str>) -> DxfResult<()> {
+        loop {
+            match self.next() {
+                Some(Ok(pair @ CodePair { code: 0, .. })) => {
+                    let is_stop_pair = match (&pair.value, stop_at) {
+                        (CodePairValue::Str(s), Some(stop_at)) => {
+                            s == stop_at || s == "ENDSEC" || s == "EOF"
+                        }
+                        _ => true,
+                    };
+                    if is_stop_pair {
+                        self.put_back(Ok(pair));
+                        return Ok(());
+                    }
+                }
+                Some(Ok(_)) => (),
+                Some(Err(e)) if !is_recoverable(&e) => return Err(e),
+                Some(Err(_)) => (), // malformed data in the skipped item
+                None => return Err(DxfError::UnexpectedEndOfInput),
+            }
+        }
+    }
+}
+
+fn is_recoverable(error: &DxfError) -> bool {
+    !matches!(
+        error.inner(),
+        DxfError::IoError(_) | DxfError::UnexpectedEndOfInput | DxfError::InvalidBinaryFile
+    )
 }
 
 impl<T: Read> Iterator for CodePairPutBack<T> {
     type Item = DxfResult<CodePair>;
 
     fn next(&mut self) -> Option<DxfResult<CodePair>> {
-        if self.top.is_empty() {
+        let pair = if self.top.is_empty() {
             self.iter.next()
         } else {
-            Some(self.top.pop().unwrap())
+            self.top.pop()
+        };
+        if let Some(Ok(ref pair)) = pair {
+            self.track_item(pair);
         }
+        pair
     }
 }
@@ -2,6 +2,8 @@ use std::borrow::Cow;
 use std::fmt;
 use std::fmt::{Debug, Display, Formatter};
 
+use crate::FloatFormat;
+
 /// Contains the data portion of a `CodePair`.
 #[derive(PartialEq)]
 #[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
@@ -258,18 +260,5 @@ fn test_ascii_unescape() {
 
 /// Formats an `f64` value with up to 12 digits of precision, ensuring at least one trailing digit after the decimal.
 fn format_f64(val: f64) -> String {
-    // format with 12 digits of precision
-    let mut val = format!("{:.12}", val);
-
-    // trim trailing zeros
-    while val.ends_with('0') {
-        val.pop();
-    }
-
-    // ensure it doesn't end with a decimal
-    if val.ends_with('.') {
-        val.push('0');
-    }
-
-    val
+    FloatFormat::default().format(val)
 }
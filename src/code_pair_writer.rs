@@ -5,7 +5,8 @@ use self::byteorder::{LittleEndian, WriteBytesExt};
 
 use crate::code_pair_value::{escape_control_characters, escape_unicode_to_ascii};
 use crate::enums::AcadVersion;
-use crate::{CodePair, CodePairValue, DxfResult};
+use crate::helper_functions::parse_f64;
+use crate::{CodePair, CodePairValue, DxfResult, ExpectedType, FloatFormat, LineEnding};
 
 pub(crate) struct CodePairWriter<'a, T>
 where
@@ -15,6 +16,8 @@ where
     as_text: bool,
     text_as_ascii: bool,
     version: AcadVersion,
+    float_format: FloatFormat,
+    line_ending: LineEnding,
 }
 
 impl<'a, T: Write + ?Sized> CodePairWriter<'a, T> {
@@ -29,8 +32,19 @@ impl<'a, T: Write + ?Sized> CodePairWriter<'a, T> {
             as_text,
             text_as_ascii,
             version,
+            float_format: FloatFormat::default(),
+            line_ending: LineEnding::default(),
         }
     }
+    /// Sets how values are written to text files.
+    pub fn set_text_format(&mut self, float_format: FloatFormat, line_ending: LineEnding) {
+        self.float_format = float_format;
+        self.line_ending = line_ending;
+    }
+    /// The version of the drawing being written.
+    pub fn version(&self) -> AcadVersion {
+        self.version
+    }
     pub fn write_prelude(&mut self) -> DxfResult<()> {
         if !self.as_text {
             self.writer
@@ -48,9 +62,24 @@ impl<'a, T: Write + ?Sized> CodePairWriter<'a, T> {
             self.write_binary_code_pair(pair)
         }
     }
+    /// Writes a code pair that was read with its doubles kept as text.  Text files get the
+    /// original string; binary files need the parsed value.
+    pub fn write_verbatim_code_pair(&mut self, pair: &CodePair) -> DxfResult<()> {
+        match pair.value {
+            CodePairValue::Str(ref s)
+                if !self.as_text
+                    && ExpectedType::get_expected_type(pair.code) == Some(ExpectedType::Double) =>
+            {
+                let value = parse_f64(s.clone(), 0)?;
+                self.write_binary_code_pair(&CodePair::new_f64(pair.code, value))
+            }
+            _ => self.write_code_pair(pair),
+        }
+    }
     fn write_ascii_code_pair(&mut self, pair: &CodePair) -> DxfResult<()> {
+        let line_ending = self.line_ending.as_str();
         self.writer
-            .write_fmt(format_args!("{: >3}\r\n", pair.code))?;
+            .write_fmt(format_args!("{: >3}{}", pair.code, line_ending))?;
         match pair.value {
             CodePairValue::Str(ref s) => {
                 let s = escape_control_characters(&s);
@@ -59,9 +88,17 @@ impl<'a, T: Write + ?Sized> CodePairWriter<'a, T> {
                 } else {
                     s
                 };
-                self.writer.write_fmt(format_args!("{}\r\n", s))?;
+                self.writer
+                    .write_fmt(format_args!("{}{}", s, line_ending))?;
+            }
+            CodePairValue::Double(d) => {
+                let d = self.float_format.format(d);
+                self.writer
+                    .write_fmt(format_args!("{}{}", d, line_ending))?;
             }
-            _ => self.writer.write_fmt(format_args!("{}\r\n", &pair.value))?,
+            _ => self
+                .writer
+                .write_fmt(format_args!("{}{}", &pair.value, line_ending))?,
         };
         Ok(())
     }
@@ -76,6 +76,46 @@ impl Color {
             raw_value: i16::from(i),
         }
     }
+    /// Returns the red, green, and blue components of the indexed color, or `None` if the color
+    /// isn't an index, e.g., `BYLAYER`.  The index of a turned off color is still honored.
+    pub fn to_rgb(&self) -> Option<(u8, u8, u8)> {
+        match self.raw_value.abs() {
+            v @ 1..=255 => Some(ACI_PALETTE[v as usize]),
+            _ => None,
+        }
+    }
+    /// Creates a `Color` from the index whose palette value is nearest to the specified red, green,
+    /// and blue components.  Index 7 is preferred over the identical index 255 for white.
+    pub fn from_rgb_nearest(r: u8, g: u8, b: u8) -> Color {
+        let distance = |(pr, pg, pb): (u8, u8, u8)| {
+            let dr = i32::from(pr) - i32::from(r);
+            let dg = i32::from(pg) - i32::from(g);
+            let db = i32::from(pb) - i32::from(b);
+            dr * dr + dg * dg + db * db
+        };
+        // `min_by_key` keeps the first of equally near entries
+        let (best, _) = ACI_PALETTE
+            .iter()
+            .enumerate()
+            .skip(1)
+            .min_by_key(|(_, rgb)| distance(**rgb))
+            .unwrap();
+        Color::from_index(best as u8)
+    }
+    /// Returns the red, green, and blue components of a 24-bit true color value as stored in
+    /// `EntityCommon::color_24_bit`.  The upper byte is ignored.
+    pub fn true_color_to_rgb(value: i32) -> (u8, u8, u8) {
+        (
+            ((value >> 16) & 0xFF) as u8,
+            ((value >> 8) & 0xFF) as u8,
+            (value & 0xFF) as u8,
+        )
+    }
+    /// Returns the 24-bit true color value, as stored in `EntityCommon::color_24_bit`, for the
+    /// specified red, green, and blue components.
+    pub fn rgb_to_true_color(r: u8, g: u8, b: u8) -> i32 {
+        (i32::from(r) << 16) | (i32::from(g) << 8) | i32::from(b)
+    }
     pub(crate) fn get_writable_color_value(&self, layer: &Layer) -> i16 {
         let value = self.get_raw_value().abs();
         if layer.is_layer_on {
@@ -85,3 +125,108 @@ impl Color {
         }
     }
 }
+
+/// A concrete color, as resolved for an entity by `Drawing::resolve_color()`.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+pub struct ResolvedColor {
+    /// The red, green, and blue components of the color.
+    pub rgb: (u8, u8, u8),
+    /// The color index the color was resolved from, or `None` if it came from a true color.
+    /// Index 7 is conventionally drawn in white on dark backgrounds and in black on light ones.
+    pub index: Option<u8>,
+    /// The color book name of the color, e.g., `"PANTONE+ Solid Coated$PANTONE 185 C"`, or an empty
+    /// string if the color isn't from a color book.
+    pub color_name: String,
+}
+
+/// The red, green, and blue components of each AutoCAD Color Index (ACI) value.  Index `0`
+/// (BYBLOCK) is black.
+#[rustfmt::skip]
+static ACI_PALETTE: [(u8, u8, u8); 256] = [
+    (0, 0, 0), (255, 0, 0), (255, 255, 0), (0, 255, 0), (0, 255, 255), // 0-4
+    (0, 0, 255), (255, 0, 255), (255, 255, 255), (128, 128, 128), (192, 192, 192), // 5-9
+    (255, 0, 0), (255, 127, 127), (204, 0, 0), (204, 102, 102), (153, 0, 0), // 10-14
+    (153, 76, 76), (127, 0, 0), (127, 63, 63), (76, 0, 0), (76, 38, 38), // 15-19
+    (255, 63, 0), (255, 159, 127), (204, 51, 0), (204, 127, 102), (153, 38, 0), // 20-24
+    (153, 95, 76), (127, 31, 0), (127, 79, 63), (76, 19, 0), (76, 47, 38), // 25-29
+    (255, 127, 0), (255, 191, 127), (204, 102, 0), (204, 153, 102), (153, 76, 0), // 30-34
+    (153, 114, 76), (127, 63, 0), (127, 95, 63), (76, 38, 0), (76, 57, 38), // 35-39
+    (255, 191, 0), (255, 223, 127), (204, 153, 0), (204, 178, 102), (153, 114, 0), // 40-44
+    (153, 133, 76), (127, 95, 0), (127, 111, 63), (76, 57, 0), (76, 66, 38), // 45-49
+    (255, 255, 0), (255, 255, 127), (204, 204, 0), (204, 204, 102), (153, 153, 0), // 50-54
+    (153, 153, 76), (127, 127, 0), (127, 127, 63), (76, 76, 0), (76, 76, 38), // 55-59
+    (191, 255, 0), (223, 255, 127), (153, 204, 0), (178, 204, 102), (114, 153, 0), // 60-64
+    (133, 153, 76), (95, 127, 0), (111, 127, 63), (57, 76, 0), (66, 76, 38), // 65-69
+    (127, 255, 0), (191, 255, 127), (102, 204, 0), (153, 204, 102), (76, 153, 0), // 70-74
+    (114, 153, 76), (63, 127, 0), (95, 127, 63), (38, 76, 0), (57, 76, 38), // 75-79
+    (63, 255, 0), (159, 255, 127), (51, 204, 0), (127, 204, 102), (38, 153, 0), // 80-84
+    (95, 153, 76), (31, 127, 0), (79, 127, 63), (19, 76, 0), (47, 76, 38), // 85-89
+    (0, 255, 0), (127, 255, 127), (0, 204, 0), (102, 204, 102), (0, 153, 0), // 90-94
+    (76, 153, 76), (0, 127, 0), (63, 127, 63), (0, 76, 0), (38, 76, 38), // 95-99
+    (0, 255, 63), (127, 255, 159), (0, 204, 51), (102, 204, 127), (0, 153, 38), // 100-104
+    (76, 153, 95), (0, 127, 31), (63, 127, 79), (0, 76, 19), (38, 76, 47), // 105-109
+    (0, 255, 127), (127, 255, 191), (0, 204, 102), (102, 204, 153), (0, 153, 76), // 110-114
+    (76, 153, 114), (0, 127, 63), (63, 127, 95), (0, 76, 38), (38, 76, 57), // 115-119
+    (0, 255, 191), (127, 255, 223), (0, 204, 153), (102, 204, 178), (0, 153, 114), // 120-124
+    (76, 153, 133), (0, 127, 95), (63, 127, 111), (0, 76, 57), (38, 76, 66), // 125-129
+    (0, 255, 255), (127, 255, 255), (0, 204, 204), (102, 204, 204), (0, 153, 153), // 130-134
+    (76, 153, 153), (0, 127, 127), (63, 127, 127), (0, 76, 76), (38, 76, 76), // 135-139
+    (0, 191, 255), (127, 223, 255), (0, 153, 204), (102, 178, 204), (0, 114, 153), // 140-144
+    (76, 133, 153), (0, 95, 127), (63, 111, 127), (0, 57, 76), (38, 66, 76), // 145-149
+    (0, 127, 255), (127, 191, 255), (0, 102, 204), (102, 153, 204), (0, 76, 153), // 150-154
+    (76, 114, 153), (0, 63, 127), (63, 95, 127), (0, 38, 76), (38, 57, 76), // 155-159
+    (0, 63, 255), (127, 159, 255), (0, 51, 204), (102, 127, 204), (0, 38, 153), // 160-164
+    (76, 95, 153), (0, 31, 127), (63, 79, 127), (0, 19, 76), (38, 47, 76), // 165-169
+    (0, 0, 255), (127, 127, 255), (0, 0, 204), (102, 102, 204), (0, 0, 153), // 170-174
+    (76, 76, 153), (0, 0, 127), (63, 63, 127), (0, 0, 76), (38, 38, 76), // 175-179
+    (63, 0, 255), (159, 127, 255), (51, 0, 204), (127, 102, 204), (38, 0, 153), // 180-184
+    (95, 76, 153), (31, 0, 127), (79, 63, 127), (19, 0, 76), (47, 38, 76), // 185-189
+    (127, 0, 255), (191, 127, 255), (102, 0, 204), (153, 102, 204), (76, 0, 153), // 190-194
+    (114, 76, 153), (63, 0, 127), (95, 63, 127), (38, 0, 76), (57, 38, 76), // 195-199
+    (191, 0, 255), (223, 127, 255), (153, 0, 204), (178, 102, 204), (114, 0, 153), // 200-204
+    (133, 76, 153), (95, 0, 127), (111, 63, 127), (57, 0, 76), (66, 38, 76), // 205-209
+    (255, 0, 255), (255, 127, 255), (204, 0, 204), (204, 102, 204), (153, 0, 153), // 210-214
+    (153, 76, 153), (127, 0, 127), (127, 63, 127), (76, 0, 76), (76, 38, 76), // 215-219
+    (255, 0, 191), (255, 127, 223), (204, 0, 153), (204, 102, 178), (153, 0, 114), // 220-224
+    (153, 76, 133), (127, 0, 95), (127, 63, 111), (76, 0, 57), (76, 38, 66), // 225-229
+    (255, 0, 127), (255, 127, 191), (204, 0, 102), (204, 102, 153), (153, 0, 76), // 230-234
+    (153, 76, 114), (127, 0, 63), (127, 63, 95), (76, 0, 38), (76, 38, 57), // 235-239
+    (255, 0, 63), (255, 127, 159), (204, 0, 51), (204, 102, 127), (153, 0, 38), // 240-244
+    (153, 76, 95), (127, 0, 31), (127, 63, 79), (76, 0, 19), (76, 38, 47), // 245-249
+    (51, 51, 51), (80, 80, 80), (105, 105, 105), (130, 130, 130), (190, 190, 190), // 250-254
+    (255, 255, 255), // 255-255
+];
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn index_to_rgb() {
+        assert_eq!(Some((255, 0, 0)), Color::from_index(1).to_rgb());
+        assert_eq!(Some((255, 127, 127)), Color::from_index(11).to_rgb());
+        assert_eq!(Some((0, 76, 153)), Color::from_index(154).to_rgb());
+        assert_eq!(Some((51, 51, 51)), Color::from_index(250).to_rgb());
+        assert_eq!(None, Color::by_layer().to_rgb());
+        assert_eq!(None, Color::by_block().to_rgb());
+
+        // a turned off layer color
+        assert_eq!(Some((0, 255, 0)), Color::from_raw_value(-3).to_rgb());
+    }
+
+    #[test]
+    fn nearest_index_from_rgb() {
+        assert_eq!(Some(1), Color::from_rgb_nearest(250, 5, 5).index());
+        assert_eq!(Some(7), Color::from_rgb_nearest(255, 255, 255).index());
+        assert_eq!(Some(154), Color::from_rgb_nearest(0, 76, 153).index());
+    }
+
+    #[test]
+    fn true_color_round_trip() {
+        assert_eq!(0x12_34_56, Color::rgb_to_true_color(0x12, 0x34, 0x56));
+        assert_eq!((0x12, 0x34, 0x56), Color::true_color_to_rgb(0x12_34_56));
+        // the upper byte is ignored
+        assert_eq!((0x12, 0x34, 0x56), Color::true_color_to_rgb(0x4212_3456));
+    }
+}
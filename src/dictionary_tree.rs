@@ -0,0 +1,548 @@
+// navigation and editing of the tree of dictionaries rooted at the named object dictionary
+
+use std::collections::HashMap;
+
+use crate::objects::*;
+use crate::{Drawing, DxfError, DxfResult};
+
+/// A `Dictionary` or `DictionaryWithDefault` in a `Drawing`.  Entries are found by name, and
+/// nested dictionaries by a `/`-separated path, e.g., `ACAD_GROUP/MyGroup`.  See
+/// `Drawing::named_object_dictionary()`.
+#[derive(Clone, Copy)]
+pub struct DictionaryNode<'a> {
+    drawing: &'a Drawing,
+    object: &'a Object,
+}
+
+impl<'a> DictionaryNode<'a> {
+    /// Returns a `DictionaryNode` for the object, if it's a `Dictionary` or
+    /// `DictionaryWithDefault`.
+    pub fn new(drawing: &'a Drawing, object: &'a Object) -> Option<Self> {
+        dictionary_entries(&object.specific).map(|_| DictionaryNode { drawing, object })
+    }
+    /// The dictionary object.
+    pub fn object(&self) -> &'a Object {
+        self.object
+    }
+    /// The handle of the dictionary object.
+    pub fn handle(&self) -> u32 {
+        self.object.common.handle
+    }
+    /// Returns the entry names in sorted order.
+    pub fn names(&self) -> Vec<&'a str> {
+        let mut names = self
+            .entries()
+            .keys()
+            .map(|k| k.as_str())
+            .collect::<Vec<_>>();
+        names.sort_unstable();
+        names
+    }
+    /// Returns the handle stored under `name`.  A `DictionaryWithDefault` returns its default
+    /// handle for names it doesn't contain.
+    pub fn get_handle(&self, name: &str) -> Option<u32> {
+        match self.entries().get(name) {
+            Some(handle) => Some(*handle),
+            None => match self.object.specific {
+                ObjectType::DictionaryWithDefault(ref d) if d.default_handle != 0 => {
+                    Some(d.default_handle)
+                }
+                _ => None,
+            },
+        }
+    }
+    /// Returns the object stored under `name`.
+    pub fn get(&self, name: &str) -> Option<&'a Object> {
+        self.get_handle(name)
+            .and_then(|handle| object_by_handle(self.drawing, handle))
+    }
+    /// Returns the dictionary stored under `name`.
+    pub fn get_dictionary(&self, name: &str) -> Option<DictionaryNode<'a>> {
+        self.get(name)
+            .and_then(|object| DictionaryNode::new(self.drawing, object))
+    }
+    /// Returns the object at the `/`-separated path of entry names, e.g., `ACAD_GROUP/MyGroup`.
+    pub fn get_path(&self, path: &str) -> Option<&'a Object> {
+        let mut names = path.split('/');
+        let first = self.get(names.next()?)?;
+        names.try_fold(first, |object, name| {
+            DictionaryNode::new(self.drawing, object)?.get(name)
+        })
+    }
+    /// Returns the `Group` at the specified path, e.g., `ACAD_GROUP/MyGroup`.
+    pub fn get_group(&self, path: &str) -> Option<&'a Group> {
+        match self.get_path(path)?.specific {
+            ObjectType::Group(ref group) => Some(group),
+            _ => None,
+        }
+    }
+    /// Returns the `MLineStyle` at the specified path, e.g., `ACAD_MLINESTYLE/STANDARD`.
+    pub fn get_mline_style(&self, path: &str) -> Option<&'a MLineStyle> {
+        match self.get_path(path)?.specific {
+            ObjectType::MLineStyle(ref style) => Some(style),
+            _ => None,
+        }
+    }
+    /// Returns the `Layout` at the specified path, e.g., `ACAD_LAYOUT/Layout1`.
+    pub fn get_layout(&self, path: &str) -> Option<&'a Layout> {
+        match self.get_path(path)?.specific {
+            ObjectType::Layout(ref layout) => Some(layout),
+            _ => None,
+        }
+    }
+    fn entries(&self) -> &'a HashMap<String, u32> {
+        dictionary_entries(&self.object.specific).unwrap()
+    }
+}
+
+impl Drawing {
+    /// Returns the named object dictionary, the root of the dictionary tree and the first object
+    /// in the `OBJECTS` section.
+    pub fn named_object_dictionary(&self) -> Option<DictionaryNode<'_>> {
+        let handle = self.root_dictionary_handle()?;
+        DictionaryNode::new(self, object_by_handle(self, handle)?)
+    }
+    /// Adds an object to the dictionary tree at the `/`-separated path, e.g., `ACAD_GROUP/MyGroup`,
+    /// and makes its dictionary its owner.  The named object dictionary and any missing
+    /// dictionaries along the path are added, an existing entry with the same name is removed with
+    /// `remove_named_object()`, and an empty name is replaced with an anonymous one, e.g., `*A1`.
+    /// The first entry of a `DictionaryWithDefault` becomes its default.  Fails with
+    /// `DxfError::WrongItemType` if the path passes through an object that isn't a dictionary.
+    pub fn add_named_object(&mut self, path: &str, mut object: Object) -> DxfResult<&Object> {
+        let mut names = path.split('/').collect::<Vec<_>>();
+        let name = names.pop().unwrap_or_default();
+        let mut parent = self.ensure_root_dictionary(&mut vec![]);
+        for dictionary_name in names {
+            let existing = self
+                .dictionary_entries(parent)
+                .and_then(|entries| entries.get(dictionary_name).cloned())
+                .and_then(|handle| object_by_handle(self, handle));
+            parent = match existing {
+                Some(existing) => match dictionary_entries(&existing.specific) {
+                    Some(_) => existing.common.handle,
+                    None => return Err(DxfError::WrongItemType),
+                },
+                None => self.ensure_dictionary_entry(parent, dictionary_name, &mut vec![]),
+            };
+        }
+
+        let is_present =
+            matches!(self.dictionary_entries(parent), Some(entries) if entries.contains_key(name));
+        if !name.is_empty() && is_present {
+            self.remove_named_object(path);
+        }
+
+        object.common.__owner_handle = parent;
+        let handle = self.add_object(object).common.handle;
+        self.add_dictionary_entry(parent, name, handle);
+        Ok(object_by_handle(self, handle).unwrap())
+    }
+    /// Removes the entry at the `/`-separated path from its dictionary.  If that dictionary owns
+    /// the object, the object is removed from the `Drawing` along with everything it owns through
+    /// nested dictionaries, and any other entries or `DictionaryWithDefault` defaults that refer
+    /// to the removed objects are dropped.  Returns the removed object, or `None` if there was no
+    /// such entry or the object isn't owned by the dictionary.
+    pub fn remove_named_object(&mut self, path: &str) -> Option<Object> {
+        let (parent, name) = match path.rfind('/') {
+            Some(index) => {
+                let parent = self.named_object_dictionary()?.get_path(&path[..index])?;
+                (parent.common.handle, &path[index + 1..])
+            }
+            None => (self.root_dictionary_handle()?, path),
+        };
+        let handle = *self.dictionary_entries(parent)?.get(name)?;
+        if let Some(object) = self.objects_mut().find(|o| o.common.handle == parent) {
+            if let Some(entries) = dictionary_entries_mut(&mut object.specific) {
+                entries.remove(name);
+            }
+            reset_default_handle(&mut object.specific, handle);
+        }
+
+        let mut removed = vec![];
+        self.remove_owned_object(parent, handle, &mut removed);
+        let removed_handles = removed.iter().map(|o| o.common.handle).collect::<Vec<_>>();
+        for object in self.objects_mut() {
+            for removed_handle in &removed_handles {
+                if let Some(entries) = dictionary_entries_mut(&mut object.specific) {
+                    entries.retain(|_, h| h != removed_handle);
+                }
+                reset_default_handle(&mut object.specific, *removed_handle);
+            }
+        }
+
+        if removed.is_empty() {
+            None
+        } else {
+            Some(removed.swap_remove(0))
+        }
+    }
+    /// Returns the handle of the named object dictionary, which is the first object.
+    pub(crate) fn root_dictionary_handle(&self) -> Option<u32> {
+        let object = self.objects().next()?;
+        match object.specific {
+            ObjectType::Dictionary(_) if object.common.__owner_handle == 0 => {
+                Some(object.common.handle)
+            }
+            _ => None,
+        }
+    }
+    /// Returns the entries of the dictionary with the specified handle.
+    pub(crate) fn dictionary_entries(&self, handle: u32) -> Option<&HashMap<String, u32>> {
+        object_by_handle(self, handle).and_then(|o| dictionary_entries(&o.specific))
+    }
+    /// Adds `handle` to the dictionary and makes it the owner of that object if it doesn't already
+    /// have one.  An empty or duplicate `name` is replaced with an anonymous one, e.g., `*A1`.
+    pub(crate) fn add_dictionary_entry(&mut self, dictionary: u32, name: &str, handle: u32) {
+        if let Some(object) = self.objects_mut().find(|o| o.common.handle == dictionary) {
+            if let ObjectType::DictionaryWithDefault(ref mut d) = object.specific {
+                if d.default_handle == 0 {
+                    d.default_handle = handle;
+                }
+            }
+            if let Some(entries) = dictionary_entries_mut(&mut object.specific) {
+                let mut name = String::from(name);
+                let mut index = 0;
+                while name.is_empty() || entries.contains_key(&name) {
+                    index += 1;
+                    name = format!("*A{}", index);
+                }
+                entries.insert(name, handle);
+            }
+        }
+        if let Some(object) = self.objects_mut().find(|o| o.common.handle == handle) {
+            if object.common.__owner_handle == 0 {
+                object.common.__owner_handle = dictionary;
+            }
+        }
+    }
+    /// Removes the object if it's owned by `owner`, then does the same for the entries of the
+    /// removed object if it's a dictionary.
+    fn remove_owned_object(&mut self, owner: u32, handle: u32, removed: &mut Vec<Object>) {
+        let index = match self
+            .objects()
+            .position(|o| o.common.handle == handle && o.common.__owner_handle == owner)
+        {
+            Some(index) => index,
+            None => return,
+        };
+        let object = self.remove_object(index).unwrap();
+        let children = dictionary_entries(&object.specific)
+            .map(|entries| entries.values().cloned().collect::<Vec<_>>())
+            .unwrap_or_default();
+        removed.push(object);
+        for child in children {
+            self.remove_owned_object(handle, child, removed);
+        }
+    }
+}
+
+fn object_by_handle(drawing: &Drawing, handle: u32) -> Option<&Object> {
+    drawing.objects().find(|o| o.common.handle == handle)
+}
+
+fn dictionary_entries(specific: &ObjectType) -> Option<&HashMap<String, u32>> {
+    match specific {
+        ObjectType::Dictionary(ref d) => Some(&d.value_handles),
+        ObjectType::DictionaryWithDefault(ref d) => Some(&d.value_handles),
+        _ => None,
+    }
+}
+
+fn dictionary_entries_mut(specific: &mut ObjectType) -> Option<&mut HashMap<String, u32>> {
+    match specific {
+        ObjectType::Dictionary(ref mut d) => Some(&mut d.value_handles),
+        ObjectType::DictionaryWithDefault(ref mut d) => Some(&mut d.value_handles),
+        _ => None,
+    }
+}
+
+/// Moves the default of a `DictionaryWithDefault` whose default entry was removed to its first
+/// remaining entry by name.
+fn reset_default_handle(specific: &mut ObjectType, removed_handle: u32) {
+    if let ObjectType::DictionaryWithDefault(ref mut d) = specific {
+        if d.default_handle == removed_handle
+            && !d.value_handles.values().any(|h| *h == removed_handle)
+        {
+            d.default_handle = d
+                .value_handles
+                .iter()
+                .min_by_key(|(name, _)| name.as_str())
+                .map_or(0, |(_, h)| *h);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::helper_functions::tests::*;
+    use crate::objects::*;
+    use crate::*;
+
+    fn drawing_with_groups() -> Drawing {
+        from_section(
+            "OBJECTS",
+            vec![
+                "  0",
+                "DICTIONARY",
+                "  5",
+                "A",
+                "330",
+                "0",
+                "100",
+                "AcDbDictionary",
+                "  3",
+                "ACAD_GROUP",
+                "350",
+                "B",
+                "  3",
+                "ACAD_MLINESTYLE",
+                "350",
+                "D",
+                "  0",
+                "DICTIONARY",
+                "  5",
+                "B",
+                "330",
+                "A",
+                "100",
+                "AcDbDictionary",
+                "  3",
+                "MyGroup",
+                "350",
+                "C",
+                "  0",
+                "GROUP",
+                "  5",
+                "C",
+                "330",
+                "B",
+                "100",
+                "AcDbGroup",
+                "300",
+                "my group",
+                "  0",
+                "ACDBDICTIONARYWDFLT",
+                "  5",
+                "D",
+                "330",
+                "A",
+                "100",
+                "AcDbDictionary",
+                "  3",
+                "STANDARD",
+                "350",
+                "E",
+                "100",
+                "AcDbDictionaryWithDefault",
+                "340",
+                "E",
+                "  0",
+                "MLINESTYLE",
+                "  5",
+                "E",
+                "330",
+                "D",
+                "100",
+                "AcDbMlineStyle",
+                "  2",
+                "STANDARD",
+            ]
+            .join("\r\n")
+            .as_str(),
+        )
+    }
+
+    fn object_owner(drawing: &Drawing, handle: u32) -> u32 {
+        drawing
+            .objects()
+            .find(|o| o.common.handle == handle)
+            .unwrap()
+            .common
+            .__owner_handle
+    }
+
+    #[test]
+    fn get_items_by_path() {
+        let drawing = drawing_with_groups();
+        let nod = drawing.named_object_dictionary().unwrap();
+        assert_eq!(0xA, nod.handle());
+        assert_eq!(vec!["ACAD_GROUP", "ACAD_MLINESTYLE"], nod.names());
+        assert_eq!(
+            "my group",
+            nod.get_group("ACAD_GROUP/MyGroup").unwrap().description
+        );
+        assert_eq!(
+            "STANDARD",
+            nod.get_mline_style("ACAD_MLINESTYLE/STANDARD")
+                .unwrap()
+                .style_name
+        );
+        assert!(nod.get_layout("ACAD_GROUP/MyGroup").is_none());
+        assert!(nod.get_path("ACAD_GROUP/MyGroup/Nested").is_none());
+        assert!(nod.get_path("ACAD_LAYOUT").is_none());
+        assert_eq!(0xB, nod.get_dictionary("ACAD_GROUP").unwrap().handle());
+    }
+
+    #[test]
+    fn dictionary_with_default_returns_default_for_missing_names() {
+        let drawing = drawing_with_groups();
+        let nod = drawing.named_object_dictionary().unwrap();
+        let styles = nod.get_dictionary("ACAD_MLINESTYLE").unwrap();
+        assert_eq!(Some(0xE), styles.get_handle("missing"));
+        assert_eq!(None, nod.get_handle("missing"));
+    }
+
+    #[test]
+    fn add_named_object_adds_dictionaries() {
+        let mut drawing = Drawing::new();
+        let handle = drawing
+            .add_named_object(
+                "ACAD_GROUP/MyGroup",
+                Object::new(ObjectType::Group(Group {
+                    description: String::from("my group"),
+                    ..Default::default()
+                })),
+            )
+            .unwrap()
+            .common
+            .handle;
+
+        let nod = drawing.named_object_dictionary().unwrap();
+        let groups = nod.get_dictionary("ACAD_GROUP").unwrap();
+        assert_eq!(Some(handle), groups.get_handle("MyGroup"));
+        assert_eq!(nod.handle(), groups.object().common.__owner_handle);
+        assert_eq!(groups.handle(), object_owner(&drawing, handle));
+        assert_eq!(
+            "my group",
+            nod.get_group("ACAD_GROUP/MyGroup").unwrap().description
+        );
+    }
+
+    #[test]
+    fn add_named_object_replaces_existing_entry() {
+        let mut drawing = drawing_with_groups();
+        let object_count = drawing.objects().count();
+        let handle = drawing
+            .add_named_object(
+                "ACAD_GROUP/MyGroup",
+                Object::new(ObjectType::Group(Default::default())),
+            )
+            .unwrap()
+            .common
+            .handle;
+        assert_eq!(object_count, drawing.objects().count());
+        assert!(drawing.objects().all(|o| o.common.handle != 0xC));
+        let nod = drawing.named_object_dictionary().unwrap();
+        assert_eq!(
+            Some(handle),
+            nod.get_dictionary("ACAD_GROUP")
+                .unwrap()
+                .get_handle("MyGroup")
+        );
+    }
+
+    #[test]
+    fn add_named_object_with_empty_name_is_anonymous() {
+        let mut drawing = drawing_with_groups();
+        drawing
+            .add_named_object(
+                "ACAD_GROUP/",
+                Object::new(ObjectType::Group(Default::default())),
+            )
+            .unwrap();
+        let nod = drawing.named_object_dictionary().unwrap();
+        assert_eq!(
+            vec!["*A1", "MyGroup"],
+            nod.get_dictionary("ACAD_GROUP").unwrap().names()
+        );
+    }
+
+    #[test]
+    fn add_named_object_through_non_dictionary_fails() {
+        let mut drawing = drawing_with_groups();
+        let result = drawing.add_named_object(
+            "ACAD_GROUP/MyGroup/Nested",
+            Object::new(ObjectType::Group(Default::default())),
+        );
+        match result {
+            Err(DxfError::WrongItemType) => (),
+            _ => panic!("expected a WrongItemType error"),
+        }
+    }
+
+    #[test]
+    fn add_named_object_to_dictionary_with_default_sets_default() {
+        let mut drawing = Drawing::new();
+        drawing
+            .add_named_object(
+                "STYLES",
+                Object::new(ObjectType::DictionaryWithDefault(Default::default())),
+            )
+            .unwrap();
+        let handle = drawing
+            .add_named_object(
+                "STYLES/First",
+                Object::new(ObjectType::MLineStyle(Default::default())),
+            )
+            .unwrap()
+            .common
+            .handle;
+        drawing
+            .add_named_object(
+                "STYLES/Second",
+                Object::new(ObjectType::MLineStyle(Default::default())),
+            )
+            .unwrap();
+        let nod = drawing.named_object_dictionary().unwrap();
+        let styles = nod.get_dictionary("STYLES").unwrap();
+        assert_eq!(Some(handle), styles.get_handle("missing"));
+    }
+
+    #[test]
+    fn remove_named_object_removes_owned_objects() {
+        let mut drawing = drawing_with_groups();
+        let removed = drawing.remove_named_object("ACAD_GROUP").unwrap();
+        assert_eq!(0xB, removed.common.handle);
+        assert!(drawing
+            .objects()
+            .all(|o| o.common.handle != 0xB && o.common.handle != 0xC));
+        let nod = drawing.named_object_dictionary().unwrap();
+        assert_eq!(vec!["ACAD_MLINESTYLE"], nod.names());
+        assert!(drawing.remove_named_object("ACAD_GROUP").is_none());
+    }
+
+    #[test]
+    fn remove_named_object_resets_dictionary_default() {
+        let mut drawing = drawing_with_groups();
+        let removed = drawing
+            .remove_named_object("ACAD_MLINESTYLE/STANDARD")
+            .unwrap();
+        assert_eq!(0xE, removed.common.handle);
+        let nod = drawing.named_object_dictionary().unwrap();
+        let styles = nod.get_dictionary("ACAD_MLINESTYLE").unwrap();
+        assert_eq!(None, styles.get_handle("STANDARD"));
+        match styles.object().specific {
+            ObjectType::DictionaryWithDefault(ref d) => assert_eq!(0, d.default_handle),
+            _ => panic!("expected a DictionaryWithDefault"),
+        }
+    }
+
+    #[test]
+    fn remove_named_object_drops_other_entries_for_removed_objects() {
+        let mut drawing = drawing_with_groups();
+        drawing.add_dictionary_entry(0xB, "Alias", 0xC);
+        assert!(drawing.remove_named_object("ACAD_GROUP/Alias").is_some());
+        let nod = drawing.named_object_dictionary().unwrap();
+        assert!(nod.get_path("ACAD_GROUP/MyGroup").is_none());
+    }
+
+    #[test]
+    fn remove_named_object_keeps_objects_owned_elsewhere() {
+        let mut drawing = drawing_with_groups();
+        drawing.add_dictionary_entry(0xA, "Alias", 0xC);
+        assert!(drawing.remove_named_object("Alias").is_none());
+        let nod = drawing.named_object_dictionary().unwrap();
+        assert!(nod.get_path("Alias").is_none());
+        assert!(nod.get_path("ACAD_GROUP/MyGroup").is_some());
+    }
+}
@@ -15,7 +15,10 @@ use crate::header::*;
 use crate::objects::*;
 use crate::tables::*;
 
-use crate::{CodePair, CodePairValue, DxfError, DxfResult};
+use crate::{
+    CodePair, CodePairValue, Color, ConversionNote, DxfError, DxfResult, LoadOptions, LoadWarning,
+    ResolvedColor, SaveOptions, XData,
+};
 
 use crate::dxb_reader::DxbReader;
 use crate::dxb_writer::DxbWriter;
@@ -25,6 +28,8 @@ use crate::object_iter::ObjectIter;
 
 use crate::block::Block;
 use crate::class::Class;
+use crate::unknown_section::UnknownSection;
+use crate::version_conversion::is_object_supported;
 
 use crate::code_pair_iter::CodePairIter;
 use crate::code_pair_writer::CodePairWriter;
@@ -39,7 +44,19 @@ use std::path::Path;
 
 pub(crate) const AUTO_REPLACE_HANDLE: u32 = 0xFFFF_FFFF;
 
+/// The sections read and written by `Drawing`, in the order they're written.
+const SUPPORTED_SECTIONS: [&str; 7] = [
+    "HEADER",
+    "CLASSES",
+    "TABLES",
+    "BLOCKS",
+    "ENTITIES",
+    "OBJECTS",
+    "THUMBNAILIMAGE",
+];
+
 /// Represents a DXF drawing.
+#[derive(Clone)]
 #[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
 pub struct Drawing {
     /// The drawing's header.  Contains various drawing-specific values and settings.
@@ -78,6 +95,10 @@ pub struct Drawing {
     /// The thumbnail image preview of the drawing.
     #[cfg_attr(feature = "serialize", serde(skip))]
     pub thumbnail: Option<DynamicImage>,
+
+    /// Sections not otherwise supported by this library.  These are preserved verbatim so that
+    /// they can be written back in their original position.
+    pub unknown_sections: Vec<UnknownSection>,
 }
 
 // public implementation
@@ -100,6 +121,7 @@ impl Drawing {
             __entities: vec![],
             __objects: vec![],
             thumbnail: None,
+            unknown_sections: vec![],
         };
         drawing.normalize();
         drawing
@@ -116,7 +138,21 @@ impl Drawing {
     where
         T: Read + ?Sized,
     {
-        let first_line = match read_line(reader, true, encoding) {
+        let options = LoadOptions::new().encoding(encoding);
+        let (drawing, _) = Drawing::load_with_options(reader, &options)?;
+        Ok(drawing)
+    }
+    /// Loads a `Drawing` from anything that implements the `Read` trait using the specified options.
+    /// Any malformed items that were skipped because of `LoadOptions::lenient()` are returned with
+    /// the drawing.
+    pub fn load_with_options<T>(
+        reader: &mut T,
+        options: &LoadOptions,
+    ) -> DxfResult<(Drawing, Vec<LoadWarning>)>
+    where
+        T: Read + ?Sized,
+    {
+        let first_line = match read_line(reader, true, options.encoding) {
             Some(Ok(line)) => line,
             Some(Err(e)) => return Err(e),
             None => return Err(DxfError::UnexpectedEndOfInput),
@@ -124,26 +160,49 @@ impl Drawing {
         match &*first_line {
             "AutoCAD DXB 1.0" => {
                 let mut reader = DxbReader::new(reader);
-                reader.load()
+                Ok((reader.load()?, vec![]))
             }
+            line if is_dwg_signature(line) => Err(DxfError::UnsupportedDwgFile),
             _ => {
-                let reader = CodePairIter::new(reader, encoding, first_line);
+                let reader = CodePairIter::new(reader, options.encoding, first_line);
                 let mut drawing = Drawing::new();
                 drawing.clear();
                 let mut iter = CodePairPutBack::from_code_pair_iter(reader);
-                Drawing::read_sections(&mut drawing, &mut iter)?;
+                iter.set_options(options);
+                let result = Drawing::read_sections(&mut drawing, &mut iter, false);
+                if options.normalize {
+                    drawing.normalize();
+                }
+                match result {
+                    Ok(_) => (),
+                    Err(e)
+                        if options.is_lenient
+                            && matches!(e.inner(), DxfError::UnexpectedEndOfInput) =>
+                    {
+                        // keep everything that was read from the truncated file
+                        let position = iter.file_position();
+                        let mut warnings = iter.take_warnings();
+                        warnings.push(LoadWarning {
+                            position,
+                            skipped: String::from("end of drawing"),
+                            error: e,
+                        });
+                        return Ok((drawing, warnings));
+                    }
+                    Err(e) => return Err(e),
+                }
                 match iter.next() {
                     Some(Ok(CodePair {
                         code: 0,
                         value: CodePairValue::Str(ref s),
                         ..
-                    })) if s == "EOF" => Ok(drawing),
+                    })) if s == "EOF" => Ok((drawing, iter.take_warnings())),
                     Some(Ok(pair)) => Err(DxfError::UnexpectedCodePair(
                         pair,
                         String::from("expected 0/EOF"),
                     )),
                     Some(Err(e)) => Err(e),
-                    None => Ok(drawing),
+                    None => Ok((drawing, iter.take_warnings())),
                 }
             }
         }
@@ -161,6 +220,16 @@ impl Drawing {
         let mut buf_reader = BufReader::new(file);
         Drawing::load_with_encoding(&mut buf_reader, encoding)
     }
+    /// Loads a `Drawing` from disk, using a `BufReader` with the specified options.  See
+    /// `load_with_options()`.
+    pub fn load_file_with_options(
+        path: impl AsRef<Path>,
+        options: &LoadOptions,
+    ) -> DxfResult<(Drawing, Vec<LoadWarning>)> {
+        let file = File::open(&path)?;
+        let mut buf_reader = BufReader::new(file);
+        Drawing::load_with_options(&mut buf_reader, options)
+    }
     /// Writes a `Drawing` to anything that implements the `Write` trait.
     pub fn save<T>(&self, writer: &mut T) -> DxfResult<()>
     where
@@ -175,24 +244,60 @@ impl Drawing {
     {
         self.save_internal(writer, false)
     }
-    fn save_internal<T>(&self, writer: &mut T, as_ascii: bool) -> DxfResult<()>
+    /// Writes a `Drawing` to anything that implements the `Write` trait using the specified options.
+    pub fn save_with_options<T>(&self, writer: &mut T, options: &SaveOptions) -> DxfResult<()>
     where
         T: Write + ?Sized,
     {
-        let text_as_ascii = self.header.version <= AcadVersion::R2004;
+        self.save_with_options_report(writer, options)?;
+        Ok(())
+    }
+    /// Writes a `Drawing` to anything that implements the `Write` trait using the specified options
+    /// and returns the notes describing how a copy of the drawing was converted to be written, as
+    /// by `Drawing::convert_to_version()`.  The notes are empty if no conversion was needed.
+    pub fn save_with_options_report<T>(
+        &self,
+        writer: &mut T,
+        options: &SaveOptions,
+    ) -> DxfResult<Vec<ConversionNote>>
+    where
+        T: Write + ?Sized,
+    {
+        let version = options.version.unwrap_or(self.header.version);
+        if self.needs_conversion(version, options.build_object_graph) {
+            let mut drawing = self.clone();
+            let notes = drawing.convert_to_version(version);
+            drawing.save_with_options_report(writer, options)?;
+            return Ok(notes);
+        }
+
+        let text_as_ascii = options
+            .text_as_ascii
+            .unwrap_or(version <= AcadVersion::R2004);
         let mut code_pair_writer =
-            CodePairWriter::new(writer, as_ascii, text_as_ascii, self.header.version);
-        let write_handles = self.header.version >= AcadVersion::R13 || self.header.handles_enabled;
+            CodePairWriter::new(writer, !options.is_binary, text_as_ascii, version);
+        code_pair_writer.set_text_format(options.float_format, options.line_ending);
+        let write_handles = options
+            .write_handles
+            .unwrap_or(version >= AcadVersion::R13 || self.header.handles_enabled);
         code_pair_writer.write_prelude()?;
-        self.header.write(&mut code_pair_writer)?;
-        self.write_classes(&mut code_pair_writer)?;
-        self.write_tables(write_handles, &mut code_pair_writer)?;
-        self.write_blocks(write_handles, &mut code_pair_writer)?;
+        self.write_sections_before_entities(
+            write_handles,
+            options.update_extents,
+            &mut code_pair_writer,
+        )?;
+        code_pair_writer.write_code_pair(&CodePair::new_str(0, "SECTION"))?;
+        code_pair_writer.write_code_pair(&CodePair::new_str(2, "ENTITIES"))?;
         self.write_entities(write_handles, &mut code_pair_writer)?;
-        self.write_objects(&mut code_pair_writer)?;
-        self.write_thumbnail(&mut code_pair_writer)?;
-        code_pair_writer.write_code_pair(&CodePair::new_str(0, "EOF"))?;
-        Ok(())
+        code_pair_writer.write_code_pair(&CodePair::new_str(0, "ENDSEC"))?;
+        self.write_sections_after_entities(!options.exclude_thumbnail, &mut code_pair_writer)?;
+        Ok(vec![])
+    }
+    fn save_internal<T>(&self, writer: &mut T, as_ascii: bool) -> DxfResult<()>
+    where
+        T: Write + ?Sized,
+    {
+        self.save_with_options(writer, &SaveOptions::new().binary(!as_ascii))
     }
     /// Writes a `Drawing` to disk, using a `BufWriter`.
     pub fn save_file(&self, path: impl AsRef<Path>) -> DxfResult<()> {
@@ -207,6 +312,16 @@ impl Drawing {
         let mut writer = BufWriter::new(file);
         self.save_internal(&mut writer, as_ascii)
     }
+    /// Writes a `Drawing` to disk, using a `BufWriter` with the specified options.
+    pub fn save_file_with_options(
+        &self,
+        path: impl AsRef<Path>,
+        options: &SaveOptions,
+    ) -> DxfResult<()> {
+        let file = File::create(&path)?;
+        let mut writer = BufWriter::new(file);
+        self.save_with_options(&mut writer, options)
+    }
     /// Writes a `Drawing` as DXB to anything that implements the `Write` trait.
     pub fn save_dxb<T>(&self, writer: &mut T) -> DxfResult<()>
     where
@@ -402,32 +517,157 @@ impl Drawing {
     /// Adds an entity to the `Drawing`.
     pub fn add_entity(&mut self, mut entity: Entity) -> &Entity {
         entity.common.handle = self.next_handle();
+        self.assign_child_handles(&mut entity);
 
-        // set child handles
-        match entity.specific {
-            EntityType::Insert(ref mut ins) => {
-                for a in ins.__attributes_and_handles.iter_mut() {
-                    if a.1 == AUTO_REPLACE_HANDLE {
-                        a.1 = self.next_handle();
+        // ensure invariants
+        self.add_entity_no_handle_set(entity)
+    }
+    /// Removes the specified `Entity` from the `Drawing`.
+    pub fn remove_entity(&mut self, index: usize) -> Option<Entity> {
+        Drawing::remove_item(&mut self.__entities, index)
+    }
+    /// Returns an iterator for the entities in model space, i.e., those in the `ENTITIES` section
+    /// that aren't in paper space.
+    pub fn model_space(&self) -> impl Iterator<Item = &Entity> {
+        self.__entities
+            .iter()
+            .filter(|e| !e.common.is_in_paper_space)
+    }
+    /// Returns the `Layout`s in tab order.  Use `Layout::entities()` to get the entities drawn on
+    /// each.
+    pub fn layouts(&self) -> impl Iterator<Item = &Layout> {
+        let mut layouts = self
+            .__objects
+            .iter()
+            .filter_map(|o| match o.specific {
+                ObjectType::Layout(ref layout) => Some(layout),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        layouts.sort_by_key(|l| l.tab_order);
+        layouts.into_iter()
+    }
+    /// Adds a paper space `Layout` with the specified name, or returns the existing one.  The
+    /// first paper space layout is owned by `*Paper_Space`, and each one after that gets its own
+    /// `*Paper_Space<n>` block and block record.  The `Model` layout, the root dictionary, and the
+    /// `ACAD_LAYOUT` dictionary are added if they're missing.
+    pub fn add_layout(&mut self, name: &str) -> &Layout {
+        let handle = self.ensure_layout_is_present(name);
+        self.__objects
+            .iter()
+            .find_map(|o| match o.specific {
+                ObjectType::Layout(ref layout) if o.common.handle == handle => Some(layout),
+                _ => None,
+            })
+            .unwrap()
+    }
+    /// Adds an entity to the named `Layout`, adding the layout with `add_layout()` if it doesn't
+    /// exist.  The entity's owner is set to the layout's block record; entities on `Model` or on
+    /// the active paper space layout are added to the `ENTITIES` section, and entities on any
+    /// other layout are added to its `*Paper_Space<n>` block.
+    pub fn add_entity_to_layout(&mut self, mut entity: Entity, layout_name: &str) -> &Entity {
+        let layout_handle = self.ensure_layout_is_present(layout_name);
+        let (record_handle, record_name) = self.ensure_layout_block_is_present(layout_handle);
+        entity.common.__owner_handle = record_handle;
+        entity.common.is_in_paper_space = !record_name.eq_ignore_ascii_case("*Model_Space");
+        if record_name.eq_ignore_ascii_case("*Model_Space")
+            || record_name.eq_ignore_ascii_case("*Paper_Space")
+        {
+            return self.add_entity(entity);
+        }
+
+        entity.common.handle = self.next_handle();
+        self.assign_child_handles(&mut entity);
+        self.ensure_items_are_present_for_entity(&entity);
+        let block = self
+            .__blocks
+            .iter_mut()
+            .find(|b| b.name.eq_ignore_ascii_case(&record_name))
+            .unwrap();
+        block.entities.push(entity);
+        block.entities.last().unwrap()
+    }
+    /// Replaces every `Insert` with the entities of its referenced `Block`, transformed to world
+    /// coordinates.  Entities on layer `0` or with a `BYBLOCK` color, line type, or lineweight take
+    /// those values from the `Insert`.  The blocks themselves are left unchanged.  See
+    /// `Insert::explode()`.
+    pub fn flatten_inserts(&mut self) {
+        let entities = std::mem::take(&mut self.__entities);
+        for entity in entities {
+            match entity.specific {
+                EntityType::Insert(ref ins) => {
+                    for e in ins.explode_internal(self, Some(&entity.common), &mut vec![]) {
+                        self.add_entity(e);
                     }
                 }
+                _ => self.__entities.push(entity),
             }
-            EntityType::Polyline(ref mut poly) => {
-                for v in poly.__vertices_and_handles.iter_mut() {
-                    if v.1 == AUTO_REPLACE_HANDLE {
-                        v.1 = self.next_handle();
-                    }
-                }
+        }
+    }
+    /// Converts the drawing so that it can be written as `version` and sets `header.version`.
+    /// Entities and objects that `version` doesn't support are replaced with ones that it does,
+    /// or removed:
+    ///
+    /// - `LwPolyline` becomes a `Polyline`.
+    /// - `Ellipse` becomes a `Circle` or `Arc` if it's circular, otherwise it's approximated with
+    ///   a `Polyline`.
+    /// - `Spline` is approximated with a `Polyline`.
+    /// - `MText` becomes one `Text` per line with its formatting removed.
+    /// - All other unsupported entities and objects are removed.
+    ///
+    /// Curves are approximated to within 1/1000 of their size.
+    ///
+    /// When `version` is R2000 or later, the object graph that newer readers expect is completed:
+    /// every `Block` gets a `BlockRecord`, `*Model_Space` and `*Paper_Space` blocks and `Layout`s
+    /// are added, entities and blocks get owners, the root `DICTIONARY` with its `ACAD_GROUP` and
+    /// `ACAD_LAYOUT` entries is created, and `Class`es are added for the objects that need them.
+    /// Existing items are kept.
+    ///
+    /// The returned notes describe each change and whether it lost information.
+    /// `Drawing::save_with_options()` performs the same conversion on a copy of the drawing when
+    /// saving as a different version, or when `SaveOptions::build_object_graph()` is set.
+    pub fn convert_to_version(&mut self, version: AcadVersion) -> Vec<ConversionNote> {
+        let mut notes = vec![];
+        let entities = std::mem::take(&mut self.__entities);
+        self.__entities = self.convert_entities(entities, version, &mut notes);
+
+        let mut blocks = std::mem::take(&mut self.__blocks);
+        for block in blocks.iter_mut() {
+            let entities = std::mem::take(&mut block.entities);
+            block.entities = self.convert_entities(entities, version, &mut notes);
+        }
+        self.__blocks = blocks;
+
+        let objects = std::mem::take(&mut self.__objects);
+        for object in objects {
+            if is_object_supported(&object, self.header.version, version) {
+                self.__objects.push(object);
+            } else {
+                notes.push(ConversionNote {
+                    handle: object.common.handle,
+                    description: format!("{} removed", object.specific.to_type_string()),
+                    is_lossy: true,
+                });
             }
-            _ => (),
         }
 
-        // ensure invariants
-        self.add_entity_no_handle_set(entity)
+        if version >= AcadVersion::R2000 {
+            self.build_object_graph(&mut notes);
+        }
+
+        self.header.version = version;
+        notes
     }
-    /// Removes the specified `Entity` from the `Drawing`.
-    pub fn remove_entity(&mut self, index: usize) -> Option<Entity> {
-        Drawing::remove_item(&mut self.__entities, index)
+    /// Returns the concrete color that `entity` is drawn with.  A true color takes precedence over
+    /// the indexed color, `BYLAYER` colors come from the entity's `Layer`, and `BYBLOCK` colors come
+    /// from the `Insert` that contains the entity.  Entities on layer `0` inside a block are drawn
+    /// on the layer of their `Insert`.
+    ///
+    /// `inserts` holds the chain of `Insert` entities the entity is drawn through, outermost first,
+    /// and is empty for entities that aren't in a block.  Colors that can't be resolved, e.g.,
+    /// `BYBLOCK` without an `Insert` or a missing `Layer`, fall back to index 7.
+    pub fn resolve_color(&self, entity: &Entity, inserts: &[&Entity]) -> ResolvedColor {
+        self.resolve_common_color(&entity.common, inserts)
     }
     /// Returns an iterator for all contained objects.
     pub fn objects(&self) -> impl Iterator<Item = &Object> {
@@ -466,6 +706,7 @@ impl Drawing {
         self.__entities.clear();
         self.__objects.clear();
         self.thumbnail = None;
+        self.unknown_sections.clear();
 
         self.header.next_available_handle = 1;
     }
@@ -646,6 +887,84 @@ impl Drawing {
         self.header.next_available_handle += 1;
         result
     }
+    pub(crate) fn assign_child_handles(&mut self, entity: &mut Entity) {
+        match entity.specific {
+            EntityType::Insert(ref mut ins) => {
+                for a in ins.__attributes_and_handles.iter_mut() {
+                    if a.1 == AUTO_REPLACE_HANDLE {
+                        a.1 = self.next_handle();
+                    }
+                }
+            }
+            EntityType::Polyline(ref mut poly) => {
+                for v in poly.__vertices_and_handles.iter_mut() {
+                    if v.1 == AUTO_REPLACE_HANDLE {
+                        v.1 = self.next_handle();
+                    }
+                }
+            }
+            _ => (),
+        }
+    }
+    pub(crate) fn resolve_common_color(
+        &self,
+        common: &EntityCommon,
+        inserts: &[&Entity],
+    ) -> ResolvedColor {
+        if let Some(rgb) = common.get_true_color() {
+            return ResolvedColor {
+                rgb,
+                index: None,
+                color_name: common.color_name.clone(),
+            };
+        }
+        if common.color.is_by_block() {
+            if let Some((insert, outer_inserts)) = inserts.split_last() {
+                return self.resolve_common_color(&insert.common, outer_inserts);
+            }
+        }
+        let color = if common.color.is_by_layer() {
+            let layer_name = Drawing::effective_layer_name(common, inserts);
+            match self
+                .layers()
+                .find(|l| l.name.eq_ignore_ascii_case(layer_name))
+            {
+                Some(layer) if layer.color_24_bit & 0x00FF_FFFF != 0 => {
+                    return ResolvedColor {
+                        rgb: Color::true_color_to_rgb(layer.color_24_bit),
+                        index: None,
+                        color_name: String::new(),
+                    };
+                }
+                Some(layer) => layer.color.clone(),
+                None => Color::from_index(7),
+            }
+        } else {
+            common.color.clone()
+        };
+        let index = match color.get_raw_value().abs() {
+            value @ 1..=255 => value as u8,
+            _ => 7,
+        };
+        ResolvedColor {
+            rgb: Color::from_index(index).to_rgb().unwrap(),
+            index: Some(index),
+            color_name: String::new(),
+        }
+    }
+    /// Returns the name of the layer an entity is drawn on; entities on layer `0` inside a block
+    /// take the layer of their `Insert`.
+    pub(crate) fn effective_layer_name<'a>(
+        common: &'a EntityCommon,
+        inserts: &[&'a Entity],
+    ) -> &'a str {
+        match inserts.split_last() {
+            Some((insert, outer_inserts)) if common.layer == "0" => {
+                Drawing::effective_layer_name(&insert.common, outer_inserts)
+            }
+            _ => &common.layer,
+        }
+    }
     fn remove_item<T>(collection: &mut Vec<T>, index: usize) -> Option<T> {
         if index < collection.len() {
             Some(collection.remove(index))
@@ -660,22 +979,144 @@ impl Drawing {
         self.__blocks.last().unwrap()
     }
     fn add_entity_no_handle_set(&mut self, entity: Entity) -> &Entity {
-        self.ensure_mline_style_is_present_for_entity(&entity);
-        self.ensure_dimension_style_is_present_for_entity(&entity);
-        self.ensure_layer_is_present(&entity.common.layer);
-        self.ensure_line_type_is_present(&entity.common.line_type_name);
-        self.ensure_text_style_is_present_for_entity(&entity);
+        self.ensure_items_are_present_for_entity(&entity);
         self.__entities.push(entity);
         self.__entities.last().unwrap()
     }
-    fn add_object_no_handle_set(&mut self, obj: Object) -> &Object {
+    fn ensure_items_are_present_for_entity(&mut self, entity: &Entity) {
+        self.ensure_mline_style_is_present_for_entity(entity);
+        self.ensure_dimension_style_is_present_for_entity(entity);
+        self.ensure_layer_is_present(&entity.common.layer);
+        self.ensure_line_type_is_present(&entity.common.line_type_name);
+        self.ensure_text_style_is_present_for_entity(entity);
+        self.ensure_app_ids_are_present_for_x_data(&entity.common.x_data);
+    }
+    /// Returns the handle of the `Layout` with the specified name, adding it and its block record
+    /// and block if necessary.
+    fn ensure_layout_is_present(&mut self, name: &str) -> u32 {
+        let existing = self.__objects.iter().find_map(|o| match o.specific {
+            ObjectType::Layout(ref layout) if layout.layout_name.eq_ignore_ascii_case(name) => {
+                Some(o.common.handle)
+            }
+            _ => None,
+        });
+        if let Some(handle) = existing {
+            return handle;
+        }
+
+        let mut notes = vec![];
+        let root = self.ensure_root_dictionary(&mut notes);
+        let dictionary = self.ensure_dictionary_entry(root, "ACAD_LAYOUT", &mut notes);
+        let model_space = self.ensure_block_record("*Model_Space", &mut notes);
+        let model_layout = self.ensure_layout(model_space, "Model", 0, dictionary, &mut notes);
+        if name.eq_ignore_ascii_case("Model") {
+            return model_layout;
+        }
+
+        let block_name = self.unused_paper_space_block_name();
+        let record = self.ensure_block_record(&block_name, &mut notes);
+        let tab_order = self.layouts().map(|l| l.tab_order).max().unwrap_or(0) + 1;
+        let layout = self.ensure_layout(record, name, tab_order, dictionary, &mut notes);
+        self.ensure_layout_block_is_present(layout);
+        layout
+    }
+    /// Returns the handle and name of the block record of the `Layout` with the specified handle,
+    /// adding the block record and its block if they're missing.
+    fn ensure_layout_block_is_present(&mut self, layout_handle: u32) -> (u32, String) {
+        let (layout_name, record_handle) = self
+            .__objects
+            .iter()
+            .find_map(|o| match o.specific {
+                ObjectType::Layout(ref layout) if o.common.handle == layout_handle => {
+                    Some((layout.layout_name.clone(), layout.__table_record_handle))
+                }
+                _ => None,
+            })
+            .unwrap_or_default();
+        let existing = self
+            .__block_records
+            .iter()
+            .find(|r| r.handle == record_handle)
+            .map(|r| r.name.clone());
+        let (record_handle, record_name) = match existing {
+            Some(record_name) => (record_handle, record_name),
+            None => {
+                let record_name = if layout_name.eq_ignore_ascii_case("Model") {
+                    String::from("*Model_Space")
+                } else {
+                    self.unused_paper_space_block_name()
+                };
+                let record_handle = self.ensure_block_record(&record_name, &mut vec![]);
+                for object in self.__objects.iter_mut() {
+                    if let ObjectType::Layout(ref mut layout) = object.specific {
+                        if object.common.handle == layout_handle {
+                            layout.__table_record_handle = record_handle;
+                        }
+                    }
+                }
+                if let Some(record) = self
+                    .__block_records
+                    .iter_mut()
+                    .find(|r| r.handle == record_handle)
+                {
+                    record.__layout_handle = layout_handle;
+                }
+                (record_handle, record_name)
+            }
+        };
+        if !self
+            .__blocks
+            .iter()
+            .any(|b| b.name.eq_ignore_ascii_case(&record_name))
+        {
+            let handle = self.next_handle();
+            self.add_block_no_handle_set(Block {
+                name: record_name.clone(),
+                handle,
+                is_in_paperspace: !record_name.eq_ignore_ascii_case("*Model_Space"),
+                __owner_handle: record_handle,
+                ..Default::default()
+            });
+        }
+
+        (record_handle, record_name)
+    }
+    /// Returns the name of the next block for a paper space `Layout`; the first uses
+    /// `*Paper_Space`, the others `*Paper_Space0`, `*Paper_Space1`, etc.
+    fn unused_paper_space_block_name(&self) -> String {
+        let record_names = self
+            .layouts()
+            .filter_map(|l| match l.get_table_record(self) {
+                Some(DrawingItem::BlockRecord(record)) => Some(record.name.to_uppercase()),
+                _ => None,
+            })
+            .collect::<HashSet<_>>();
+        std::iter::once(String::from("*Paper_Space"))
+            .chain((0..).map(|i| format!("*Paper_Space{}", i)))
+            .find(|n| !record_names.contains(&n.to_uppercase()))
+            .unwrap()
+    }
+    pub(crate) fn add_object_no_handle_set(&mut self, obj: Object) -> &Object {
         self.ensure_layer_is_present_for_object(&obj);
         self.ensure_line_type_is_present_for_object(&obj);
         self.ensure_text_style_is_present_for_object(&obj);
         self.ensure_view_is_present(&obj);
+        self.ensure_app_ids_are_present_for_x_data(&obj.common.x_data);
         self.__objects.push(obj);
         self.__objects.last().unwrap()
     }
+    /// Moves the object with the specified handle to the start of the `OBJECTS` section, where
+    /// readers expect the root dictionary.
+    pub(crate) fn move_object_to_front(&mut self, handle: u32) {
+        if let Some(index) = self
+            .__objects
+            .iter()
+            .position(|o| o.common.handle == handle)
+        {
+            let obj = self.__objects.remove(index);
+            self.__objects.insert(0, obj);
+        }
+    }
     pub(crate) fn add_app_id_no_handle_set(&mut self, app_id: AppId) -> &AppId {
         // TODO: ensure invariants
         self.__app_ids.push(app_id);
@@ -732,12 +1173,11 @@ impl Drawing {
             });
         }
     }
-    fn ensure_block_record_is_present(&mut self, name: &str) {
-        if !self.block_records().any(|b| b.name == name) {
-            self.add_block_record(BlockRecord {
-                name: String::from(name),
-                ..Default::default()
-            });
+    fn ensure_app_ids_are_present_for_x_data(&mut self, x_data: &[XData]) {
+        for x in x_data {
+            if !x.application_name.is_empty() {
+                self.ensure_app_id_is_present(&x.application_name);
+            }
         }
     }
     fn ensure_mline_style_is_present_for_entity(&mut self, entity: &Entity) {
@@ -878,6 +1318,55 @@ impl Drawing {
             });
         }
     }
+    pub(crate) fn write_sections_before_entities<T>(
+        &self,
+        write_handles: bool,
+        update_extents: bool,
+        writer: &mut CodePairWriter<T>,
+    ) -> DxfResult<()>
+    where
+        T: Write + ?Sized,
+    {
+        self.write_unknown_sections("", writer)?;
+        let extents = if update_extents { self.extents() } else { None };
+        if extents.is_some() || writer.version() != self.header.version {
+            let mut header = self.header.clone();
+            header.version = writer.version();
+            if let Some(extents) = extents {
+                header.minimum_drawing_extents = extents.min;
+                header.maximum_drawing_extents = extents.max;
+            }
+            header.write(writer)?;
+        } else {
+            self.header.write(writer)?;
+        }
+        self.write_unknown_sections("HEADER", writer)?;
+        self.write_classes(writer)?;
+        self.write_unknown_sections("CLASSES", writer)?;
+        self.write_tables(write_handles, writer)?;
+        self.write_unknown_sections("TABLES", writer)?;
+        self.write_blocks(write_handles, writer)?;
+        self.write_unknown_sections("BLOCKS", writer)?;
+        Ok(())
+    }
+    pub(crate) fn write_sections_after_entities<T>(
+        &self,
+        include_thumbnail: bool,
+        writer: &mut CodePairWriter<T>,
+    ) -> DxfResult<()>
+    where
+        T: Write + ?Sized,
+    {
+        self.write_unknown_sections("ENTITIES", writer)?;
+        self.write_objects(writer)?;
+        self.write_unknown_sections("OBJECTS", writer)?;
+        if include_thumbnail {
+            self.write_thumbnail(writer)?;
+        }
+        self.write_unknown_sections("THUMBNAILIMAGE", writer)?;
+        writer.write_code_pair(&CodePair::new_str(0, "EOF"))?;
+        Ok(())
+    }
     fn write_classes<T>(&self, writer: &mut CodePairWriter<T>) -> DxfResult<()>
     where
         T: Write + ?Sized,
@@ -889,7 +1378,7 @@ impl Drawing {
         writer.write_code_pair(&CodePair::new_str(0, "SECTION"))?;
         writer.write_code_pair(&CodePair::new_str(2, "CLASSES"))?;
         for c in &self.classes {
-            c.write(self.header.version, writer)?;
+            c.write(writer.version(), writer)?;
         }
 
         writer.write_code_pair(&CodePair::new_str(0, "ENDSEC"))?;
@@ -916,13 +1405,13 @@ impl Drawing {
         writer.write_code_pair(&CodePair::new_str(0, "SECTION"))?;
         writer.write_code_pair(&CodePair::new_str(2, "BLOCKS"))?;
         for b in &self.__blocks {
-            b.write(self.header.version, write_handles, writer)?;
+            b.write(writer.version(), write_handles, writer)?;
         }
 
         writer.write_code_pair(&CodePair::new_str(0, "ENDSEC"))?;
         Ok(())
     }
-    fn write_entities<T>(
+    pub(crate) fn write_entities<T>(
         &self,
         write_handles: bool,
         writer: &mut CodePairWriter<T>,
@@ -930,13 +1419,10 @@ impl Drawing {
     where
         T: Write + ?Sized,
     {
-        writer.write_code_pair(&CodePair::new_str(0, "SECTION"))?;
-        writer.write_code_pair(&CodePair::new_str(2, "ENTITIES"))?;
         for e in &self.__entities {
-            e.write(self.header.version, write_handles, writer)?;
+            e.write(writer.version(), write_handles, writer)?;
         }
 
-        writer.write_code_pair(&CodePair::new_str(0, "ENDSEC"))?;
         Ok(())
     }
     fn write_objects<T>(&self, writer: &mut CodePairWriter<T>) -> DxfResult<()>
@@ -946,7 +1432,7 @@ impl Drawing {
         writer.write_code_pair(&CodePair::new_str(0, "SECTION"))?;
         writer.write_code_pair(&CodePair::new_str(2, "OBJECTS"))?;
         for o in &self.__objects {
-            o.write(self.header.version, writer)?;
+            o.write(writer.version(), writer)?;
         }
 
         writer.write_code_pair(&CodePair::new_str(0, "ENDSEC"))?;
@@ -956,7 +1442,7 @@ impl Drawing {
     where
         T: Write + ?Sized,
     {
-        if self.header.version >= AcadVersion::R2000 {
+        if writer.version() >= AcadVersion::R2000 {
             if let Some(ref img) = self.thumbnail {
                 writer.write_code_pair(&CodePair::new_str(0, "SECTION"))?;
                 writer.write_code_pair(&CodePair::new_str(2, "THUMBNAILIMAGE"))?;
@@ -976,10 +1462,41 @@ impl Drawing {
         }
         Ok(())
     }
-    fn read_sections<T>(drawing: &mut Drawing, iter: &mut CodePairPutBack<T>) -> DxfResult<()>
+    fn write_unknown_sections<T>(
+        &self,
+        preceding_section: &str,
+        writer: &mut CodePairWriter<T>,
+    ) -> DxfResult<()>
+    where
+        T: Write + ?Sized,
+    {
+        for section in &self.unknown_sections {
+            let is_known_position = SUPPORTED_SECTIONS.contains(&&*section.preceding_section);
+            let should_write = if is_known_position {
+                section.preceding_section == preceding_section
+            } else {
+                // no sensible position; write after all other sections
+                preceding_section == "THUMBNAILIMAGE"
+            };
+            if should_write {
+                section.write(writer)?;
+            }
+        }
+
+        Ok(())
+    }
+    /// Reads all sections into `drawing`.  If `stop_at_entities` is set, reading stops directly after
+    /// the `0/SECTION` and `2/ENTITIES` pairs and `true` is returned.
+    pub(crate) fn read_sections<T>(
+        drawing: &mut Drawing,
+        iter: &mut CodePairPutBack<T>,
+        stop_at_entities: bool,
+    ) -> DxfResult<bool>
     where
         T: Read,
     {
+        let mut preceding_section = String::new();
+        let mut read_section_names = vec![];
         loop {
             match iter.next() {
                 Some(Ok(pair @ CodePair { code: 0, .. })) => match &*pair.assert_string()? {
@@ -993,96 +1510,129 @@ impl Drawing {
                             value: CodePairValue::Str(s),
                             ..
                         })) => {
-                            match &*s {
-                                "HEADER" => drawing.header = Header::read(iter)?,
-                                "CLASSES" => Class::read_classes(drawing, iter)?,
+                            let result = match &*s {
+                                _ if !iter.options().should_read_section(&s) => {
+                                    iter.skip_to(Some("ENDSEC"))
+                                }
+                                "HEADER" => {
+                                    Header::read(iter).map(|header| drawing.header = header)
+                                }
+                                "CLASSES" => Class::read_classes(drawing, iter),
                                 "TABLES" => {
-                                    drawing.read_section_item(iter, "TABLE", read_specific_table)?
+                                    drawing.read_section_item(iter, "TABLE", read_specific_table)
                                 }
                                 "BLOCKS" => {
-                                    drawing.read_section_item(iter, "BLOCK", Block::read_block)?
+                                    drawing.read_section_item(iter, "BLOCK", Block::read_block)
                                 }
-                                "ENTITIES" => drawing.read_entities(iter)?,
-                                "OBJECTS" => drawing.read_objects(iter)?,
-                                "THUMBNAILIMAGE" => {
-                                    let _ = drawing.read_thumbnail(iter)?;
+                                "ENTITIES" if stop_at_entities => return Ok(true),
+                                "ENTITIES" => drawing.read_entities(iter),
+                                "OBJECTS" => drawing.read_objects(iter),
+                                "THUMBNAILIMAGE" => drawing.read_thumbnail(iter).map(|_| ()),
+                                _ => {
+                                    UnknownSection::read(s.clone(), preceding_section.clone(), iter)
+                                        .map(|section| drawing.unknown_sections.push(section))
                                 }
-                                _ => Drawing::swallow_section(iter)?,
+                            };
+
+                            if SUPPORTED_SECTIONS.contains(&&*s) {
+                                preceding_section = s.clone();
                             }
 
-                            match iter.next() {
-                                Some(Ok(CodePair {
-                                    code: 0,
-                                    value: CodePairValue::Str(ref s),
-                                    ..
-                                })) if s == "ENDSEC" => (),
-                                Some(Ok(pair)) => {
-                                    return Err(DxfError::UnexpectedCodePair(
-                                        pair,
-                                        String::from("expected 0/ENDSEC"),
-                                    ))
+                            if let Err(e) = result.and_then(|_| Drawing::read_section_end(iter)) {
+                                // skip the rest of the section
+                                iter.recover(e, &format!("{} section", s), Some("ENDSEC"))?;
+                                match iter.next() {
+                                    Some(Ok(CodePair {
+                                        code: 0,
+                                        value: CodePairValue::Str(ref s),
+                                        ..
+                                    })) if s == "ENDSEC" => (),
+                                    Some(pair) => iter.put_back(pair), // 0/EOF
+                                    None => (),
                                 }
-                                Some(Err(e)) => return Err(e),
-                                None => return Err(DxfError::UnexpectedEndOfInput),
+                            }
+
+                            read_section_names.push(s);
+                            let has_read_requested_sections = match iter.options().sections {
+                                Some(ref sections) => {
+                                    sections.iter().all(|s| read_section_names.contains(s))
+                                }
+                                None => false,
+                            };
+                            if has_read_requested_sections {
+                                // the remaining sections aren't needed; stop as if the drawing
+                                // ended here
+                                iter.put_back(Ok(CodePair::new_str(0, "EOF")));
+                                break;
                             }
                         }
-                        Some(Ok(pair)) => {
-                            return Err(DxfError::UnexpectedCodePair(
+                        Some(Ok(pair)) => iter.recover(
+                            DxfError::UnexpectedCodePair(
                                 pair,
                                 String::from("expected 2/<section-name>"),
-                            ))
-                        }
-                        Some(Err(e)) => return Err(e),
+                            ),
+                            "section",
+                            Some("SECTION"),
+                        )?,
+                        Some(Err(e)) => iter.recover(e, "section", Some("SECTION"))?,
                         None => return Err(DxfError::UnexpectedEndOfInput),
                     },
-                    _ => {
-                        return Err(DxfError::UnexpectedCodePair(
-                            pair,
-                            String::from("expected 0/SECTION"),
-                        ))
-                    }
+                    _ => iter.recover(
+                        DxfError::UnexpectedCodePair(pair, String::from("expected 0/SECTION")),
+                        "code pair",
+                        Some("SECTION"),
+                    )?,
                 },
-                Some(Ok(pair)) => {
-                    return Err(DxfError::UnexpectedCodePair(
-                        pair,
-                        String::from("expected 0/SECTION or 0/EOF"),
-                    ))
-                }
-                Some(Err(e)) => return Err(e),
+                Some(Ok(pair)) => iter.recover(
+                    DxfError::UnexpectedCodePair(pair, String::from("expected 0/SECTION or 0/EOF")),
+                    "code pair",
+                    Some("SECTION"),
+                )?,
+                Some(Err(e)) => iter.recover(e, "code pair", Some("SECTION"))?,
                 None => break, // ideally should have been 0/EOF
             }
         }
 
-        Ok(())
+        Ok(false)
     }
-    fn swallow_section<T>(iter: &mut CodePairPutBack<T>) -> DxfResult<()>
+    fn read_section_end<T>(iter: &mut CodePairPutBack<T>) -> DxfResult<()>
     where
         T: Read,
     {
-        loop {
-            match iter.next() {
-                Some(Ok(pair)) => {
-                    if pair.code == 0 && pair.assert_string()? == "ENDSEC" {
-                        iter.put_back(Ok(pair));
-                        break;
-                    }
-                }
-                Some(Err(e)) => return Err(e),
-                None => break,
-            }
+        match iter.next() {
+            Some(Ok(CodePair {
+                code: 0,
+                value: CodePairValue::Str(ref s),
+                ..
+            })) if s == "ENDSEC" => Ok(()),
+            Some(Ok(pair)) => Err(DxfError::UnexpectedCodePair(
+                pair,
+                String::from("expected 0/ENDSEC"),
+            )),
+            Some(Err(e)) => Err(e),
+            None => Err(DxfError::UnexpectedEndOfInput),
         }
-
-        Ok(())
     }
     fn read_entities<T>(&mut self, iter: &mut CodePairPutBack<T>) -> DxfResult<()>
     where
         T: Read,
     {
-        let mut iter = EntityIter { iter };
+        let max_entities = iter.options().max_entities;
+        let assign_handles = iter.options().assign_handles;
         let mut entities = vec![];
-        iter.read_entities_into_vec(&mut entities)?;
+        let mut entity_iter = EntityIter { iter };
+        match max_entities {
+            Some(max_entities) => {
+                entity_iter.read_entities_into_vec_with_limit(&mut entities, max_entities)?;
+                if entities.len() == max_entities {
+                    // ignore the remaining entities
+                    iter.skip_to(Some("ENDSEC"))?;
+                }
+            }
+            None => entity_iter.read_entities_into_vec(&mut entities)?,
+        }
         for e in entities {
-            if e.common.handle == 0 {
+            if e.common.handle == 0 && assign_handles {
                 self.add_entity(e);
             } else {
                 self.add_entity_no_handle_set(e);
@@ -1094,9 +1644,10 @@ impl Drawing {
     where
         T: Read,
     {
+        let assign_handles = iter.options().assign_handles;
         let iter = put_back(ObjectIter { iter });
         for o in iter {
-            if o.common.handle == 0 {
+            if o.common.handle == 0 && assign_handles {
                 self.add_object(o);
             } else {
                 self.add_object_no_handle_set(o);
@@ -1209,14 +1760,18 @@ impl Drawing {
                             }
                             val => {
                                 if val == item_type {
-                                    callback(self, iter)?;
+                                    if let Err(e) = callback(self, iter) {
+                                        iter.recover(e, item_type, Some(item_type))?;
+                                    }
                                 } else {
-                                    return Err(DxfError::UnexpectedCodePair(pair, String::new()));
+                                    let e = DxfError::UnexpectedCodePair(pair, String::new());
+                                    iter.recover(e, item_type, Some(item_type))?;
                                 }
                             }
                         }
                     } else {
-                        return Err(DxfError::UnexpectedCodePair(pair, String::new()));
+                        let e = DxfError::UnexpectedCodePair(pair, String::new());
+                        iter.recover(e, item_type, Some(item_type))?;
                     }
                 }
                 Some(Err(e)) => return Err(e),
@@ -1274,8 +1829,8 @@ impl Drawing {
     }
     fn normalize_block_records(&mut self) {
         // ensure all block records that should exist do
-        self.ensure_block_record_is_present("*MODEL_SPACE");
-        self.ensure_block_record_is_present("*PAPER_SPACE");
+        self.ensure_block_record("*MODEL_SPACE", &mut vec![]);
+        self.ensure_block_record("*PAPER_SPACE", &mut vec![]);
     }
     fn normalize_layers(&mut self) {
         self.ensure_layer_is_present(&self.header.current_layer.clone());
@@ -1343,6 +1898,7 @@ impl Drawing {
 #[cfg(test)]
 mod tests {
     use crate::entities::*;
+    use crate::enums::*;
     use crate::helper_functions::tests::*;
     use crate::objects::*;
     use crate::tables::*;
@@ -1522,6 +2078,712 @@ mod tests {
         assert_eq!(0x3333, line.common.handle);
     }
 
+    #[test]
+    fn load_dwg_file_reports_unsupported() {
+        let mut bytes: &[u8] = b"AC1015\x00\x00\x00\x00\x00\x0f\x01";
+        match Drawing::load(&mut bytes) {
+            Err(DxfError::UnsupportedDwgFile) => (),
+            _ => panic!("expected an unsupported DWG error"),
+        }
+    }
+
+    fn malformed_entities() -> String {
+        vec![
+            "  0",
+            "SECTION",
+            "  2",
+            "ENTITIES",
+            "  0",
+            "LINE",
+            "  5",
+            "2A",
+            " 10",
+            "1.0",
+            " 20",
+            "not-a-number",
+            " 30",
+            "0.0",
+            "  0",
+            "CIRCLE",
+            " 40",
+            "2.0",
+            "  0",
+            "ENDSEC",
+            "  0",
+            "EOF",
+        ]
+        .join("\n")
+    }
+
+    #[test]
+    fn malformed_entity_fails_strict_load() {
+        let error = match Drawing::load(&mut malformed_entities().as_bytes()) {
+            Err(e) => e,
+            Ok(_) => panic!("expected a parse error"),
+        };
+        match error.inner() {
+            DxfError::ParseFloatError(_, 12) => (),
+            e => panic!("unexpected error: {:?}", e),
+        }
+        let context = error.context().unwrap();
+        assert_eq!(Some(FilePosition::Line(12)), context.position);
+        assert_eq!(
+            vec![
+                ContextFrame::Section(String::from("ENTITIES")),
+                ContextFrame::Item(String::from("LINE")),
+                ContextFrame::Handle(0x2A),
+                ContextFrame::Code(20),
+            ],
+            context.frames
+        );
+        assert!(error
+            .to_string()
+            .ends_with("(while reading section ENTITIES > LINE > handle 2A > code 20 at line 12)"));
+        assert!(std::error::Error::source(&error).is_some());
+    }
+
+    #[test]
+    fn error_context_in_binary_file() {
+        let mut bytes = b"AutoCAD Binary DXF\r\n\x1A\x00".to_vec();
+        for &(code, value) in &[
+            (0, "SECTION"),
+            (2, "HEADER"),
+            (9, "$HANDSEED"),
+            (5, "not-a-handle"),
+            (0, "ENDSEC"),
+            (0, "EOF"),
+        ] {
+            bytes.push(code);
+            bytes.extend_from_slice(value.as_bytes());
+            bytes.push(0);
+        }
+        let error = match Drawing::load(&mut bytes.as_slice()) {
+            Err(e) => e,
+            Ok(_) => panic!("expected a parse error"),
+        };
+        let context = error.context().unwrap();
+        match context.position {
+            Some(FilePosition::ByteOffset(_)) => (),
+            ref p => panic!("expected a byte offset, found {:?}", p),
+        }
+        assert_eq!(
+            vec![
+                ContextFrame::Section(String::from("HEADER")),
+                ContextFrame::Item(String::from("$HANDSEED")),
+                ContextFrame::Code(5),
+            ],
+            context.frames
+        );
+    }
+
+    #[test]
+    fn malformed_entity_is_skipped_when_lenient() {
+        let options = LoadOptions::new().lenient(true);
+        let (drawing, warnings) =
+            Drawing::load_with_options(&mut malformed_entities().as_bytes(), &options).unwrap();
+        let entities = drawing.entities().collect::<Vec<_>>();
+        assert_eq!(1, entities.len());
+        match entities[0].specific {
+            EntityType::Circle(ref circle) => assert!(approx_eq!(f64, 2.0, circle.radius)),
+            _ => panic!("expected a circle"),
+        }
+        assert_eq!(1, warnings.len());
+        assert_eq!(FilePosition::Line(12), warnings[0].position);
+        assert_eq!("entity", warnings[0].skipped);
+        match warnings[0].error.inner() {
+            DxfError::ParseFloatError(_, 12) => (),
+            e => panic!("unexpected error: {:?}", e),
+        }
+    }
+
+    #[test]
+    fn malformed_items_are_skipped_when_lenient() {
+        let text = vec![
+            "  0",
+            "SECTION",
+            "  2",
+            "HEADER",
+            "  9",
+            "$ACADVER",
+            "  1",
+            "AC1015",
+            "  0",
+            "ENDSEC",
+            "  0",
+            "SECTION",
+            "  2",
+            "TABLES",
+            "  0",
+            "TABLE",
+            "  2",
+            "LAYER",
+            "  0",
+            "LAYER",
+            "  2",
+            "bad-layer",
+            " 62",
+            "red",
+            "  0",
+            "LAYER",
+            "  2",
+            "good-layer",
+            " 62",
+            "1",
+            "  0",
+            "ENDTAB",
+            "  0",
+            "ENDSEC",
+            "  0",
+            "SECTION",
+            "  2",
+            "BLOCKS",
+            "  0",
+            "BLOCK",
+            "  2",
+            "bad-block",
+            " 10",
+            "?",
+            "  0",
+            "LINE",
+            "  0",
+            "ENDBLK",
+            "  0",
+            "BLOCK",
+            "  2",
+            "good-block",
+            "  0",
+            "LINE",
+            " 11",
+            "oops",
+            "  0",
+            "CIRCLE",
+            "  0",
+            "ENDBLK",
+            "  0",
+            "ENDSEC",
+            "  0",
+            "SECTION",
+            "  2",
+            "OBJECTS",
+            "  0",
+            "DICTIONARY",
+            "  5",
+            "not-a-handle",
+            "  0",
+            "DICTIONARY",
+            "  5",
+            "42",
+            "  0",
+            "ENDSEC",
+            "  0",
+            "EOF",
+        ]
+        .join("\n");
+        let options = LoadOptions::new().lenient(true);
+        let (drawing, warnings) =
+            Drawing::load_with_options(&mut text.as_bytes(), &options).unwrap();
+        assert_eq!(AcadVersion::R2000, drawing.header.version);
+
+        let layers = drawing
+            .layers()
+            .map(|l| l.name.as_str())
+            .collect::<Vec<_>>();
+        assert!(layers.contains(&"good-layer"));
+        assert!(!layers.contains(&"bad-layer"));
+
+        let blocks = drawing.blocks().collect::<Vec<_>>();
+        assert_eq!(1, blocks.len());
+        assert_eq!("good-block", blocks[0].name);
+        assert_eq!(1, blocks[0].entities.len());
+        match blocks[0].entities[0].specific {
+            EntityType::Circle(_) => (),
+            _ => panic!("expected a circle"),
+        }
+
+        let objects = drawing.objects().collect::<Vec<_>>();
+        assert_eq!(1, objects.len());
+        assert_eq!(0x42, objects[0].common.handle);
+
+        let skipped = warnings
+            .iter()
+            .map(|w| w.skipped.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            vec!["LAYER table entry", "BLOCK", "entity", "object"],
+            skipped
+        );
+    }
+
+    #[test]
+    fn truncated_drawing_is_kept_when_lenient() {
+        let text = vec![
+            "  0", "SECTION", "  2", "ENTITIES", "  0", "LINE", "  0", "CIRCLE", " 40", "2.0",
+        ]
+        .join("\n");
+        assert!(Drawing::load(&mut text.as_bytes()).is_err());
+
+        let options = LoadOptions::new().lenient(true);
+        let (drawing, warnings) =
+            Drawing::load_with_options(&mut text.as_bytes(), &options).unwrap();
+        assert_eq!(1, drawing.entities().count());
+        assert_eq!(1, warnings.len());
+        match warnings[0].error.inner() {
+            DxfError::UnexpectedEndOfInput => (),
+            e => panic!("unexpected error: {:?}", e),
+        }
+    }
+
+    fn drawing_with_all_sections() -> String {
+        vec![
+            "  0",
+            "SECTION",
+            "  2",
+            "HEADER",
+            "  9",
+            "$ACADVER",
+            "  1",
+            "AC1015",
+            "  0",
+            "ENDSEC",
+            "  0",
+            "SECTION",
+            "  2",
+            "TABLES",
+            "  0",
+            "TABLE",
+            "  2",
+            "LAYER",
+            "  0",
+            "LAYER",
+            "  2",
+            "walls",
+            "  0",
+            "ENDTAB",
+            "  0",
+            "ENDSEC",
+            "  0",
+            "SECTION",
+            "  2",
+            "ENTITIES",
+            "  0",
+            "LINE",
+            "  0",
+            "CIRCLE",
+            "  0",
+            "ARC",
+            "  0",
+            "ENDSEC",
+            "  0",
+            "SECTION",
+            "  2",
+            "OBJECTS",
+            "  0",
+            "DICTIONARY",
+            "  5",
+            "42",
+            "  0",
+            "ENDSEC",
+            "  0",
+            "EOF",
+        ]
+        .join("\n")
+    }
+
+    fn load_with_options(text: &str, options: &LoadOptions) -> Drawing {
+        let (drawing, warnings) =
+            Drawing::load_with_options(&mut text.as_bytes(), options).unwrap();
+        assert!(warnings.is_empty());
+        drawing
+    }
+
+    #[test]
+    fn read_only_requested_sections() {
+        // everything after the requested sections is ignored, even if it's malformed
+        let text = drawing_with_all_sections().replace("CIRCLE", "CIRCLE\n 40\nnot-a-number");
+        let options = LoadOptions::new().sections(&["header", "tables"]);
+        let drawing = load_with_options(&text, &options);
+        assert_eq!(AcadVersion::R2000, drawing.header.version);
+        assert!(drawing.layers().any(|l| l.name == "walls"));
+        assert_eq!(0, drawing.entities().count());
+        assert_eq!(0, drawing.objects().count());
+    }
+
+    #[test]
+    fn skip_section_on_load() {
+        let options = LoadOptions::new()
+            .skip_section("OBJECTS")
+            .skip_section("THUMBNAILIMAGE");
+        let drawing = load_with_options(&drawing_with_all_sections(), &options);
+        assert_eq!(3, drawing.entities().count());
+        assert_eq!(0, drawing.objects().count());
+    }
+
+    #[test]
+    fn max_entities_on_load() {
+        let options = LoadOptions::new().max_entities(2);
+        let drawing = load_with_options(&drawing_with_all_sections(), &options);
+        let entities = drawing.entities().collect::<Vec<_>>();
+        assert_eq!(2, entities.len());
+        match entities[1].specific {
+            EntityType::Circle(_) => (),
+            _ => panic!("expected a circle"),
+        }
+
+        // sections after the entities are still read
+        assert_eq!(1, drawing.objects().count());
+    }
+
+    #[test]
+    fn handles_are_not_assigned_on_load() {
+        let drawing = load_with_options(&drawing_with_all_sections(), &LoadOptions::new());
+        assert!(drawing.entities().all(|e| e.common.handle != 0));
+
+        let options = LoadOptions::new().assign_handles(false);
+        let drawing = load_with_options(&drawing_with_all_sections(), &options);
+        assert!(drawing.entities().all(|e| e.common.handle == 0));
+        assert_eq!(0x42, drawing.objects().next().unwrap().common.handle);
+    }
+
+    #[test]
+    fn normalize_on_load() {
+        let text = drawing_with_all_sections();
+        let drawing = load_with_options(&text, &LoadOptions::new());
+        assert_eq!(0, drawing.dim_styles().count());
+
+        let drawing = load_with_options(&text, &LoadOptions::new().normalize(true));
+        assert!(drawing.dim_styles().any(|d| d.name == "STANDARD"));
+    }
+
+    fn save_with_options(drawing: &Drawing, options: &SaveOptions) -> String {
+        let mut buf = vec![];
+        drawing.save_with_options(&mut buf, options).unwrap();
+        String::from_utf8(buf).unwrap()
+    }
+
+    #[test]
+    fn save_as_different_version() {
+        let mut drawing = Drawing::new();
+        drawing.header.version = AcadVersion::R2000;
+        let text = save_with_options(&drawing, &SaveOptions::new().version(AcadVersion::R12));
+        assert!(text.contains(&vec!["  9", "$ACADVER", "  1", "AC1009"].join("\r\n")));
+        assert_eq!(AcadVersion::R2000, drawing.header.version);
+
+        // round trip
+        let drawing = parse_drawing(&text);
+        assert_eq!(AcadVersion::R12, drawing.header.version);
+    }
+
+    #[test]
+    fn save_with_options_reports_conversion() {
+        let mut drawing = Drawing::new();
+        drawing.convert_to_version(AcadVersion::R2000);
+        let handle = drawing
+            .add_entity(Entity::new(EntityType::LwPolyline(Default::default())))
+            .common
+            .handle;
+        let options = SaveOptions::new().version(AcadVersion::R12);
+        let notes = drawing
+            .save_with_options_report(&mut vec![], &options)
+            .unwrap();
+        assert!(notes.iter().any(|n| n.handle == handle));
+
+        // nothing to convert
+        let notes = drawing
+            .save_with_options_report(&mut vec![], &SaveOptions::new())
+            .unwrap();
+        assert!(notes.is_empty());
+    }
+
+    #[test]
+    fn save_with_options_converts_unsupported_objects() {
+        let mut drawing = Drawing::new();
+        drawing.convert_to_version(AcadVersion::R2000);
+        let handle = drawing
+            .add_object(Object::new(ObjectType::AcadProxyObject(Default::default())))
+            .common
+            .handle;
+        drawing.header.version = AcadVersion::R14;
+        assert!(drawing.needs_conversion(AcadVersion::R14, false));
+        let notes = drawing
+            .save_with_options_report(&mut vec![], &SaveOptions::new())
+            .unwrap();
+        assert!(notes.iter().any(|n| n.handle == handle && n.is_lossy));
+    }
+
+    #[test]
+    fn save_with_float_format_and_line_ending() {
+        let mut drawing = Drawing::new();
+        drawing.add_entity(Entity::new(EntityType::Line(Line::new(
+            Point::new(1.5, 2.0, 0.0),
+            Point::origin(),
+        ))));
+        let options = SaveOptions::new()
+            .float_format(FloatFormat::Fixed(3))
+            .line_ending(LineEnding::Lf);
+        let text = save_with_options(&drawing, &options);
+        assert!(!text.contains('\r'));
+        assert!(text.contains(&vec![" 10", "1.500", " 20", "2.000", " 30", "0.000"].join("\n")));
+
+        // default format
+        let text = save_with_options(&drawing, &SaveOptions::new());
+        assert!(text.contains(&vec![" 10", "1.5", " 20", "2.0", " 30", "0.0"].join("\r\n")));
+    }
+
+    #[test]
+    fn float_formats() {
+        assert_eq!("1.5", FloatFormat::Trimmed(12).format(1.5));
+        assert_eq!("2.0", FloatFormat::Trimmed(12).format(2.0));
+        assert_eq!("0.333", FloatFormat::Trimmed(3).format(1.0 / 3.0));
+        assert_eq!("1.50", FloatFormat::Fixed(2).format(1.5));
+        assert_eq!("2.0", FloatFormat::Fixed(0).format(2.0));
+        assert_eq!("0.1", FloatFormat::RoundTrip.format(0.1));
+        assert_eq!("-3.0", FloatFormat::RoundTrip.format(-3.0));
+    }
+
+    #[test]
+    fn save_without_handles() {
+        let mut drawing = Drawing::new();
+        drawing.header.version = AcadVersion::R2000;
+        drawing.add_entity(Entity::new(EntityType::Line(Line::default())));
+        let text = save_with_options(&drawing, &SaveOptions::new());
+        assert!(text.contains(&vec!["  0", "LINE", "  5"].join("\r\n")));
+
+        let text = save_with_options(&drawing, &SaveOptions::new().write_handles(false));
+        assert!(text.contains(&vec!["  0", "LINE", "100"].join("\r\n")));
+    }
+
+    #[test]
+    fn save_text_as_utf8() {
+        let mut drawing = Drawing::new();
+        drawing.header.version = AcadVersion::R2004;
+        drawing.header.project_name = String::from("\u{00E8}");
+        let text = save_with_options(&drawing, &SaveOptions::new());
+        assert!(text.contains(&vec!["$PROJECTNAME", "  1", "\\U+00E8"].join("\r\n")));
+
+        let text = save_with_options(&drawing, &SaveOptions::new().text_as_ascii(false));
+        assert!(text.contains(&vec!["$PROJECTNAME", "  1", "\u{00E8}"].join("\r\n")));
+    }
+
+    #[test]
+    fn save_without_thumbnail() {
+        let mut imgbuf = image::ImageBuffer::new(1, 1);
+        imgbuf.put_pixel(0, 0, image::Rgb([255u8, 0, 0]));
+        let mut drawing = Drawing::new();
+        drawing.header.version = AcadVersion::R2000;
+        drawing.thumbnail = Some(image::DynamicImage::ImageRgb8(imgbuf));
+        let text = save_with_options(&drawing, &SaveOptions::new());
+        assert!(text.contains("THUMBNAILIMAGE"));
+
+        let text = save_with_options(&drawing, &SaveOptions::new().include_thumbnail(false));
+        assert!(!text.contains("THUMBNAILIMAGE"));
+    }
+
+    #[test]
+    fn read_unknown_section() {
+        let drawing = parse_drawing(
+            vec![
+                "  0",
+                "SECTION",
+                "  2",
+                "ENTITIES",
+                "  0",
+                "ENDSEC",
+                "  0",
+                "SECTION",
+                "  2",
+                "ACDSDATA",
+                " 70",
+                "2",
+                "  0",
+                "ACDSSCHEMA",
+                "  0",
+                "ENDSEC",
+                "  0",
+                "EOF",
+            ]
+            .join("\r\n")
+            .as_str(),
+        );
+        assert_eq!(1, drawing.unknown_sections.len());
+        let section = &drawing.unknown_sections[0];
+        assert_eq!("ACDSDATA", section.name);
+        assert_eq!("ENTITIES", section.preceding_section);
+        assert_eq!(
+            vec![CodePair::new_i16(70, 2), CodePair::new_str(0, "ACDSSCHEMA")],
+            section.pairs
+        );
+    }
+
+    #[test]
+    fn write_unknown_section_in_original_position() {
+        let drawing = parse_drawing(
+            vec![
+                "  0", "SECTION", "  2", "ENTITIES", "  0", "ENDSEC", "  0", "SECTION", "  2",
+                "ACDSDATA", " 70", "2", "  0", "ENDSEC", "  0", "SECTION", "  2", "OBJECTS", "  0",
+                "ENDSEC", "  0", "EOF",
+            ]
+            .join("\r\n")
+            .as_str(),
+        );
+        assert_contains(
+            &drawing,
+            vec![
+                "  0", "ENDSEC", "  0", "SECTION", "  2", "ACDSDATA", " 70", "     2", "  0",
+                "ENDSEC", "  0", "SECTION", "  2", "OBJECTS",
+            ]
+            .join("\r\n"),
+        );
+    }
+
+    #[test]
+    fn update_extents_on_save() {
+        let mut drawing = Drawing::new();
+        drawing.add_entity(Entity::new(EntityType::Line(Line::new(
+            Point::new(1.0, 2.0, 0.0),
+            Point::new(3.0, 4.0, 0.0),
+        ))));
+        let extmin = vec!["  9", "$EXTMIN", " 10", "1.0", " 20", "2.0", " 30", "0.0"].join("\r\n");
+        assert!(!to_test_string(&drawing).contains(&extmin));
+
+        let mut buf = vec![];
+        let options = SaveOptions::new().update_extents(true);
+        drawing.save_with_options(&mut buf, &options).unwrap();
+        let text = String::from_utf8(buf).unwrap();
+        assert!(text.contains(&extmin));
+        assert!(text
+            .contains(&["  9", "$EXTMAX", " 10", "3.0", " 20", "4.0", " 30", "0.0"].join("\r\n")));
+
+        // the drawing itself is unchanged
+        assert_eq!(Point::origin(), drawing.header.minimum_drawing_extents);
+    }
+
+    #[test]
+    fn resolve_color_from_entity_and_layer() {
+        let mut drawing = Drawing::new();
+        drawing.add_layer(Layer {
+            name: String::from("blue"),
+            color: Color::from_index(5),
+            ..Default::default()
+        });
+        drawing.add_layer(Layer {
+            name: String::from("orange"),
+            color: Color::from_index(30),
+            color_24_bit: 0xFF_80_00,
+            ..Default::default()
+        });
+
+        let mut entity = Entity::new(EntityType::Line(Line::default()));
+        entity.common.layer = String::from("blue");
+        let color = drawing.resolve_color(&entity, &[]);
+        assert_eq!((0, 0, 255), color.rgb);
+        assert_eq!(Some(5), color.index);
+
+        entity.common.layer = String::from("orange");
+        let color = drawing.resolve_color(&entity, &[]);
+        assert_eq!((0xFF, 0x80, 0x00), color.rgb);
+        assert_eq!(None, color.index);
+
+        entity.common.color = Color::from_index(3);
+        assert_eq!((0, 255, 0), drawing.resolve_color(&entity, &[]).rgb);
+
+        entity.common.color_24_bit = 0x12_34_56;
+        entity.common.color_name = String::from("BOOK$COLOR");
+        let color = drawing.resolve_color(&entity, &[]);
+        assert_eq!((0x12, 0x34, 0x56), color.rgb);
+        assert_eq!("BOOK$COLOR", color.color_name);
+
+        entity.common.layer = String::from("missing");
+        entity.common.color = Color::by_layer();
+        entity.common.set_true_color(None);
+        assert_eq!(Some(7), drawing.resolve_color(&entity, &[]).index);
+    }
+
+    #[test]
+    fn resolve_color_through_inserts() {
+        let mut drawing = Drawing::new();
+        drawing.add_layer(Layer {
+            name: String::from("red"),
+            color: Color::from_index(1),
+            ..Default::default()
+        });
+        let mut outer = Entity::new(EntityType::Insert(Insert::default()));
+        outer.common.layer = String::from("red");
+        outer.common.color = Color::from_index(2);
+        let mut inner = Entity::new(EntityType::Insert(Insert::default()));
+        inner.common.color = Color::by_block();
+
+        // BYBLOCK is taken from the innermost insert, which is itself BYBLOCK
+        let mut entity = Entity::new(EntityType::Line(Line::default()));
+        entity.common.color = Color::by_block();
+        assert_eq!(
+            Some(2),
+            drawing.resolve_color(&entity, &[&outer, &inner]).index
+        );
+        assert_eq!(Some(7), drawing.resolve_color(&entity, &[]).index);
+
+        // BYLAYER on layer 0 uses the layer of the insert
+        entity.common.color = Color::by_layer();
+        assert_eq!(
+            Some(1),
+            drawing.resolve_color(&entity, &[&outer, &inner]).index
+        );
+    }
+
+    #[test]
+    fn flatten_inserts() {
+        let mut drawing = Drawing::new();
+        let mut block = Block {
+            name: String::from("b"),
+            ..Default::default()
+        };
+        block.entities.push(Entity::new(EntityType::Line(Line::new(
+            Point::origin(),
+            Point::new(1.0, 0.0, 0.0),
+        ))));
+        drawing.add_block(block);
+        drawing.add_entity(Entity::new(EntityType::Circle(Circle::new(
+            Point::origin(),
+            1.0,
+        ))));
+        let mut ins = Entity::new(EntityType::Insert(Insert {
+            name: String::from("b"),
+            location: Point::new(2.0, 0.0, 0.0),
+            ..Default::default()
+        }));
+        ins.common.layer = String::from("insert-layer");
+        drawing.add_entity(ins);
+
+        drawing.flatten_inserts();
+        let entities = drawing.entities().collect::<Vec<_>>();
+        assert_eq!(2, entities.len());
+        match entities[1].specific {
+            EntityType::Line(ref line) => {
+                assert_eq!(Point::new(2.0, 0.0, 0.0), line.p1);
+                assert_eq!(Point::new(3.0, 0.0, 0.0), line.p2);
+            }
+            _ => panic!("expected a line"),
+        }
+        assert_eq!("insert-layer", entities[1].common.layer);
+        assert_ne!(0, entities[1].common.handle);
+        assert!(drawing.layers().any(|l| l.name == "insert-layer"));
+    }
+
+    #[test]
+    fn write_unknown_section_without_known_position_at_end() {
+        let mut drawing = Drawing::new();
+        let mut section = UnknownSection::new("CUSTOM");
+        section.pairs.push(CodePair::new_str(1, "value"));
+        drawing.unknown_sections.push(section);
+        let contents = to_test_string(&drawing);
+        assert!(contents.ends_with(
+            &vec![
+                "  0", "SECTION", "  2", "CUSTOM", "  1", "value", "  0", "ENDSEC", "  0", "EOF",
+                "",
+            ]
+            .join("\r\n")
+        ));
+    }
+
     #[test]
     fn object_handle_is_honored_during_read_if_specified() {
         let drawing = parse_drawing(
@@ -2302,4 +3564,166 @@ mod tests {
             .collect::<Vec<_>>();
         assert_eq!(1, views.len());
     }
+
+    fn layout_entity_layers(drawing: &Drawing, layout: &Layout) -> Vec<String> {
+        layout
+            .entities(drawing)
+            .iter()
+            .map(|e| e.common.layer.clone())
+            .collect()
+    }
+
+    #[test]
+    fn model_space_excludes_paper_space_entities() {
+        let mut drawing = Drawing::new();
+        let mut line = Entity::new(EntityType::Line(Default::default()));
+        line.common.layer = String::from("model");
+        drawing.add_entity(line.clone());
+        line.common.layer = String::from("paper");
+        line.common.is_in_paper_space = true;
+        drawing.add_entity(line);
+        let layers = drawing
+            .model_space()
+            .map(|e| e.common.layer.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(vec!["model"], layers);
+    }
+
+    #[test]
+    fn normalize_keeps_block_records_with_different_case() {
+        let mut drawing = Drawing::new();
+        drawing.clear();
+        drawing.add_block_record(BlockRecord {
+            name: String::from("*Model_Space"),
+            ..Default::default()
+        });
+        drawing.normalize();
+        let count = drawing
+            .block_records()
+            .filter(|r| r.name.eq_ignore_ascii_case("*MODEL_SPACE"))
+            .count();
+        assert_eq!(1, count);
+    }
+
+    #[test]
+    fn add_layout_adds_paper_space_blocks() {
+        let mut drawing = Drawing::new();
+        assert_eq!("Sheet 1", drawing.add_layout("Sheet 1").layout_name);
+        drawing.add_layout("Sheet 2");
+        drawing.add_layout("sheet 1");
+        let layouts = drawing
+            .layouts()
+            .map(|l| (l.layout_name.as_str(), l.tab_order))
+            .collect::<Vec<_>>();
+        assert_eq!(vec![("Model", 0), ("Sheet 1", 1), ("Sheet 2", 2)], layouts);
+
+        let record_names = drawing
+            .layouts()
+            .map(|l| match l.get_table_record(&drawing) {
+                Some(DrawingItem::BlockRecord(record)) => record.name.to_uppercase(),
+                _ => panic!("expected a block record"),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            vec!["*MODEL_SPACE", "*PAPER_SPACE", "*PAPER_SPACE0"],
+            record_names
+        );
+        let block = drawing
+            .blocks()
+            .find(|b| b.name == "*Paper_Space0")
+            .unwrap();
+        assert!(block.is_in_paperspace);
+        let record = drawing
+            .block_records()
+            .find(|r| r.name == "*Paper_Space0")
+            .unwrap();
+        assert_eq!(record.handle, block.__owner_handle);
+        let layout_handle = drawing
+            .objects()
+            .find(|o| match o.specific {
+                ObjectType::Layout(ref l) => l.layout_name == "Sheet 2",
+                _ => false,
+            })
+            .unwrap()
+            .common
+            .handle;
+        assert_eq!(layout_handle, record.__layout_handle);
+    }
+
+    #[test]
+    fn add_entity_to_layout() {
+        let mut drawing = Drawing::new();
+        let mut line = Entity::new(EntityType::Line(Default::default()));
+        for (layer, layout) in &[("a", "Model"), ("b", "Sheet 1"), ("c", "Sheet 2")] {
+            line.common.layer = layer.to_string();
+            drawing.add_entity_to_layout(line.clone(), layout);
+        }
+
+        let layouts = drawing.layouts().collect::<Vec<_>>();
+        assert_eq!(vec!["a"], layout_entity_layers(&drawing, layouts[0]));
+        assert_eq!(vec!["b"], layout_entity_layers(&drawing, layouts[1]));
+        assert_eq!(vec!["c"], layout_entity_layers(&drawing, layouts[2]));
+        assert_eq!(2, drawing.entities().count());
+        assert_eq!(1, drawing.model_space().count());
+
+        let block = drawing
+            .blocks()
+            .find(|b| b.name == "*Paper_Space0")
+            .unwrap();
+        let entity = &block.entities[0];
+        assert!(entity.common.is_in_paper_space);
+        assert_eq!(block.__owner_handle, entity.common.__owner_handle);
+        assert_ne!(0, entity.common.handle);
+        assert!(drawing.layers().any(|l| l.name == "c"));
+    }
+
+    #[test]
+    fn add_entity_to_layout_without_block_record() {
+        let mut drawing = Drawing::new();
+        drawing.add_object(Object::new(ObjectType::Layout(Layout {
+            layout_name: String::from("X"),
+            ..Default::default()
+        })));
+        let mut line = Entity::new(EntityType::Line(Default::default()));
+        line.common.layer = String::from("x");
+        drawing.add_entity_to_layout(line, "X");
+
+        let layout = drawing.layouts().find(|l| l.layout_name == "X").unwrap();
+        assert_eq!(vec!["x"], layout_entity_layers(&drawing, layout));
+        let record = match layout.get_table_record(&drawing) {
+            Some(DrawingItem::BlockRecord(record)) => record,
+            _ => panic!("expected a block record"),
+        };
+        assert_ne!(0, record.__layout_handle);
+        let block = drawing
+            .blocks()
+            .find(|b| b.name.eq_ignore_ascii_case(&record.name))
+            .unwrap();
+        assert_eq!(record.handle, block.__owner_handle);
+    }
+
+    #[test]
+    fn entities_added_to_layouts_round_trip() {
+        let mut drawing = Drawing::new();
+        drawing.header.version = AcadVersion::R2000;
+        let mut line = Entity::new(EntityType::Line(Default::default()));
+        for (layer, layout) in &[("a", "Model"), ("b", "Sheet 1"), ("c", "Sheet 2")] {
+            line.common.layer = layer.to_string();
+            drawing.add_entity_to_layout(line.clone(), layout);
+        }
+
+        let drawing = parse_drawing(&to_test_string(&drawing));
+        let layouts = drawing
+            .layouts()
+            .map(|l| (l.layout_name.clone(), layout_entity_layers(&drawing, l)))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            vec![
+                (String::from("Model"), vec![String::from("a")]),
+                (String::from("Sheet 1"), vec![String::from("b")]),
+                (String::from("Sheet 2"), vec![String::from("c")]),
+            ],
+            layouts
+        );
+    }
 }
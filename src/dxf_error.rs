@@ -23,8 +23,66 @@ pub enum DxfError {
     ExpectedTableType(usize),
     WrongValueType(usize),
     InvalidBinaryFile,
+    /// The input is a DWG drawing.  Reading and writing DWG is out of scope; convert it to DXF first.
+    UnsupportedDwgFile,
     MalformedString,
     WrongItemType,
+    /// `XData` or `XRecordObject` items don't have the expected shape.
+    UnexpectedXData(String),
+    /// A `DrawingWriter` ran out of the handles it reserved for streamed entities.
+    HandlesExhausted(u32),
+    /// An error with a description of what was being read when it occurred.
+    Context(Box<DxfError>, ErrorContext),
+}
+
+/// Describes where in a file a `DxfError` occurred and what was being read at the time.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ErrorContext {
+    /// The position in the file where the error occurred.
+    pub position: Option<FilePosition>,
+    /// What was being read, outermost first, e.g., the `ENTITIES` section, then a `LINE` with
+    /// handle `2A`, then group code `20`.
+    pub frames: Vec<ContextFrame>,
+}
+
+/// One level of an `ErrorContext`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ContextFrame {
+    /// A section, e.g., `ENTITIES`.
+    Section(String),
+    /// An entity, object, table entry, or block by its type string, e.g., `LINE`, or a header
+    /// variable, e.g., `$ACADVER`.
+    Item(String),
+    /// The handle of the item being read.
+    Handle(u32),
+    /// The group code being read.
+    Code(i32),
+}
+
+/// A position in a DXF file.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum FilePosition {
+    /// A 1-based line number in a text file.
+    Line(usize),
+    /// A byte offset in a binary file.
+    ByteOffset(usize),
+}
+
+impl DxfError {
+    /// Returns the error without any `ErrorContext`.
+    pub fn inner(&self) -> &DxfError {
+        match *self {
+            DxfError::Context(ref e, _) => e.inner(),
+            _ => self,
+        }
+    }
+    /// Returns the description of what was being read when the error occurred, if known.
+    pub fn context(&self) -> Option<&ErrorContext> {
+        match *self {
+            DxfError::Context(_, ref context) => Some(context),
+            _ => None,
+        }
+    }
 }
 
 impl From<io::Error> for DxfError {
@@ -89,19 +147,54 @@ impl fmt::Display for DxfError {
                 o
             ),
             DxfError::InvalidBinaryFile => write!(formatter, "the binary file is invalid"),
+            DxfError::UnsupportedDwgFile => write!(
+                formatter,
+                "the file is a DWG drawing; only DXF and DXB files are supported"
+            ),
             DxfError::MalformedString => write!(formatter, "the string is malformed"),
             DxfError::WrongItemType => write!(formatter, "the specified item type is not correct"),
+            DxfError::UnexpectedXData(ref s) => {
+                write!(formatter, "the XData items were not expected: {}", s)
+            }
+            DxfError::HandlesExhausted(reserve) => write!(
+                formatter,
+                "more handles were needed than the {} reserved for streamed entities",
+                reserve
+            ),
+            DxfError::Context(ref e, ref context) => write!(formatter, "{} ({})", e, context),
+        }
+    }
+}
+
+impl fmt::Display for ErrorContext {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(formatter, "while reading")?;
+        for (i, frame) in self.frames.iter().enumerate() {
+            let separator = if i == 0 { " " } else { " > " };
+            match *frame {
+                ContextFrame::Section(ref s) => write!(formatter, "{}section {}", separator, s)?,
+                ContextFrame::Item(ref s) => write!(formatter, "{}{}", separator, s)?,
+                ContextFrame::Handle(h) => write!(formatter, "{}handle {:X}", separator, h)?,
+                ContextFrame::Code(c) => write!(formatter, "{}code {}", separator, c)?,
+            }
+        }
+        match self.position {
+            Some(FilePosition::Line(l)) => write!(formatter, " at line {}", l),
+            Some(FilePosition::ByteOffset(o)) => write!(formatter, " at byte offset {}", o),
+            None => Ok(()),
         }
     }
 }
 
 impl error::Error for DxfError {
-    fn cause(&self) -> Option<&dyn error::Error> {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
         match *self {
             DxfError::IoError(ref e) => Some(e),
             DxfError::ImageError(ref e) => Some(e),
             DxfError::ParseFloatError(ref e, _) => Some(e),
             DxfError::ParseIntError(ref e, _) => Some(e),
+            // the context is already part of the message, so report the underlying cause directly
+            DxfError::Context(ref e, _) => e.source(),
             _ => None,
         }
     }
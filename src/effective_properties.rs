@@ -0,0 +1,276 @@
+use crate::entities::*;
+use crate::helper_functions::transparency_to_alpha;
+use crate::tables::{Layer, LineType};
+use crate::{Drawing, LineWeight, ResolvedColor};
+
+/// The `Insert`s and viewport that an entity is drawn through.  See
+/// `Drawing::effective_properties()`.
+#[derive(Clone, Debug, Default)]
+pub struct InsertContext<'a> {
+    /// The chain of `Insert` entities that contain the entity, outermost first.  Empty for entities
+    /// that aren't in a block.
+    pub inserts: Vec<&'a Entity>,
+    /// The names of the layers that are frozen in the viewport being drawn, e.g., the
+    /// per-viewport frozen layers of a paper space viewport.
+    pub viewport_frozen_layers: Vec<String>,
+}
+
+impl<'a> InsertContext<'a> {
+    /// Creates a context for an entity that is drawn through the specified `Insert`s, outermost
+    /// first.
+    pub fn new(inserts: Vec<&'a Entity>) -> Self {
+        InsertContext {
+            inserts,
+            viewport_frozen_layers: vec![],
+        }
+    }
+    fn is_frozen_in_viewport(&self, layer_name: &str) -> bool {
+        self.viewport_frozen_layers
+            .iter()
+            .any(|l| l.eq_ignore_ascii_case(layer_name))
+    }
+}
+
+/// The properties an entity is drawn with after resolving `BYLAYER` and `BYBLOCK` values.  See
+/// `Drawing::effective_properties()`.
+#[derive(Clone, Debug)]
+pub struct EffectiveProperties<'a> {
+    /// The layer the entity is drawn on.  Entities on layer `0` inside a block are drawn on the
+    /// layer of their `Insert`.  `None` if the layer isn't in the drawing.
+    pub layer: Option<&'a Layer>,
+    /// The concrete color of the entity.
+    pub color: ResolvedColor,
+    /// The line type of the entity, or `None` if it's continuous or not in the drawing.
+    pub line_type: Option<&'a LineType>,
+    /// The combined line type scale of the entity and the drawing.
+    pub line_type_scale: f64,
+    /// The line weight of the entity; never `BYLAYER` or `BYBLOCK`.
+    pub line_weight: LineWeight,
+    /// The opacity of the entity from `0` (fully transparent) to `255` (opaque).
+    pub alpha: u8,
+    /// Whether the entity is shown, i.e., it and its `Insert`s are visible and none of their
+    /// layers are off or frozen, either globally or in the viewport.
+    pub is_visible: bool,
+    /// Whether the entity is plotted, i.e., it's visible and its layer is plotted.
+    pub is_plotted: bool,
+}
+
+impl Drawing {
+    /// Returns the properties `entity` is drawn with.  `BYLAYER` values come from the entity's
+    /// `Layer` and `BYBLOCK` values come from the innermost `Insert` of `context`.  Entities on
+    /// layer `0` inside a block take the layer of their `Insert`.  See `resolve_color()` for how
+    /// colors are resolved.
+    pub fn effective_properties<'a>(
+        &'a self,
+        entity: &Entity,
+        context: &InsertContext,
+    ) -> EffectiveProperties<'a> {
+        let inserts = &context.inserts[..];
+        let layer = self.find_layer(Drawing::effective_layer_name(&entity.common, inserts));
+        let line_type_name = self.effective_line_type_name(&entity.common, inserts);
+        let line_type = self
+            .line_types()
+            .find(|lt| lt.name.eq_ignore_ascii_case(&line_type_name))
+            .filter(|lt| !lt.dash_dot_space_lengths.is_empty());
+
+        // an `Insert` on a layer that's off still shows its entities on other layers, but frozen
+        // layers hide everything on and inside them
+        let mut is_visible = entity.common.is_visible && layer.iter().all(|l| l.is_layer_on);
+        let mut common = &entity.common;
+        let mut outer_inserts = inserts;
+        loop {
+            let layer_name = Drawing::effective_layer_name(common, outer_inserts);
+            is_visible &= !self
+                .find_layer(layer_name)
+                .iter()
+                .any(|l| l.get_is_frozen());
+            is_visible &= !context.is_frozen_in_viewport(layer_name);
+            match outer_inserts.split_last() {
+                Some((insert, rest)) => {
+                    is_visible &= insert.common.is_visible;
+                    common = &insert.common;
+                    outer_inserts = rest;
+                }
+                None => break,
+            }
+        }
+
+        EffectiveProperties {
+            layer,
+            color: self.resolve_common_color(&entity.common, inserts),
+            line_type,
+            line_type_scale: entity.common.line_type_scale * self.header.line_type_scale,
+            line_weight: self.effective_line_weight(&entity.common, inserts),
+            alpha: self.effective_alpha(&entity.common, inserts),
+            is_visible,
+            is_plotted: is_visible && layer.iter().all(|l| l.is_layer_plotted),
+        }
+    }
+    fn find_layer(&self, name: &str) -> Option<&Layer> {
+        self.layers().find(|l| l.name.eq_ignore_ascii_case(name))
+    }
+    fn effective_line_type_name(&self, common: &EntityCommon, inserts: &[&Entity]) -> String {
+        let name = &common.line_type_name;
+        if name.eq_ignore_ascii_case("BYBLOCK") {
+            match inserts.split_last() {
+                Some((insert, outer_inserts)) => {
+                    self.effective_line_type_name(&insert.common, outer_inserts)
+                }
+                None => String::from("CONTINUOUS"),
+            }
+        } else if name.is_empty() || name.eq_ignore_ascii_case("BYLAYER") {
+            self.find_layer(Drawing::effective_layer_name(common, inserts))
+                .map_or_else(|| String::from("CONTINUOUS"), |l| l.line_type_name.clone())
+        } else {
+            name.clone()
+        }
+    }
+    fn effective_line_weight(&self, common: &EntityCommon, inserts: &[&Entity]) -> LineWeight {
+        let line_weight = LineWeight::from_raw_value(common.lineweight_enum_value);
+        if line_weight.is_by_block() {
+            match inserts.split_last() {
+                Some((insert, outer_inserts)) => {
+                    self.effective_line_weight(&insert.common, outer_inserts)
+                }
+                None => LineWeight::standard(),
+            }
+        } else if line_weight.is_by_layer() {
+            match self.find_layer(Drawing::effective_layer_name(common, inserts)) {
+                Some(layer) if layer.line_weight.get_raw_value() >= 0 => layer.line_weight.clone(),
+                _ => LineWeight::standard(),
+            }
+        } else {
+            line_weight
+        }
+    }
+    fn effective_alpha(&self, common: &EntityCommon, inserts: &[&Entity]) -> u8 {
+        match common.transparency {
+            // BYBLOCK
+            0x0100_0000 => match inserts.split_last() {
+                Some((insert, outer_inserts)) => {
+                    self.effective_alpha(&insert.common, outer_inserts)
+                }
+                None => 255,
+            },
+            value => transparency_to_alpha(value).unwrap_or_else(|| {
+                self.find_layer(Drawing::effective_layer_name(common, inserts))
+                    .map_or(255, |l| l.get_alpha())
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::entities::*;
+    use crate::tables::*;
+    use crate::*;
+
+    fn drawing() -> Drawing {
+        let mut drawing = Drawing::new();
+        drawing.add_line_type(LineType {
+            name: String::from("DASHED"),
+            dash_dot_space_lengths: vec![0.5, -0.25],
+            ..Default::default()
+        });
+        drawing.add_layer(Layer {
+            name: String::from("walls"),
+            color: Color::from_index(1),
+            line_type_name: String::from("DASHED"),
+            line_weight: LineWeight::from_raw_value(50),
+            ..Default::default()
+        });
+        drawing
+    }
+
+    #[test]
+    fn properties_from_layer() {
+        let drawing = drawing();
+        let mut entity = Entity::new(EntityType::Line(Line::default()));
+        entity.common.layer = String::from("walls");
+        entity.common.lineweight_enum_value = -2;
+        let properties = drawing.effective_properties(&entity, &InsertContext::default());
+        assert_eq!("walls", properties.layer.unwrap().name);
+        assert_eq!(Some(1), properties.color.index);
+        assert_eq!("DASHED", properties.line_type.unwrap().name);
+        assert_eq!(50, properties.line_weight.get_raw_value());
+        assert_eq!(255, properties.alpha);
+        assert!(properties.is_visible);
+        assert!(properties.is_plotted);
+    }
+
+    #[test]
+    fn properties_from_insert() {
+        let drawing = drawing();
+        let mut insert = Entity::new(EntityType::Insert(Insert::default()));
+        insert.common.layer = String::from("walls");
+        insert.common.color = Color::from_index(3);
+        insert.common.lineweight_enum_value = 35;
+        insert.common.transparency = 0x0200_0040;
+
+        // entities on layer 0 inside a block use the insert's layer
+        let mut entity = Entity::new(EntityType::Line(Line::default()));
+        entity.common.color = Color::by_block();
+        entity.common.line_type_name = String::from("BYLAYER");
+        entity.common.lineweight_enum_value = -1;
+        entity.common.transparency = 0x0100_0000;
+        let context = InsertContext::new(vec![&insert]);
+        let properties = drawing.effective_properties(&entity, &context);
+        assert_eq!("walls", properties.layer.unwrap().name);
+        assert_eq!(Some(3), properties.color.index);
+        assert_eq!("DASHED", properties.line_type.unwrap().name);
+        assert_eq!(35, properties.line_weight.get_raw_value());
+        assert_eq!(0x40, properties.alpha);
+
+        // without an insert, BYBLOCK values fall back to their defaults
+        let properties = drawing.effective_properties(&entity, &InsertContext::default());
+        assert_eq!("0", properties.layer.unwrap().name);
+        assert_eq!(Some(7), properties.color.index);
+        assert!(properties.line_type.is_none());
+        assert!(properties.line_weight.is_standard());
+        assert_eq!(255, properties.alpha);
+    }
+
+    #[test]
+    fn visibility_from_layers_and_viewport() {
+        let mut drawing = drawing();
+        let mut entity = Entity::new(EntityType::Line(Line::default()));
+        entity.common.layer = String::from("walls");
+        let context = InsertContext::default();
+        assert!(drawing.effective_properties(&entity, &context).is_visible);
+
+        let viewport_context = InsertContext {
+            viewport_frozen_layers: vec![String::from("WALLS")],
+            ..Default::default()
+        };
+        assert!(
+            !drawing
+                .effective_properties(&entity, &viewport_context)
+                .is_visible
+        );
+
+        drawing
+            .layers_mut()
+            .for_each(|l| l.is_layer_plotted = false);
+        let properties = drawing.effective_properties(&entity, &context);
+        assert!(properties.is_visible);
+        assert!(!properties.is_plotted);
+
+        drawing.layers_mut().for_each(|l| l.set_is_frozen(true));
+        assert!(!drawing.effective_properties(&entity, &context).is_visible);
+
+        // a frozen insert layer hides the block's entities, but an insert layer that's off doesn't
+        let mut insert = Entity::new(EntityType::Insert(Insert::default()));
+        insert.common.layer = String::from("walls");
+        let mut entity = Entity::new(EntityType::Line(Line::default()));
+        entity.common.layer = String::from("other");
+        let context = InsertContext::new(vec![&insert]);
+        assert!(!drawing.effective_properties(&entity, &context).is_visible);
+
+        drawing.layers_mut().for_each(|l| {
+            l.set_is_frozen(false);
+            l.is_layer_on = false;
+        });
+        assert!(drawing.effective_properties(&entity, &context).is_visible);
+    }
+}
@@ -3,15 +3,40 @@
 use enum_primitive::FromPrimitive;
 use std::io::{Read, Write};
 
-use crate::{CodePair, Color, DxfError, DxfResult, Point, Vector};
+use crate::{
+    Block, CodePair, Color, DrawingItem, DxfError, DxfResult, HatchBoundaryPath, LineWeight,
+    MLeaderContextData, Point, TransformationMatrix, Vector,
+};
 
 use crate::code_pair_put_back::CodePairPutBack;
 use crate::code_pair_writer::CodePairWriter;
 use crate::entities::*;
 use crate::enums::*;
 use crate::helper_functions::*;
+use crate::tables::BlockRecord;
 use crate::Drawing;
 
+/// Creates a `Text` with the same appearance as an `Attribute` or `AttributeDefinition`.
+macro_rules! text_from_attribute {
+    ($att:expr) => {
+        Text {
+            thickness: $att.thickness,
+            location: $att.location.clone(),
+            text_height: $att.text_height,
+            value: $att.value.clone(),
+            rotation: $att.rotation,
+            relative_x_scale_factor: $att.relative_x_scale_factor,
+            oblique_angle: $att.oblique_angle,
+            text_style_name: $att.text_style_name.clone(),
+            text_generation_flags: $att.text_generation_flags,
+            horizontal_text_justification: $att.horizontal_text_justification,
+            second_alignment_point: $att.second_alignment_point.clone(),
+            normal: $att.normal.clone(),
+            vertical_text_justification: $att.vertical_text_justification,
+        }
+    };
+}
+
 //------------------------------------------------------------------------------
 //                                                                           Arc
 //------------------------------------------------------------------------------
@@ -25,6 +50,27 @@ impl Arc {
             ..Default::default()
         }
     }
+    /// Returns the center of the arc in world coordinates.
+    pub fn center_wcs(&self) -> Point {
+        self.normal.ocs_matrix().transform_point(&self.center)
+    }
+    /// Returns the point where the arc starts in world coordinates.
+    pub fn start_point_wcs(&self) -> Point {
+        self.point_at_angle_wcs(self.start_angle)
+    }
+    /// Returns the point where the arc ends in world coordinates.
+    pub fn end_point_wcs(&self) -> Point {
+        self.point_at_angle_wcs(self.end_angle)
+    }
+    fn point_at_angle_wcs(&self, angle: f64) -> Point {
+        let (sin, cos) = angle.to_radians().sin_cos();
+        let p = Point::new(
+            self.center.x + self.radius * cos,
+            self.center.y + self.radius * sin,
+            self.center.z,
+        );
+        self.normal.ocs_matrix().transform_point(&p)
+    }
 }
 
 //------------------------------------------------------------------------------
@@ -38,6 +84,10 @@ impl Circle {
             ..Default::default()
         }
     }
+    /// Returns the center of the circle in world coordinates.
+    pub fn center_wcs(&self) -> Point {
+        self.normal.ocs_matrix().transform_point(&self.center)
+    }
 }
 
 //------------------------------------------------------------------------------
@@ -86,6 +136,31 @@ impl Face3D {
     }
 }
 
+//------------------------------------------------------------------------------
+//                                                            HatchGradientColor
+//------------------------------------------------------------------------------
+/// Represents a single color of a gradient `Hatch`.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+pub struct HatchGradientColor {
+    pub value: f64,
+    pub color: Color,
+    pub color_24_bit: i32,
+}
+
+//------------------------------------------------------------------------------
+//                                                    HatchPatternDefinitionLine
+//------------------------------------------------------------------------------
+/// Represents a single line of a `Hatch` pattern definition.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+pub struct HatchPatternDefinitionLine {
+    pub angle: f64,
+    pub base_point: Point,
+    pub offset: Vector,
+    pub dash_lengths: Vec<f64>,
+}
+
 //------------------------------------------------------------------------------
 //                                                                        Insert
 //------------------------------------------------------------------------------
@@ -100,6 +175,123 @@ impl Insert {
         let att_handle = drawing.next_handle();
         self.__attributes_and_handles.push((att, att_handle));
     }
+    /// Returns the insertion point in world coordinates.
+    pub fn location_wcs(&self) -> Point {
+        self.extrusion_direction
+            .ocs_matrix()
+            .transform_point(&self.location)
+    }
+    /// Returns the entities of the referenced `Block` transformed to world coordinates, repeated
+    /// for every row and column of the array.  Nested `Insert`s are expanded recursively, constant
+    /// `AttributeDefinition`s and the visible `Attribute`s of the insert are returned as `Text`,
+    /// and the returned entities have no handles.
+    ///
+    /// Entities of nested blocks that are on layer `0` or use a `BYBLOCK` color, line type, or
+    /// lineweight take those values from the nested `Insert`.  Use `Drawing::flatten_inserts()` to
+    /// also resolve them against the top-level insert.
+    pub fn explode(&self, drawing: &Drawing) -> Vec<Entity> {
+        self.explode_internal(drawing, None, &mut vec![])
+    }
+    /// Returns the transformation from the coordinates of the referenced `Block` to world
+    /// coordinates for the array instance at the specified column and row.
+    pub fn block_transform(&self, block: &Block, column: i16, row: i16) -> TransformationMatrix {
+        let base_point = &block.base_point;
+        TransformationMatrix::identity()
+            .translate(&Vector::new(-base_point.x, -base_point.y, -base_point.z))
+            .scale(
+                self.x_scale_factor,
+                self.y_scale_factor,
+                self.z_scale_factor,
+            )
+            .translate(&Vector::new(
+                f64::from(column) * self.column_spacing,
+                f64::from(row) * self.row_spacing,
+                0.0,
+            ))
+            .rotate(&Vector::z_axis(), self.rotation)
+            .translate(&Vector::new(
+                self.location.x,
+                self.location.y,
+                self.location.z,
+            ))
+            .compose(&self.extrusion_direction.ocs_matrix())
+    }
+    pub(crate) fn explode_internal(
+        &self,
+        drawing: &Drawing,
+        insert_common: Option<&EntityCommon>,
+        visited_blocks: &mut Vec<String>,
+    ) -> Vec<Entity> {
+        let mut result = vec![];
+        let block = drawing.blocks().find(|b| b.name == self.name);
+        match block {
+            // a block that contains itself can't be expanded
+            Some(block) if !visited_blocks.contains(&block.name) => {
+                visited_blocks.push(block.name.clone());
+                let mut block_entities = vec![];
+                for e in &block.entities {
+                    match e.specific {
+                        EntityType::Insert(ref ins) => {
+                            block_entities.extend(ins.explode_internal(
+                                drawing,
+                                Some(&e.common),
+                                visited_blocks,
+                            ));
+                        }
+                        EntityType::AttributeDefinition(ref att) => {
+                            if att.get_is_constant() && !att.get_is_invisible() {
+                                let mut text =
+                                    Entity::new(EntityType::Text(text_from_attribute!(att)));
+                                text.common = e.common.clone();
+                                block_entities.push(text);
+                            }
+                        }
+                        _ => block_entities.push(e.clone()),
+                    }
+                }
+                visited_blocks.pop();
+
+                for column in 0..self.column_count.max(1) {
+                    for row in 0..self.row_count.max(1) {
+                        let matrix = self.block_transform(block, column, row);
+                        for e in &block_entities {
+                            let mut e = e.clone();
+                            e.transform(&matrix);
+                            result.push(e);
+                        }
+                    }
+                }
+            }
+            _ => (),
+        }
+
+        // attributes are already in world coordinates
+        for att in self.attributes().filter(|a| !a.get_is_invisible()) {
+            result.push(Entity::new(EntityType::Text(text_from_attribute!(att))));
+        }
+
+        for e in &mut result {
+            e.common.handle = 0;
+            e.common.__owner_handle = 0;
+            if let Some(common) = insert_common {
+                e.common.is_in_paper_space = common.is_in_paper_space;
+                if e.common.layer == "0" {
+                    e.common.layer = common.layer.clone();
+                }
+                if e.common.color.is_by_block() {
+                    e.common.color = common.color.clone();
+                }
+                if e.common.line_type_name.eq_ignore_ascii_case("BYBLOCK") {
+                    e.common.line_type_name = common.line_type_name.clone();
+                }
+                if LineWeight::from_raw_value(e.common.lineweight_enum_value).is_by_block() {
+                    e.common.lineweight_enum_value = common.lineweight_enum_value;
+                }
+            }
+        }
+
+        result
+    }
 }
 
 //------------------------------------------------------------------------------
@@ -115,6 +307,21 @@ impl Line {
     }
 }
 
+//------------------------------------------------------------------------------
+//                                                                    LwPolyline
+//------------------------------------------------------------------------------
+impl LwPolyline {
+    /// Returns the vertices in world coordinates.  The `elevation` is stored with the entity's
+    /// common values, e.g., `entity.common.elevation`.
+    pub fn vertices_wcs(&self, elevation: f64) -> Vec<Point> {
+        let matrix = self.extrusion_direction.ocs_matrix();
+        self.vertices
+            .iter()
+            .map(|v| matrix.transform_point(&Point::new(v.x, v.y, elevation)))
+            .collect()
+    }
+}
+
 //------------------------------------------------------------------------------
 //                                                              LwPolylineVertex
 //------------------------------------------------------------------------------
@@ -130,6 +337,66 @@ pub struct LwPolylineVertex {
     pub bulge: f64,
 }
 
+//------------------------------------------------------------------------------
+//                                                              MLeaderArrowhead
+//------------------------------------------------------------------------------
+/// Represents an arrowhead override of an `MLeader`.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+pub struct MLeaderArrowhead {
+    pub index: i32,
+    #[doc(hidden)]
+    pub __arrowhead_handle: u32,
+}
+
+impl MLeaderArrowhead {
+    pub fn get_arrowhead<'a>(&self, drawing: &'a Drawing) -> Option<&'a BlockRecord> {
+        match drawing.get_item_by_handle(self.__arrowhead_handle) {
+            Some(DrawingItem::BlockRecord(val)) => Some(val),
+            _ => None,
+        }
+    }
+    pub fn set_arrowhead(&mut self, item: &BlockRecord) {
+        self.__arrowhead_handle = DrawingItem::BlockRecord(item).get_handle();
+    }
+}
+
+//------------------------------------------------------------------------------
+//                                                         MLeaderBlockAttribute
+//------------------------------------------------------------------------------
+/// Represents the value of an attribute of an `MLeader` block content.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+pub struct MLeaderBlockAttribute {
+    #[doc(hidden)]
+    pub __attribute_definition_handle: u32,
+    pub index: i16,
+    pub width: f64,
+    pub text: String,
+}
+
+impl MLeaderBlockAttribute {
+    pub fn get_attribute_definition<'a>(&self, drawing: &'a Drawing) -> Option<&'a Entity> {
+        match drawing.get_item_by_handle(self.__attribute_definition_handle) {
+            Some(DrawingItem::Entity(val)) => match val.specific {
+                EntityType::AttributeDefinition(_) => Some(val),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+    pub fn set_attribute_definition(&mut self, item: &Entity) -> DxfResult<()> {
+        match item.specific {
+            EntityType::AttributeDefinition { .. } => {
+                self.__attribute_definition_handle = item.common.handle
+            }
+            _ => return Err(DxfError::WrongItemType),
+        }
+
+        Ok(())
+    }
+}
+
 //------------------------------------------------------------------------------
 //                                                                    ModelPoint
 //------------------------------------------------------------------------------
@@ -156,6 +423,24 @@ impl Polyline {
         let vertex_handle = drawing.next_handle();
         self.__vertices_and_handles.push((vertex, vertex_handle));
     }
+    /// Returns the vertex locations in world coordinates.  The vertices of 2D polylines are
+    /// stored in the object coordinate system with the elevation in `location.z`; all other
+    /// polylines already use world coordinates.
+    pub fn vertices_wcs(&self) -> Vec<Point> {
+        if self.is_2d() {
+            let matrix = self.normal.ocs_matrix();
+            self.vertices()
+                .map(|v| {
+                    matrix.transform_point(&Point::new(v.location.x, v.location.y, self.location.z))
+                })
+                .collect()
+        } else {
+            self.vertices().map(|v| v.location.clone()).collect()
+        }
+    }
+    pub(crate) fn is_2d(&self) -> bool {
+        !self.get_is_3d_polyline() && !self.get_is_3d_polygon_mesh() && !self.get_is_polyface_mesh()
+    }
 }
 
 //------------------------------------------------------------------------------
@@ -197,6 +482,26 @@ impl Solid {
             ..Default::default()
         }
     }
+    /// Returns the four corners in world coordinates.
+    pub fn corners_wcs(&self) -> Vec<Point> {
+        let matrix = self.extrusion_direction.ocs_matrix();
+        vec![
+            matrix.transform_point(&self.first_corner),
+            matrix.transform_point(&self.second_corner),
+            matrix.transform_point(&self.third_corner),
+            matrix.transform_point(&self.fourth_corner),
+        ]
+    }
+}
+
+//------------------------------------------------------------------------------
+//                                                                          Text
+//------------------------------------------------------------------------------
+impl Text {
+    /// Returns the first alignment point in world coordinates.
+    pub fn location_wcs(&self) -> Point {
+        self.normal.ocs_matrix().transform_point(&self.location)
+    }
 }
 
 //------------------------------------------------------------------------------
@@ -217,6 +522,16 @@ impl Trace {
             ..Default::default()
         }
     }
+    /// Returns the four corners in world coordinates.
+    pub fn corners_wcs(&self) -> Vec<Point> {
+        let matrix = self.extrusion_direction.ocs_matrix();
+        vec![
+            matrix.transform_point(&self.first_corner),
+            matrix.transform_point(&self.second_corner),
+            matrix.transform_point(&self.third_corner),
+            matrix.transform_point(&self.fourth_corner),
+        ]
+    }
 }
 
 //------------------------------------------------------------------------------
@@ -389,6 +704,35 @@ impl EntityCommon {
     pub fn normalize(&mut self) {
         default_if_empty(&mut self.layer, "0");
     }
+    /// Returns the red, green, and blue components of the entity's true color, or `None` if the
+    /// entity only has an indexed `color`.  Since `color_24_bit` is `0` when unset, a true color of
+    /// pure black is only recognized when it comes from a color book.
+    pub fn get_true_color(&self) -> Option<(u8, u8, u8)> {
+        if self.color_24_bit & 0x00FF_FFFF == 0 && self.color_name.is_empty() {
+            None
+        } else {
+            Some(Color::true_color_to_rgb(self.color_24_bit))
+        }
+    }
+    /// Sets the entity's true color, or clears it if `None`.  Any color book name is cleared since
+    /// it no longer describes the color.  The indexed `color` is set to the nearest palette entry so
+    /// older readers show a similar color.
+    pub fn set_true_color(&mut self, rgb: Option<(u8, u8, u8)>) {
+        match rgb {
+            Some((r, g, b)) => {
+                self.color_24_bit = Color::rgb_to_true_color(r, g, b);
+                self.color = Color::from_rgb_nearest(r, g, b);
+            }
+            None => self.color_24_bit = 0,
+        }
+        self.color_name.clear();
+    }
+    /// Returns the color book and color names from `color_name`, e.g., `("PANTONE+ Solid Coated",
+    /// "PANTONE 185 C")`, or `None` if the entity's color isn't from a color book.
+    pub fn get_color_book_name(&self) -> Option<(&str, &str)> {
+        let index = self.color_name.find('$')?;
+        Some((&self.color_name[..index], &self.color_name[index + 1..]))
+    }
 }
 
 //------------------------------------------------------------------------------
@@ -407,6 +751,35 @@ impl Entity {
         self.common.normalize();
         // no entity-specific values to set
     }
+    /// Returns the matrix that converts points stored with the entity to world coordinates (WCS).
+    /// For entities that store their points in an object coordinate system (OCS), e.g., `Circle`
+    /// and 2D `Polyline`, this is derived from the entity's normal with the arbitrary axis
+    /// algorithm.  For all other entities, including dimensions, the identity matrix is returned.
+    pub fn ocs_matrix(&self) -> TransformationMatrix {
+        let normal = match self.specific {
+            EntityType::Arc(ref arc) => &arc.normal,
+            EntityType::ArcAlignedText(ref text) => &text.extrusion_direction,
+            EntityType::Attribute(ref att) => &att.normal,
+            EntityType::AttributeDefinition(ref att) => &att.normal,
+            EntityType::Circle(ref circle) => &circle.normal,
+            EntityType::Hatch(ref hatch) => &hatch.extrusion_direction,
+            EntityType::Insert(ref ins) => &ins.extrusion_direction,
+            EntityType::LwPolyline(ref poly) => &poly.extrusion_direction,
+            EntityType::Polyline(ref poly) if poly.is_2d() => &poly.normal,
+            EntityType::RText(ref text) => &text.extrusion_direction,
+            EntityType::Shape(ref shape) => &shape.extrusion_direction,
+            EntityType::Solid(ref solid) => &solid.extrusion_direction,
+            EntityType::Text(ref text) => &text.normal,
+            EntityType::Trace(ref trace) => &trace.extrusion_direction,
+            _ => return TransformationMatrix::identity(),
+        };
+        normal.ocs_matrix()
+    }
+    /// Converts a point stored with the entity, e.g., the `center` of a `Circle`, to world
+    /// coordinates.  See `ocs_matrix()`.
+    pub fn to_wcs(&self, point: &Point) -> Point {
+        self.ocs_matrix().transform_point(point)
+    }
     pub(crate) fn read<I>(iter: &mut CodePairPutBack<I>) -> DxfResult<Option<Entity>>
     where
         I: Read,
@@ -644,19 +1017,32 @@ impl Entity {
                                     return Ok(Some(entity));
                                 }
                                 None => {
-                                    // swallow unsupported entity
-                                    loop {
-                                        match iter.next() {
-                                            Some(Ok(pair @ CodePair { code: 0, .. })) => {
-                                                // found another entity or ENDSEC
-                                                iter.put_back(Ok(pair));
-                                                break;
+                                    // preserve unsupported entity so it can be written back as-is
+                                    let mut common = EntityCommon::default();
+                                    let pairs = iter.read_verbatim(|iter| {
+                                        let mut pairs = vec![];
+                                        loop {
+                                            match iter.next() {
+                                                Some(Ok(pair @ CodePair { code: 0, .. })) => {
+                                                    // found another entity or ENDSEC
+                                                    iter.put_back(Ok(pair));
+                                                    return Ok(pairs);
+                                                }
+                                                Some(Ok(pair)) => pairs.push(pair),
+                                                Some(Err(e)) => return Err(e),
+                                                None => return Err(DxfError::UnexpectedEndOfInput),
                                             }
-                                            Some(Ok(_)) => (), // part of the unsupported entity
-                                            Some(Err(e)) => return Err(e),
-                                            None => return Err(DxfError::UnexpectedEndOfInput),
                                         }
-                                    }
+                                    })?;
+
+                                    let (handle, owner) = get_handle_and_owner(&pairs)?;
+                                    common.handle = handle;
+                                    common.__owner_handle = owner;
+
+                                    return Ok(Some(Entity {
+                                        common,
+                                        specific: EntityType::Unknown { type_string, pairs },
+                                    }));
                                 }
                             }
                         }
@@ -808,9 +1194,15 @@ impl Entity {
             EntityType::AttributeDefinition(ref mut att) => {
                 Entity::apply_custom_reader_attributedefinition(&mut self.common, att, iter)
             }
+            EntityType::Hatch(ref mut hatch) => {
+                Entity::apply_custom_reader_hatch(&mut self.common, hatch, iter)
+            }
             EntityType::LwPolyline(ref mut poly) => {
                 Entity::apply_custom_reader_lwpolyline(&mut self.common, poly, iter)
             }
+            EntityType::MLeader(ref mut mleader) => {
+                Entity::apply_custom_reader_mleader(&mut self.common, mleader, iter)
+            }
             EntityType::MText(ref mut mtext) => {
                 Entity::apply_custom_reader_mtext(&mut self.common, mtext, iter)
             }
@@ -1122,6 +1514,154 @@ impl Entity {
             }
         }
     }
+    fn apply_custom_reader_hatch<I>(
+        common: &mut EntityCommon,
+        hatch: &mut Hatch,
+        iter: &mut CodePairPutBack<I>,
+    ) -> DxfResult<bool>
+    where
+        I: Read,
+    {
+        // codes 10 and 20 are re-used for the seed points after code 98
+        let mut reading_seed_points = false;
+        loop {
+            let pair = next_pair!(iter);
+            match pair.code {
+                10 if reading_seed_points => {
+                    // start a new seed point
+                    hatch
+                        .seed_points
+                        .push(Point::new(pair.assert_f64()?, 0.0, 0.0));
+                }
+                20 if reading_seed_points => {
+                    vec_last!(hatch.seed_points).y = pair.assert_f64()?;
+                }
+                10 => {
+                    hatch.elevation_point.x = pair.assert_f64()?;
+                }
+                20 => {
+                    hatch.elevation_point.y = pair.assert_f64()?;
+                }
+                30 => {
+                    hatch.elevation_point.z = pair.assert_f64()?;
+                }
+                210 => {
+                    hatch.extrusion_direction.x = pair.assert_f64()?;
+                }
+                220 => {
+                    hatch.extrusion_direction.y = pair.assert_f64()?;
+                }
+                230 => {
+                    hatch.extrusion_direction.z = pair.assert_f64()?;
+                }
+                2 => {
+                    hatch.pattern_name = pair.assert_string()?;
+                }
+                70 => {
+                    hatch.is_solid_fill = as_bool(pair.assert_i16()?);
+                }
+                71 => {
+                    hatch.is_associative = as_bool(pair.assert_i16()?);
+                }
+                91 => {
+                    // path count; implied by the paths that follow
+                    while let Some(path) = HatchBoundaryPath::read(iter)? {
+                        hatch.boundary_paths.push(path);
+                    }
+                }
+                75 => {
+                    hatch.hatch_style =
+                        enum_from_number!(HatchStyle, OddParity, from_i16, pair.assert_i16()?);
+                }
+                76 => {
+                    hatch.pattern_type = enum_from_number!(
+                        HatchPatternType,
+                        Predefined,
+                        from_i16,
+                        pair.assert_i16()?
+                    );
+                }
+                52 => {
+                    hatch.pattern_angle = pair.assert_f64()?;
+                }
+                41 => {
+                    hatch.pattern_scale = pair.assert_f64()?;
+                }
+                77 => {
+                    hatch.is_pattern_double = as_bool(pair.assert_i16()?);
+                }
+                78 => (), // pattern definition line count; implied by the lines that follow
+                // pattern definition line pairs
+                53 => {
+                    // start a new pattern definition line
+                    hatch
+                        .pattern_definition_lines
+                        .push(HatchPatternDefinitionLine::default());
+                    vec_last!(hatch.pattern_definition_lines).angle = pair.assert_f64()?;
+                }
+                43 => {
+                    vec_last!(hatch.pattern_definition_lines).base_point.x = pair.assert_f64()?;
+                }
+                44 => {
+                    vec_last!(hatch.pattern_definition_lines).base_point.y = pair.assert_f64()?;
+                }
+                45 => {
+                    vec_last!(hatch.pattern_definition_lines).offset.x = pair.assert_f64()?;
+                }
+                46 => {
+                    vec_last!(hatch.pattern_definition_lines).offset.y = pair.assert_f64()?;
+                }
+                79 => (), // dash length count; implied by the values that follow
+                49 => {
+                    vec_last!(hatch.pattern_definition_lines)
+                        .dash_lengths
+                        .push(pair.assert_f64()?);
+                }
+                47 => {
+                    hatch.pixel_size = pair.assert_f64()?;
+                }
+                98 => {
+                    reading_seed_points = true;
+                }
+                // gradient pairs
+                450 => {
+                    hatch.is_gradient = pair.assert_i64()? != 0;
+                }
+                451 => (), // reserved
+                452 => {
+                    hatch.is_single_color_gradient = pair.assert_i64()? != 0;
+                }
+                453 => (), // color count; implied by the colors that follow
+                460 => {
+                    hatch.gradient_rotation_angle = pair.assert_f64()?;
+                }
+                461 => {
+                    hatch.gradient_shift = pair.assert_f64()?;
+                }
+                462 => {
+                    hatch.gradient_color_tint = pair.assert_f64()?;
+                }
+                463 => {
+                    // start a new gradient color
+                    hatch.gradient_colors.push(HatchGradientColor::default());
+                    vec_last!(hatch.gradient_colors).value = pair.assert_f64()?;
+                }
+                63 => {
+                    vec_last!(hatch.gradient_colors).color =
+                        Color::from_raw_value(pair.assert_i16()?);
+                }
+                421 => {
+                    vec_last!(hatch.gradient_colors).color_24_bit = pair.assert_i32()?;
+                }
+                470 => {
+                    hatch.gradient_name = pair.assert_string()?;
+                }
+                _ => {
+                    common.apply_individual_pair(&pair, iter)?;
+                }
+            }
+        }
+    }
     fn apply_custom_reader_lwpolyline<I>(
         common: &mut EntityCommon,
         poly: &mut LwPolyline,
@@ -1179,103 +1719,274 @@ impl Entity {
             }
         }
     }
-    fn apply_custom_reader_mtext<I>(
+    fn apply_custom_reader_mleader<I>(
         common: &mut EntityCommon,
-        mtext: &mut MText,
+        mleader: &mut MLeader,
         iter: &mut CodePairPutBack<I>,
     ) -> DxfResult<bool>
     where
         I: Read,
     {
-        let mut reading_column_data = false;
-        let mut read_column_count = false;
+        // codes 92 and 330 mean something else before the `AcDbMLeader` subclass marker
+        let mut is_mleader_subclass = false;
         loop {
             let pair = next_pair!(iter);
             match pair.code {
-                10 => {
-                    mtext.insertion_point.x = pair.assert_f64()?;
-                }
-                20 => {
-                    mtext.insertion_point.y = pair.assert_f64()?;
+                100 => {
+                    is_mleader_subclass = pair.assert_string()? == "AcDbMLeader";
                 }
-                30 => {
-                    mtext.insertion_point.z = pair.assert_f64()?;
+                92 if is_mleader_subclass => {
+                    mleader.text_color = pair.assert_i32()?;
                 }
-                40 => {
-                    mtext.initial_text_height = pair.assert_f64()?;
+                330 if is_mleader_subclass => {
+                    // start a new block attribute
+                    mleader
+                        .block_attributes
+                        .push(MLeaderBlockAttribute::default());
+                    vec_last!(mleader.block_attributes).__attribute_definition_handle =
+                        pair.as_handle()?;
                 }
-                41 => {
-                    mtext.reference_rectangle_width = pair.assert_f64()?;
+                270 => {
+                    mleader.version = pair.assert_i16()?;
                 }
-                71 => {
-                    mtext.attachment_point =
-                        enum_from_number!(AttachmentPoint, TopLeft, from_i16, pair.assert_i16()?);
+                300 => {
+                    // CONTEXT_DATA{
+                    mleader.context_data = MLeaderContextData::read(iter)?;
                 }
-                72 => {
-                    mtext.drawing_direction = enum_from_number!(
-                        DrawingDirection,
-                        LeftToRight,
-                        from_i16,
-                        pair.assert_i16()?
-                    );
+                340 => {
+                    mleader.__leader_style_handle = pair.as_handle()?;
                 }
-                3 => {
-                    mtext.extended_text.push(pair.assert_string()?);
+                90 => {
+                    mleader.property_override_flags = pair.assert_i32()?;
                 }
-                1 => {
-                    mtext.text = pair.assert_string()?;
+                170 => {
+                    mleader.leader_line_type = pair.assert_i16()?;
                 }
-                7 => {
-                    mtext.text_style_name = pair.assert_string()?;
+                91 => {
+                    mleader.leader_line_color = pair.assert_i32()?;
                 }
-                210 => {
-                    mtext.extrusion_direction.x = pair.assert_f64()?;
+                341 => {
+                    mleader.__line_leader_type_handle = pair.as_handle()?;
                 }
-                220 => {
-                    mtext.extrusion_direction.y = pair.assert_f64()?;
+                171 => {
+                    mleader.leader_line_weight = pair.assert_i16()?;
                 }
-                230 => {
-                    mtext.extrusion_direction.z = pair.assert_f64()?;
+                290 => {
+                    mleader.enable_landing = pair.assert_bool()?;
                 }
-                11 => {
-                    mtext.x_axis_direction.x = pair.assert_f64()?;
+                291 => {
+                    mleader.enable_dogleg = pair.assert_bool()?;
                 }
-                21 => {
-                    mtext.x_axis_direction.y = pair.assert_f64()?;
+                41 => {
+                    mleader.dogleg_length = pair.assert_f64()?;
                 }
-                31 => {
-                    mtext.x_axis_direction.z = pair.assert_f64()?;
+                342 => {
+                    mleader.__arrowhead_handle = pair.as_handle()?;
                 }
                 42 => {
-                    mtext.horizontal_width = pair.assert_f64()?;
+                    mleader.arrowhead_size = pair.assert_f64()?;
                 }
-                43 => {
-                    mtext.vertical_height = pair.assert_f64()?;
+                172 => {
+                    mleader.content_type = pair.assert_i16()?;
                 }
-                50 => {
-                    if reading_column_data {
-                        if read_column_count {
-                            mtext.column_heights.push(pair.assert_f64()?);
-                        } else {
-                            mtext.column_count = pair.assert_f64()? as i32;
-                            read_column_count = true;
-                        }
-                    } else {
-                        mtext.rotation_angle = pair.assert_f64()?;
-                    }
+                343 => {
+                    mleader.__m_text_style_handle = pair.as_handle()?;
                 }
-                73 => {
-                    mtext.line_spacing_style = enum_from_number!(
-                        MTextLineSpacingStyle,
-                        AtLeast,
-                        from_i16,
-                        pair.assert_i16()?
-                    );
+                173 => {
+                    mleader.text_left_attachment_type = pair.assert_i16()?;
                 }
-                44 => {
-                    mtext.line_spacing_factor = pair.assert_f64()?;
+                95 => {
+                    mleader.text_right_attachment_type = pair.assert_i32()?;
                 }
-                90 => {
+                174 => {
+                    mleader.text_angle_type = pair.assert_i16()?;
+                }
+                175 => {
+                    mleader.text_alignment_type = pair.assert_i16()?;
+                }
+                292 => {
+                    mleader.enable_frame_text = pair.assert_bool()?;
+                }
+                344 => {
+                    mleader.__block_content_handle = pair.as_handle()?;
+                }
+                93 => {
+                    mleader.block_content_color = pair.assert_i32()?;
+                }
+                10 => {
+                    mleader.block_content_scale.x = pair.assert_f64()?;
+                }
+                20 => {
+                    mleader.block_content_scale.y = pair.assert_f64()?;
+                }
+                30 => {
+                    mleader.block_content_scale.z = pair.assert_f64()?;
+                }
+                43 => {
+                    mleader.block_content_rotation = pair.assert_f64()?;
+                }
+                176 => {
+                    mleader.block_content_connection_type = pair.assert_i16()?;
+                }
+                293 => {
+                    mleader.enable_annotation_scale = pair.assert_bool()?;
+                }
+                // arrowhead pairs
+                94 => {
+                    // start a new arrowhead
+                    mleader.arrowheads.push(MLeaderArrowhead::default());
+                    vec_last!(mleader.arrowheads).index = pair.assert_i32()?;
+                }
+                345 => {
+                    vec_last!(mleader.arrowheads).__arrowhead_handle = pair.as_handle()?;
+                }
+                // block attribute pairs
+                177 => {
+                    vec_last!(mleader.block_attributes).index = pair.assert_i16()?;
+                }
+                44 => {
+                    vec_last!(mleader.block_attributes).width = pair.assert_f64()?;
+                }
+                302 => {
+                    vec_last!(mleader.block_attributes).text = pair.assert_string()?;
+                }
+                // other pairs
+                294 => {
+                    mleader.is_text_direction_negative = pair.assert_bool()?;
+                }
+                178 => {
+                    mleader.text_align_in_ipe = pair.assert_i16()?;
+                }
+                179 => {
+                    mleader.text_attachment_point = pair.assert_i16()?;
+                }
+                271 => {
+                    mleader.text_attachment_direction = enum_from_number!(
+                        TextAttachmentDirection,
+                        Horizontal,
+                        from_i16,
+                        pair.assert_i16()?
+                    );
+                }
+                272 => {
+                    mleader.bottom_text_attachment_direction = enum_from_number!(
+                        BottomTextAttachmentDirection,
+                        Center,
+                        from_i16,
+                        pair.assert_i16()?
+                    );
+                }
+                273 => {
+                    mleader.top_text_attachment_direction = enum_from_number!(
+                        TopTextAttachmentDirection,
+                        Center,
+                        from_i16,
+                        pair.assert_i16()?
+                    );
+                }
+                _ => {
+                    common.apply_individual_pair(&pair, iter)?;
+                }
+            }
+        }
+    }
+    fn apply_custom_reader_mtext<I>(
+        common: &mut EntityCommon,
+        mtext: &mut MText,
+        iter: &mut CodePairPutBack<I>,
+    ) -> DxfResult<bool>
+    where
+        I: Read,
+    {
+        let mut reading_column_data = false;
+        let mut read_column_count = false;
+        loop {
+            let pair = next_pair!(iter);
+            match pair.code {
+                10 => {
+                    mtext.insertion_point.x = pair.assert_f64()?;
+                }
+                20 => {
+                    mtext.insertion_point.y = pair.assert_f64()?;
+                }
+                30 => {
+                    mtext.insertion_point.z = pair.assert_f64()?;
+                }
+                40 => {
+                    mtext.initial_text_height = pair.assert_f64()?;
+                }
+                41 => {
+                    mtext.reference_rectangle_width = pair.assert_f64()?;
+                }
+                71 => {
+                    mtext.attachment_point =
+                        enum_from_number!(AttachmentPoint, TopLeft, from_i16, pair.assert_i16()?);
+                }
+                72 => {
+                    mtext.drawing_direction = enum_from_number!(
+                        DrawingDirection,
+                        LeftToRight,
+                        from_i16,
+                        pair.assert_i16()?
+                    );
+                }
+                3 => {
+                    mtext.extended_text.push(pair.assert_string()?);
+                }
+                1 => {
+                    mtext.text = pair.assert_string()?;
+                }
+                7 => {
+                    mtext.text_style_name = pair.assert_string()?;
+                }
+                210 => {
+                    mtext.extrusion_direction.x = pair.assert_f64()?;
+                }
+                220 => {
+                    mtext.extrusion_direction.y = pair.assert_f64()?;
+                }
+                230 => {
+                    mtext.extrusion_direction.z = pair.assert_f64()?;
+                }
+                11 => {
+                    mtext.x_axis_direction.x = pair.assert_f64()?;
+                }
+                21 => {
+                    mtext.x_axis_direction.y = pair.assert_f64()?;
+                }
+                31 => {
+                    mtext.x_axis_direction.z = pair.assert_f64()?;
+                }
+                42 => {
+                    mtext.horizontal_width = pair.assert_f64()?;
+                }
+                43 => {
+                    mtext.vertical_height = pair.assert_f64()?;
+                }
+                50 => {
+                    if reading_column_data {
+                        if read_column_count {
+                            mtext.column_heights.push(pair.assert_f64()?);
+                        } else {
+                            mtext.column_count = pair.assert_f64()? as i32;
+                            read_column_count = true;
+                        }
+                    } else {
+                        mtext.rotation_angle = pair.assert_f64()?;
+                    }
+                }
+                73 => {
+                    mtext.line_spacing_style = enum_from_number!(
+                        MTextLineSpacingStyle,
+                        AtLeast,
+                        from_i16,
+                        pair.assert_i16()?
+                    );
+                }
+                44 => {
+                    mtext.line_spacing_factor = pair.assert_f64()?;
+                }
+                90 => {
                     mtext.background_fill_setting =
                         enum_from_number!(BackgroundFillSetting, Off, from_i32, pair.assert_i32()?);
                 }
@@ -1328,6 +2039,14 @@ impl Entity {
     where
         T: Write + ?Sized,
     {
+        if let EntityType::Unknown { ref pairs, .. } = self.specific {
+            // unknown entities are written back as they were read with their current handle and owner
+            writer.write_code_pair(&CodePair::new_str(0, self.specific.to_type_string()))?;
+            let handle = if write_handles { self.common.handle } else { 0 };
+            write_unknown_pairs(handle, self.common.__owner_handle, pairs, writer)?;
+            return Ok(());
+        }
+
         if self.specific.is_supported_on_version(version) {
             writer.write_code_pair(&CodePair::new_str(0, self.specific.to_type_string()))?;
             self.common.write(version, write_handles, writer)?;
@@ -1367,6 +2086,12 @@ impl Entity {
             EntityType::OrdinateDimension(ref dim) => {
                 Entity::apply_custom_writer_ordinatedimension(dim, version, writer)?;
             }
+            EntityType::Hatch(ref hatch) => {
+                Entity::apply_custom_writer_hatch(hatch, version, writer)?;
+            }
+            EntityType::MLeader(ref mleader) => {
+                Entity::apply_custom_writer_mleader(mleader, version, writer)?;
+            }
             EntityType::Polyline(ref poly) => {
                 Entity::apply_custom_writer_polyline(poly, version, writer)?;
             }
@@ -1480,6 +2205,181 @@ impl Entity {
         writer.write_code_pair(&CodePair::new_f64(34, dim.definition_point_3.z))?;
         Ok(true)
     }
+    fn apply_custom_writer_hatch<T>(
+        hatch: &Hatch,
+        version: AcadVersion,
+        writer: &mut CodePairWriter<T>,
+    ) -> DxfResult<bool>
+    where
+        T: Write + ?Sized,
+    {
+        writer.write_code_pair(&CodePair::new_str(100, "AcDbHatch"))?;
+        writer.write_code_pair(&CodePair::new_f64(10, hatch.elevation_point.x))?;
+        writer.write_code_pair(&CodePair::new_f64(20, hatch.elevation_point.y))?;
+        writer.write_code_pair(&CodePair::new_f64(30, hatch.elevation_point.z))?;
+        writer.write_code_pair(&CodePair::new_f64(210, hatch.extrusion_direction.x))?;
+        writer.write_code_pair(&CodePair::new_f64(220, hatch.extrusion_direction.y))?;
+        writer.write_code_pair(&CodePair::new_f64(230, hatch.extrusion_direction.z))?;
+        writer.write_code_pair(&CodePair::new_string(2, &hatch.pattern_name))?;
+        writer.write_code_pair(&CodePair::new_i16(70, as_i16(hatch.is_solid_fill)))?;
+        writer.write_code_pair(&CodePair::new_i16(71, as_i16(hatch.is_associative)))?;
+        writer.write_code_pair(&CodePair::new_i32(91, hatch.boundary_paths.len() as i32))?;
+        for path in &hatch.boundary_paths {
+            path.write(version, writer)?;
+        }
+        writer.write_code_pair(&CodePair::new_i16(75, hatch.hatch_style as i16))?;
+        writer.write_code_pair(&CodePair::new_i16(76, hatch.pattern_type as i16))?;
+        if !hatch.is_solid_fill {
+            writer.write_code_pair(&CodePair::new_f64(52, hatch.pattern_angle))?;
+            writer.write_code_pair(&CodePair::new_f64(41, hatch.pattern_scale))?;
+            writer.write_code_pair(&CodePair::new_i16(77, as_i16(hatch.is_pattern_double)))?;
+            writer.write_code_pair(&CodePair::new_i16(
+                78,
+                hatch.pattern_definition_lines.len() as i16,
+            ))?;
+            for line in &hatch.pattern_definition_lines {
+                writer.write_code_pair(&CodePair::new_f64(53, line.angle))?;
+                writer.write_code_pair(&CodePair::new_f64(43, line.base_point.x))?;
+                writer.write_code_pair(&CodePair::new_f64(44, line.base_point.y))?;
+                writer.write_code_pair(&CodePair::new_f64(45, line.offset.x))?;
+                writer.write_code_pair(&CodePair::new_f64(46, line.offset.y))?;
+                writer.write_code_pair(&CodePair::new_i16(79, line.dash_lengths.len() as i16))?;
+                for dash_length in &line.dash_lengths {
+                    writer.write_code_pair(&CodePair::new_f64(49, *dash_length))?;
+                }
+            }
+        }
+        if hatch.pixel_size != 0.0 {
+            writer.write_code_pair(&CodePair::new_f64(47, hatch.pixel_size))?;
+        }
+        writer.write_code_pair(&CodePair::new_i32(98, hatch.seed_points.len() as i32))?;
+        for seed_point in &hatch.seed_points {
+            writer.write_code_pair(&CodePair::new_f64(10, seed_point.x))?;
+            writer.write_code_pair(&CodePair::new_f64(20, seed_point.y))?;
+        }
+        if version >= AcadVersion::R2004 && hatch.is_gradient {
+            writer.write_code_pair(&CodePair::new_i64(450, 1))?;
+            writer.write_code_pair(&CodePair::new_i64(451, 0))?;
+            writer.write_code_pair(&CodePair::new_f64(460, hatch.gradient_rotation_angle))?;
+            writer.write_code_pair(&CodePair::new_f64(461, hatch.gradient_shift))?;
+            writer.write_code_pair(&CodePair::new_i64(
+                452,
+                if hatch.is_single_color_gradient { 1 } else { 0 },
+            ))?;
+            writer.write_code_pair(&CodePair::new_f64(462, hatch.gradient_color_tint))?;
+            writer.write_code_pair(&CodePair::new_i64(453, hatch.gradient_colors.len() as i64))?;
+            for color in &hatch.gradient_colors {
+                writer.write_code_pair(&CodePair::new_f64(463, color.value))?;
+                if color.color.is_index() {
+                    writer.write_code_pair(&CodePair::new_i16(63, color.color.get_raw_value()))?;
+                }
+                writer.write_code_pair(&CodePair::new_i32(421, color.color_24_bit))?;
+            }
+            writer.write_code_pair(&CodePair::new_string(470, &hatch.gradient_name))?;
+        }
+        Ok(true)
+    }
+    fn apply_custom_writer_mleader<T>(
+        mleader: &MLeader,
+        _version: AcadVersion,
+        writer: &mut CodePairWriter<T>,
+    ) -> DxfResult<bool>
+    where
+        T: Write + ?Sized,
+    {
+        writer.write_code_pair(&CodePair::new_str(100, "AcDbMLeader"))?;
+        writer.write_code_pair(&CodePair::new_i16(270, mleader.version))?;
+        mleader.context_data.write(writer)?;
+        if mleader.__leader_style_handle != 0 {
+            writer.write_code_pair(&CodePair::new_string(
+                340,
+                &as_handle(mleader.__leader_style_handle),
+            ))?;
+        }
+        writer.write_code_pair(&CodePair::new_i32(90, mleader.property_override_flags))?;
+        writer.write_code_pair(&CodePair::new_i16(170, mleader.leader_line_type))?;
+        writer.write_code_pair(&CodePair::new_i32(91, mleader.leader_line_color))?;
+        if mleader.__line_leader_type_handle != 0 {
+            writer.write_code_pair(&CodePair::new_string(
+                341,
+                &as_handle(mleader.__line_leader_type_handle),
+            ))?;
+        }
+        writer.write_code_pair(&CodePair::new_i16(171, mleader.leader_line_weight))?;
+        writer.write_code_pair(&CodePair::new_bool(290, mleader.enable_landing))?;
+        writer.write_code_pair(&CodePair::new_bool(291, mleader.enable_dogleg))?;
+        writer.write_code_pair(&CodePair::new_f64(41, mleader.dogleg_length))?;
+        if mleader.__arrowhead_handle != 0 {
+            writer.write_code_pair(&CodePair::new_string(
+                342,
+                &as_handle(mleader.__arrowhead_handle),
+            ))?;
+        }
+        writer.write_code_pair(&CodePair::new_f64(42, mleader.arrowhead_size))?;
+        writer.write_code_pair(&CodePair::new_i16(172, mleader.content_type))?;
+        if mleader.__m_text_style_handle != 0 {
+            writer.write_code_pair(&CodePair::new_string(
+                343,
+                &as_handle(mleader.__m_text_style_handle),
+            ))?;
+        }
+        writer.write_code_pair(&CodePair::new_i16(173, mleader.text_left_attachment_type))?;
+        writer.write_code_pair(&CodePair::new_i32(95, mleader.text_right_attachment_type))?;
+        writer.write_code_pair(&CodePair::new_i16(174, mleader.text_angle_type))?;
+        writer.write_code_pair(&CodePair::new_i16(175, mleader.text_alignment_type))?;
+        writer.write_code_pair(&CodePair::new_i32(92, mleader.text_color))?;
+        writer.write_code_pair(&CodePair::new_bool(292, mleader.enable_frame_text))?;
+        if mleader.__block_content_handle != 0 {
+            writer.write_code_pair(&CodePair::new_string(
+                344,
+                &as_handle(mleader.__block_content_handle),
+            ))?;
+        }
+        writer.write_code_pair(&CodePair::new_i32(93, mleader.block_content_color))?;
+        writer.write_code_pair(&CodePair::new_f64(10, mleader.block_content_scale.x))?;
+        writer.write_code_pair(&CodePair::new_f64(20, mleader.block_content_scale.y))?;
+        writer.write_code_pair(&CodePair::new_f64(30, mleader.block_content_scale.z))?;
+        writer.write_code_pair(&CodePair::new_f64(43, mleader.block_content_rotation))?;
+        writer.write_code_pair(&CodePair::new_i16(
+            176,
+            mleader.block_content_connection_type,
+        ))?;
+        writer.write_code_pair(&CodePair::new_bool(293, mleader.enable_annotation_scale))?;
+        for arrowhead in &mleader.arrowheads {
+            writer.write_code_pair(&CodePair::new_i32(94, arrowhead.index))?;
+            if arrowhead.__arrowhead_handle != 0 {
+                writer.write_code_pair(&CodePair::new_string(
+                    345,
+                    &as_handle(arrowhead.__arrowhead_handle),
+                ))?;
+            }
+        }
+        for attribute in &mleader.block_attributes {
+            writer.write_code_pair(&CodePair::new_string(
+                330,
+                &as_handle(attribute.__attribute_definition_handle),
+            ))?;
+            writer.write_code_pair(&CodePair::new_i16(177, attribute.index))?;
+            writer.write_code_pair(&CodePair::new_f64(44, attribute.width))?;
+            writer.write_code_pair(&CodePair::new_string(302, &attribute.text))?;
+        }
+        writer.write_code_pair(&CodePair::new_bool(294, mleader.is_text_direction_negative))?;
+        writer.write_code_pair(&CodePair::new_i16(178, mleader.text_align_in_ipe))?;
+        writer.write_code_pair(&CodePair::new_i16(179, mleader.text_attachment_point))?;
+        writer.write_code_pair(&CodePair::new_i16(
+            271,
+            mleader.text_attachment_direction as i16,
+        ))?;
+        writer.write_code_pair(&CodePair::new_i16(
+            272,
+            mleader.bottom_text_attachment_direction as i16,
+        ))?;
+        writer.write_code_pair(&CodePair::new_i16(
+            273,
+            mleader.top_text_attachment_direction as i16,
+        ))?;
+        Ok(true)
+    }
     fn apply_custom_writer_polyline<T>(
         poly: &Polyline,
         version: AcadVersion,
@@ -1743,7 +2643,17 @@ mod tests {
             .as_str(),
         );
         let entities = drawing.entities().collect::<Vec<_>>();
-        assert_eq!(0, entities.len());
+        assert_eq!(1, entities.len());
+        match entities[0].specific {
+            EntityType::Unknown {
+                ref type_string,
+                ref pairs,
+            } => {
+                assert_eq!("UNSUPPORTED_ENTITY", type_string);
+                assert_eq!(vec![CodePair::new_str(1, "unsupported string")], *pairs);
+            }
+            _ => panic!("expected an unknown entity"),
+        }
     }
 
     #[test]
@@ -1771,59 +2681,249 @@ mod tests {
             .as_str(),
         );
         let entities = drawing.entities().collect::<Vec<_>>();
-        assert_eq!(2, entities.len());
+        assert_eq!(3, entities.len());
         match entities[0].specific {
             EntityType::Line(_) => (),
             _ => panic!("expected a line"),
         }
         match entities[1].specific {
+            EntityType::Unknown { .. } => (),
+            _ => panic!("expected an unknown entity"),
+        }
+        match entities[2].specific {
             EntityType::Circle(_) => (),
             _ => panic!("expected a circle"),
         }
     }
 
     #[test]
-    fn read_entity_with_no_values() {
-        let drawing = parse_drawing(
+    fn read_unsupported_entity_handle() {
+        let ent = read_entity("UNSUPPORTED_ENTITY", vec!["5", "A1"].join("\r\n"));
+        assert_eq!(0xA1, ent.common.handle);
+    }
+
+    #[test]
+    fn write_unsupported_entity_verbatim() {
+        let drawing = from_section(
+            "ENTITIES",
             vec![
-                "0", "SECTION", "2", "ENTITIES", "0", "LINE", "0", "ENDSEC", "0", "EOF",
+                "0",
+                "LINE",
+                "0",
+                "UNSUPPORTED_ENTITY",
+                "5",
+                "A1",
+                "100",
+                "AcDbUnsupported",
+                "40",
+                "1.5",
+                "1001",
+                "APP",
+                "0",
+                "CIRCLE",
             ]
             .join("\r\n")
             .as_str(),
         );
-        let entities = drawing.entities().collect::<Vec<_>>();
-        assert_eq!(1, entities.len());
-        match entities[0].specific {
-            EntityType::Line(_) => (),
-            _ => panic!("expected a line"),
-        }
-    }
-
-    #[test]
-    fn read_common_entity_fields() {
-        let ent = read_entity("LINE", vec!["8", "layer"].join("\r\n"));
-        assert_eq!("layer", ent.common.layer);
+        assert_contains(
+            &drawing,
+            vec![
+                "  0",
+                "UNSUPPORTED_ENTITY",
+                "  5",
+                "A1",
+                "100",
+                "AcDbUnsupported",
+                " 40",
+                "1.5",
+                "1001",
+                "APP",
+                "  0",
+                "CIRCLE",
+            ]
+            .join("\r\n"),
+        );
     }
 
     #[test]
-    fn read_line() {
-        let ent = read_entity(
-            "LINE",
-            vec![
-                "10", "1.1", // p1
-                "20", "2.2", "30", "3.3", "11", "4.4", // p2
-                "21", "5.5", "31", "6.6",
+    fn write_unsupported_entity_with_current_handle_and_owner() {
+        let mut drawing = from_section(
+            "ENTITIES",
+            [
+                "0",
+                "UNSUPPORTED_ENTITY",
+                "5",
+                "A1",
+                "102",
+                "{ACAD_REACTORS",
+                "330",
+                "B1",
+                "102",
+                "}",
+                "330",
+                "C1",
+                "100",
+                "AcDbUnsupported",
+                "330",
+                "D1",
             ]
-            .join("\r\n"),
+            .join("\r\n")
+            .as_str(),
         );
-        match ent.specific {
-            EntityType::Line(ref line) => {
-                assert_eq!(Point::new(1.1, 2.2, 3.3), line.p1);
-                assert_eq!(Point::new(4.4, 5.5, 6.6), line.p2);
+        let entity = drawing.entities().next().unwrap();
+        assert_eq!(0xA1, entity.common.handle);
+        assert_eq!(0xC1, entity.common.__owner_handle);
+        match entity.specific {
+            EntityType::Unknown { ref pairs, .. } => {
+                assert_eq!(1, pairs.iter().filter(|p| p.code == 5).count());
+                assert_eq!(3, pairs.iter().filter(|p| p.code == 330).count());
             }
-            _ => panic!("expected a line"),
+            _ => panic!("expected an unknown entity"),
         }
-    }
+
+        for entity in drawing.entities_mut() {
+            entity.common.handle = 0xA2;
+            entity.common.__owner_handle = 0xC2;
+        }
+        assert_contains(
+            &drawing,
+            [
+                "  0",
+                "UNSUPPORTED_ENTITY",
+                "  5",
+                "A2",
+                "102",
+                "{ACAD_REACTORS",
+                "330",
+                "B1",
+                "102",
+                "}",
+                "330",
+                "C2",
+                "100",
+                "AcDbUnsupported",
+                "330",
+                "D1",
+            ]
+            .join("\r\n"),
+        );
+    }
+
+    #[test]
+    fn write_unsupported_entity_pairs_in_original_order() {
+        let drawing = from_section(
+            "ENTITIES",
+            [
+                "0",
+                "UNSUPPORTED_ENTITY",
+                "330",
+                "C1",
+                "5",
+                "A1",
+                "40",
+                "1.50",
+                "100",
+                "AcDbUnsupported",
+                "41",
+                "2.000000000000",
+            ]
+            .join("\r\n")
+            .as_str(),
+        );
+        assert_contains(
+            &drawing,
+            [
+                "  0",
+                "UNSUPPORTED_ENTITY",
+                "330",
+                "C1",
+                "  5",
+                "A1",
+                " 40",
+                "1.50",
+                "100",
+                "AcDbUnsupported",
+                " 41",
+                "2.000000000000",
+            ]
+            .join("\r\n"),
+        );
+    }
+
+    #[test]
+    fn round_trip_unsupported_entity() {
+        let drawing = from_section(
+            "ENTITIES",
+            vec![
+                "0",
+                "UNSUPPORTED_ENTITY",
+                "5",
+                "A1",
+                "100",
+                "AcDbUnsupported",
+                "40",
+                "1.5",
+            ]
+            .join("\r\n")
+            .as_str(),
+        );
+        let expected = drawing.entities().next().unwrap().clone();
+
+        let text_drawing = parse_drawing(&to_test_string(&drawing));
+        assert_eq!(
+            expected.specific,
+            text_drawing.entities().next().unwrap().specific
+        );
+
+        let binary_drawing = unwrap_drawing(Drawing::load(&mut to_binary(&drawing).as_slice()));
+        assert_eq!(
+            expected.specific,
+            binary_drawing.entities().next().unwrap().specific
+        );
+    }
+
+    #[test]
+    fn read_entity_with_no_values() {
+        let drawing = parse_drawing(
+            vec![
+                "0", "SECTION", "2", "ENTITIES", "0", "LINE", "0", "ENDSEC", "0", "EOF",
+            ]
+            .join("\r\n")
+            .as_str(),
+        );
+        let entities = drawing.entities().collect::<Vec<_>>();
+        assert_eq!(1, entities.len());
+        match entities[0].specific {
+            EntityType::Line(_) => (),
+            _ => panic!("expected a line"),
+        }
+    }
+
+    #[test]
+    fn read_common_entity_fields() {
+        let ent = read_entity("LINE", vec!["8", "layer"].join("\r\n"));
+        assert_eq!("layer", ent.common.layer);
+    }
+
+    #[test]
+    fn read_line() {
+        let ent = read_entity(
+            "LINE",
+            vec![
+                "10", "1.1", // p1
+                "20", "2.2", "30", "3.3", "11", "4.4", // p2
+                "21", "5.5", "31", "6.6",
+            ]
+            .join("\r\n"),
+        );
+        match ent.specific {
+            EntityType::Line(ref line) => {
+                assert_eq!(Point::new(1.1, 2.2, 3.3), line.p1);
+                assert_eq!(Point::new(4.4, 5.5, 6.6), line.p2);
+            }
+            _ => panic!("expected a line"),
+        }
+    }
 
     #[test]
     fn write_common_entity_fields() {
@@ -2769,6 +3869,97 @@ mod tests {
         );
     }
 
+    #[test]
+    fn entity_true_color() {
+        let mut common = EntityCommon::default();
+        assert_eq!(None, common.get_true_color());
+        common.set_true_color(Some((250, 10, 10)));
+        assert_eq!(0xFA_0A_0A, common.color_24_bit);
+        assert_eq!(Some(1), common.color.index());
+        assert_eq!(Some((250, 10, 10)), common.get_true_color());
+
+        assert_eq!(None, common.get_color_book_name());
+        common.color_name = String::from("PANTONE+ Solid Coated$PANTONE 185 C");
+        assert_eq!(
+            Some(("PANTONE+ Solid Coated", "PANTONE 185 C")),
+            common.get_color_book_name()
+        );
+        common.set_true_color(Some((10, 10, 250)));
+        assert_eq!(None, common.get_color_book_name());
+
+        common.color_name = String::from("PANTONE+ Solid Coated$PANTONE 185 C");
+        common.set_true_color(None);
+        assert_eq!(None, common.get_true_color());
+        assert_eq!("", common.color_name);
+    }
+
+    #[test]
+    fn read_spline_weights() {
+        let spline = read_entity(
+            "SPLINE",
+            vec![
+                "100",
+                "AcDbSpline",
+                " 71",
+                "2",
+                " 40",
+                "0.0",
+                " 40",
+                "1.0",
+                " 41",
+                "1.0",
+                " 41",
+                "0.5",
+                " 41",
+                "1.0",
+                " 10",
+                "1.0",
+                " 20",
+                "0.0",
+                " 30",
+                "0.0",
+                " 10",
+                "1.0",
+                " 20",
+                "1.0",
+                " 30",
+                "0.0",
+                " 10",
+                "0.0",
+                " 20",
+                "1.0",
+                " 30",
+                "0.0",
+            ]
+            .join("\r\n"),
+        );
+        match spline.specific {
+            EntityType::Spline(ref spline) => {
+                assert_eq!(vec![1.0, 0.5, 1.0], spline.weights);
+                assert_eq!(3, spline.control_points.len());
+            }
+            _ => panic!("expected a spline"),
+        }
+    }
+
+    #[test]
+    fn write_spline_weights() {
+        let mut drawing = Drawing::new();
+        drawing.header.version = AcadVersion::R2013;
+        let mut spline = Spline::default();
+        spline.knot_values = vec![0.0, 1.0];
+        spline.weights = vec![1.0, 0.5];
+        spline.control_points = vec![Point::new(1.0, 2.0, 3.0), Point::new(4.0, 5.0, 6.0)];
+        drawing.add_entity(Entity::new(EntityType::Spline(spline)));
+        assert_contains(
+            &drawing,
+            vec![
+                " 40", "0.0", " 40", "1.0", " 41", "1.0", " 41", "0.5", " 10", "1.0",
+            ]
+            .join("\r\n"),
+        );
+    }
+
     #[test]
     fn read_dimension() {
         let ent = read_entity(
@@ -3256,4 +4447,832 @@ mod tests {
         assert_eq!("STANDARD", dim_styles[1].name);
         assert_eq!("style name", dim_styles[2].name);
     }
+
+    #[test]
+    fn read_hatch_with_polyline_boundary() {
+        let ent = read_entity(
+            "HATCH",
+            vec![
+                "100",
+                "AcDbHatch",
+                " 10",
+                "0.0",
+                " 20",
+                "0.0",
+                " 30",
+                "3.0",
+                "  2",
+                "ANSI31",
+                " 70",
+                "     0",
+                " 71",
+                "     1",
+                " 91",
+                "        1",
+                // polyline path
+                " 92",
+                "        3",
+                " 72",
+                "     1",
+                " 73",
+                "     1",
+                " 93",
+                "        2",
+                " 10",
+                "1.0",
+                " 20",
+                "2.0",
+                " 42",
+                "0.5",
+                " 10",
+                "3.0",
+                " 20",
+                "4.0",
+                " 42",
+                "0.0",
+                " 97",
+                "        1",
+                "330",
+                "ABC",
+                // pattern
+                " 75",
+                "     1",
+                " 76",
+                "     1",
+                " 52",
+                "45.0",
+                " 41",
+                "2.0",
+                " 77",
+                "     0",
+                " 78",
+                "     1",
+                " 53",
+                "45.0",
+                " 43",
+                "0.0",
+                " 44",
+                "0.0",
+                " 45",
+                "-0.1",
+                " 46",
+                "0.1",
+                " 79",
+                "     2",
+                " 49",
+                "0.25",
+                " 49",
+                "-0.125",
+                " 98",
+                "        1",
+                " 10",
+                "5.0",
+                " 20",
+                "6.0",
+            ]
+            .join("\r\n"),
+        );
+        match ent.specific {
+            EntityType::Hatch(ref hatch) => {
+                assert_eq!(Point::new(0.0, 0.0, 3.0), hatch.elevation_point);
+                assert_eq!("ANSI31", hatch.pattern_name);
+                assert!(!hatch.is_solid_fill);
+                assert!(hatch.is_associative);
+                assert_eq!(1, hatch.boundary_paths.len());
+                let path = &hatch.boundary_paths[0];
+                assert!(path.get_is_external());
+                assert!(path.get_is_polyline());
+                assert!(path.is_closed);
+                assert_eq!(2, path.vertices.len());
+                assert!(approx_eq!(f64, 1.0, path.vertices[0].x));
+                assert!(approx_eq!(f64, 2.0, path.vertices[0].y));
+                assert!(approx_eq!(f64, 0.5, path.vertices[0].bulge));
+                assert!(approx_eq!(f64, 3.0, path.vertices[1].x));
+                assert!(approx_eq!(f64, 4.0, path.vertices[1].y));
+                assert_eq!(vec![0xABC], path.__source_boundary_entities_handle);
+                assert_eq!(HatchStyle::OutermostAreaOnly, hatch.hatch_style);
+                assert_eq!(HatchPatternType::Predefined, hatch.pattern_type);
+                assert!(approx_eq!(f64, 45.0, hatch.pattern_angle));
+                assert!(approx_eq!(f64, 2.0, hatch.pattern_scale));
+                assert_eq!(1, hatch.pattern_definition_lines.len());
+                let line = &hatch.pattern_definition_lines[0];
+                assert!(approx_eq!(f64, 45.0, line.angle));
+                assert_eq!(Vector::new(-0.1, 0.1, 0.0), line.offset);
+                assert_eq!(vec![0.25, -0.125], line.dash_lengths);
+                assert_eq!(vec![Point::new(5.0, 6.0, 0.0)], hatch.seed_points);
+            }
+            _ => panic!("expected a HATCH"),
+        }
+    }
+
+    #[test]
+    fn read_hatch_with_edge_boundary() {
+        let ent = read_entity(
+            "HATCH",
+            vec![
+                "100",
+                "AcDbHatch",
+                " 91",
+                "        1",
+                " 92",
+                "        1",
+                " 93",
+                "        3",
+                // line
+                " 72",
+                "     1",
+                " 10",
+                "1.0",
+                " 20",
+                "2.0",
+                " 11",
+                "3.0",
+                " 21",
+                "4.0",
+                // arc
+                " 72",
+                "     2",
+                " 10",
+                "5.0",
+                " 20",
+                "6.0",
+                " 40",
+                "7.0",
+                " 50",
+                "0.0",
+                " 51",
+                "90.0",
+                " 73",
+                "     0",
+                // spline
+                " 72",
+                "     4",
+                " 94",
+                "        3",
+                " 73",
+                "     1",
+                " 74",
+                "     0",
+                " 95",
+                "        2",
+                " 96",
+                "        1",
+                " 40",
+                "0.0",
+                " 40",
+                "1.0",
+                " 10",
+                "8.0",
+                " 20",
+                "9.0",
+                " 42",
+                "0.5",
+                " 97",
+                "        0",
+                // back to the path
+                " 97",
+                "        0",
+                " 75",
+                "     2",
+            ]
+            .join("\r\n"),
+        );
+        match ent.specific {
+            EntityType::Hatch(ref hatch) => {
+                assert_eq!(1, hatch.boundary_paths.len());
+                let path = &hatch.boundary_paths[0];
+                assert!(!path.get_is_polyline());
+                assert_eq!(
+                    vec![
+                        HatchBoundaryEdge::Line {
+                            start: Point::new(1.0, 2.0, 0.0),
+                            end: Point::new(3.0, 4.0, 0.0),
+                        },
+                        HatchBoundaryEdge::CircularArc {
+                            center: Point::new(5.0, 6.0, 0.0),
+                            radius: 7.0,
+                            start_angle: 0.0,
+                            end_angle: 90.0,
+                            is_counter_clockwise: false,
+                        },
+                        HatchBoundaryEdge::Spline {
+                            degree: 3,
+                            is_rational: true,
+                            is_periodic: false,
+                            knot_values: vec![0.0, 1.0],
+                            control_points: vec![Point::new(8.0, 9.0, 0.0)],
+                            weights: vec![0.5],
+                            fit_points: vec![],
+                            start_tangent: Vector::zero(),
+                            end_tangent: Vector::zero(),
+                        },
+                    ],
+                    path.edges
+                );
+                assert_eq!(HatchStyle::EntireArea, hatch.hatch_style);
+            }
+            _ => panic!("expected a HATCH"),
+        }
+    }
+
+    #[test]
+    fn write_hatch() {
+        let mut drawing = Drawing::new();
+        drawing.header.version = AcadVersion::R2000;
+        let mut path = HatchBoundaryPath::default();
+        path.set_is_polyline(true);
+        path.is_closed = true;
+        path.vertices.push(LwPolylineVertex {
+            x: 1.0,
+            y: 2.0,
+            ..Default::default()
+        });
+        let hatch = Hatch {
+            boundary_paths: vec![path],
+            ..Default::default()
+        };
+        drawing.add_entity(Entity::new(EntityType::Hatch(hatch)));
+        assert_contains(
+            &drawing,
+            vec![
+                "100",
+                "AcDbHatch",
+                " 10",
+                "0.0",
+                " 20",
+                "0.0",
+                " 30",
+                "0.0",
+                "210",
+                "0.0",
+                "220",
+                "0.0",
+                "230",
+                "1.0",
+                "  2",
+                "SOLID",
+                " 70",
+                "     1",
+                " 71",
+                "     0",
+                " 91",
+                "        1",
+                " 92",
+                "        2",
+                " 72",
+                "     0",
+                " 73",
+                "     1",
+                " 93",
+                "        1",
+                " 10",
+                "1.0",
+                " 20",
+                "2.0",
+                " 97",
+                "        0",
+                " 75",
+                "     0",
+                " 76",
+                "     1",
+                " 98",
+                "        0",
+            ]
+            .join("\r\n"),
+        );
+    }
+
+    #[test]
+    fn round_trip_hatch() {
+        let mut polyline_path = HatchBoundaryPath::default();
+        polyline_path.set_is_polyline(true);
+        polyline_path.set_is_external(true);
+        polyline_path.is_closed = true;
+        polyline_path.vertices.push(LwPolylineVertex {
+            x: 1.0,
+            y: 2.0,
+            bulge: 0.5,
+            ..Default::default()
+        });
+        polyline_path.vertices.push(LwPolylineVertex {
+            x: 3.0,
+            y: 4.0,
+            ..Default::default()
+        });
+        let mut edge_path = HatchBoundaryPath::default();
+        edge_path.edges.push(HatchBoundaryEdge::Line {
+            start: Point::new(1.0, 2.0, 0.0),
+            end: Point::new(3.0, 4.0, 0.0),
+        });
+        edge_path.edges.push(HatchBoundaryEdge::EllipticArc {
+            center: Point::new(5.0, 6.0, 0.0),
+            major_axis: Vector::new(7.0, 0.0, 0.0),
+            minor_axis_ratio: 0.5,
+            start_angle: 0.0,
+            end_angle: 180.0,
+            is_counter_clockwise: true,
+        });
+        edge_path.edges.push(HatchBoundaryEdge::Spline {
+            degree: 3,
+            is_rational: false,
+            is_periodic: false,
+            knot_values: vec![0.0, 0.0, 0.0, 0.0, 1.0, 1.0, 1.0, 1.0],
+            control_points: vec![
+                Point::new(0.0, 0.0, 0.0),
+                Point::new(1.0, 1.0, 0.0),
+                Point::new(2.0, 1.0, 0.0),
+                Point::new(3.0, 0.0, 0.0),
+            ],
+            weights: vec![],
+            fit_points: vec![Point::new(0.0, 0.0, 0.0), Point::new(3.0, 0.0, 0.0)],
+            start_tangent: Vector::new(1.0, 0.0, 0.0),
+            end_tangent: Vector::new(1.0, 0.0, 0.0),
+        });
+        edge_path.__source_boundary_entities_handle.push(0x42);
+        let hatch = Hatch {
+            pattern_name: String::from("DASHED"),
+            is_solid_fill: false,
+            boundary_paths: vec![polyline_path, edge_path],
+            pattern_type: HatchPatternType::UserDefined,
+            pattern_angle: 45.0,
+            pattern_scale: 2.0,
+            pattern_definition_lines: vec![HatchPatternDefinitionLine {
+                angle: 45.0,
+                base_point: Point::new(1.0, 1.0, 0.0),
+                offset: Vector::new(-0.5, 0.5, 0.0),
+                dash_lengths: vec![0.25, -0.125],
+            }],
+            seed_points: vec![Point::new(2.0, 3.0, 0.0)],
+            is_gradient: true,
+            gradient_rotation_angle: 30.0,
+            gradient_colors: vec![
+                HatchGradientColor {
+                    value: 0.0,
+                    color: Color::from_index(5),
+                    color_24_bit: 0x0000FF,
+                },
+                HatchGradientColor {
+                    value: 1.0,
+                    color: Color::from_index(2),
+                    color_24_bit: 0xFFFF00,
+                },
+            ],
+            ..Default::default()
+        };
+        let mut drawing = Drawing::new();
+        drawing.header.version = AcadVersion::R2010;
+        drawing.add_entity(Entity::new(EntityType::Hatch(hatch.clone())));
+
+        let text_drawing = parse_drawing(&to_test_string(&drawing));
+        let binary_drawing = unwrap_drawing(Drawing::load(&mut to_binary(&drawing).as_slice()));
+        for drawing in &[text_drawing, binary_drawing] {
+            let entities = drawing.entities().collect::<Vec<_>>();
+            assert_eq!(1, entities.len());
+            match entities[0].specific {
+                EntityType::Hatch(ref h) => assert_eq!(&hatch, h),
+                _ => panic!("expected a HATCH"),
+            }
+        }
+    }
+
+    #[test]
+    fn read_mleader_with_m_text_content() {
+        let ent = read_entity(
+            "MULTILEADER",
+            vec![
+                "100",
+                "AcDbMLeader",
+                "270",
+                "     2",
+                "300",
+                "CONTEXT_DATA{",
+                " 40",
+                "1.0",
+                " 10",
+                "10.0",
+                " 20",
+                "20.0",
+                " 30",
+                "0.0",
+                " 41",
+                "0.18",
+                "290",
+                "     1",
+                "304",
+                "leader text",
+                " 12",
+                "11.0",
+                " 22",
+                "21.0",
+                " 32",
+                "0.0",
+                "296",
+                "     0",
+                "302",
+                "LEADER{",
+                "290",
+                "     1",
+                " 10",
+                "9.0",
+                " 20",
+                "20.0",
+                " 30",
+                "0.0",
+                " 40",
+                "0.36",
+                "304",
+                "LEADER_LINE{",
+                " 10",
+                "1.0",
+                " 20",
+                "2.0",
+                " 30",
+                "0.0",
+                " 10",
+                "3.0",
+                " 20",
+                "4.0",
+                " 30",
+                "0.0",
+                " 91",
+                "        0",
+                "305",
+                "}",
+                "271",
+                "     0",
+                "303",
+                "}",
+                "301",
+                "}",
+                "340",
+                "ABC",
+                " 92",
+                "       42",
+                " 94",
+                "        0",
+                "345",
+                "DEF",
+                "271",
+                "     1",
+            ]
+            .join("\r\n"),
+        );
+        match ent.specific {
+            EntityType::MLeader(ref mleader) => {
+                assert_eq!(0xABC, mleader.__leader_style_handle);
+                assert_eq!(42, mleader.text_color);
+                assert_eq!(0, ent.common.image_byte_count);
+                assert_eq!(1, mleader.arrowheads.len());
+                assert_eq!(0xDEF, mleader.arrowheads[0].__arrowhead_handle);
+                assert_eq!(
+                    TextAttachmentDirection::Vertical,
+                    mleader.text_attachment_direction
+                );
+                let context = &mleader.context_data;
+                assert_eq!(Point::new(10.0, 20.0, 0.0), context.content_base_point);
+                assert!(approx_eq!(f64, 0.18, context.text_height));
+                match context.content {
+                    MLeaderContent::MText(ref m_text) => {
+                        assert_eq!("leader text", m_text.text);
+                        assert_eq!(Point::new(11.0, 21.0, 0.0), m_text.location);
+                    }
+                    _ => panic!("expected MText content"),
+                }
+                assert_eq!(1, context.roots.len());
+                let root = &context.roots[0];
+                assert_eq!(Point::new(9.0, 20.0, 0.0), root.last_leader_line_point);
+                assert!(approx_eq!(f64, 0.36, root.dogleg_length));
+                assert_eq!(1, root.lines.len());
+                assert_eq!(
+                    vec![Point::new(1.0, 2.0, 0.0), Point::new(3.0, 4.0, 0.0)],
+                    root.lines[0].vertices
+                );
+            }
+            _ => panic!("expected a MULTILEADER"),
+        }
+    }
+
+    #[test]
+    fn write_mleader() {
+        let mut drawing = Drawing::new();
+        drawing.header.version = AcadVersion::R2007;
+        let mut mleader = MLeader::default();
+        mleader.context_data.roots.push(MLeaderRoot {
+            lines: vec![MLeaderLine {
+                vertices: vec![Point::new(1.0, 2.0, 3.0)],
+                leader_line_index: 0,
+            }],
+            ..Default::default()
+        });
+        drawing.add_entity(Entity::new(EntityType::MLeader(mleader)));
+        assert_contains(
+            &drawing,
+            vec![
+                "100",
+                "AcDbMLeader",
+                "270",
+                "     2",
+                "300",
+                "CONTEXT_DATA{",
+            ]
+            .join("\r\n"),
+        );
+        assert_contains(
+            &drawing,
+            vec![
+                "302",
+                "LEADER{",
+                "290",
+                "1",
+                "291",
+                "1",
+                " 10",
+                "0.0",
+                " 20",
+                "0.0",
+                " 30",
+                "0.0",
+                " 11",
+                "1.0",
+                " 21",
+                "0.0",
+                " 31",
+                "0.0",
+                " 90",
+                "        0",
+                " 40",
+                "0.0",
+                "304",
+                "LEADER_LINE{",
+                " 10",
+                "1.0",
+                " 20",
+                "2.0",
+                " 30",
+                "3.0",
+                " 91",
+                "        0",
+                "305",
+                "}",
+                "271",
+                "     0",
+                "303",
+                "}",
+            ]
+            .join("\r\n"),
+        );
+        // null style handles aren't written
+        assert_contains(&drawing, ["301", "}", " 90"].join("\r\n"));
+    }
+
+    #[test]
+    fn round_trip_mleader() {
+        let mut drawing = Drawing::new();
+        drawing.header.version = AcadVersion::R2013;
+        let mleader = MLeader {
+            context_data: MLeaderContextData {
+                content: MLeaderContent::Block(MLeaderBlockContent {
+                    location: Point::new(1.0, 2.0, 3.0),
+                    rotation: 45.0,
+                    ..Default::default()
+                }),
+                roots: vec![MLeaderRoot {
+                    last_leader_line_point: Point::new(4.0, 5.0, 6.0),
+                    lines: vec![
+                        MLeaderLine {
+                            vertices: vec![Point::new(7.0, 8.0, 9.0)],
+                            leader_line_index: 0,
+                        },
+                        MLeaderLine {
+                            vertices: vec![Point::new(10.0, 11.0, 12.0)],
+                            leader_line_index: 1,
+                        },
+                    ],
+                    ..Default::default()
+                }],
+                ..Default::default()
+            },
+            text_color: 42,
+            block_attributes: vec![MLeaderBlockAttribute {
+                __attribute_definition_handle: 0xABC,
+                index: 1,
+                width: 2.0,
+                text: String::from("attribute text"),
+            }],
+            ..Default::default()
+        };
+        drawing.add_entity(Entity::new(EntityType::MLeader(mleader.clone())));
+
+        let text_drawing = parse_drawing(&to_test_string(&drawing));
+        let binary_drawing = unwrap_drawing(Drawing::load(&mut to_binary(&drawing).as_slice()));
+        for drawing in &[text_drawing, binary_drawing] {
+            let entities = drawing.entities().collect::<Vec<_>>();
+            assert_eq!(1, entities.len());
+            match entities[0].specific {
+                EntityType::MLeader(ref m) => assert_eq!(&mleader, m),
+                _ => panic!("expected a MULTILEADER"),
+            }
+        }
+    }
+
+    fn drawing_with_line_block() -> Drawing {
+        let mut drawing = Drawing::new();
+        let mut block = Block {
+            name: String::from("b"),
+            base_point: Point::new(1.0, 0.0, 0.0),
+            ..Default::default()
+        };
+        block.entities.push(Entity::new(EntityType::Line(Line::new(
+            Point::new(1.0, 0.0, 0.0),
+            Point::new(2.0, 0.0, 0.0),
+        ))));
+        drawing.add_block(block);
+        drawing
+    }
+
+    fn lines(entities: &[Entity]) -> Vec<(Point, Point)> {
+        entities
+            .iter()
+            .map(|e| match e.specific {
+                EntityType::Line(ref line) => {
+                    let round = |p: &Point| {
+                        Point::new(
+                            (p.x * 1e9).round() / 1e9,
+                            (p.y * 1e9).round() / 1e9,
+                            (p.z * 1e9).round() / 1e9,
+                        )
+                    };
+                    (round(&line.p1), round(&line.p2))
+                }
+                _ => panic!("expected a line"),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn explode_insert() {
+        let drawing = drawing_with_line_block();
+        let ins = Insert {
+            name: String::from("b"),
+            location: Point::new(5.0, 5.0, 0.0),
+            x_scale_factor: 2.0,
+            rotation: 90.0,
+            ..Default::default()
+        };
+        let entities = ins.explode(&drawing);
+        assert_eq!(
+            vec![(Point::new(5.0, 5.0, 0.0), Point::new(5.0, 7.0, 0.0))],
+            lines(&entities)
+        );
+        assert_eq!(0, entities[0].common.handle);
+    }
+
+    #[test]
+    fn explode_insert_array() {
+        let drawing = drawing_with_line_block();
+        let ins = Insert {
+            name: String::from("b"),
+            column_count: 2,
+            row_count: 2,
+            column_spacing: 10.0,
+            row_spacing: 20.0,
+            ..Default::default()
+        };
+        assert_eq!(
+            vec![
+                (Point::new(0.0, 0.0, 0.0), Point::new(1.0, 0.0, 0.0)),
+                (Point::new(0.0, 20.0, 0.0), Point::new(1.0, 20.0, 0.0)),
+                (Point::new(10.0, 0.0, 0.0), Point::new(11.0, 0.0, 0.0)),
+                (Point::new(10.0, 20.0, 0.0), Point::new(11.0, 20.0, 0.0)),
+            ],
+            lines(&ins.explode(&drawing))
+        );
+    }
+
+    #[test]
+    fn explode_nested_insert_with_by_block_properties() {
+        let mut drawing = drawing_with_line_block();
+        drawing.blocks_mut().next().unwrap().entities[0]
+            .common
+            .color = Color::by_block();
+        let mut outer = Block {
+            name: String::from("outer"),
+            ..Default::default()
+        };
+        let mut nested = Entity::new(EntityType::Insert(Insert {
+            name: String::from("b"),
+            location: Point::new(0.0, 3.0, 0.0),
+            ..Default::default()
+        }));
+        nested.common.layer = String::from("nested-layer");
+        nested.common.color = Color::from_index(3);
+        outer.entities.push(nested);
+        drawing.add_block(outer);
+
+        let ins = Insert {
+            name: String::from("outer"),
+            location: Point::new(1.0, 1.0, 0.0),
+            ..Default::default()
+        };
+        let entities = ins.explode(&drawing);
+        assert_eq!(
+            vec![(Point::new(1.0, 4.0, 0.0), Point::new(2.0, 4.0, 0.0))],
+            lines(&entities)
+        );
+        assert_eq!("nested-layer", entities[0].common.layer);
+        assert_eq!(Some(3), entities[0].common.color.index());
+    }
+
+    #[test]
+    fn explode_insert_attributes_as_text() {
+        let mut drawing = drawing_with_line_block();
+        let mut ins = Insert {
+            name: String::from("b"),
+            ..Default::default()
+        };
+        ins.add_attribute(
+            &mut drawing,
+            Attribute {
+                location: Point::new(3.0, 4.0, 0.0),
+                value: String::from("attribute-value"),
+                ..Default::default()
+            },
+        );
+        let entities = ins.explode(&drawing);
+        assert_eq!(2, entities.len());
+        match entities[1].specific {
+            EntityType::Text(ref text) => {
+                assert_eq!("attribute-value", text.value);
+                assert_eq!(Point::new(3.0, 4.0, 0.0), text.location);
+            }
+            _ => panic!("expected text"),
+        }
+    }
+
+    #[test]
+    fn explode_self_referencing_insert() {
+        let mut drawing = drawing_with_line_block();
+        drawing
+            .blocks_mut()
+            .next()
+            .unwrap()
+            .entities
+            .push(Entity::new(EntityType::Insert(Insert {
+                name: String::from("b"),
+                ..Default::default()
+            })));
+        let ins = Insert {
+            name: String::from("b"),
+            ..Default::default()
+        };
+        assert_eq!(1, ins.explode(&drawing).len());
+    }
+
+    #[test]
+    fn arc_points_in_wcs() {
+        let mut arc = Arc::new(Point::new(1.0, 0.0, 2.0), 1.0, 0.0, 90.0);
+        arc.normal = Vector::new(0.0, 0.0, -1.0);
+        assert_eq!(Point::new(-1.0, 0.0, -2.0), arc.center_wcs());
+        assert_eq!(Point::new(-2.0, 0.0, -2.0), arc.start_point_wcs());
+        let end = arc.end_point_wcs();
+        assert!((end.x + 1.0).abs() < 1e-9 && (end.y - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn entity_to_wcs() {
+        let mut circle = Circle::new(Point::new(1.0, 2.0, 3.0), 1.0);
+        circle.normal = Vector::x_axis();
+        let ent = Entity::new(EntityType::Circle(circle));
+        assert_eq!(
+            Point::new(3.0, 1.0, 2.0),
+            ent.to_wcs(&Point::new(1.0, 2.0, 3.0))
+        );
+
+        // world coordinate entities are unchanged
+        let ent = Entity::new(EntityType::Line(Line::default()));
+        assert_eq!(TransformationMatrix::identity(), ent.ocs_matrix());
+    }
+
+    #[test]
+    fn polyline_vertices_in_wcs() {
+        let mut drawing = Drawing::new();
+        let mut poly = Polyline {
+            normal: Vector::new(0.0, 0.0, -1.0),
+            location: Point::new(0.0, 0.0, 5.0),
+            ..Default::default()
+        };
+        poly.add_vertex(&mut drawing, Vertex::new(Point::new(1.0, 1.0, 0.0)));
+        assert_eq!(vec![Point::new(-1.0, 1.0, -5.0)], poly.vertices_wcs());
+
+        let poly = LwPolyline {
+            extrusion_direction: Vector::new(0.0, 0.0, -1.0),
+            vertices: vec![LwPolylineVertex {
+                x: 1.0,
+                y: 1.0,
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        assert_eq!(vec![Point::new(-1.0, 1.0, -5.0)], poly.vertices_wcs(5.0));
+    }
 }
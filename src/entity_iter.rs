@@ -15,9 +15,18 @@ impl<'a, I: 'a + Read> Iterator for EntityIter<'a, I> {
     type Item = Entity;
 
     fn next(&mut self) -> Option<Entity> {
-        match Entity::read(self.iter) {
-            Ok(Some(e)) => Some(e),
-            Ok(None) | Err(_) => None,
+        loop {
+            match Entity::read(self.iter) {
+                Ok(Some(e)) => return Some(e),
+                Ok(None) => return None,
+                Err(e) => {
+                    if let Err(e) = self.iter.recover(e, "entity", None) {
+                        // leave the error for the caller to report
+                        self.iter.put_back(Err(e));
+                        return None;
+                    }
+                }
+            }
         }
     }
 }
@@ -26,22 +35,53 @@ impl<'a, I: 'a + Read> EntityIter<'a, I> {
     pub(crate) fn read_entities_into_vec(&mut self, entities: &mut Vec<Entity>) -> DxfResult<()> {
         collect_entities(self, entities)
     }
+    /// Like `read_entities_into_vec()`, but stops after `max_count` entities have been read.
+    pub(crate) fn read_entities_into_vec_with_limit(
+        &mut self,
+        entities: &mut Vec<Entity>,
+        max_count: usize,
+    ) -> DxfResult<()> {
+        let mut iter = put_back(self.map(Ok));
+        while entities.len() < max_count {
+            match next_gathered_entity(&mut iter)? {
+                Some(entity) => entities.push(entity),
+                None => break,
+            }
+        }
+
+        Ok(())
+    }
 }
 
 pub(crate) fn collect_entities<I>(iter: &mut I, entities: &mut Vec<Entity>) -> DxfResult<()>
 where
     I: Iterator<Item = Entity>,
+{
+    let mut iter = put_back(iter.map(Ok));
+    while let Some(entity) = next_gathered_entity(&mut iter)? {
+        entities.push(entity);
+    }
+
+    Ok(())
+}
+
+/// Reads the next entity from `iter`, gathering any trailing `ATTRIB`, `MTEXT`, `VERTEX`, and `SEQEND`
+/// entities into their owning entity.
+pub(crate) fn next_gathered_entity<I>(iter: &mut PutBack<I>) -> DxfResult<Option<Entity>>
+where
+    I: Iterator<Item = DxfResult<Entity>>,
 {
     fn swallow_seqend<I>(iter: &mut PutBack<I>) -> DxfResult<()>
     where
-        I: Iterator<Item = Entity>,
+        I: Iterator<Item = DxfResult<Entity>>,
     {
         match iter.next() {
-            Some(Entity {
+            Some(Ok(Entity {
                 specific: EntityType::Seqend(_),
                 ..
-            }) => (),
-            Some(ent) => iter.put_back(ent),
+            })) => (),
+            Some(Ok(ent)) => iter.put_back(Ok(ent)),
+            Some(Err(e)) => return Err(e),
             None => (),
         }
 
@@ -50,122 +90,115 @@ where
 
     fn get_mtext<I>(iter: &mut PutBack<I>) -> DxfResult<Option<MText>>
     where
-        I: Iterator<Item = Entity>,
+        I: Iterator<Item = DxfResult<Entity>>,
     {
         let m_text = match iter.next() {
-            Some(Entity {
+            Some(Ok(Entity {
                 specific: EntityType::MText(m),
                 ..
-            }) => Some(m),
-            Some(ent) => {
-                iter.put_back(ent);
+            })) => Some(m),
+            Some(Ok(ent)) => {
+                iter.put_back(Ok(ent));
                 None
             }
+            Some(Err(e)) => return Err(e),
             None => None,
         };
 
         Ok(m_text)
     }
 
-    let mut iter = put_back(iter);
-    loop {
-        match iter.next() {
-            Some(Entity {
-                ref common,
-                specific: EntityType::Attribute(ref att),
-            }) => {
-                let mut att = att.clone(); // 27 fields
-                match get_mtext(&mut iter) {
-                    Ok(Some(m_text)) => att.m_text = m_text,
-                    Ok(None) => (),
-                    Err(e) => return Err(e),
-                }
+    let entity = match iter.next() {
+        Some(Ok(Entity {
+            common,
+            specific: EntityType::Attribute(mut att),
+        })) => {
+            if let Some(m_text) = get_mtext(iter)? {
+                att.m_text = m_text;
+            }
 
-                entities.push(Entity {
-                    common: common.clone(), // 18 fields
-                    specific: EntityType::Attribute(att),
-                });
+            Entity {
+                common,
+                specific: EntityType::Attribute(att),
+            }
+        }
+        Some(Ok(Entity {
+            common,
+            specific: EntityType::AttributeDefinition(mut att),
+        })) => {
+            if let Some(m_text) = get_mtext(iter)? {
+                att.m_text = m_text;
             }
-            Some(Entity {
-                ref common,
-                specific: EntityType::AttributeDefinition(ref att),
-            }) => {
-                let mut att = att.clone(); // 27 fields
-                match get_mtext(&mut iter) {
-                    Ok(Some(m_text)) => att.m_text = m_text,
-                    Ok(None) => (),
-                    Err(e) => return Err(e),
-                }
 
-                entities.push(Entity {
-                    common: common.clone(), // 18 fields
-                    specific: EntityType::AttributeDefinition(att),
-                });
+            Entity {
+                common,
+                specific: EntityType::AttributeDefinition(att),
             }
-            Some(Entity {
-                ref common,
-                specific: EntityType::Insert(ref ins),
-            }) if ins.__has_attributes => {
-                let mut ins = ins.clone(); // 12 fields
-                loop {
-                    match iter.next() {
-                        Some(Entity {
-                            specific: EntityType::Attribute(att),
-                            ..
-                        }) => ins
-                            .__attributes_and_handles
-                            .push((att, AUTO_REPLACE_HANDLE)),
-                        Some(ent) => {
-                            // stop gathering on any non-ATTRIBUTE
-                            iter.put_back(ent);
-                            break;
-                        }
-                        None => break,
+        }
+        Some(Ok(Entity {
+            common,
+            specific: EntityType::Insert(mut ins),
+        })) if ins.__has_attributes => {
+            loop {
+                match iter.next() {
+                    Some(Ok(Entity {
+                        specific: EntityType::Attribute(att),
+                        ..
+                    })) => ins
+                        .__attributes_and_handles
+                        .push((att, AUTO_REPLACE_HANDLE)),
+                    Some(Ok(ent)) => {
+                        // stop gathering on any non-ATTRIBUTE
+                        iter.put_back(Ok(ent));
+                        break;
                     }
+                    Some(Err(e)) => return Err(e),
+                    None => break,
                 }
+            }
 
-                swallow_seqend(&mut iter)?;
+            swallow_seqend(iter)?;
 
-                // and finally keep the INSERT
-                entities.push(Entity {
-                    common: common.clone(), // 18 fields
-                    specific: EntityType::Insert(ins),
-                });
-            }
-            Some(Entity {
+            // and finally keep the INSERT
+            Entity {
                 common,
-                specific: EntityType::Polyline(poly),
-            }) => {
-                let mut poly = poly.clone(); // 13 fields
-                loop {
-                    match iter.next() {
-                        Some(Entity {
-                            specific: EntityType::Vertex(vertex),
-                            ..
-                        }) => poly
-                            .__vertices_and_handles
-                            .push((vertex, AUTO_REPLACE_HANDLE)),
-                        Some(ent) => {
-                            // stop gathering on any non-VERTEX
-                            iter.put_back(ent);
-                            break;
-                        }
-                        None => break,
+                specific: EntityType::Insert(ins),
+            }
+        }
+        Some(Ok(Entity {
+            common,
+            specific: EntityType::Polyline(mut poly),
+        })) => {
+            loop {
+                match iter.next() {
+                    Some(Ok(Entity {
+                        specific: EntityType::Vertex(vertex),
+                        ..
+                    })) => poly
+                        .__vertices_and_handles
+                        .push((vertex, AUTO_REPLACE_HANDLE)),
+                    Some(Ok(ent)) => {
+                        // stop gathering on any non-VERTEX
+                        iter.put_back(Ok(ent));
+                        break;
                     }
+                    Some(Err(e)) => return Err(e),
+                    None => break,
                 }
+            }
 
-                swallow_seqend(&mut iter)?;
+            swallow_seqend(iter)?;
 
-                // and finally keep the POLYLINE
-                entities.push(Entity {
-                    common: common.clone(), // 18 fields
-                    specific: EntityType::Polyline(poly),
-                });
+            // and finally keep the POLYLINE
+            Entity {
+                common,
+                specific: EntityType::Polyline(poly),
             }
-            Some(entity) => entities.push(entity),
-            None => break,
         }
-    }
+        Some(Ok(entity)) => entity,
+        Some(Err(e)) => return Err(e),
+        None => return Ok(None),
+    };
 
-    Ok(())
+    Ok(Some(entity))
 }
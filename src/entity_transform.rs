@@ -0,0 +1,1027 @@
+use std::f64::consts::PI;
+
+use crate::entities::*;
+use crate::{
+    HatchBoundaryEdge, HatchBoundaryPath, MLeaderContent, Point, TransformationMatrix, Vector,
+};
+
+/// Transforms the `location`, `second_alignment_point`, `rotation`, `text_height`,
+/// `relative_x_scale_factor`, `thickness`, and `normal` of a `Text`, `Attribute`, or
+/// `AttributeDefinition`.
+macro_rules! transform_text {
+    ($matrix:expr, $text:expr) => {{
+        let planar = PlanarTransform::new($matrix, &$text.normal);
+        let x_scale = planar.scale_along($text.rotation);
+        let y_scale = planar.scale_along($text.rotation + 90.0);
+        $text.location = planar.point(&$text.location);
+        $text.second_alignment_point = planar.point(&$text.second_alignment_point);
+        $text.rotation = planar.angle($text.rotation);
+        $text.text_height *= y_scale;
+        if y_scale != 0.0 {
+            $text.relative_x_scale_factor *= x_scale / y_scale;
+        }
+        if planar.is_mirrored {
+            // text rotated by 180 degrees and drawn upside down is the same as mirrored text
+            let is_upside_down = $text.get_is_text_upside_down();
+            $text.set_is_text_upside_down(!is_upside_down);
+        }
+        $text.thickness = planar.thickness($text.thickness);
+        $text.normal = planar.normal();
+    }};
+}
+
+/// Transforms the `insertion_point`, scale, `rotation_angle`, and `normal` of an underlay.
+macro_rules! transform_underlay {
+    ($matrix:expr, $underlay:expr) => {{
+        let planar = PlanarTransform::new($matrix, &$underlay.normal);
+        let x_scale = planar.scale_along($underlay.rotation_angle);
+        let y_scale = planar.scale_along($underlay.rotation_angle + 90.0);
+        $underlay.insertion_point = $matrix.transform_point(&$underlay.insertion_point);
+        $underlay.rotation_angle = planar.angle($underlay.rotation_angle);
+        $underlay.x_scale *= x_scale;
+        $underlay.y_scale *= if planar.is_mirrored {
+            -y_scale
+        } else {
+            y_scale
+        };
+        $underlay.z_scale = planar.thickness($underlay.z_scale);
+        $underlay.normal = planar.normal();
+    }};
+}
+
+impl Entity {
+    /// Applies the transformation to every geometric value of the entity.
+    ///
+    /// Values stored in an object coordinate system (OCS) are re-expressed in the OCS of the
+    /// transformed normal.  When the transformation mirrors the entity, the normal is kept on the
+    /// same side of the plane and the direction of arcs and bulges is reversed instead.  Circles
+    /// and arcs that are scaled non-uniformly are converted to ellipses.
+    ///
+    /// Values that describe a shear or a non-uniform scale that the entity can't represent, e.g.,
+    /// the radius of a `Helix`, are scaled by the average scale factor of the transformation.
+    /// Proprietary data, e.g., the ACIS data of a `Solid3D`, and the anonymous blocks of
+    /// dimensions are not updated.
+    pub fn transform(&mut self, matrix: &TransformationMatrix) {
+        let m = matrix;
+        let scale = uniform_scale(m);
+        let mut replacement = None;
+        match self.specific {
+            EntityType::Arc(ref mut arc) => {
+                let planar = PlanarTransform::new(m, &arc.normal);
+                if planar.is_uniform() {
+                    arc.center = planar.point(&arc.center);
+                    arc.radius *= planar.length_scale();
+                    let (start, end) = (planar.angle(arc.start_angle), planar.angle(arc.end_angle));
+                    if planar.is_mirrored {
+                        arc.start_angle = end;
+                        arc.end_angle = start;
+                    } else {
+                        arc.start_angle = start;
+                        arc.end_angle = end;
+                    }
+                    arc.thickness = planar.thickness(arc.thickness);
+                    arc.normal = planar.normal();
+                } else {
+                    let mut end_angle = arc.end_angle;
+                    while end_angle <= arc.start_angle {
+                        end_angle += 360.0;
+                    }
+                    replacement = Some(EntityType::Ellipse(ellipse_from_circular_arc(
+                        m,
+                        &arc.normal,
+                        &arc.center,
+                        arc.radius,
+                        arc.start_angle.to_radians(),
+                        end_angle.to_radians(),
+                    )));
+                }
+            }
+            EntityType::ArcAlignedText(ref mut text) => {
+                let planar = PlanarTransform::new(m, &text.extrusion_direction);
+                let length_scale = planar.length_scale();
+                text.center_point = planar.point(&text.center_point);
+                text.arc_radius *= length_scale;
+                text.text_height *= length_scale;
+                text.character_spacing *= length_scale;
+                text.offset_from_arc *= length_scale;
+                text.right_offset *= length_scale;
+                text.left_offset *= length_scale;
+                let (start, end) = (planar.angle(text.start_angle), planar.angle(text.end_angle));
+                if planar.is_mirrored {
+                    text.start_angle = end;
+                    text.end_angle = start;
+                } else {
+                    text.start_angle = start;
+                    text.end_angle = end;
+                }
+                text.extrusion_direction = planar.normal();
+            }
+            EntityType::Attribute(ref mut att) => {
+                transform_text!(m, att);
+            }
+            EntityType::AttributeDefinition(ref mut att) => {
+                transform_text!(m, att);
+            }
+            EntityType::Circle(ref mut circle) => {
+                let planar = PlanarTransform::new(m, &circle.normal);
+                if planar.is_uniform() {
+                    circle.center = planar.point(&circle.center);
+                    circle.radius *= planar.length_scale();
+                    circle.thickness = planar.thickness(circle.thickness);
+                    circle.normal = planar.normal();
+                } else {
+                    replacement = Some(EntityType::Ellipse(ellipse_from_circular_arc(
+                        m,
+                        &circle.normal,
+                        &circle.center,
+                        circle.radius,
+                        0.0,
+                        2.0 * PI,
+                    )));
+                }
+            }
+            EntityType::RotatedDimension(ref mut dim) => {
+                let planar = transform_dimension_base(m, &mut dim.dimension_base);
+                dim.insertion_point = planar.point(&dim.insertion_point);
+                dim.definition_point_2 = m.transform_point(&dim.definition_point_2);
+                dim.definition_point_3 = m.transform_point(&dim.definition_point_3);
+                dim.rotation_angle = planar.angle(dim.rotation_angle);
+                dim.extension_line_angle = planar.angle(dim.extension_line_angle);
+            }
+            EntityType::RadialDimension(ref mut dim) => {
+                transform_dimension_base(m, &mut dim.dimension_base);
+                dim.definition_point_2 = m.transform_point(&dim.definition_point_2);
+                dim.leader_length *= scale;
+            }
+            EntityType::DiameterDimension(ref mut dim) => {
+                transform_dimension_base(m, &mut dim.dimension_base);
+                dim.definition_point_2 = m.transform_point(&dim.definition_point_2);
+                dim.leader_length *= scale;
+            }
+            EntityType::AngularThreePointDimension(ref mut dim) => {
+                let planar = transform_dimension_base(m, &mut dim.dimension_base);
+                dim.definition_point_2 = m.transform_point(&dim.definition_point_2);
+                dim.definition_point_3 = m.transform_point(&dim.definition_point_3);
+                dim.definition_point_4 = m.transform_point(&dim.definition_point_4);
+                dim.definition_point_5 = planar.point(&dim.definition_point_5);
+            }
+            EntityType::OrdinateDimension(ref mut dim) => {
+                transform_dimension_base(m, &mut dim.dimension_base);
+                dim.definition_point_2 = m.transform_point(&dim.definition_point_2);
+                dim.definition_point_3 = m.transform_point(&dim.definition_point_3);
+            }
+            EntityType::Ellipse(ref mut ellipse) => {
+                let normal = ellipse.normal.normalize();
+                let minor_axis =
+                    scale_vector(&normal.cross(&ellipse.major_axis), ellipse.minor_axis_ratio);
+                let planar = PlanarTransform::new(m, &normal);
+                let (center, major_axis, minor_axis_ratio, start, end) = transform_elliptical_arc(
+                    m,
+                    &planar.normal(),
+                    &ellipse.center,
+                    &ellipse.major_axis,
+                    &minor_axis,
+                    ellipse.start_parameter,
+                    ellipse.end_parameter,
+                );
+                ellipse.center = center;
+                ellipse.major_axis = major_axis;
+                ellipse.minor_axis_ratio = minor_axis_ratio;
+                ellipse.start_parameter = start;
+                ellipse.end_parameter = end;
+                ellipse.normal = planar.normal();
+            }
+            EntityType::Face3D(ref mut face) => {
+                face.first_corner = m.transform_point(&face.first_corner);
+                face.second_corner = m.transform_point(&face.second_corner);
+                face.third_corner = m.transform_point(&face.third_corner);
+                face.fourth_corner = m.transform_point(&face.fourth_corner);
+            }
+            EntityType::Hatch(ref mut hatch) => {
+                let planar = PlanarTransform::new(m, &hatch.extrusion_direction);
+                let elevation = planar.point(&hatch.elevation_point).z;
+                for path in &mut hatch.boundary_paths {
+                    transform_hatch_boundary_path(&planar, hatch.elevation_point.z, path);
+                }
+                for p in &mut hatch.seed_points {
+                    *p = planar.point(p);
+                }
+                hatch.pattern_angle = planar.angle(hatch.pattern_angle);
+                hatch.pattern_scale *= planar.length_scale();
+                hatch.elevation_point = Point::new(0.0, 0.0, elevation);
+                hatch.extrusion_direction = planar.normal();
+            }
+            EntityType::Helix(ref mut helix) => {
+                helix.axis_base_point = m.transform_point(&helix.axis_base_point);
+                helix.start_point = m.transform_point(&helix.start_point);
+                helix.axis_vector = m.transform_vector(&helix.axis_vector);
+                helix.radius *= scale;
+                helix.turn_height *= scale;
+                if linear_determinant(m) < 0.0 {
+                    helix.is_right_handed = !helix.is_right_handed;
+                }
+            }
+            EntityType::Image(ref mut image) => {
+                image.location = m.transform_point(&image.location);
+                image.u_vector = m.transform_vector(&image.u_vector);
+                image.v_vector = m.transform_vector(&image.v_vector);
+            }
+            EntityType::Insert(ref mut ins) => {
+                let planar = PlanarTransform::new(m, &ins.extrusion_direction);
+                let x_scale = planar.scale_along(ins.rotation);
+                let y_scale = planar.scale_along(ins.rotation + 90.0);
+                let mirror = if planar.is_mirrored { -1.0 } else { 1.0 };
+                ins.location = planar.point(&ins.location);
+                ins.rotation = planar.angle(ins.rotation);
+                ins.x_scale_factor *= x_scale;
+                ins.y_scale_factor *= y_scale * mirror;
+                ins.z_scale_factor = planar.thickness(ins.z_scale_factor);
+                ins.column_spacing *= x_scale;
+                ins.row_spacing *= y_scale * mirror;
+                ins.extrusion_direction = planar.normal();
+                for att in ins.attributes_mut() {
+                    transform_text!(m, att);
+                }
+            }
+            EntityType::Leader(ref mut leader) => {
+                for v in &mut leader.vertices {
+                    *v = m.transform_point(v);
+                }
+                leader.normal = PlanarTransform::new(m, &leader.normal).normal();
+                leader.right = m.transform_vector(&leader.right).normalize();
+                leader.block_offset = m.transform_vector(&leader.block_offset);
+                leader.annotation_offset = m.transform_vector(&leader.annotation_offset);
+                leader.text_annotation_height *= scale;
+                leader.text_annotation_width *= scale;
+            }
+            EntityType::Light(ref mut light) => {
+                light.position = m.transform_point(&light.position);
+                light.target_location = m.transform_point(&light.target_location);
+            }
+            EntityType::Line(ref mut line) => {
+                let planar = PlanarTransform::new(m, &line.extrusion_direction);
+                line.p1 = m.transform_point(&line.p1);
+                line.p2 = m.transform_point(&line.p2);
+                line.thickness = planar.thickness(line.thickness);
+                line.extrusion_direction = planar.normal();
+            }
+            EntityType::LwPolyline(ref mut poly) => {
+                let planar = PlanarTransform::new(m, &poly.extrusion_direction);
+                let elevation = self.common.elevation;
+                let length_scale = planar.length_scale();
+                for v in &mut poly.vertices {
+                    let p = planar.point(&Point::new(v.x, v.y, elevation));
+                    v.x = p.x;
+                    v.y = p.y;
+                    v.starting_width *= length_scale;
+                    v.ending_width *= length_scale;
+                    if planar.is_mirrored {
+                        v.bulge = -v.bulge;
+                    }
+                }
+                self.common.elevation = planar.point(&Point::new(0.0, 0.0, elevation)).z;
+                poly.constant_width *= length_scale;
+                poly.thickness = planar.thickness(poly.thickness);
+                poly.extrusion_direction = planar.normal();
+            }
+            EntityType::MLeader(ref mut mleader) => {
+                let is_mirrored = linear_determinant(m) < 0.0;
+                let context = &mut mleader.context_data;
+                context.content_base_point = m.transform_point(&context.content_base_point);
+                context.plane_origin = m.transform_point(&context.plane_origin);
+                context.plane_x_axis = m.transform_vector(&context.plane_x_axis).normalize();
+                context.plane_y_axis = m.transform_vector(&context.plane_y_axis).normalize();
+                if is_mirrored {
+                    context.is_plane_normal_reversed = !context.is_plane_normal_reversed;
+                }
+                context.scale *= scale;
+                context.text_height *= scale;
+                context.arrowhead_size *= scale;
+                context.landing_gap *= scale;
+                for root in &mut context.roots {
+                    root.last_leader_line_point = m.transform_point(&root.last_leader_line_point);
+                    root.dogleg_vector = m.transform_vector(&root.dogleg_vector).normalize();
+                    root.dogleg_length *= scale;
+                    for line in &mut root.lines {
+                        for v in &mut line.vertices {
+                            *v = m.transform_point(v);
+                        }
+                    }
+                }
+                match context.content {
+                    MLeaderContent::MText(ref mut text) => {
+                        text.location = m.transform_point(&text.location);
+                        text.direction = m.transform_vector(&text.direction).normalize();
+                        text.normal = PlanarTransform::new(m, &text.normal).normal();
+                        text.boundary_width *= scale;
+                        text.boundary_height *= scale;
+                    }
+                    MLeaderContent::Block(ref mut block) => {
+                        block.location = m.transform_point(&block.location);
+                        block.normal = PlanarTransform::new(m, &block.normal).normal();
+                        block.scale = scale_vector(&block.scale, scale);
+                        block.transformation_matrix = block.transformation_matrix.compose(m);
+                    }
+                    MLeaderContent::None => (),
+                }
+                mleader.dogleg_length *= scale;
+                mleader.arrowhead_size *= scale;
+            }
+            EntityType::MLine(ref mut mline) => {
+                mline.start_point = m.transform_point(&mline.start_point);
+                for v in &mut mline.vertices {
+                    *v = m.transform_point(v);
+                }
+                for d in &mut mline.segment_directions {
+                    *d = m.transform_vector(d).normalize();
+                }
+                for d in &mut mline.miter_directions {
+                    *d = m.transform_vector(d).normalize();
+                }
+                for p in &mut mline.parameters {
+                    *p *= scale;
+                }
+                for p in &mut mline.area_fill_parameters {
+                    *p *= scale;
+                }
+                mline.scale_factor *= scale;
+                mline.normal = PlanarTransform::new(m, &mline.normal).normal();
+            }
+            EntityType::ModelPoint(ref mut point) => {
+                let planar = PlanarTransform::new(m, &point.extrusion_direction);
+                point.location = m.transform_point(&point.location);
+                point.angle = planar.angle(point.angle);
+                point.thickness = planar.thickness(point.thickness);
+                point.extrusion_direction = planar.normal();
+            }
+            EntityType::MText(ref mut mtext) => {
+                let planar = PlanarTransform::new(m, &mtext.extrusion_direction);
+                let length_scale = planar.length_scale();
+                mtext.insertion_point = m.transform_point(&mtext.insertion_point);
+                if mtext.x_axis_direction.length() > 0.0 {
+                    mtext.x_axis_direction =
+                        m.transform_vector(&mtext.x_axis_direction).normalize();
+                } else {
+                    // the rotation angle is in radians
+                    let rotation = planar.angle(mtext.rotation_angle.to_degrees());
+                    mtext.rotation_angle = rotation.to_radians();
+                }
+                mtext.initial_text_height *= length_scale;
+                mtext.reference_rectangle_width *= length_scale;
+                mtext.horizontal_width *= length_scale;
+                mtext.vertical_height *= length_scale;
+                mtext.column_width *= length_scale;
+                for h in &mut mtext.column_heights {
+                    *h *= length_scale;
+                }
+                mtext.extrusion_direction = planar.normal();
+            }
+            EntityType::Ole2Frame(ref mut frame) => {
+                frame.upper_left_corner = m.transform_point(&frame.upper_left_corner);
+                frame.lower_right_corner = m.transform_point(&frame.lower_right_corner);
+            }
+            EntityType::Polyline(ref mut poly) => {
+                if poly.get_is_3d_polyline() || poly.get_is_3d_polygon_mesh() {
+                    for v in poly.vertices_mut() {
+                        v.location = m.transform_point(&v.location);
+                    }
+                } else if poly.get_is_polyface_mesh() {
+                    // face records don't contain locations
+                    for v in poly.vertices_mut().filter(|v| v.get_is_3d_polygon_mesh()) {
+                        v.location = m.transform_point(&v.location);
+                    }
+                } else {
+                    let planar = PlanarTransform::new(m, &poly.normal);
+                    let elevation = poly.location.z;
+                    let length_scale = planar.length_scale();
+                    for v in poly.vertices_mut() {
+                        let p = planar.point(&Point::new(v.location.x, v.location.y, elevation));
+                        v.location = Point::new(p.x, p.y, 0.0);
+                        v.starting_width *= length_scale;
+                        v.ending_width *= length_scale;
+                        if planar.is_mirrored {
+                            v.bulge = -v.bulge;
+                        }
+                    }
+                    poly.location =
+                        Point::new(0.0, 0.0, planar.point(&Point::new(0.0, 0.0, elevation)).z);
+                    poly.default_starting_width *= length_scale;
+                    poly.default_ending_width *= length_scale;
+                    poly.thickness = planar.thickness(poly.thickness);
+                    poly.normal = planar.normal();
+                }
+            }
+            EntityType::Ray(ref mut ray) => {
+                ray.start_point = m.transform_point(&ray.start_point);
+                ray.unit_direction_vector =
+                    m.transform_vector(&ray.unit_direction_vector).normalize();
+            }
+            EntityType::RText(ref mut text) => {
+                let planar = PlanarTransform::new(m, &text.extrusion_direction);
+                text.insertion_point = planar.point(&text.insertion_point);
+                text.text_height *= planar.scale_along(text.rotation_angle + 90.0);
+                text.rotation_angle = planar.angle(text.rotation_angle);
+                text.extrusion_direction = planar.normal();
+            }
+            EntityType::Section(ref mut section) => {
+                for v in &mut section.vertices {
+                    *v = m.transform_point(v);
+                }
+                for v in &mut section.back_line_vertices {
+                    *v = m.transform_point(v);
+                }
+                section.vertical_direction =
+                    m.transform_vector(&section.vertical_direction).normalize();
+                section.top_height *= scale;
+                section.bottom_height *= scale;
+            }
+            EntityType::Shape(ref mut shape) => {
+                let planar = PlanarTransform::new(m, &shape.extrusion_direction);
+                let x_scale = planar.scale_along(shape.rotation_angle);
+                let y_scale = planar.scale_along(shape.rotation_angle + 90.0);
+                shape.location = planar.point(&shape.location);
+                shape.rotation_angle = planar.angle(shape.rotation_angle);
+                shape.size *= y_scale;
+                if y_scale != 0.0 {
+                    shape.relative_x_scale_factor *= x_scale / y_scale;
+                }
+                shape.thickness = planar.thickness(shape.thickness);
+                shape.extrusion_direction = planar.normal();
+            }
+            EntityType::Solid(ref mut solid) => {
+                let planar = PlanarTransform::new(m, &solid.extrusion_direction);
+                solid.first_corner = planar.point(&solid.first_corner);
+                solid.second_corner = planar.point(&solid.second_corner);
+                solid.third_corner = planar.point(&solid.third_corner);
+                solid.fourth_corner = planar.point(&solid.fourth_corner);
+                solid.thickness = planar.thickness(solid.thickness);
+                solid.extrusion_direction = planar.normal();
+            }
+            EntityType::Spline(ref mut spline) => {
+                for p in &mut spline.control_points {
+                    *p = m.transform_point(p);
+                }
+                for p in &mut spline.fit_points {
+                    *p = m.transform_point(p);
+                }
+                spline.start_tangent = transform_tangent(m, &spline.start_tangent);
+                spline.end_tangent = transform_tangent(m, &spline.end_tangent);
+                if spline.normal.length() > 0.0 {
+                    spline.normal = PlanarTransform::new(m, &spline.normal).normal();
+                }
+            }
+            EntityType::Text(ref mut text) => {
+                transform_text!(m, text);
+            }
+            EntityType::Tolerance(ref mut tol) => {
+                tol.insertion_point = m.transform_point(&tol.insertion_point);
+                tol.direction_vector = m.transform_vector(&tol.direction_vector);
+                tol.extrusion_direction =
+                    PlanarTransform::new(m, &tol.extrusion_direction).normal();
+            }
+            EntityType::Trace(ref mut trace) => {
+                let planar = PlanarTransform::new(m, &trace.extrusion_direction);
+                trace.first_corner = planar.point(&trace.first_corner);
+                trace.second_corner = planar.point(&trace.second_corner);
+                trace.third_corner = planar.point(&trace.third_corner);
+                trace.fourth_corner = planar.point(&trace.fourth_corner);
+                trace.thickness = planar.thickness(trace.thickness);
+                trace.extrusion_direction = planar.normal();
+            }
+            EntityType::DgnUnderlay(ref mut underlay) => {
+                transform_underlay!(m, underlay);
+            }
+            EntityType::DwfUnderlay(ref mut underlay) => {
+                transform_underlay!(m, underlay);
+            }
+            EntityType::PdfUnderlay(ref mut underlay) => {
+                transform_underlay!(m, underlay);
+            }
+            EntityType::Vertex(ref mut vertex) => {
+                vertex.location = m.transform_point(&vertex.location);
+            }
+            EntityType::Wipeout(ref mut wipeout) => {
+                wipeout.location = m.transform_point(&wipeout.location);
+                wipeout.u_vector = m.transform_vector(&wipeout.u_vector);
+                wipeout.v_vector = m.transform_vector(&wipeout.v_vector);
+            }
+            EntityType::XLine(ref mut xline) => {
+                xline.first_point = m.transform_point(&xline.first_point);
+                xline.unit_direction_vector =
+                    m.transform_vector(&xline.unit_direction_vector).normalize();
+            }
+            // geometry is stored as proprietary data
+            EntityType::Body(_)
+            | EntityType::OleFrame(_)
+            | EntityType::ProxyEntity(_)
+            | EntityType::Region(_)
+            | EntityType::Seqend(_)
+            | EntityType::Solid3D(_)
+            | EntityType::Unknown { .. } => (),
+        }
+
+        if let Some(specific) = replacement {
+            self.specific = specific;
+        }
+    }
+}
+
+/// Maps values in an entity's object coordinate system (OCS) to the OCS of the transformed entity.
+struct PlanarTransform<'a> {
+    matrix: &'a TransformationMatrix,
+    old_axes: (Vector, Vector, Vector),
+    new_axes: (Vector, Vector, Vector),
+    /// The transformed OCS X and Y axes, expressed in world coordinates.
+    x_image: Vector,
+    y_image: Vector,
+    /// Whether angles in the OCS plane run clockwise after the transformation.
+    is_mirrored: bool,
+}
+
+impl<'a> PlanarTransform<'a> {
+    fn new(matrix: &'a TransformationMatrix, normal: &Vector) -> Self {
+        let old_axes = normal.ocs_axes();
+        let x_image = matrix.transform_vector(&old_axes.0);
+        let y_image = matrix.transform_vector(&old_axes.1);
+        let is_mirrored = linear_determinant(matrix) < 0.0;
+
+        // keep the normal on the same side of the plane when mirroring so that 2D entities keep a
+        // normal of +Z
+        let mut new_normal = x_image.cross(&y_image).normalize();
+        if is_mirrored {
+            new_normal = scale_vector(&new_normal, -1.0);
+        }
+
+        let new_axes = if new_normal.length() > 0.0 {
+            new_normal.ocs_axes()
+        } else {
+            old_axes.clone()
+        };
+        PlanarTransform {
+            matrix,
+            old_axes,
+            new_axes,
+            x_image,
+            y_image,
+            is_mirrored,
+        }
+    }
+    fn normal(&self) -> Vector {
+        self.new_axes.2.clone()
+    }
+    fn point(&self, p: &Point) -> Point {
+        let (x, y, z) = &self.old_axes;
+        let world = Point::new(
+            x.x * p.x + y.x * p.y + z.x * p.z,
+            x.y * p.x + y.y * p.y + z.y * p.z,
+            x.z * p.x + y.z * p.y + z.z * p.z,
+        );
+        let world = self.matrix.transform_point(&world);
+        let world = Vector::new(world.x, world.y, world.z);
+        let (x, y, z) = &self.new_axes;
+        Point::new(world.dot(x), world.dot(y), world.dot(z))
+    }
+    fn vector(&self, v: &Vector) -> Vector {
+        let world = add_vectors(
+            &scale_vector(&self.x_image, v.x),
+            &scale_vector(&self.y_image, v.y),
+        );
+        let world = add_vectors(
+            &world,
+            &self
+                .matrix
+                .transform_vector(&scale_vector(&self.old_axes.2, v.z)),
+        );
+        let (x, y, z) = &self.new_axes;
+        Vector::new(world.dot(x), world.dot(y), world.dot(z))
+    }
+    /// Returns the transformed direction of an angle in degrees.
+    fn angle(&self, angle: f64) -> f64 {
+        let (sin, cos) = angle.to_radians().sin_cos();
+        let v = self.vector(&Vector::new(cos, sin, 0.0));
+        if v.x == 0.0 && v.y == 0.0 {
+            angle
+        } else {
+            v.y.atan2(v.x).to_degrees().rem_euclid(360.0)
+        }
+    }
+    /// Returns how much a length along the direction of an angle in degrees is scaled.
+    fn scale_along(&self, angle: f64) -> f64 {
+        let (sin, cos) = angle.to_radians().sin_cos();
+        add_vectors(
+            &scale_vector(&self.x_image, cos),
+            &scale_vector(&self.y_image, sin),
+        )
+        .length()
+    }
+    /// Returns the average scale of lengths in the OCS plane.
+    fn length_scale(&self) -> f64 {
+        self.x_image.cross(&self.y_image).length().sqrt()
+    }
+    /// Returns `true` if circles in the OCS plane remain circles.
+    fn is_uniform(&self) -> bool {
+        let x_length = self.x_image.length();
+        let y_length = self.y_image.length();
+        let tolerance = 1e-9 * x_length.max(y_length);
+        (x_length - y_length).abs() <= tolerance
+            && self.x_image.dot(&self.y_image).abs() <= tolerance * x_length.max(y_length)
+    }
+    /// Returns the thickness along the transformed normal.
+    fn thickness(&self, thickness: f64) -> f64 {
+        thickness
+            * self
+                .matrix
+                .transform_vector(&self.old_axes.2)
+                .dot(&self.new_axes.2)
+    }
+}
+
+fn add_vectors(a: &Vector, b: &Vector) -> Vector {
+    Vector::new(a.x + b.x, a.y + b.y, a.z + b.z)
+}
+
+fn scale_vector(v: &Vector, s: f64) -> Vector {
+    Vector::new(v.x * s, v.y * s, v.z * s)
+}
+
+/// Returns the determinant of the upper-left 3x3 portion of the matrix.
+fn linear_determinant(m: &TransformationMatrix) -> f64 {
+    m.m11 * (m.m22 * m.m33 - m.m23 * m.m32) - m.m12 * (m.m21 * m.m33 - m.m23 * m.m31)
+        + m.m13 * (m.m21 * m.m32 - m.m22 * m.m31)
+}
+
+/// Returns the average scale factor of the transformation.
+fn uniform_scale(m: &TransformationMatrix) -> f64 {
+    linear_determinant(m).abs().cbrt()
+}
+
+fn transform_tangent(m: &TransformationMatrix, tangent: &Point) -> Point {
+    let v = m.transform_vector(&Vector::new(tangent.x, tangent.y, tangent.z));
+    Point::new(v.x, v.y, v.z)
+}
+
+/// Returns the major and minor axes of the ellipse `u * cos(t) + v * sin(t)`, where `u` and `v`
+/// are any pair of conjugate semi-diameters, along with the value of `t` at the major axis.
+fn principal_axes(u: &Vector, v: &Vector) -> (Vector, Vector, f64) {
+    let t0 = 0.5 * (2.0 * u.dot(v)).atan2(u.dot(u) - v.dot(v));
+    let (sin, cos) = t0.sin_cos();
+    let major = add_vectors(&scale_vector(u, cos), &scale_vector(v, sin));
+    let minor = add_vectors(&scale_vector(u, -sin), &scale_vector(v, cos));
+    (major, minor, t0)
+}
+
+/// Transforms the elliptical arc `center + u * cos(t) + v * sin(t)` for `t` from `start` to `end`
+/// and returns the center, major axis, minor axis ratio, and start and end parameters of the
+/// result, where the minor axis is the cross product of `normal` and the major axis.
+fn transform_elliptical_arc(
+    m: &TransformationMatrix,
+    normal: &Vector,
+    center: &Point,
+    u: &Vector,
+    v: &Vector,
+    start: f64,
+    end: f64,
+) -> (Point, Vector, f64, f64, f64) {
+    let (major, minor, t0) = principal_axes(&m.transform_vector(u), &m.transform_vector(v));
+    let major_length = major.length();
+    let ratio = if major_length == 0.0 {
+        1.0
+    } else {
+        minor.length() / major_length
+    };
+    let (start, end) = if major.cross(&minor).dot(normal) >= 0.0 {
+        (start - t0, end - t0)
+    } else {
+        // the parameter runs clockwise around the normal
+        (t0 - end, t0 - start)
+    };
+    let is_full = (end - start - 2.0 * PI).abs() < 1e-12;
+    let start = start.rem_euclid(2.0 * PI);
+    let end = if is_full {
+        start + 2.0 * PI
+    } else {
+        end.rem_euclid(2.0 * PI)
+    };
+    (m.transform_point(center), major, ratio, start, end)
+}
+
+fn ellipse_from_circular_arc(
+    m: &TransformationMatrix,
+    normal: &Vector,
+    center: &Point,
+    radius: f64,
+    start: f64,
+    end: f64,
+) -> Ellipse {
+    let planar = PlanarTransform::new(m, normal);
+    let (x, y, z) = &planar.old_axes;
+    let world_center = Point::new(
+        x.x * center.x + y.x * center.y + z.x * center.z,
+        x.y * center.x + y.y * center.y + z.y * center.z,
+        x.z * center.x + y.z * center.y + z.z * center.z,
+    );
+    let (center, major_axis, minor_axis_ratio, start_parameter, end_parameter) =
+        transform_elliptical_arc(
+            m,
+            &planar.normal(),
+            &world_center,
+            &scale_vector(x, radius),
+            &scale_vector(y, radius),
+            start,
+            end,
+        );
+    Ellipse {
+        center,
+        major_axis,
+        normal: planar.normal(),
+        minor_axis_ratio,
+        start_parameter,
+        end_parameter,
+    }
+}
+
+fn transform_dimension_base<'a>(
+    m: &'a TransformationMatrix,
+    dim: &mut DimensionBase,
+) -> PlanarTransform<'a> {
+    let planar = PlanarTransform::new(m, &dim.normal);
+    dim.definition_point_1 = m.transform_point(&dim.definition_point_1);
+    dim.text_mid_point = planar.point(&dim.text_mid_point);
+    dim.horizontal_direction_angle = planar.angle(dim.horizontal_direction_angle);
+    dim.normal = planar.normal();
+    planar
+}
+
+fn transform_hatch_boundary_path(
+    planar: &PlanarTransform,
+    elevation: f64,
+    path: &mut HatchBoundaryPath,
+) {
+    let point = |p: &Point| {
+        let p = planar.point(&Point::new(p.x, p.y, elevation));
+        Point::new(p.x, p.y, 0.0)
+    };
+    let length_scale = planar.length_scale();
+    for v in &mut path.vertices {
+        let p = point(&Point::new(v.x, v.y, 0.0));
+        v.x = p.x;
+        v.y = p.y;
+        if planar.is_mirrored {
+            v.bulge = -v.bulge;
+        }
+    }
+
+    for edge in &mut path.edges {
+        match edge {
+            HatchBoundaryEdge::Line { start, end } => {
+                *start = point(start);
+                *end = point(end);
+            }
+            HatchBoundaryEdge::CircularArc {
+                center,
+                radius,
+                start_angle,
+                end_angle,
+                is_counter_clockwise,
+            } => {
+                *center = point(center);
+                *radius *= length_scale;
+                *start_angle = planar.angle(*start_angle);
+                *end_angle = planar.angle(*end_angle);
+                if planar.is_mirrored {
+                    *is_counter_clockwise = !*is_counter_clockwise;
+                }
+            }
+            HatchBoundaryEdge::EllipticArc {
+                center,
+                major_axis,
+                minor_axis_ratio,
+                start_angle,
+                end_angle,
+                is_counter_clockwise,
+            } => {
+                // work in the old and new OCS directly
+                let u = planar.vector(major_axis);
+                let v = planar.vector(&scale_vector(
+                    &Vector::new(-major_axis.y, major_axis.x, 0.0),
+                    *minor_axis_ratio,
+                ));
+                let (major, minor, t0) = principal_axes(&u, &v);
+                let major_length = major.length();
+                if major_length != 0.0 {
+                    *minor_axis_ratio = minor.length() / major_length;
+                }
+                let (start, end) = (start_angle.to_radians(), end_angle.to_radians());
+                let (start, end) = if major.cross(&minor).z >= 0.0 {
+                    (start - t0, end - t0)
+                } else {
+                    *is_counter_clockwise = !*is_counter_clockwise;
+                    (t0 - start, t0 - end)
+                };
+                *start_angle = start.to_degrees().rem_euclid(360.0);
+                *end_angle = end.to_degrees().rem_euclid(360.0);
+                *center = point(center);
+                *major_axis = Vector::new(major.x, major.y, 0.0);
+            }
+            HatchBoundaryEdge::Spline {
+                control_points,
+                fit_points,
+                start_tangent,
+                end_tangent,
+                ..
+            } => {
+                for p in control_points.iter_mut().chain(fit_points.iter_mut()) {
+                    *p = point(p);
+                }
+                *start_tangent = planar.vector(start_tangent);
+                *end_tangent = planar.vector(end_tangent);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::entities::*;
+    use crate::*;
+
+    fn assert_close(expected: f64, actual: f64) {
+        assert!(
+            (expected - actual).abs() < 1e-9,
+            "expected {} but was {}",
+            expected,
+            actual
+        );
+    }
+
+    fn assert_point(expected: Point, actual: &Point) {
+        assert_close(expected.x, actual.x);
+        assert_close(expected.y, actual.y);
+        assert_close(expected.z, actual.z);
+    }
+
+    fn transformed(specific: EntityType, matrix: &TransformationMatrix) -> EntityType {
+        let mut ent = Entity::new(specific);
+        ent.transform(matrix);
+        ent.specific
+    }
+
+    #[test]
+    fn transform_line() {
+        let matrix = TransformationMatrix::identity()
+            .rotate(&Vector::z_axis(), 90.0)
+            .translate(&Vector::new(1.0, 0.0, 0.0));
+        match transformed(
+            EntityType::Line(Line::new(Point::origin(), Point::new(1.0, 0.0, 0.0))),
+            &matrix,
+        ) {
+            EntityType::Line(ref line) => {
+                assert_point(Point::new(1.0, 0.0, 0.0), &line.p1);
+                assert_point(Point::new(1.0, 1.0, 0.0), &line.p2);
+            }
+            _ => panic!("expected a line"),
+        }
+    }
+
+    #[test]
+    fn transform_circle_with_uniform_scale() {
+        let matrix = TransformationMatrix::identity().scale(2.0, 2.0, 2.0);
+        match transformed(
+            EntityType::Circle(Circle::new(Point::new(1.0, 1.0, 0.0), 1.5)),
+            &matrix,
+        ) {
+            EntityType::Circle(ref circle) => {
+                assert_point(Point::new(2.0, 2.0, 0.0), &circle.center);
+                assert_close(3.0, circle.radius);
+            }
+            _ => panic!("expected a circle"),
+        }
+    }
+
+    #[test]
+    fn transform_circle_with_non_uniform_scale() {
+        let matrix = TransformationMatrix::identity().scale(1.0, 3.0, 1.0);
+        match transformed(
+            EntityType::Circle(Circle::new(Point::origin(), 1.0)),
+            &matrix,
+        ) {
+            EntityType::Ellipse(ref ellipse) => {
+                assert_close(3.0, ellipse.major_axis.length());
+                assert_close(0.0, ellipse.major_axis.x);
+                assert_close(1.0 / 3.0, ellipse.minor_axis_ratio);
+                assert_close(
+                    2.0 * std::f64::consts::PI,
+                    ellipse.end_parameter - ellipse.start_parameter,
+                );
+            }
+            _ => panic!("expected an ellipse"),
+        }
+    }
+
+    #[test]
+    fn transform_arc_with_mirror() {
+        // mirroring across the Y axis maps 0..90 degrees to 90..180 degrees
+        let matrix = TransformationMatrix::identity().mirror(&Vector::x_axis());
+        match transformed(
+            EntityType::Arc(Arc::new(Point::new(1.0, 0.0, 0.0), 1.0, 0.0, 90.0)),
+            &matrix,
+        ) {
+            EntityType::Arc(ref arc) => {
+                assert_point(Point::new(-1.0, 0.0, 0.0), &arc.center);
+                assert_eq!(Vector::z_axis(), arc.normal);
+                assert_close(90.0, arc.start_angle);
+                assert_close(180.0, arc.end_angle);
+            }
+            _ => panic!("expected an arc"),
+        }
+    }
+
+    #[test]
+    fn transform_lw_polyline_bulge_with_mirror() {
+        let mut poly = LwPolyline::default();
+        poly.vertices.push(LwPolylineVertex {
+            x: 1.0,
+            bulge: 1.0,
+            ..Default::default()
+        });
+        poly.vertices.push(LwPolylineVertex {
+            x: 2.0,
+            ..Default::default()
+        });
+        let matrix = TransformationMatrix::identity().mirror(&Vector::y_axis());
+        match transformed(EntityType::LwPolyline(poly), &matrix) {
+            EntityType::LwPolyline(ref poly) => {
+                assert_close(1.0, poly.vertices[0].x);
+                assert_close(-1.0, poly.vertices[0].bulge);
+                assert_eq!(Vector::z_axis(), poly.extrusion_direction);
+            }
+            _ => panic!("expected a polyline"),
+        }
+    }
+
+    #[test]
+    fn transform_ellipse_with_rotation() {
+        let ellipse = Ellipse {
+            major_axis: Vector::new(2.0, 0.0, 0.0),
+            minor_axis_ratio: 0.5,
+            start_parameter: 0.0,
+            end_parameter: 1.0,
+            ..Default::default()
+        };
+        let matrix = TransformationMatrix::identity().rotate(&Vector::z_axis(), 90.0);
+        match transformed(EntityType::Ellipse(ellipse), &matrix) {
+            EntityType::Ellipse(ref ellipse) => {
+                assert_close(0.0, ellipse.major_axis.x);
+                assert_close(2.0, ellipse.major_axis.y);
+                assert_close(0.5, ellipse.minor_axis_ratio);
+                assert_close(0.0, ellipse.start_parameter);
+                assert_close(1.0, ellipse.end_parameter);
+            }
+            _ => panic!("expected an ellipse"),
+        }
+    }
+
+    #[test]
+    fn transform_text_rotation_and_height() {
+        let text = Text {
+            location: Point::new(1.0, 0.0, 0.0),
+            text_height: 1.0,
+            rotation: 10.0,
+            ..Default::default()
+        };
+        let matrix = TransformationMatrix::identity()
+            .scale(2.0, 2.0, 2.0)
+            .rotate(&Vector::z_axis(), 30.0);
+        match transformed(EntityType::Text(text), &matrix) {
+            EntityType::Text(ref text) => {
+                let (sin, cos) = 30.0f64.to_radians().sin_cos();
+                assert_point(Point::new(2.0 * cos, 2.0 * sin, 0.0), &text.location);
+                assert_close(40.0, text.rotation);
+                assert_close(2.0, text.text_height);
+                assert_close(1.0, text.relative_x_scale_factor);
+            }
+            _ => panic!("expected text"),
+        }
+    }
+
+    #[test]
+    fn transform_insert_scale_and_mirror() {
+        let ins = Insert {
+            location: Point::new(1.0, 1.0, 0.0),
+            ..Default::default()
+        };
+        let matrix = TransformationMatrix::identity()
+            .scale(3.0, 3.0, 3.0)
+            .mirror(&Vector::x_axis());
+        match transformed(EntityType::Insert(ins), &matrix) {
+            EntityType::Insert(ref ins) => {
+                assert_point(Point::new(-3.0, 3.0, 0.0), &ins.location);
+                assert_close(180.0, ins.rotation);
+                assert_close(3.0, ins.x_scale_factor);
+                assert_close(-3.0, ins.y_scale_factor);
+                assert_close(3.0, ins.z_scale_factor);
+            }
+            _ => panic!("expected an insert"),
+        }
+    }
+}
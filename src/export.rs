@@ -0,0 +1,3 @@
+//! Renders a `Drawing` to other file formats.
+
+pub mod svg;
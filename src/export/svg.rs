@@ -0,0 +1,1252 @@
+//! Renders model space or a paper space `Layout` of a `Drawing` as an SVG image.
+//!
+//! Colors are resolved through the entity, its `Layer`, and the AutoCAD Color Index palette,
+//! `LineType` dash patterns and lineweights are applied to strokes, and text is drawn with the font
+//! of its `Style`.  `Insert`s are expanded, dimensions are drawn from their anonymous blocks, and
+//! hatches are filled or drawn with their pattern lines.  Entities on layers that are turned off
+//! and entities without a 2D representation (e.g., 3D solids, images, and meshes) are skipped.
+//! Everything is projected onto the XY plane of the world coordinate system.
+//!
+//! ``` rust
+//! # fn ex() -> dxf::DxfResult<()> {
+//! use dxf::Drawing;
+//! use dxf::export::svg::{self, SvgOptions};
+//!
+//! let drawing = Drawing::load_file("path/to/file.dxf")?;
+//! let mut file = std::fs::File::create("path/to/file.svg")?;
+//! svg::write(&drawing, None, &SvgOptions::default(), &mut file)?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::f64::consts::PI;
+use std::io::Write;
+
+use crate::entities::*;
+use crate::enums::*;
+use crate::helper_functions::strip_m_text_formatting;
+use crate::objects::Layout;
+use crate::{
+    BoundingBox, Drawing, DxfResult, EffectiveProperties, HatchBoundaryEdge, HatchBoundaryPath,
+    HatchPatternDefinitionLine, InsertContext, LineWeight, Point, PolylineSegment,
+    TransformationMatrix, Vector,
+};
+
+/// The most hatch pattern lines that are drawn for a single `Hatch` before falling back to a solid
+/// fill.
+const MAX_HATCH_PATTERN_LINES: usize = 5_000;
+
+/// Options that control how a `Drawing` is rendered.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SvgOptions {
+    /// The width of the image in pixels.  The height follows from the aspect ratio of the rendered
+    /// geometry.
+    pub width: f64,
+    /// The empty space around the geometry, as a fraction of its larger dimension.
+    pub margin: f64,
+    /// The red, green, and blue components of the background, or `None` for a transparent
+    /// background.  Color index 7 is drawn in white on dark backgrounds and in black otherwise.
+    pub background: Option<(u8, u8, u8)>,
+    /// The width, in millimeters, of lines that use the default lineweight.
+    pub default_line_weight: f64,
+    /// The number of pixels per millimeter of lineweight.  Lines are never drawn thinner than one
+    /// pixel.
+    pub pixels_per_millimeter: f64,
+}
+
+impl Default for SvgOptions {
+    fn default() -> Self {
+        SvgOptions {
+            width: 1000.0,
+            margin: 0.02,
+            background: None,
+            default_line_weight: 0.25,
+            pixels_per_millimeter: 96.0 / 25.4,
+        }
+    }
+}
+
+/// Writes the SVG rendering of model space, or of `layout` if specified, to `writer`.
+pub fn write<T>(
+    drawing: &Drawing,
+    layout: Option<&Layout>,
+    options: &SvgOptions,
+    writer: &mut T,
+) -> DxfResult<()>
+where
+    T: Write + ?Sized,
+{
+    writer.write_all(to_string(drawing, layout, options).as_bytes())?;
+    Ok(())
+}
+
+/// Returns the SVG rendering of model space, or of `layout` if specified.
+pub fn to_string(drawing: &Drawing, layout: Option<&Layout>, options: &SvgOptions) -> String {
+    let entities = match layout {
+        Some(layout) => layout.entities(drawing),
+        None => drawing.model_space().collect(),
+    };
+    let mut bounds: Option<BoundingBox> = None;
+    for entity in &entities {
+        if let Some(bbox) = entity.bounding_box(drawing) {
+            bounds = Some(match bounds {
+                Some(existing) => existing.union(&bbox),
+                None => bbox,
+            });
+        }
+    }
+    let bounds = bounds.unwrap_or_else(|| BoundingBox::new(Point::origin(), Point::origin()));
+
+    // keep degenerate (e.g., a single point or a vertical line) drawings visible
+    let size = (bounds.max.x - bounds.min.x).max(bounds.max.y - bounds.min.y);
+    let size = if size > 0.0 { size } else { 1.0 };
+    let width = (bounds.max.x - bounds.min.x).max(size / 100.0);
+    let height = (bounds.max.y - bounds.min.y).max(size / 100.0);
+    let center_x = (bounds.min.x + bounds.max.x) / 2.0;
+    let center_y = (bounds.min.y + bounds.max.y) / 2.0;
+    let margin = size * options.margin.max(0.0);
+    let view_width = width + 2.0 * margin;
+    let view_height = height + 2.0 * margin;
+    let view_x = center_x - view_width / 2.0;
+    let view_y = -(center_y + view_height / 2.0);
+    let pixel_width = if options.width > 0.0 {
+        options.width
+    } else {
+        SvgOptions::default().width
+    };
+    let pixel_height = pixel_width * view_height / view_width;
+
+    let foreground = match options.background {
+        Some((r, g, b))
+            if (u32::from(r) * 299 + u32::from(g) * 587 + u32::from(b) * 114) < 128_000 =>
+        {
+            (255, 255, 255)
+        }
+        _ => (0, 0, 0),
+    };
+    let units_per_pixel = view_width / pixel_width;
+    let mut renderer = Renderer {
+        drawing,
+        options,
+        units_per_pixel,
+        chord_tolerance: units_per_pixel / 4.0,
+        foreground,
+        clip_path_count: 0,
+        output: String::new(),
+    };
+
+    renderer
+        .output
+        .push_str("<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"no\"?>\n");
+    renderer.output.push_str(&format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" viewBox=\"{} {} {} {}\">\n",
+        num(pixel_width),
+        num(pixel_height),
+        num(view_x),
+        num(view_y),
+        num(view_width),
+        num(view_height)
+    ));
+    if let Some(background) = options.background {
+        renderer.output.push_str(&format!(
+            "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"{}\"/>\n",
+            num(view_x),
+            num(view_y),
+            num(view_width),
+            num(view_height),
+            hex(background)
+        ));
+    }
+    renderer
+        .output
+        .push_str("<g fill=\"none\" stroke-linecap=\"round\" stroke-linejoin=\"round\">\n");
+    for entity in &entities {
+        renderer.render_entity(entity);
+    }
+    renderer.output.push_str("</g>\n</svg>\n");
+    renderer.output
+}
+
+/// The stroke attributes of an entity.
+struct Stroke {
+    color: String,
+    width: f64,
+    /// The dash array and dash offset, if the line type isn't continuous.
+    dashes: Option<(Vec<f64>, f64)>,
+}
+
+impl Stroke {
+    fn attributes(&self) -> String {
+        let mut attributes = format!(
+            " stroke=\"{}\" stroke-width=\"{}\"",
+            self.color,
+            num(self.width)
+        );
+        if let Some((ref dashes, offset)) = self.dashes {
+            attributes.push_str(&format!(
+                " stroke-dasharray=\"{}\"",
+                dashes.iter().map(|d| num(*d)).collect::<Vec<_>>().join(",")
+            ));
+            if offset != 0.0 {
+                attributes.push_str(&format!(" stroke-dashoffset=\"{}\"", num(offset)));
+            }
+        }
+        attributes
+    }
+}
+
+struct Renderer<'a> {
+    drawing: &'a Drawing,
+    options: &'a SvgOptions,
+    units_per_pixel: f64,
+    chord_tolerance: f64,
+    foreground: (u8, u8, u8),
+    clip_path_count: usize,
+    output: String,
+}
+
+impl<'a> Renderer<'a> {
+    fn render_entity(&mut self, entity: &Entity) {
+        let properties = self
+            .drawing
+            .effective_properties(entity, &InsertContext::default());
+        let is_insert = matches!(entity.specific, EntityType::Insert(_));
+        // the entities of an `Insert` on a layer that's off are still drawn unless they're on layer
+        // `0`, which `explode_internal()` moves to the insert's layer
+        let is_frozen = properties.layer.iter().any(|l| l.get_is_frozen());
+        if !entity.common.is_visible || is_frozen || (!is_insert && !properties.is_visible) {
+            return;
+        }
+
+        let dimension_base = match entity.specific {
+            EntityType::Insert(ref ins) => {
+                for e in ins.explode_internal(self.drawing, Some(&entity.common), &mut vec![]) {
+                    self.render_entity(&e);
+                }
+                return;
+            }
+            EntityType::RotatedDimension(ref dim) => Some(&dim.dimension_base),
+            EntityType::RadialDimension(ref dim) => Some(&dim.dimension_base),
+            EntityType::DiameterDimension(ref dim) => Some(&dim.dimension_base),
+            EntityType::AngularThreePointDimension(ref dim) => Some(&dim.dimension_base),
+            EntityType::OrdinateDimension(ref dim) => Some(&dim.dimension_base),
+            _ => None,
+        };
+        if let Some(dimension_base) = dimension_base {
+            // dimensions are drawn by their anonymous block, which is already in world coordinates
+            let ins = Insert {
+                name: dimension_base.block_name.clone(),
+                ..Default::default()
+            };
+            for e in ins.explode_internal(self.drawing, Some(&entity.common), &mut vec![]) {
+                self.render_entity(&e);
+            }
+            return;
+        }
+
+        let stroke = self.stroke(&properties);
+        let matrix = entity.ocs_matrix();
+        match entity.specific {
+            EntityType::Arc(ref arc) => {
+                let (x_axis, y_axis, _) = arc.normal.ocs_axes();
+                let mut sweep = (arc.end_angle - arc.start_angle) % 360.0;
+                if sweep <= 0.0 {
+                    sweep += 360.0;
+                }
+                let points = self.arc_points(
+                    &arc.center_wcs(),
+                    &x_axis.scale(arc.radius),
+                    &y_axis.scale(arc.radius),
+                    arc.start_angle.to_radians(),
+                    sweep.to_radians(),
+                );
+                self.path(&points, false, &stroke);
+            }
+            EntityType::Circle(ref circle) => {
+                let (x_axis, y_axis, _) = circle.normal.ocs_axes();
+                let mut points = self.arc_points(
+                    &circle.center_wcs(),
+                    &x_axis.scale(circle.radius),
+                    &y_axis.scale(circle.radius),
+                    0.0,
+                    2.0 * PI,
+                );
+                points.pop();
+                self.path(&points, true, &stroke);
+            }
+            EntityType::Ellipse(ref ellipse) => {
+                let minor_axis = ellipse
+                    .normal
+                    .normalize()
+                    .cross(&ellipse.major_axis)
+                    .scale(ellipse.minor_axis_ratio);
+                let mut sweep = ellipse.end_parameter - ellipse.start_parameter;
+                if sweep <= 0.0 {
+                    sweep += 2.0 * PI;
+                }
+                let points = self.arc_points(
+                    &ellipse.center,
+                    &ellipse.major_axis,
+                    &minor_axis,
+                    ellipse.start_parameter,
+                    sweep,
+                );
+                self.path(&points, false, &stroke);
+            }
+            EntityType::Face3D(ref face) => {
+                let points = vec![
+                    face.first_corner.clone(),
+                    face.second_corner.clone(),
+                    face.third_corner.clone(),
+                    face.fourth_corner.clone(),
+                ];
+                self.path(&points, true, &stroke);
+            }
+            EntityType::Hatch(ref hatch) => self.render_hatch(hatch, &matrix, &stroke),
+            EntityType::Leader(ref leader) => self.path(&leader.vertices, false, &stroke),
+            EntityType::Line(ref line) => {
+                self.path(&[line.p1.clone(), line.p2.clone()], false, &stroke)
+            }
+            EntityType::LwPolyline(ref poly) => {
+                let elevation = entity.common.elevation;
+                let mut points = poly
+                    .tessellate(self.chord_tolerance)
+                    .iter()
+                    .map(|p| matrix.transform_point(&Point::new(p.x, p.y, elevation)))
+                    .collect::<Vec<_>>();
+                let is_closed = poly.get_is_closed();
+                if is_closed {
+                    points.pop();
+                }
+                let mut stroke = stroke;
+                if poly.constant_width > 0.0 {
+                    stroke.width = poly.constant_width;
+                }
+                self.path(&points, is_closed, &stroke);
+            }
+            EntityType::MText(ref mtext) => self.render_m_text(mtext, &stroke),
+            EntityType::ModelPoint(ref point) => {
+                self.output.push_str(&format!(
+                    "<circle cx=\"{}\" cy=\"{}\" r=\"{}\" fill=\"{}\" stroke=\"none\"/>\n",
+                    num(point.location.x),
+                    num(-point.location.y),
+                    num(stroke.width / 2.0),
+                    stroke.color
+                ));
+            }
+            EntityType::Polyline(ref poly) => {
+                let mut points = poly
+                    .tessellate(self.chord_tolerance)
+                    .iter()
+                    .map(|p| matrix.transform_point(p))
+                    .collect::<Vec<_>>();
+                let is_closed = poly.get_is_closed();
+                if is_closed {
+                    points.pop();
+                }
+                self.path(&points, is_closed, &stroke);
+            }
+            EntityType::Solid(ref solid) => self.quadrilateral(&solid.corners_wcs(), &stroke),
+            EntityType::Spline(ref spline) => {
+                let points = spline.tessellate(self.chord_tolerance);
+                self.path(&points, false, &stroke);
+            }
+            EntityType::Text(ref text) => self.render_text(text, &stroke),
+            EntityType::Trace(ref trace) => self.quadrilateral(&trace.corners_wcs(), &stroke),
+            _ => (),
+        }
+    }
+    fn stroke(&self, properties: &EffectiveProperties) -> Stroke {
+        Stroke {
+            color: match properties.color.index {
+                Some(7) => hex(self.foreground),
+                _ => hex(properties.color.rgb),
+            },
+            width: self.line_width(&properties.line_weight),
+            dashes: self.dashes(properties),
+        }
+    }
+    /// Returns the stroke width in drawing units.
+    fn line_width(&self, line_weight: &LineWeight) -> f64 {
+        let raw_value = line_weight.get_raw_value();
+        let millimeters = if raw_value >= 0 {
+            f64::from(raw_value) / 100.0
+        } else {
+            self.options.default_line_weight
+        };
+        (millimeters * self.options.pixels_per_millimeter).max(1.0) * self.units_per_pixel
+    }
+    fn dashes(&self, properties: &EffectiveProperties) -> Option<(Vec<f64>, f64)> {
+        let line_type = properties.line_type?;
+        let (dashes, offset) = dash_array(
+            &line_type.dash_dot_space_lengths,
+            properties.line_type_scale,
+        )?;
+        if dashes.iter().sum::<f64>() < self.units_per_pixel {
+            // too fine to be seen
+            return None;
+        }
+        Some((dashes, offset))
+    }
+    /// Returns points along the elliptical arc `center + cos(t) * x_axis + sin(t) * y_axis` for
+    /// `t` from `start` through `start + sweep` (in radians).
+    fn arc_points(
+        &self,
+        center: &Point,
+        x_axis: &Vector,
+        y_axis: &Vector,
+        start: f64,
+        sweep: f64,
+    ) -> Vec<Point> {
+        let radius = x_axis.length().max(y_axis.length());
+        let max_step = if radius > 0.0 {
+            2.0 * (1.0 - self.chord_tolerance / radius).max(0.0).acos()
+        } else {
+            PI
+        };
+        let pieces = (sweep.abs() / max_step)
+            .max(sweep.abs() / (PI / 2.0))
+            .ceil()
+            .clamp(1.0, 4096.0) as usize;
+        (0..=pieces)
+            .map(|i| {
+                let angle = start + sweep * i as f64 / pieces as f64;
+                let (sin, cos) = angle.sin_cos();
+                Point::new(
+                    center.x + x_axis.x * cos + y_axis.x * sin,
+                    center.y + x_axis.y * cos + y_axis.y * sin,
+                    center.z + x_axis.z * cos + y_axis.z * sin,
+                )
+            })
+            .collect()
+    }
+    fn path(&mut self, points: &[Point], is_closed: bool, stroke: &Stroke) {
+        if points.len() < 2 {
+            return;
+        }
+        self.output.push_str(&format!(
+            "<path d=\"{}\"{}/>\n",
+            path_data(points, is_closed),
+            stroke.attributes()
+        ));
+    }
+    /// Fills a `Solid` or `Trace`, whose third and fourth corners are stored in swapped order.
+    fn quadrilateral(&mut self, corners: &[Point], stroke: &Stroke) {
+        let points = [
+            corners[0].clone(),
+            corners[1].clone(),
+            corners[3].clone(),
+            corners[2].clone(),
+        ];
+        self.output.push_str(&format!(
+            "<path d=\"{}\" fill=\"{}\" stroke=\"none\"/>\n",
+            path_data(&points, true),
+            stroke.color
+        ));
+    }
+    fn render_text(&mut self, text: &Text, stroke: &Stroke) {
+        let matrix = text.normal.ocs_matrix();
+        let is_default_justification = text.horizontal_text_justification
+            == HorizontalTextJustification::Left
+            && text.vertical_text_justification == VerticalTextJustification::Baseline;
+        let (anchor, rotation) = match text.horizontal_text_justification {
+            HorizontalTextJustification::Aligned | HorizontalTextJustification::Fit => {
+                let dx = text.second_alignment_point.x - text.location.x;
+                let dy = text.second_alignment_point.y - text.location.y;
+                (&text.location, dy.atan2(dx).to_degrees())
+            }
+            _ if is_default_justification => (&text.location, text.rotation),
+            _ => (&text.second_alignment_point, text.rotation),
+        };
+        let text_anchor = match text.horizontal_text_justification {
+            HorizontalTextJustification::Center | HorizontalTextJustification::Middle => "middle",
+            HorizontalTextJustification::Right => "end",
+            _ => "start",
+        };
+        let baseline = match text.vertical_text_justification {
+            VerticalTextJustification::Baseline
+                if text.horizontal_text_justification == HorizontalTextJustification::Middle =>
+            {
+                Some("central")
+            }
+            VerticalTextJustification::Baseline => None,
+            VerticalTextJustification::Bottom => Some("text-after-edge"),
+            VerticalTextJustification::Middle => Some("central"),
+            VerticalTextJustification::Top => Some("text-before-edge"),
+        };
+
+        let location = matrix.transform_point(anchor);
+        let angle = projected_angle(&matrix, rotation);
+        let mut transform = format!(
+            "translate({} {}) rotate({})",
+            num(location.x),
+            num(-location.y),
+            num(-angle)
+        );
+        let mut x_scale = if text.relative_x_scale_factor > 0.0 {
+            text.relative_x_scale_factor
+        } else {
+            1.0
+        };
+        if text.get_is_text_backwards() {
+            x_scale = -x_scale;
+        }
+        let y_scale = if text.get_is_text_upside_down() {
+            -1.0
+        } else {
+            1.0
+        };
+        if x_scale != 1.0 || y_scale != 1.0 {
+            transform.push_str(&format!(" scale({} {})", num(x_scale), num(y_scale)));
+        }
+        if text.oblique_angle != 0.0 {
+            transform.push_str(&format!(" skewX({})", num(-text.oblique_angle)));
+        }
+
+        let baseline = match baseline {
+            Some(baseline) => format!(" dominant-baseline=\"{}\"", baseline),
+            None => String::new(),
+        };
+        self.output.push_str(&format!(
+            "<text transform=\"{}\" font-family=\"{}\" font-size=\"{}\" text-anchor=\"{}\"{} fill=\"{}\" stroke=\"none\">{}</text>\n",
+            transform,
+            self.font_family(&text.text_style_name),
+            num(text.text_height),
+            text_anchor,
+            baseline,
+            stroke.color,
+            escape(&decode_special_codes(&text.value))
+        ));
+    }
+    fn render_m_text(&mut self, mtext: &MText, stroke: &Stroke) {
+        let mut value = mtext.extended_text.join("");
+        value.push_str(&mtext.text);
+        let value = m_text_plain_text(&value);
+        let lines = value.split('\n').collect::<Vec<_>>();
+        let height = mtext.initial_text_height;
+        let line_spacing_factor = if mtext.line_spacing_factor > 0.0 {
+            mtext.line_spacing_factor
+        } else {
+            1.0
+        };
+        // successive lines are spaced 5/3 of the text height apart
+        let spacing = height * 5.0 / 3.0 * line_spacing_factor;
+        let total_height = height + spacing * (lines.len() - 1) as f64;
+
+        let angle = if mtext.x_axis_direction.length() > 0.0 {
+            mtext
+                .x_axis_direction
+                .y
+                .atan2(mtext.x_axis_direction.x)
+                .to_degrees()
+        } else {
+            projected_angle(
+                &mtext.extrusion_direction.ocs_matrix(),
+                mtext.rotation_angle.to_degrees(),
+            )
+        };
+        let text_anchor = match mtext.attachment_point {
+            AttachmentPoint::TopLeft
+            | AttachmentPoint::MiddleLeft
+            | AttachmentPoint::BottomLeft => "start",
+            AttachmentPoint::TopCenter
+            | AttachmentPoint::MiddleCenter
+            | AttachmentPoint::BottomCenter => "middle",
+            AttachmentPoint::TopRight
+            | AttachmentPoint::MiddleRight
+            | AttachmentPoint::BottomRight => "end",
+        };
+        // the offset of the top of the text, downwards being positive
+        let top = match mtext.attachment_point {
+            AttachmentPoint::TopLeft | AttachmentPoint::TopCenter | AttachmentPoint::TopRight => {
+                0.0
+            }
+            AttachmentPoint::MiddleLeft
+            | AttachmentPoint::MiddleCenter
+            | AttachmentPoint::MiddleRight => -total_height / 2.0,
+            AttachmentPoint::BottomLeft
+            | AttachmentPoint::BottomCenter
+            | AttachmentPoint::BottomRight => -total_height,
+        };
+
+        self.output.push_str(&format!(
+            "<g transform=\"translate({} {}) rotate({})\" font-family=\"{}\" font-size=\"{}\" text-anchor=\"{}\" fill=\"{}\" stroke=\"none\">\n",
+            num(mtext.insertion_point.x),
+            num(-mtext.insertion_point.y),
+            num(-angle),
+            self.font_family(&mtext.text_style_name),
+            num(height),
+            text_anchor,
+            stroke.color
+        ));
+        for (i, line) in lines.iter().enumerate() {
+            self.output.push_str(&format!(
+                "<text x=\"0\" y=\"{}\">{}</text>\n",
+                num(top + height + spacing * i as f64),
+                escape(line)
+            ));
+        }
+        self.output.push_str("</g>\n");
+    }
+    fn font_family(&self, style_name: &str) -> String {
+        let font = self
+            .drawing
+            .styles()
+            .find(|s| s.name.eq_ignore_ascii_case(style_name))
+            .map_or("", |s| s.primary_font_file_name.as_str());
+        let file_name = font.rsplit(&['/', '\\'][..]).next().unwrap_or("");
+        let stem = match file_name.rfind('.') {
+            Some(index) => &file_name[..index],
+            None => file_name,
+        };
+        let stem = stem.replace('\'', "");
+        if stem.is_empty() {
+            String::from("sans-serif")
+        } else {
+            format!("'{}', sans-serif", escape(&stem))
+        }
+    }
+    fn render_hatch(&mut self, hatch: &Hatch, matrix: &TransformationMatrix, stroke: &Stroke) {
+        let loops = hatch
+            .boundary_paths
+            .iter()
+            .map(|path| self.hatch_boundary_points(path))
+            .filter(|points| points.len() >= 3)
+            .collect::<Vec<_>>();
+        if loops.is_empty() {
+            return;
+        }
+
+        let elevation = hatch.elevation_point.z;
+        let to_wcs = |p: &Point| matrix.transform_point(&Point::new(p.x, p.y, elevation));
+        let data = loops
+            .iter()
+            .map(|points| path_data(&points.iter().map(to_wcs).collect::<Vec<_>>(), true))
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        let pattern_lines = if hatch.is_solid_fill || hatch.is_gradient {
+            None
+        } else {
+            let mut bounds = BoundingBox::from_point(&loops[0][0]);
+            for p in loops.iter().flatten() {
+                bounds.add_point(p);
+            }
+            hatch_pattern_lines(&hatch.pattern_definition_lines, &bounds)
+        };
+        match pattern_lines {
+            Some(families) => {
+                let id = format!("hatch{}", self.clip_path_count);
+                self.clip_path_count += 1;
+                self.output.push_str(&format!(
+                    "<clipPath id=\"{}\"><path d=\"{}\" clip-rule=\"evenodd\"/></clipPath>\n",
+                    id, data
+                ));
+                self.output
+                    .push_str(&format!("<g clip-path=\"url(#{})\">\n", id));
+                for (lines, dashes) in families {
+                    let data = lines
+                        .iter()
+                        .map(|(start, end)| path_data(&[to_wcs(start), to_wcs(end)], false))
+                        .collect::<Vec<_>>()
+                        .join(" ");
+                    let stroke = Stroke {
+                        color: stroke.color.clone(),
+                        width: stroke.width,
+                        dashes,
+                    };
+                    self.output.push_str(&format!(
+                        "<path d=\"{}\"{}/>\n",
+                        data,
+                        stroke.attributes()
+                    ));
+                }
+                self.output.push_str("</g>\n");
+            }
+            None => self.output.push_str(&format!(
+                "<path d=\"{}\" fill=\"{}\" fill-rule=\"evenodd\" stroke=\"none\"/>\n",
+                data, stroke.color
+            )),
+        }
+    }
+    /// Returns the points of a closed hatch boundary in the hatch's object coordinate system.
+    fn hatch_boundary_points(&self, path: &HatchBoundaryPath) -> Vec<Point> {
+        let mut points = vec![];
+        let count = path.vertices.len();
+        for (i, vertex) in path.vertices.iter().enumerate() {
+            let next = &path.vertices[(i + 1) % count];
+            let segment = PolylineSegment::from_bulge(
+                Point::new(vertex.x, vertex.y, 0.0),
+                Point::new(next.x, next.y, 0.0),
+                vertex.bulge,
+            );
+            append_points(&mut points, segment.tessellate(self.chord_tolerance));
+        }
+        for edge in &path.edges {
+            let edge_points = match edge {
+                HatchBoundaryEdge::Line { start, end } => vec![start.clone(), end.clone()],
+                HatchBoundaryEdge::CircularArc {
+                    center,
+                    radius,
+                    start_angle,
+                    end_angle,
+                    is_counter_clockwise,
+                } => {
+                    let sweep = hatch_edge_sweep(*start_angle, *end_angle, *is_counter_clockwise);
+                    self.arc_points(
+                        center,
+                        &Vector::new(*radius, 0.0, 0.0),
+                        &Vector::new(0.0, *radius, 0.0),
+                        start_angle.to_radians(),
+                        sweep,
+                    )
+                }
+                HatchBoundaryEdge::EllipticArc {
+                    center,
+                    major_axis,
+                    minor_axis_ratio,
+                    start_angle,
+                    end_angle,
+                    is_counter_clockwise,
+                } => {
+                    let sweep = hatch_edge_sweep(*start_angle, *end_angle, *is_counter_clockwise);
+                    let minor_axis = Vector::new(
+                        -major_axis.y * minor_axis_ratio,
+                        major_axis.x * minor_axis_ratio,
+                        0.0,
+                    );
+                    self.arc_points(
+                        center,
+                        major_axis,
+                        &minor_axis,
+                        start_angle.to_radians(),
+                        sweep,
+                    )
+                }
+                HatchBoundaryEdge::Spline {
+                    degree,
+                    knot_values,
+                    control_points,
+                    weights,
+                    fit_points,
+                    start_tangent,
+                    end_tangent,
+                    ..
+                } => {
+                    let spline = Spline {
+                        degree_of_curve: *degree,
+                        knot_values: knot_values.clone(),
+                        control_points: control_points.clone(),
+                        weights: weights.clone(),
+                        fit_points: fit_points.clone(),
+                        start_tangent: Point::new(start_tangent.x, start_tangent.y, 0.0),
+                        end_tangent: Point::new(end_tangent.x, end_tangent.y, 0.0),
+                        ..Default::default()
+                    };
+                    spline.tessellate(self.chord_tolerance)
+                }
+            };
+            append_points(&mut points, edge_points);
+        }
+        points
+    }
+}
+
+/// Converts line type or hatch pattern dash lengths (positive for dashes, negative for spaces, and
+/// zero for dots) to an SVG dash array that starts with a dash, and the dash offset that keeps the
+/// pattern starting where it should.  Returns `None` for continuous lines.
+fn dash_array(lengths: &[f64], scale: f64) -> Option<(Vec<f64>, f64)> {
+    fn push(runs: &mut Vec<(bool, f64)>, is_dash: bool, length: f64) {
+        match runs.last_mut() {
+            Some(last) if last.0 == is_dash => last.1 += length,
+            _ => runs.push((is_dash, length)),
+        }
+    }
+
+    let mut runs = vec![];
+    for length in lengths {
+        push(&mut runs, *length >= 0.0, length.abs() * scale.abs());
+    }
+    let total = runs.iter().map(|r| r.1).sum::<f64>();
+    if runs.len() < 2 || total <= 0.0 {
+        return None;
+    }
+
+    let mut offset = 0.0;
+    if !runs[0].0 {
+        // move the leading space to the end
+        let space = runs.remove(0);
+        offset = total - space.1;
+        push(&mut runs, false, space.1);
+    }
+    if runs.len() % 2 == 1 {
+        // the pattern ends with a dash; join it with the first one
+        let (_, length) = runs.pop().unwrap();
+        runs[0].1 += length;
+        offset = (offset + length) % total;
+    }
+    if runs.len() < 2 {
+        return None;
+    }
+    Some((runs.iter().map(|r| r.1).collect(), offset))
+}
+
+/// Returns the lines (in the hatch's object coordinate system) and dash arrays of each pattern line
+/// family that covers `bounds`, or `None` if there is nothing to draw or there would be too many
+/// lines.
+#[allow(clippy::type_complexity)]
+fn hatch_pattern_lines(
+    definition_lines: &[HatchPatternDefinitionLine],
+    bounds: &BoundingBox,
+) -> Option<Vec<(Vec<(Point, Point)>, Option<(Vec<f64>, f64)>)>> {
+    let corners = [
+        (bounds.min.x, bounds.min.y),
+        (bounds.max.x, bounds.min.y),
+        (bounds.max.x, bounds.max.y),
+        (bounds.min.x, bounds.max.y),
+    ];
+    let mut families = vec![];
+    let mut line_count = 0;
+    for definition in definition_lines {
+        let (sin, cos) = definition.angle.to_radians().sin_cos();
+        let direction = (cos, sin);
+        let normal = (-sin, cos);
+        let spacing = definition.offset.x * normal.0 + definition.offset.y * normal.1;
+        if spacing.abs() < 1e-12 {
+            continue;
+        }
+        let shift = definition.offset.x * direction.0 + definition.offset.y * direction.1;
+        let base = &definition.base_point;
+        let project = |axis: (f64, f64)| {
+            let values = corners
+                .iter()
+                .map(|(x, y)| (x - base.x) * axis.0 + (y - base.y) * axis.1)
+                .collect::<Vec<_>>();
+            let min = values.iter().cloned().fold(f64::MAX, f64::min);
+            let max = values.iter().cloned().fold(f64::MIN, f64::max);
+            (min, max)
+        };
+        let (normal_min, normal_max) = project(normal);
+        let (along_min, along_max) = project(direction);
+        let (first, last) = if spacing > 0.0 {
+            (
+                (normal_min / spacing).ceil(),
+                (normal_max / spacing).floor(),
+            )
+        } else {
+            (
+                (normal_max / spacing).ceil(),
+                (normal_min / spacing).floor(),
+            )
+        };
+        if last < first {
+            continue;
+        }
+        line_count += (last - first) as usize + 1;
+        if line_count > MAX_HATCH_PATTERN_LINES {
+            return None;
+        }
+
+        let dashes = dash_array(&definition.dash_lengths, 1.0);
+        let pattern_length = definition.dash_lengths.iter().map(|d| d.abs()).sum::<f64>();
+        let mut lines = vec![];
+        let mut k = first;
+        while k <= last {
+            // start each line at a whole pattern repetition so the dashes line up
+            let origin = k * shift;
+            let start = if dashes.is_some() && pattern_length > 0.0 {
+                origin + ((along_min - origin) / pattern_length).floor() * pattern_length
+            } else {
+                along_min
+            };
+            let point_at = |along: f64| {
+                Point::new(
+                    base.x + normal.0 * k * spacing + direction.0 * along,
+                    base.y + normal.1 * k * spacing + direction.1 * along,
+                    0.0,
+                )
+            };
+            lines.push((point_at(start), point_at(along_max)));
+            k += 1.0;
+        }
+        families.push((lines, dashes));
+    }
+    if families.is_empty() {
+        None
+    } else {
+        Some(families)
+    }
+}
+
+/// Returns the signed sweep, in radians, of a hatch boundary arc; clockwise arcs run towards
+/// decreasing angles.
+fn hatch_edge_sweep(start: f64, end: f64, is_counter_clockwise: bool) -> f64 {
+    let sweep = if is_counter_clockwise {
+        end - start
+    } else {
+        start - end
+    };
+    let mut sweep = sweep % 360.0;
+    if sweep <= 0.0 {
+        sweep += 360.0;
+    }
+    if is_counter_clockwise {
+        sweep.to_radians()
+    } else {
+        -sweep.to_radians()
+    }
+}
+
+/// Appends `new_points` to `points`, skipping the first new point if it repeats the last one.
+fn append_points(points: &mut Vec<Point>, new_points: Vec<Point>) {
+    let mut new_points = new_points.into_iter();
+    if let Some(first) = new_points.next() {
+        if points.last() != Some(&first) {
+            points.push(first);
+        }
+    }
+    points.extend(new_points);
+}
+
+/// Returns the angle, in degrees, of the rotated X axis of an object coordinate system when
+/// projected onto the world XY plane.
+fn projected_angle(ocs_matrix: &TransformationMatrix, rotation: f64) -> f64 {
+    let (sin, cos) = rotation.to_radians().sin_cos();
+    let direction = ocs_matrix.transform_vector(&Vector::new(cos, sin, 0.0));
+    direction.y.atan2(direction.x).to_degrees()
+}
+
+/// Strips the formatting codes from `MText` and replaces paragraph breaks with `\n`.
+fn m_text_plain_text(text: &str) -> String {
+    decode_special_codes(&strip_m_text_formatting(text))
+}
+
+/// Replaces the `%%` control codes of text values with the characters they represent.
+fn decode_special_codes(text: &str) -> String {
+    let mut result = String::new();
+    let mut rest = text;
+    while let Some(index) = rest.find("%%") {
+        result.push_str(&rest[..index]);
+        let code = rest[index + 2..].chars().next();
+        match code {
+            Some('d') | Some('D') => result.push('\u{B0}'),
+            Some('p') | Some('P') => result.push('\u{B1}'),
+            Some('c') | Some('C') => result.push('\u{2300}'),
+            Some('%') => result.push('%'),
+            // underline and overline toggles
+            Some('u') | Some('U') | Some('o') | Some('O') => (),
+            Some(c) => {
+                result.push_str("%%");
+                result.push(c);
+            }
+            None => result.push_str("%%"),
+        }
+        rest = &rest[index + 2 + code.map_or(0, char::len_utf8)..];
+    }
+    result.push_str(rest);
+    result
+}
+
+fn path_data(points: &[Point], is_closed: bool) -> String {
+    let mut data = String::new();
+    for (i, p) in points.iter().enumerate() {
+        if i > 0 {
+            data.push(' ');
+        }
+        data.push(if i == 0 { 'M' } else { 'L' });
+        data.push_str(&format!("{} {}", num(p.x), num(-p.y)));
+    }
+    if is_closed {
+        data.push_str(" Z");
+    }
+    data
+}
+
+fn hex(color: (u8, u8, u8)) -> String {
+    format!("#{:02x}{:02x}{:02x}", color.0, color.1, color.2)
+}
+
+/// Formats a number with at most 6 decimal places and no trailing zeros.
+fn num(value: f64) -> String {
+    let mut text = format!("{:.6}", value);
+    if text.contains('.') {
+        while text.ends_with('0') {
+            text.pop();
+        }
+        if text.ends_with('.') {
+            text.pop();
+        }
+    }
+    if text == "-0" {
+        text = String::from("0");
+    }
+    text
+}
+
+fn escape(text: &str) -> String {
+    let mut result = String::new();
+    for c in text.chars() {
+        match c {
+            '&' => result.push_str("&amp;"),
+            '<' => result.push_str("&lt;"),
+            '>' => result.push_str("&gt;"),
+            '"' => result.push_str("&quot;"),
+            '\'' => result.push_str("&apos;"),
+            _ => result.push(c),
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::export::svg::*;
+    use crate::objects::*;
+    use crate::tables::*;
+    use crate::*;
+
+    fn render(drawing: &Drawing) -> String {
+        to_string(drawing, None, &SvgOptions::default())
+    }
+
+    fn assert_contains(svg: &str, expected: &str) {
+        assert!(
+            svg.contains(expected),
+            "expected to find\n{}\nin\n{}",
+            expected,
+            svg
+        );
+    }
+
+    #[test]
+    fn render_empty_drawing() {
+        let svg = render(&Drawing::new());
+        assert!(svg.starts_with("<?xml"));
+        assert_contains(&svg, "<svg xmlns=\"http://www.w3.org/2000/svg\"");
+        assert!(svg.ends_with("</svg>\n"));
+    }
+
+    #[test]
+    fn render_line_with_layer_color() {
+        let mut drawing = Drawing::new();
+        drawing.add_layer(Layer {
+            name: String::from("red"),
+            color: Color::from_index(1),
+            ..Default::default()
+        });
+        let mut line = Entity::new(EntityType::Line(Line::new(
+            Point::new(0.0, 0.0, 0.0),
+            Point::new(10.0, 5.0, 0.0),
+        )));
+        line.common.layer = String::from("red");
+        drawing.add_entity(line);
+        let svg = render(&drawing);
+        assert_contains(&svg, "viewBox=\"-0.2 -5.2 10.4 5.4\"");
+        assert_contains(&svg, "<path d=\"M0 0 L10 -5\" stroke=\"#ff0000\"");
+    }
+
+    #[test]
+    fn render_skips_layers_that_are_off() {
+        let mut drawing = Drawing::new();
+        drawing.add_layer(Layer {
+            name: String::from("off"),
+            is_layer_on: false,
+            ..Default::default()
+        });
+        let mut line = Entity::new(EntityType::Line(Line::default()));
+        line.common.layer = String::from("off");
+        drawing.add_entity(line);
+        assert!(!render(&drawing).contains("<path"));
+    }
+
+    #[test]
+    fn render_line_type_dashes() {
+        let mut drawing = Drawing::new();
+        drawing.add_line_type(LineType {
+            name: String::from("DASHDOT"),
+            dash_dot_space_lengths: vec![0.5, -0.25, 0.0, -0.25],
+            ..Default::default()
+        });
+        let mut line = Entity::new(EntityType::Line(Line::new(
+            Point::new(0.0, 0.0, 0.0),
+            Point::new(10.0, 0.0, 0.0),
+        )));
+        line.common.line_type_name = String::from("DASHDOT");
+        line.common.line_type_scale = 2.0;
+        drawing.add_entity(line);
+        assert_contains(&render(&drawing), "stroke-dasharray=\"1,0.5,0,0.5\"");
+    }
+
+    #[test]
+    fn dash_array_starting_with_a_space() {
+        assert_eq!(None, dash_array(&[], 1.0));
+        assert_eq!(None, dash_array(&[1.0, 2.0], 1.0));
+        assert_eq!(Some((vec![1.0, 2.0], 1.0)), dash_array(&[-2.0, 1.0], 1.0));
+        assert_eq!(
+            Some((vec![3.0, 1.0], 1.0)),
+            dash_array(&[2.0, -1.0, 1.0], 1.0)
+        );
+    }
+
+    #[test]
+    fn render_insert() {
+        let mut drawing = Drawing::new();
+        let mut block = Block {
+            name: String::from("b"),
+            ..Default::default()
+        };
+        let mut line = Entity::new(EntityType::Line(Line::new(
+            Point::new(0.0, 0.0, 0.0),
+            Point::new(1.0, 0.0, 0.0),
+        )));
+        line.common.color = Color::by_block();
+        block.entities.push(line);
+        drawing.add_block(block);
+        let mut insert = Entity::new(EntityType::Insert(Insert {
+            name: String::from("b"),
+            location: Point::new(5.0, 5.0, 0.0),
+            x_scale_factor: 2.0,
+            ..Default::default()
+        }));
+        insert.common.color = Color::from_index(3);
+        drawing.add_entity(insert);
+        assert_contains(
+            &render(&drawing),
+            "<path d=\"M5 -5 L7 -5\" stroke=\"#00ff00\"",
+        );
+    }
+
+    #[test]
+    fn render_dimension_from_block() {
+        let mut drawing = Drawing::new();
+        let mut block = Block {
+            name: String::from("*D1"),
+            ..Default::default()
+        };
+        block.entities.push(Entity::new(EntityType::Line(Line::new(
+            Point::new(1.0, 2.0, 0.0),
+            Point::new(3.0, 2.0, 0.0),
+        ))));
+        drawing.add_block(block);
+        let mut dim = RotatedDimension::default();
+        dim.dimension_base.block_name = String::from("*D1");
+        drawing.add_entity(Entity::new(EntityType::RotatedDimension(dim)));
+        assert_contains(&render(&drawing), "<path d=\"M1 -2 L3 -2\"");
+    }
+
+    #[test]
+    fn render_text_with_style_font() {
+        let mut drawing = Drawing::new();
+        drawing.add_style(Style {
+            name: String::from("fancy"),
+            primary_font_file_name: String::from("C:\\fonts\\Arial.ttf"),
+            ..Default::default()
+        });
+        let text = Text {
+            location: Point::new(1.0, 2.0, 0.0),
+            text_height: 0.5,
+            rotation: 90.0,
+            value: String::from("a < b %%d"),
+            text_style_name: String::from("fancy"),
+            ..Default::default()
+        };
+        drawing.add_entity(Entity::new(EntityType::Text(text)));
+        assert_contains(
+            &render(&drawing),
+            "<text transform=\"translate(1 -2) rotate(-90)\" font-family=\"'Arial', sans-serif\" font-size=\"0.5\" text-anchor=\"start\" fill=\"#000000\" stroke=\"none\">a &lt; b \u{B0}</text>",
+        );
+    }
+
+    #[test]
+    fn m_text_formatting_is_removed() {
+        assert_eq!(
+            "line 1\nbold 1/2",
+            m_text_plain_text("line 1\\P{\\fArial|b1;bold} \\S1^2;")
+        );
+    }
+
+    #[test]
+    fn render_solid_hatch() {
+        let mut drawing = Drawing::new();
+        let mut hatch = Hatch::default();
+        let mut path = HatchBoundaryPath::default();
+        path.set_is_polyline(true);
+        for (x, y) in &[(0.0, 0.0), (2.0, 0.0), (2.0, 2.0)] {
+            path.vertices.push(LwPolylineVertex {
+                x: *x,
+                y: *y,
+                ..Default::default()
+            });
+        }
+        hatch.boundary_paths.push(path);
+        let mut entity = Entity::new(EntityType::Hatch(hatch));
+        entity.common.color = Color::from_index(5);
+        drawing.add_entity(entity);
+        assert_contains(
+            &render(&drawing),
+            "<path d=\"M0 0 L2 0 L2 -2 L0 0 Z\" fill=\"#0000ff\" fill-rule=\"evenodd\"",
+        );
+    }
+
+    #[test]
+    fn render_pattern_hatch() {
+        let mut drawing = Drawing::new();
+        let mut hatch = Hatch {
+            is_solid_fill: false,
+            ..Default::default()
+        };
+        hatch
+            .pattern_definition_lines
+            .push(HatchPatternDefinitionLine {
+                angle: 0.0,
+                base_point: Point::origin(),
+                offset: Vector::new(0.0, 1.0, 0.0),
+                dash_lengths: vec![],
+            });
+        let mut path = HatchBoundaryPath::default();
+        path.edges.push(HatchBoundaryEdge::CircularArc {
+            center: Point::origin(),
+            radius: 2.5,
+            start_angle: 0.0,
+            end_angle: 360.0,
+            is_counter_clockwise: true,
+        });
+        hatch.boundary_paths.push(path);
+        drawing.add_entity(Entity::new(EntityType::Hatch(hatch)));
+        let svg = render(&drawing);
+        assert_contains(&svg, "<clipPath id=\"hatch0\">");
+        assert_contains(&svg, "<g clip-path=\"url(#hatch0)\">");
+        // one line for each of y = -2 through y = 2
+        assert_contains(
+            &svg,
+            "M-2.5 2 L2.5 2 M-2.5 1 L2.5 1 M-2.5 0 L2.5 0 M-2.5 -1 L2.5 -1 M-2.5 -2 L2.5 -2\"",
+        );
+    }
+
+    #[test]
+    fn render_paper_space_layout() {
+        let mut drawing = Drawing::new();
+        drawing.add_entity(Entity::new(EntityType::Line(Line::new(
+            Point::new(0.0, 0.0, 0.0),
+            Point::new(1.0, 1.0, 0.0),
+        ))));
+        let mut paper_line = Entity::new(EntityType::Line(Line::new(
+            Point::new(4.0, 4.0, 0.0),
+            Point::new(8.0, 4.0, 0.0),
+        )));
+        paper_line.common.is_in_paper_space = true;
+        drawing.add_entity(paper_line);
+
+        let layout = Layout {
+            layout_name: String::from("Layout1"),
+            ..Default::default()
+        };
+        let svg = to_string(&drawing, Some(&layout), &SvgOptions::default());
+        assert_contains(&svg, "M4 -4 L8 -4");
+        assert!(!svg.contains("M0 0 L1 -1"));
+
+        let svg = render(&drawing);
+        assert_contains(&svg, "M0 0 L1 -1");
+        assert!(!svg.contains("M4 -4 L8 -4"));
+    }
+}
@@ -0,0 +1,590 @@
+use std::io::{Read, Write};
+
+use crate::entities::Entity;
+use crate::enums::AcadVersion;
+use crate::helper_functions::*;
+use crate::{CodePair, Drawing, DrawingItem, DxfError, DxfResult, LwPolylineVertex, Point, Vector};
+
+use crate::code_pair_put_back::CodePairPutBack;
+use crate::code_pair_writer::CodePairWriter;
+
+/// Represents a single edge of a `HatchBoundaryPath` that is not a polyline.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+pub enum HatchBoundaryEdge {
+    Line {
+        start: Point,
+        end: Point,
+    },
+    CircularArc {
+        center: Point,
+        radius: f64,
+        start_angle: f64,
+        end_angle: f64,
+        is_counter_clockwise: bool,
+    },
+    EllipticArc {
+        center: Point,
+        /// The end point of the major axis, relative to the center.
+        major_axis: Vector,
+        minor_axis_ratio: f64,
+        start_angle: f64,
+        end_angle: f64,
+        is_counter_clockwise: bool,
+    },
+    Spline {
+        degree: i32,
+        is_rational: bool,
+        is_periodic: bool,
+        knot_values: Vec<f64>,
+        control_points: Vec<Point>,
+        /// Only present when the spline is rational.
+        weights: Vec<f64>,
+        fit_points: Vec<Point>,
+        start_tangent: Vector,
+        end_tangent: Vector,
+    },
+}
+
+/// Represents a single boundary path of a `Hatch`.  Depending on the flags, the path is either
+/// made up of `vertices` (polyline) or `edges`.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+pub struct HatchBoundaryPath {
+    pub flags: i32,
+    /// Only used when the path is a polyline.
+    pub is_closed: bool,
+    /// Only used when the path is a polyline.  Only the `x`, `y`, and `bulge` values are written.
+    pub vertices: Vec<LwPolylineVertex>,
+    /// Only used when the path is not a polyline.
+    pub edges: Vec<HatchBoundaryEdge>,
+    #[doc(hidden)]
+    pub __source_boundary_entities_handle: Vec<u32>,
+}
+
+// public implementation
+impl HatchBoundaryPath {
+    pub fn get_is_external(&self) -> bool {
+        self.flags & 1 != 0
+    }
+    pub fn set_is_external(&mut self, val: bool) {
+        self.set_flag(1, val);
+    }
+    pub fn get_is_polyline(&self) -> bool {
+        self.flags & 2 != 0
+    }
+    pub fn set_is_polyline(&mut self, val: bool) {
+        self.set_flag(2, val);
+    }
+    pub fn get_is_derived(&self) -> bool {
+        self.flags & 4 != 0
+    }
+    pub fn set_is_derived(&mut self, val: bool) {
+        self.set_flag(4, val);
+    }
+    pub fn get_is_text_box(&self) -> bool {
+        self.flags & 8 != 0
+    }
+    pub fn set_is_text_box(&mut self, val: bool) {
+        self.set_flag(8, val);
+    }
+    pub fn get_is_outermost(&self) -> bool {
+        self.flags & 16 != 0
+    }
+    pub fn set_is_outermost(&mut self, val: bool) {
+        self.set_flag(16, val);
+    }
+    pub fn get_source_boundary_entities<'a>(&self, drawing: &'a Drawing) -> Vec<&'a Entity> {
+        self.__source_boundary_entities_handle
+            .iter()
+            .filter_map(|&h| match drawing.get_item_by_handle(h) {
+                Some(DrawingItem::Entity(val)) => Some(val),
+                _ => None,
+            })
+            .collect()
+    }
+    pub fn add_source_boundary_entities(&mut self, item: &Entity) {
+        self.__source_boundary_entities_handle
+            .push(item.common.handle);
+    }
+}
+
+// internal visibility only
+impl HatchBoundaryPath {
+    pub(crate) fn read<I>(iter: &mut CodePairPutBack<I>) -> DxfResult<Option<HatchBoundaryPath>>
+    where
+        I: Read,
+    {
+        let mut path = HatchBoundaryPath::default();
+
+        // check the first pair; only code 92 can start one of these
+        match iter.next() {
+            Some(Ok(pair @ CodePair { code: 92, .. })) => {
+                path.flags = pair.assert_i32()?;
+            }
+            Some(Ok(pair)) => {
+                iter.put_back(Ok(pair));
+                return Ok(None);
+            }
+            Some(Err(e)) => return Err(e),
+            None => return Ok(None),
+        }
+
+        if path.get_is_polyline() {
+            path.read_polyline(iter)?;
+        } else {
+            path.read_edges(iter)?;
+        }
+
+        loop {
+            let pair = match iter.next() {
+                Some(Ok(pair)) => pair,
+                Some(Err(e)) => return Err(e),
+                None => break,
+            };
+            match pair.code {
+                97 => (), // source boundary count; implied by the number of 330 pairs
+                330 => {
+                    path.__source_boundary_entities_handle
+                        .push(pair.as_handle()?);
+                }
+                _ => {
+                    iter.put_back(Ok(pair));
+                    break;
+                }
+            }
+        }
+
+        Ok(Some(path))
+    }
+    pub(crate) fn write<T>(
+        &self,
+        version: AcadVersion,
+        writer: &mut CodePairWriter<T>,
+    ) -> DxfResult<()>
+    where
+        T: Write + ?Sized,
+    {
+        writer.write_code_pair(&CodePair::new_i32(92, self.flags))?;
+        if self.get_is_polyline() {
+            let has_bulge = self.vertices.iter().any(|v| v.bulge != 0.0);
+            writer.write_code_pair(&CodePair::new_i16(72, as_i16(has_bulge)))?;
+            writer.write_code_pair(&CodePair::new_i16(73, as_i16(self.is_closed)))?;
+            writer.write_code_pair(&CodePair::new_i32(93, self.vertices.len() as i32))?;
+            for v in &self.vertices {
+                writer.write_code_pair(&CodePair::new_f64(10, v.x))?;
+                writer.write_code_pair(&CodePair::new_f64(20, v.y))?;
+                if has_bulge {
+                    writer.write_code_pair(&CodePair::new_f64(42, v.bulge))?;
+                }
+            }
+        } else {
+            writer.write_code_pair(&CodePair::new_i32(93, self.edges.len() as i32))?;
+            for edge in &self.edges {
+                edge.write(version, writer)?;
+            }
+        }
+
+        writer.write_code_pair(&CodePair::new_i32(
+            97,
+            self.__source_boundary_entities_handle.len() as i32,
+        ))?;
+        for handle in &self.__source_boundary_entities_handle {
+            writer.write_code_pair(&CodePair::new_string(330, &as_handle(*handle)))?;
+        }
+
+        Ok(())
+    }
+}
+
+// private implementation
+impl HatchBoundaryPath {
+    fn set_flag(&mut self, mask: i32, val: bool) {
+        if val {
+            self.flags |= mask;
+        } else {
+            self.flags &= !mask;
+        }
+    }
+    fn read_polyline<I>(&mut self, iter: &mut CodePairPutBack<I>) -> DxfResult<()>
+    where
+        I: Read,
+    {
+        loop {
+            let pair = match iter.next() {
+                Some(Ok(pair)) => pair,
+                Some(Err(e)) => return Err(e),
+                None => return Ok(()),
+            };
+            match pair.code {
+                72 => (), // has bulge; implied by the vertex values
+                73 => {
+                    self.is_closed = as_bool(pair.assert_i16()?);
+                }
+                93 => (), // vertex count; implied by the number of vertices
+                10 => {
+                    // start a new vertex
+                    self.vertices.push(LwPolylineVertex::default());
+                    vec_last!(self.vertices).x = pair.assert_f64()?;
+                }
+                20 => {
+                    vec_last!(self.vertices).y = pair.assert_f64()?;
+                }
+                42 => {
+                    vec_last!(self.vertices).bulge = pair.assert_f64()?;
+                }
+                _ => {
+                    iter.put_back(Ok(pair));
+                    return Ok(());
+                }
+            }
+        }
+    }
+    fn read_edges<I>(&mut self, iter: &mut CodePairPutBack<I>) -> DxfResult<()>
+    where
+        I: Read,
+    {
+        loop {
+            let pair = match iter.next() {
+                Some(Ok(pair)) => pair,
+                Some(Err(e)) => return Err(e),
+                None => return Ok(()),
+            };
+            match pair.code {
+                93 => (), // edge count; implied by the number of edges
+                72 => {
+                    let edge = HatchBoundaryEdge::read(&pair, iter)?;
+                    self.edges.push(edge);
+                }
+                _ => {
+                    iter.put_back(Ok(pair));
+                    return Ok(());
+                }
+            }
+        }
+    }
+}
+
+// internal visibility only
+impl HatchBoundaryEdge {
+    pub(crate) fn read<I>(
+        edge_type_pair: &CodePair,
+        iter: &mut CodePairPutBack<I>,
+    ) -> DxfResult<HatchBoundaryEdge>
+    where
+        I: Read,
+    {
+        let mut edge = match edge_type_pair.assert_i16()? {
+            1 => HatchBoundaryEdge::Line {
+                start: Point::origin(),
+                end: Point::origin(),
+            },
+            2 => HatchBoundaryEdge::CircularArc {
+                center: Point::origin(),
+                radius: 0.0,
+                start_angle: 0.0,
+                end_angle: 360.0,
+                is_counter_clockwise: true,
+            },
+            3 => HatchBoundaryEdge::EllipticArc {
+                center: Point::origin(),
+                major_axis: Vector::x_axis(),
+                minor_axis_ratio: 1.0,
+                start_angle: 0.0,
+                end_angle: 360.0,
+                is_counter_clockwise: true,
+            },
+            4 => HatchBoundaryEdge::Spline {
+                degree: 3,
+                is_rational: false,
+                is_periodic: false,
+                knot_values: vec![],
+                control_points: vec![],
+                weights: vec![],
+                fit_points: vec![],
+                start_tangent: Vector::zero(),
+                end_tangent: Vector::zero(),
+            },
+            _ => return Err(DxfError::UnexpectedEnumValue(edge_type_pair.offset)),
+        };
+
+        // only the first 97 code belongs to a spline; a second one is the path's source boundary count
+        let mut seen_fit_point_count = false;
+        loop {
+            let pair = match iter.next() {
+                Some(Ok(pair)) => pair,
+                Some(Err(e)) => return Err(e),
+                None => return Ok(edge),
+            };
+            let handled = match edge {
+                HatchBoundaryEdge::Line {
+                    ref mut start,
+                    ref mut end,
+                } => match pair.code {
+                    10 => {
+                        start.x = pair.assert_f64()?;
+                        true
+                    }
+                    20 => {
+                        start.y = pair.assert_f64()?;
+                        true
+                    }
+                    11 => {
+                        end.x = pair.assert_f64()?;
+                        true
+                    }
+                    21 => {
+                        end.y = pair.assert_f64()?;
+                        true
+                    }
+                    _ => false,
+                },
+                HatchBoundaryEdge::CircularArc {
+                    ref mut center,
+                    ref mut radius,
+                    ref mut start_angle,
+                    ref mut end_angle,
+                    ref mut is_counter_clockwise,
+                } => match pair.code {
+                    10 => {
+                        center.x = pair.assert_f64()?;
+                        true
+                    }
+                    20 => {
+                        center.y = pair.assert_f64()?;
+                        true
+                    }
+                    40 => {
+                        *radius = pair.assert_f64()?;
+                        true
+                    }
+                    50 => {
+                        *start_angle = pair.assert_f64()?;
+                        true
+                    }
+                    51 => {
+                        *end_angle = pair.assert_f64()?;
+                        true
+                    }
+                    73 => {
+                        *is_counter_clockwise = as_bool(pair.assert_i16()?);
+                        true
+                    }
+                    _ => false,
+                },
+                HatchBoundaryEdge::EllipticArc {
+                    ref mut center,
+                    ref mut major_axis,
+                    ref mut minor_axis_ratio,
+                    ref mut start_angle,
+                    ref mut end_angle,
+                    ref mut is_counter_clockwise,
+                } => match pair.code {
+                    10 => {
+                        center.x = pair.assert_f64()?;
+                        true
+                    }
+                    20 => {
+                        center.y = pair.assert_f64()?;
+                        true
+                    }
+                    11 => {
+                        major_axis.x = pair.assert_f64()?;
+                        true
+                    }
+                    21 => {
+                        major_axis.y = pair.assert_f64()?;
+                        true
+                    }
+                    40 => {
+                        *minor_axis_ratio = pair.assert_f64()?;
+                        true
+                    }
+                    50 => {
+                        *start_angle = pair.assert_f64()?;
+                        true
+                    }
+                    51 => {
+                        *end_angle = pair.assert_f64()?;
+                        true
+                    }
+                    73 => {
+                        *is_counter_clockwise = as_bool(pair.assert_i16()?);
+                        true
+                    }
+                    _ => false,
+                },
+                HatchBoundaryEdge::Spline {
+                    ref mut degree,
+                    ref mut is_rational,
+                    ref mut is_periodic,
+                    ref mut knot_values,
+                    ref mut control_points,
+                    ref mut weights,
+                    ref mut fit_points,
+                    ref mut start_tangent,
+                    ref mut end_tangent,
+                } => match pair.code {
+                    94 => {
+                        *degree = pair.assert_i32()?;
+                        true
+                    }
+                    73 => {
+                        *is_rational = as_bool(pair.assert_i16()?);
+                        true
+                    }
+                    74 => {
+                        *is_periodic = as_bool(pair.assert_i16()?);
+                        true
+                    }
+                    95 | 96 => true, // knot and control point counts; implied by the values
+                    40 => {
+                        knot_values.push(pair.assert_f64()?);
+                        true
+                    }
+                    10 => {
+                        control_points.push(Point::new(pair.assert_f64()?, 0.0, 0.0));
+                        true
+                    }
+                    20 => {
+                        vec_last!(control_points).y = pair.assert_f64()?;
+                        true
+                    }
+                    42 => {
+                        weights.push(pair.assert_f64()?);
+                        true
+                    }
+                    97 if !seen_fit_point_count => {
+                        seen_fit_point_count = true;
+                        true
+                    }
+                    11 => {
+                        fit_points.push(Point::new(pair.assert_f64()?, 0.0, 0.0));
+                        true
+                    }
+                    21 => {
+                        vec_last!(fit_points).y = pair.assert_f64()?;
+                        true
+                    }
+                    12 => {
+                        start_tangent.x = pair.assert_f64()?;
+                        true
+                    }
+                    22 => {
+                        start_tangent.y = pair.assert_f64()?;
+                        true
+                    }
+                    13 => {
+                        end_tangent.x = pair.assert_f64()?;
+                        true
+                    }
+                    23 => {
+                        end_tangent.y = pair.assert_f64()?;
+                        true
+                    }
+                    _ => false,
+                },
+            };
+
+            if !handled {
+                // not part of this edge; put the pair back and return what we have
+                iter.put_back(Ok(pair));
+                return Ok(edge);
+            }
+        }
+    }
+    pub(crate) fn write<T>(
+        &self,
+        version: AcadVersion,
+        writer: &mut CodePairWriter<T>,
+    ) -> DxfResult<()>
+    where
+        T: Write + ?Sized,
+    {
+        match *self {
+            HatchBoundaryEdge::Line { ref start, ref end } => {
+                writer.write_code_pair(&CodePair::new_i16(72, 1))?;
+                writer.write_code_pair(&CodePair::new_f64(10, start.x))?;
+                writer.write_code_pair(&CodePair::new_f64(20, start.y))?;
+                writer.write_code_pair(&CodePair::new_f64(11, end.x))?;
+                writer.write_code_pair(&CodePair::new_f64(21, end.y))?;
+            }
+            HatchBoundaryEdge::CircularArc {
+                ref center,
+                radius,
+                start_angle,
+                end_angle,
+                is_counter_clockwise,
+            } => {
+                writer.write_code_pair(&CodePair::new_i16(72, 2))?;
+                writer.write_code_pair(&CodePair::new_f64(10, center.x))?;
+                writer.write_code_pair(&CodePair::new_f64(20, center.y))?;
+                writer.write_code_pair(&CodePair::new_f64(40, radius))?;
+                writer.write_code_pair(&CodePair::new_f64(50, start_angle))?;
+                writer.write_code_pair(&CodePair::new_f64(51, end_angle))?;
+                writer.write_code_pair(&CodePair::new_i16(73, as_i16(is_counter_clockwise)))?;
+            }
+            HatchBoundaryEdge::EllipticArc {
+                ref center,
+                ref major_axis,
+                minor_axis_ratio,
+                start_angle,
+                end_angle,
+                is_counter_clockwise,
+            } => {
+                writer.write_code_pair(&CodePair::new_i16(72, 3))?;
+                writer.write_code_pair(&CodePair::new_f64(10, center.x))?;
+                writer.write_code_pair(&CodePair::new_f64(20, center.y))?;
+                writer.write_code_pair(&CodePair::new_f64(11, major_axis.x))?;
+                writer.write_code_pair(&CodePair::new_f64(21, major_axis.y))?;
+                writer.write_code_pair(&CodePair::new_f64(40, minor_axis_ratio))?;
+                writer.write_code_pair(&CodePair::new_f64(50, start_angle))?;
+                writer.write_code_pair(&CodePair::new_f64(51, end_angle))?;
+                writer.write_code_pair(&CodePair::new_i16(73, as_i16(is_counter_clockwise)))?;
+            }
+            HatchBoundaryEdge::Spline {
+                degree,
+                is_rational,
+                is_periodic,
+                ref knot_values,
+                ref control_points,
+                ref weights,
+                ref fit_points,
+                ref start_tangent,
+                ref end_tangent,
+            } => {
+                writer.write_code_pair(&CodePair::new_i16(72, 4))?;
+                writer.write_code_pair(&CodePair::new_i32(94, degree))?;
+                writer.write_code_pair(&CodePair::new_i16(73, as_i16(is_rational)))?;
+                writer.write_code_pair(&CodePair::new_i16(74, as_i16(is_periodic)))?;
+                writer.write_code_pair(&CodePair::new_i32(95, knot_values.len() as i32))?;
+                writer.write_code_pair(&CodePair::new_i32(96, control_points.len() as i32))?;
+                for knot in knot_values {
+                    writer.write_code_pair(&CodePair::new_f64(40, *knot))?;
+                }
+                for (i, point) in control_points.iter().enumerate() {
+                    writer.write_code_pair(&CodePair::new_f64(10, point.x))?;
+                    writer.write_code_pair(&CodePair::new_f64(20, point.y))?;
+                    if is_rational && i < weights.len() {
+                        writer.write_code_pair(&CodePair::new_f64(42, weights[i]))?;
+                    }
+                }
+                if version >= AcadVersion::R2010 {
+                    writer.write_code_pair(&CodePair::new_i32(97, fit_points.len() as i32))?;
+                    for point in fit_points {
+                        writer.write_code_pair(&CodePair::new_f64(11, point.x))?;
+                        writer.write_code_pair(&CodePair::new_f64(21, point.y))?;
+                    }
+                    if !fit_points.is_empty() {
+                        writer.write_code_pair(&CodePair::new_f64(12, start_tangent.x))?;
+                        writer.write_code_pair(&CodePair::new_f64(22, start_tangent.y))?;
+                        writer.write_code_pair(&CodePair::new_f64(13, end_tangent.x))?;
+                        writer.write_code_pair(&CodePair::new_f64(23, end_tangent.y))?;
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -1,5 +1,5 @@
 use std::io;
-use std::io::Read;
+use std::io::{Read, Write};
 use std::time::Duration as StdDuration;
 
 extern crate byteorder;
@@ -17,6 +17,7 @@ use self::uuid::Uuid;
 
 use enum_primitive::FromPrimitive;
 
+use crate::code_pair_writer::CodePairWriter;
 use crate::enums::*;
 use crate::tables::Layer;
 use crate::{CodePair, Color, DxfError, DxfResult};
@@ -29,6 +30,41 @@ pub(crate) fn verify_code(pair: &CodePair, expected: i32) -> DxfResult<()> {
     }
 }
 
+/// Removes the formatting codes from an `MText` value, e.g., `{\fArial;text}`, and replaces
+/// paragraph and column breaks with `\n`.  `%%` control codes are left as-is.
+pub(crate) fn strip_m_text_formatting(text: &str) -> String {
+    let mut result = String::new();
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next() {
+                Some('P') | Some('X') => result.push('\n'),
+                Some('~') => result.push('\u{A0}'),
+                Some('S') => {
+                    // stacked text, e.g., `\S1^2;`
+                    for c in chars.by_ref().take_while(|c| *c != ';') {
+                        result.push(match c {
+                            '^' | '#' => '/',
+                            _ => c,
+                        });
+                    }
+                }
+                Some('A') | Some('C') | Some('F') | Some('f') | Some('H') | Some('Q')
+                | Some('T') | Some('W') | Some('c') | Some('p') => {
+                    // formatting with a value ending in `;`
+                    chars.by_ref().find(|c| *c == ';');
+                }
+                Some('L') | Some('l') | Some('O') | Some('o') | Some('K') | Some('k') => (),
+                Some(c) => result.push(c),
+                None => (),
+            },
+            '{' | '}' => (),
+            _ => result.push(c),
+        }
+    }
+    result
+}
+
 pub(crate) fn as_bool(v: i16) -> bool {
     v == 1
 }
@@ -276,6 +312,16 @@ pub(crate) fn read_color_value(layer: &mut Layer, color: i16) -> Color {
     Color::from_raw_value(color.abs())
 }
 
+/// Returns the alpha value, from `0` (fully transparent) to `255` (opaque), of a raw transparency
+/// value, or `None` if the transparency isn't explicit, e.g., BYLAYER or BYBLOCK.
+pub(crate) fn transparency_to_alpha(value: i32) -> Option<u8> {
+    if value & 0x0200_0000 != 0 {
+        Some((value & 0xFF) as u8)
+    } else {
+        None
+    }
+}
+
 pub(crate) fn read_line<T>(
     reader: &mut T,
     allow_bom: bool,
@@ -318,6 +364,82 @@ where
     Some(Ok(result))
 }
 
+/// Returns the indices of the handle (code 5) and the owner (the first code 330 outside of a 102
+/// group) that precede the first subclass marker in the code pairs of an unsupported entity or
+/// object.
+fn handle_and_owner_indices(pairs: &[CodePair]) -> DxfResult<(Option<usize>, Option<usize>)> {
+    let mut handle_index = None;
+    let mut owner_index = None;
+    let mut is_in_group = false;
+    for (i, pair) in pairs.iter().enumerate() {
+        match pair.code {
+            100 => break,
+            5 if handle_index.is_none() => handle_index = Some(i),
+            330 if owner_index.is_none() && !is_in_group => owner_index = Some(i),
+            102 => is_in_group = pair.assert_string()?.starts_with('{'),
+            _ => (),
+        }
+    }
+
+    Ok((handle_index, owner_index))
+}
+
+/// Returns the handle and owner of an unsupported entity or object as `(handle, owner)`.  The code
+/// pairs are left as they are.
+pub(crate) fn get_handle_and_owner(pairs: &[CodePair]) -> DxfResult<(u32, u32)> {
+    let (handle_index, owner_index) = handle_and_owner_indices(pairs)?;
+    let handle = match handle_index {
+        Some(i) => pairs[i].as_handle()?,
+        None => 0,
+    };
+    let owner = match owner_index {
+        Some(i) => pairs[i].as_handle()?,
+        None => 0,
+    };
+
+    Ok((handle, owner))
+}
+
+/// Writes the code pairs of an unsupported entity or object exactly as they were read, except that
+/// the values of its handle and owner pairs are replaced with `handle` and `owner`, if set.
+pub(crate) fn write_unknown_pairs<T>(
+    handle: u32,
+    owner: u32,
+    pairs: &[CodePair],
+    writer: &mut CodePairWriter<T>,
+) -> DxfResult<()>
+where
+    T: Write + ?Sized,
+{
+    let (handle_index, owner_index) = handle_and_owner_indices(pairs)?;
+    for (i, pair) in pairs.iter().enumerate() {
+        if Some(i) == handle_index && handle != 0 {
+            writer.write_code_pair(&CodePair::new_string(5, &as_handle(handle)))?;
+        } else if Some(i) == owner_index && owner != 0 {
+            writer.write_code_pair(&CodePair::new_string(330, &as_handle(owner)))?;
+        } else {
+            writer.write_verbatim_code_pair(pair)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Returns `true` if the first line of a file looks like a DWG version signature, e.g., `AC1015`.
+/// No DXF file can start this way.
+pub(crate) fn is_dwg_signature(first_line: &str) -> bool {
+    first_line.starts_with("AC1") || first_line.starts_with("AC2.")
+}
+
+#[test]
+fn is_dwg_signature_test() {
+    assert!(is_dwg_signature("AC1015\u{0}\u{0}"));
+    assert!(is_dwg_signature("AC2.10"));
+    assert!(!is_dwg_signature("  0"));
+    assert!(!is_dwg_signature("AutoCAD Binary DXF\r"));
+    assert!(!is_dwg_signature("AutoCAD DXB 1.0"));
+}
+
 pub(crate) fn read_u8<T: Read + ?Sized>(reader: &mut T) -> Option<io::Result<u8>> {
     let mut buf = [0];
     let size = match reader.read(&mut buf) {
@@ -1,4 +1,5 @@
-//! This crate provides the ability to read and write DXF and DXB CAD files.
+//! This crate provides the ability to read and write DXF and DXB CAD files.  DWG files are not
+//! supported and must first be converted to DXF.
 //!
 //! # Usage
 //!
@@ -127,6 +128,9 @@ extern crate serde_derive;
 #[cfg(feature = "serialize")]
 extern crate serde;
 
+mod bounding_box;
+pub use crate::bounding_box::BoundingBox;
+
 mod code_pair;
 pub use crate::code_pair::CodePair;
 
@@ -148,15 +152,38 @@ pub use crate::extension_data::*;
 
 mod x_data;
 pub use crate::x_data::*;
+#[cfg(feature = "serialize")]
+mod x_data_serde;
 
 mod table;
 
 mod drawing;
 pub use crate::drawing::Drawing;
 
+mod load_options;
+pub use crate::load_options::{LoadOptions, LoadWarning};
+
+mod save_options;
+pub use crate::save_options::{FloatFormat, LineEnding, SaveOptions};
+
+mod version_conversion;
+pub use crate::version_conversion::ConversionNote;
+
+mod dictionary_tree;
+pub use crate::dictionary_tree::DictionaryNode;
+
 mod drawing_item;
 pub use crate::drawing_item::{DrawingItem, DrawingItemMut};
 
+mod hatch_boundary_path;
+pub use crate::hatch_boundary_path::{HatchBoundaryEdge, HatchBoundaryPath};
+
+mod m_leader_context_data;
+pub use crate::m_leader_context_data::{
+    MLeaderBlockContent, MLeaderContent, MLeaderContextData, MLeaderLine, MLeaderMTextContent,
+    MLeaderRoot,
+};
+
 mod section_geometry_settings;
 pub use crate::section_geometry_settings::SectionGeometrySettings;
 
@@ -169,10 +196,17 @@ pub use crate::table_cell_style::TableCellStyle;
 mod transformation_matrix;
 pub use crate::transformation_matrix::TransformationMatrix;
 
+mod unknown_section;
+pub use crate::unknown_section::UnknownSection;
+
 pub mod enums;
 
+pub mod stream;
+
+pub mod export;
+
 mod color;
-pub use crate::color::Color;
+pub use crate::color::{Color, ResolvedColor};
 
 mod point;
 pub use crate::point::Point;
@@ -209,14 +243,25 @@ pub use crate::header::Header;
 mod line_weight;
 pub use crate::line_weight::LineWeight;
 
+mod audit;
+pub use crate::audit::{AuditIssue, AuditSeverity};
+mod effective_properties;
 mod entity;
-pub use crate::entity::LwPolylineVertex;
+mod entity_transform;
+pub use crate::effective_properties::{EffectiveProperties, InsertContext};
+mod polyline_segment;
+pub use crate::polyline_segment::PolylineSegment;
+mod spline;
+pub use crate::entity::{
+    HatchGradientColor, HatchPatternDefinitionLine, LwPolylineVertex, MLeaderArrowhead,
+    MLeaderBlockAttribute,
+};
 
 mod object;
 pub use crate::object::{GeoMeshPoint, MLineStyleElement};
 
 mod dxf_error;
-pub use crate::dxf_error::DxfError;
+pub use crate::dxf_error::{ContextFrame, DxfError, ErrorContext, FilePosition};
 
 mod dxf_result;
 pub use crate::dxf_result::DxfResult;
@@ -17,6 +17,10 @@ impl LineWeight {
     pub fn by_layer() -> LineWeight {
         LineWeight::from_raw_value(-2)
     }
+    /// Creates a new `LineWeight` that uses the application's default line weight.
+    pub fn standard() -> LineWeight {
+        LineWeight::from_raw_value(-3)
+    }
     /// Gets the raw value of the `LineWeight`.
     pub fn get_raw_value(&self) -> i16 {
         self.raw_value
@@ -29,4 +33,8 @@ impl LineWeight {
     pub fn is_by_layer(&self) -> bool {
         self.raw_value == -2
     }
+    /// Returns `true` if the `LineWeight` is the application's default line weight.
+    pub fn is_standard(&self) -> bool {
+        self.raw_value == -3
+    }
 }
@@ -0,0 +1,115 @@
+use std::fmt;
+
+use encoding_rs::Encoding;
+
+use crate::{DxfError, FilePosition};
+
+/// Options for reading a `Drawing`.  See `Drawing::load_with_options()`.
+///
+/// ```
+/// # use dxf::LoadOptions;
+/// // quickly read only the header and tables
+/// let options = LoadOptions::new().sections(&["HEADER", "TABLES"]);
+/// ```
+#[derive(Clone, Debug)]
+pub struct LoadOptions {
+    pub(crate) encoding: &'static Encoding,
+    pub(crate) is_lenient: bool,
+    pub(crate) sections: Option<Vec<String>>,
+    pub(crate) skipped_sections: Vec<String>,
+    pub(crate) normalize: bool,
+    pub(crate) assign_handles: bool,
+    pub(crate) max_entities: Option<usize>,
+}
+
+impl Default for LoadOptions {
+    fn default() -> Self {
+        LoadOptions {
+            encoding: encoding_rs::WINDOWS_1252,
+            is_lenient: false,
+            sections: None,
+            skipped_sections: vec![],
+            normalize: false,
+            assign_handles: true,
+            max_entities: None,
+        }
+    }
+}
+
+impl LoadOptions {
+    /// Creates options that read a drawing the same way as `Drawing::load()`.
+    pub fn new() -> Self {
+        Default::default()
+    }
+    /// Sets the text encoding used for strings.  The default is Windows-1252.
+    pub fn encoding(mut self, encoding: &'static Encoding) -> Self {
+        self.encoding = encoding;
+        self
+    }
+    /// When `true`, malformed entities, table entries, objects, and sections are skipped and
+    /// reported as `LoadWarning`s instead of failing the load.  Reading resumes at the next `0`
+    /// code pair.  The default is `false`.
+    pub fn lenient(mut self, is_lenient: bool) -> Self {
+        self.is_lenient = is_lenient;
+        self
+    }
+    /// Reads only the named sections, e.g., `HEADER` and `TABLES`; all others are skipped.
+    /// Reading stops as soon as all of the named sections have been read.  By default all
+    /// sections are read.
+    pub fn sections(mut self, names: &[&str]) -> Self {
+        self.sections = Some(names.iter().map(|n| n.to_uppercase()).collect());
+        self
+    }
+    /// Skips the named section, e.g., `OBJECTS` or `THUMBNAILIMAGE`.
+    pub fn skip_section(mut self, name: &str) -> Self {
+        self.skipped_sections.push(name.to_uppercase());
+        self
+    }
+    /// When `true`, `Drawing::normalize()` is called after the drawing is read.  The default is
+    /// `false`.
+    pub fn normalize(mut self, normalize: bool) -> Self {
+        self.normalize = normalize;
+        self
+    }
+    /// When `true`, items that don't specify a handle are given the next available handle as
+    /// they're read.  When `false`, their handle is left as `0`.  The default is `true`.
+    pub fn assign_handles(mut self, assign_handles: bool) -> Self {
+        self.assign_handles = assign_handles;
+        self
+    }
+    /// Reads at most `count` entities from the `ENTITIES` section; the rest are skipped.  Entities
+    /// in blocks aren't counted.  By default all entities are read.
+    pub fn max_entities(mut self, count: usize) -> Self {
+        self.max_entities = Some(count);
+        self
+    }
+    pub(crate) fn should_read_section(&self, name: &str) -> bool {
+        let is_requested = match self.sections {
+            Some(ref sections) => sections.iter().any(|s| s == name),
+            None => true,
+        };
+        is_requested && !self.skipped_sections.iter().any(|s| s == name)
+    }
+}
+
+/// A malformed part of a drawing that was skipped while reading with `LoadOptions::lenient()`.
+#[derive(Debug)]
+pub struct LoadWarning {
+    /// The position in the file where the error was found.
+    pub position: FilePosition,
+    /// What was skipped, e.g., `entity` or `LAYER table entry`.
+    pub skipped: String,
+    /// The error that caused the item to be skipped.
+    pub error: DxfError,
+}
+
+impl fmt::Display for LoadWarning {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(formatter, "skipped {} ", self.skipped)?;
+        match self.position {
+            FilePosition::Line(l) => write!(formatter, "at line {}", l)?,
+            FilePosition::ByteOffset(o) => write!(formatter, "at byte offset {}", o)?,
+        }
+        write!(formatter, ": {}", self.error)
+    }
+}
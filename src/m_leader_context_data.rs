@@ -0,0 +1,614 @@
+use std::io::{Read, Write};
+
+use crate::helper_functions::*;
+use crate::tables::{BlockRecord, Style};
+use crate::{
+    CodePair, Drawing, DrawingItem, DxfError, DxfResult, Point, TransformationMatrix, Vector,
+};
+
+use crate::code_pair_put_back::CodePairPutBack;
+use crate::code_pair_writer::CodePairWriter;
+
+/// The content displayed by an `MLeader`.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+pub enum MLeaderContent {
+    None,
+    MText(MLeaderMTextContent),
+    Block(MLeaderBlockContent),
+}
+
+/// The text content of an `MLeader`.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+pub struct MLeaderMTextContent {
+    pub text: String,
+    pub normal: Vector,
+    #[doc(hidden)]
+    pub __text_style_handle: u32,
+    pub location: Point,
+    pub direction: Vector,
+    pub rotation: f64,
+    pub boundary_width: f64,
+    pub boundary_height: f64,
+    pub line_spacing_factor: f64,
+    pub line_spacing_style: i16,
+    pub color: i32,
+    pub attachment: i16,
+    pub flow_direction: i16,
+    pub background_color: i32,
+    pub background_scale_factor: f64,
+    pub background_transparency: i32,
+    pub is_background_color_on: bool,
+    pub is_background_fill_on: bool,
+    pub column_type: i16,
+    pub use_text_height_auto: bool,
+    pub column_width: f64,
+    pub column_gutter_width: f64,
+    pub is_column_flow_reversed: bool,
+    pub column_sizes: Vec<f64>,
+    pub use_word_break: bool,
+}
+
+impl Default for MLeaderMTextContent {
+    fn default() -> Self {
+        MLeaderMTextContent {
+            text: String::new(),
+            normal: Vector::z_axis(),
+            __text_style_handle: 0,
+            location: Point::origin(),
+            direction: Vector::x_axis(),
+            rotation: 0.0,
+            boundary_width: 0.0,
+            boundary_height: 0.0,
+            line_spacing_factor: 1.0,
+            line_spacing_style: 1,
+            color: 0,
+            attachment: 1,
+            flow_direction: 1,
+            background_color: 0,
+            background_scale_factor: 1.5,
+            background_transparency: 0,
+            is_background_color_on: false,
+            is_background_fill_on: false,
+            column_type: 0,
+            use_text_height_auto: false,
+            column_width: 0.0,
+            column_gutter_width: 0.0,
+            is_column_flow_reversed: false,
+            column_sizes: vec![],
+            use_word_break: true,
+        }
+    }
+}
+
+/// The block content of an `MLeader`.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+pub struct MLeaderBlockContent {
+    #[doc(hidden)]
+    pub __block_record_handle: u32,
+    pub normal: Vector,
+    pub location: Point,
+    pub scale: Vector,
+    pub rotation: f64,
+    pub color: i32,
+    pub transformation_matrix: TransformationMatrix,
+}
+
+impl Default for MLeaderBlockContent {
+    fn default() -> Self {
+        MLeaderBlockContent {
+            __block_record_handle: 0,
+            normal: Vector::z_axis(),
+            location: Point::origin(),
+            scale: Vector::new(1.0, 1.0, 1.0),
+            rotation: 0.0,
+            color: 0,
+            transformation_matrix: TransformationMatrix::identity(),
+        }
+    }
+}
+
+/// A single leader line of an `MLeaderRoot`.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+pub struct MLeaderLine {
+    pub vertices: Vec<Point>,
+    pub leader_line_index: i32,
+}
+
+/// A leader root of an `MLeader`; the point where one or more leader lines connect to the content.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+pub struct MLeaderRoot {
+    pub has_set_last_leader_line_point: bool,
+    pub has_set_dogleg_vector: bool,
+    pub last_leader_line_point: Point,
+    pub dogleg_vector: Vector,
+    pub leader_branch_index: i32,
+    pub dogleg_length: f64,
+    pub lines: Vec<MLeaderLine>,
+    pub attachment_direction: i16,
+}
+
+impl Default for MLeaderRoot {
+    fn default() -> Self {
+        MLeaderRoot {
+            has_set_last_leader_line_point: true,
+            has_set_dogleg_vector: true,
+            last_leader_line_point: Point::origin(),
+            dogleg_vector: Vector::x_axis(),
+            leader_branch_index: 0,
+            dogleg_length: 0.0,
+            lines: vec![],
+            attachment_direction: 0,
+        }
+    }
+}
+
+/// The `CONTEXT_DATA{` group of an `MLeader`.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+pub struct MLeaderContextData {
+    pub scale: f64,
+    pub content_base_point: Point,
+    pub text_height: f64,
+    pub arrowhead_size: f64,
+    pub landing_gap: f64,
+    pub text_left_attachment_type: i16,
+    pub text_right_attachment_type: i16,
+    pub text_alignment_type: i16,
+    pub block_content_connection_type: i16,
+    pub content: MLeaderContent,
+    pub plane_origin: Point,
+    pub plane_x_axis: Vector,
+    pub plane_y_axis: Vector,
+    pub is_plane_normal_reversed: bool,
+    pub roots: Vec<MLeaderRoot>,
+    pub text_bottom_attachment_type: i16,
+    pub text_top_attachment_type: i16,
+}
+
+impl Default for MLeaderContextData {
+    fn default() -> Self {
+        MLeaderContextData {
+            scale: 1.0,
+            content_base_point: Point::origin(),
+            text_height: 0.0,
+            arrowhead_size: 0.0,
+            landing_gap: 0.0,
+            text_left_attachment_type: 1,
+            text_right_attachment_type: 1,
+            text_alignment_type: 0,
+            block_content_connection_type: 0,
+            content: MLeaderContent::None,
+            plane_origin: Point::origin(),
+            plane_x_axis: Vector::x_axis(),
+            plane_y_axis: Vector::y_axis(),
+            is_plane_normal_reversed: false,
+            roots: vec![],
+            text_bottom_attachment_type: 9,
+            text_top_attachment_type: 9,
+        }
+    }
+}
+
+// public implementation
+impl MLeaderMTextContent {
+    pub fn get_text_style<'a>(&self, drawing: &'a Drawing) -> Option<&'a Style> {
+        match drawing.get_item_by_handle(self.__text_style_handle) {
+            Some(DrawingItem::Style(val)) => Some(val),
+            _ => None,
+        }
+    }
+    pub fn set_text_style(&mut self, item: &Style) {
+        self.__text_style_handle = DrawingItem::Style(item).get_handle();
+    }
+}
+
+// public implementation
+impl MLeaderBlockContent {
+    pub fn get_block_record<'a>(&self, drawing: &'a Drawing) -> Option<&'a BlockRecord> {
+        match drawing.get_item_by_handle(self.__block_record_handle) {
+            Some(DrawingItem::BlockRecord(val)) => Some(val),
+            _ => None,
+        }
+    }
+    pub fn set_block_record(&mut self, item: &BlockRecord) {
+        self.__block_record_handle = DrawingItem::BlockRecord(item).get_handle();
+    }
+}
+
+// internal visibility only
+impl MLeaderContextData {
+    pub(crate) fn read<I>(iter: &mut CodePairPutBack<I>) -> DxfResult<MLeaderContextData>
+    where
+        I: Read,
+    {
+        let mut context = MLeaderContextData::default();
+        loop {
+            let pair = match iter.next() {
+                Some(Ok(pair @ CodePair { code: 0, .. })) => {
+                    // unexpected end; put the pair back and return what we have
+                    iter.put_back(Ok(pair));
+                    break;
+                }
+                Some(Ok(pair)) => pair,
+                Some(Err(e)) => return Err(e),
+                None => break,
+            };
+            match pair.code {
+                301 => break, // end of CONTEXT_DATA{
+                10 => context.content_base_point.x = pair.assert_f64()?,
+                20 => context.content_base_point.y = pair.assert_f64()?,
+                30 => context.content_base_point.z = pair.assert_f64()?,
+                40 => context.scale = pair.assert_f64()?,
+                41 => context.text_height = pair.assert_f64()?,
+                110 => context.plane_origin.x = pair.assert_f64()?,
+                120 => context.plane_origin.y = pair.assert_f64()?,
+                130 => context.plane_origin.z = pair.assert_f64()?,
+                111 => context.plane_x_axis.x = pair.assert_f64()?,
+                121 => context.plane_x_axis.y = pair.assert_f64()?,
+                131 => context.plane_x_axis.z = pair.assert_f64()?,
+                112 => context.plane_y_axis.x = pair.assert_f64()?,
+                122 => context.plane_y_axis.y = pair.assert_f64()?,
+                132 => context.plane_y_axis.z = pair.assert_f64()?,
+                140 => context.arrowhead_size = pair.assert_f64()?,
+                145 => context.landing_gap = pair.assert_f64()?,
+                174 => context.text_left_attachment_type = pair.assert_i16()?,
+                175 => context.text_right_attachment_type = pair.assert_i16()?,
+                176 => context.text_alignment_type = pair.assert_i16()?,
+                177 => context.block_content_connection_type = pair.assert_i16()?,
+                272 => context.text_bottom_attachment_type = pair.assert_i16()?,
+                273 => context.text_top_attachment_type = pair.assert_i16()?,
+                290 if pair.assert_bool()? => {
+                    context.content = MLeaderContent::MText(MLeaderMTextContent::read(iter)?);
+                }
+                296 if pair.assert_bool()? => {
+                    context.content = MLeaderContent::Block(MLeaderBlockContent::read(iter)?);
+                }
+                297 => context.is_plane_normal_reversed = pair.assert_bool()?,
+                302 => context.roots.push(MLeaderRoot::read(iter)?), // LEADER{
+                _ => (),                                             // unknown code, just ignore
+            }
+        }
+
+        Ok(context)
+    }
+    pub(crate) fn write<T>(&self, writer: &mut CodePairWriter<T>) -> DxfResult<()>
+    where
+        T: Write + ?Sized,
+    {
+        writer.write_code_pair(&CodePair::new_str(300, "CONTEXT_DATA{"))?;
+        writer.write_code_pair(&CodePair::new_f64(40, self.scale))?;
+        writer.write_code_pair(&CodePair::new_f64(10, self.content_base_point.x))?;
+        writer.write_code_pair(&CodePair::new_f64(20, self.content_base_point.y))?;
+        writer.write_code_pair(&CodePair::new_f64(30, self.content_base_point.z))?;
+        writer.write_code_pair(&CodePair::new_f64(41, self.text_height))?;
+        writer.write_code_pair(&CodePair::new_f64(140, self.arrowhead_size))?;
+        writer.write_code_pair(&CodePair::new_f64(145, self.landing_gap))?;
+        writer.write_code_pair(&CodePair::new_i16(174, self.text_left_attachment_type))?;
+        writer.write_code_pair(&CodePair::new_i16(175, self.text_right_attachment_type))?;
+        writer.write_code_pair(&CodePair::new_i16(176, self.text_alignment_type))?;
+        writer.write_code_pair(&CodePair::new_i16(177, self.block_content_connection_type))?;
+        match self.content {
+            MLeaderContent::MText(ref m_text) => {
+                writer.write_code_pair(&CodePair::new_bool(290, true))?;
+                m_text.write(writer)?;
+                writer.write_code_pair(&CodePair::new_bool(296, false))?;
+            }
+            MLeaderContent::Block(ref block) => {
+                writer.write_code_pair(&CodePair::new_bool(290, false))?;
+                writer.write_code_pair(&CodePair::new_bool(296, true))?;
+                block.write(writer)?;
+            }
+            MLeaderContent::None => {
+                writer.write_code_pair(&CodePair::new_bool(290, false))?;
+                writer.write_code_pair(&CodePair::new_bool(296, false))?;
+            }
+        }
+        writer.write_code_pair(&CodePair::new_f64(110, self.plane_origin.x))?;
+        writer.write_code_pair(&CodePair::new_f64(120, self.plane_origin.y))?;
+        writer.write_code_pair(&CodePair::new_f64(130, self.plane_origin.z))?;
+        writer.write_code_pair(&CodePair::new_f64(111, self.plane_x_axis.x))?;
+        writer.write_code_pair(&CodePair::new_f64(121, self.plane_x_axis.y))?;
+        writer.write_code_pair(&CodePair::new_f64(131, self.plane_x_axis.z))?;
+        writer.write_code_pair(&CodePair::new_f64(112, self.plane_y_axis.x))?;
+        writer.write_code_pair(&CodePair::new_f64(122, self.plane_y_axis.y))?;
+        writer.write_code_pair(&CodePair::new_f64(132, self.plane_y_axis.z))?;
+        writer.write_code_pair(&CodePair::new_bool(297, self.is_plane_normal_reversed))?;
+        for root in &self.roots {
+            root.write(writer)?;
+        }
+        writer.write_code_pair(&CodePair::new_i16(272, self.text_bottom_attachment_type))?;
+        writer.write_code_pair(&CodePair::new_i16(273, self.text_top_attachment_type))?;
+        writer.write_code_pair(&CodePair::new_str(301, "}"))?;
+        Ok(())
+    }
+}
+
+// internal visibility only
+impl MLeaderMTextContent {
+    pub(crate) fn read<I>(iter: &mut CodePairPutBack<I>) -> DxfResult<MLeaderMTextContent>
+    where
+        I: Read,
+    {
+        let mut m_text = MLeaderMTextContent::default();
+        loop {
+            let pair = match iter.next() {
+                Some(Ok(pair)) => pair,
+                Some(Err(e)) => return Err(e),
+                None => break,
+            };
+            match pair.code {
+                304 => m_text.text = pair.assert_string()?,
+                11 => m_text.normal.x = pair.assert_f64()?,
+                21 => m_text.normal.y = pair.assert_f64()?,
+                31 => m_text.normal.z = pair.assert_f64()?,
+                340 => m_text.__text_style_handle = pair.as_handle()?,
+                12 => m_text.location.x = pair.assert_f64()?,
+                22 => m_text.location.y = pair.assert_f64()?,
+                32 => m_text.location.z = pair.assert_f64()?,
+                13 => m_text.direction.x = pair.assert_f64()?,
+                23 => m_text.direction.y = pair.assert_f64()?,
+                33 => m_text.direction.z = pair.assert_f64()?,
+                42 => m_text.rotation = pair.assert_f64()?,
+                43 => m_text.boundary_width = pair.assert_f64()?,
+                44 => m_text.boundary_height = pair.assert_f64()?,
+                45 => m_text.line_spacing_factor = pair.assert_f64()?,
+                170 => m_text.line_spacing_style = pair.assert_i16()?,
+                90 => m_text.color = pair.assert_i32()?,
+                171 => m_text.attachment = pair.assert_i16()?,
+                172 => m_text.flow_direction = pair.assert_i16()?,
+                91 => m_text.background_color = pair.assert_i32()?,
+                141 => m_text.background_scale_factor = pair.assert_f64()?,
+                92 => m_text.background_transparency = pair.assert_i32()?,
+                291 => m_text.is_background_color_on = pair.assert_bool()?,
+                292 => m_text.is_background_fill_on = pair.assert_bool()?,
+                173 => m_text.column_type = pair.assert_i16()?,
+                293 => m_text.use_text_height_auto = pair.assert_bool()?,
+                142 => m_text.column_width = pair.assert_f64()?,
+                143 => m_text.column_gutter_width = pair.assert_f64()?,
+                294 => m_text.is_column_flow_reversed = pair.assert_bool()?,
+                144 => m_text.column_sizes.push(pair.assert_f64()?),
+                295 => m_text.use_word_break = pair.assert_bool()?,
+                _ => {
+                    // not part of the text content; put the pair back and return what we have
+                    iter.put_back(Ok(pair));
+                    break;
+                }
+            }
+        }
+
+        Ok(m_text)
+    }
+    pub(crate) fn write<T>(&self, writer: &mut CodePairWriter<T>) -> DxfResult<()>
+    where
+        T: Write + ?Sized,
+    {
+        writer.write_code_pair(&CodePair::new_string(304, &self.text))?;
+        writer.write_code_pair(&CodePair::new_f64(11, self.normal.x))?;
+        writer.write_code_pair(&CodePair::new_f64(21, self.normal.y))?;
+        writer.write_code_pair(&CodePair::new_f64(31, self.normal.z))?;
+        if self.__text_style_handle != 0 {
+            writer.write_code_pair(&CodePair::new_string(
+                340,
+                &as_handle(self.__text_style_handle),
+            ))?;
+        }
+        writer.write_code_pair(&CodePair::new_f64(12, self.location.x))?;
+        writer.write_code_pair(&CodePair::new_f64(22, self.location.y))?;
+        writer.write_code_pair(&CodePair::new_f64(32, self.location.z))?;
+        writer.write_code_pair(&CodePair::new_f64(13, self.direction.x))?;
+        writer.write_code_pair(&CodePair::new_f64(23, self.direction.y))?;
+        writer.write_code_pair(&CodePair::new_f64(33, self.direction.z))?;
+        writer.write_code_pair(&CodePair::new_f64(42, self.rotation))?;
+        writer.write_code_pair(&CodePair::new_f64(43, self.boundary_width))?;
+        writer.write_code_pair(&CodePair::new_f64(44, self.boundary_height))?;
+        writer.write_code_pair(&CodePair::new_f64(45, self.line_spacing_factor))?;
+        writer.write_code_pair(&CodePair::new_i16(170, self.line_spacing_style))?;
+        writer.write_code_pair(&CodePair::new_i32(90, self.color))?;
+        writer.write_code_pair(&CodePair::new_i16(171, self.attachment))?;
+        writer.write_code_pair(&CodePair::new_i16(172, self.flow_direction))?;
+        writer.write_code_pair(&CodePair::new_i32(91, self.background_color))?;
+        writer.write_code_pair(&CodePair::new_f64(141, self.background_scale_factor))?;
+        writer.write_code_pair(&CodePair::new_i32(92, self.background_transparency))?;
+        writer.write_code_pair(&CodePair::new_bool(291, self.is_background_color_on))?;
+        writer.write_code_pair(&CodePair::new_bool(292, self.is_background_fill_on))?;
+        writer.write_code_pair(&CodePair::new_i16(173, self.column_type))?;
+        writer.write_code_pair(&CodePair::new_bool(293, self.use_text_height_auto))?;
+        writer.write_code_pair(&CodePair::new_f64(142, self.column_width))?;
+        writer.write_code_pair(&CodePair::new_f64(143, self.column_gutter_width))?;
+        writer.write_code_pair(&CodePair::new_bool(294, self.is_column_flow_reversed))?;
+        for column_size in &self.column_sizes {
+            writer.write_code_pair(&CodePair::new_f64(144, *column_size))?;
+        }
+        writer.write_code_pair(&CodePair::new_bool(295, self.use_word_break))?;
+        Ok(())
+    }
+}
+
+// internal visibility only
+impl MLeaderBlockContent {
+    pub(crate) fn read<I>(iter: &mut CodePairPutBack<I>) -> DxfResult<MLeaderBlockContent>
+    where
+        I: Read,
+    {
+        let mut block = MLeaderBlockContent::default();
+        let mut matrix_values = vec![];
+        loop {
+            let pair = match iter.next() {
+                Some(Ok(pair)) => pair,
+                Some(Err(e)) => return Err(e),
+                None => break,
+            };
+            match pair.code {
+                341 => block.__block_record_handle = pair.as_handle()?,
+                14 => block.normal.x = pair.assert_f64()?,
+                24 => block.normal.y = pair.assert_f64()?,
+                34 => block.normal.z = pair.assert_f64()?,
+                15 => block.location.x = pair.assert_f64()?,
+                25 => block.location.y = pair.assert_f64()?,
+                35 => block.location.z = pair.assert_f64()?,
+                16 => block.scale.x = pair.assert_f64()?,
+                26 => block.scale.y = pair.assert_f64()?,
+                36 => block.scale.z = pair.assert_f64()?,
+                46 => block.rotation = pair.assert_f64()?,
+                93 => block.color = pair.assert_i32()?,
+                47 => matrix_values.push(pair.assert_f64()?),
+                _ => {
+                    // not part of the block content; put the pair back and return what we have
+                    iter.put_back(Ok(pair));
+                    break;
+                }
+            }
+        }
+
+        if !matrix_values.is_empty() {
+            block.transformation_matrix = TransformationMatrix::from_vec(&matrix_values);
+        }
+
+        Ok(block)
+    }
+    pub(crate) fn write<T>(&self, writer: &mut CodePairWriter<T>) -> DxfResult<()>
+    where
+        T: Write + ?Sized,
+    {
+        if self.__block_record_handle != 0 {
+            writer.write_code_pair(&CodePair::new_string(
+                341,
+                &as_handle(self.__block_record_handle),
+            ))?;
+        }
+        writer.write_code_pair(&CodePair::new_f64(14, self.normal.x))?;
+        writer.write_code_pair(&CodePair::new_f64(24, self.normal.y))?;
+        writer.write_code_pair(&CodePair::new_f64(34, self.normal.z))?;
+        writer.write_code_pair(&CodePair::new_f64(15, self.location.x))?;
+        writer.write_code_pair(&CodePair::new_f64(25, self.location.y))?;
+        writer.write_code_pair(&CodePair::new_f64(35, self.location.z))?;
+        writer.write_code_pair(&CodePair::new_f64(16, self.scale.x))?;
+        writer.write_code_pair(&CodePair::new_f64(26, self.scale.y))?;
+        writer.write_code_pair(&CodePair::new_f64(36, self.scale.z))?;
+        writer.write_code_pair(&CodePair::new_f64(46, self.rotation))?;
+        writer.write_code_pair(&CodePair::new_i32(93, self.color))?;
+        for value in self.transformation_matrix.get_values() {
+            writer.write_code_pair(&CodePair::new_f64(47, value))?;
+        }
+        Ok(())
+    }
+}
+
+// internal visibility only
+impl MLeaderRoot {
+    pub(crate) fn read<I>(iter: &mut CodePairPutBack<I>) -> DxfResult<MLeaderRoot>
+    where
+        I: Read,
+    {
+        let mut root = MLeaderRoot::default();
+        loop {
+            let pair = match iter.next() {
+                Some(Ok(pair @ CodePair { code: 0, .. })) => {
+                    // unexpected end; put the pair back and return what we have
+                    iter.put_back(Ok(pair));
+                    break;
+                }
+                Some(Ok(pair)) => pair,
+                Some(Err(e)) => return Err(e),
+                None => break,
+            };
+            match pair.code {
+                303 => break, // end of LEADER{
+                290 => root.has_set_last_leader_line_point = pair.assert_bool()?,
+                291 => root.has_set_dogleg_vector = pair.assert_bool()?,
+                10 => root.last_leader_line_point.x = pair.assert_f64()?,
+                20 => root.last_leader_line_point.y = pair.assert_f64()?,
+                30 => root.last_leader_line_point.z = pair.assert_f64()?,
+                11 => root.dogleg_vector.x = pair.assert_f64()?,
+                21 => root.dogleg_vector.y = pair.assert_f64()?,
+                31 => root.dogleg_vector.z = pair.assert_f64()?,
+                90 => root.leader_branch_index = pair.assert_i32()?,
+                40 => root.dogleg_length = pair.assert_f64()?,
+                271 => root.attachment_direction = pair.assert_i16()?,
+                304 => root.lines.push(MLeaderLine::read(iter)?), // LEADER_LINE{
+                _ => (),                                          // unknown code, just ignore
+            }
+        }
+
+        Ok(root)
+    }
+    pub(crate) fn write<T>(&self, writer: &mut CodePairWriter<T>) -> DxfResult<()>
+    where
+        T: Write + ?Sized,
+    {
+        writer.write_code_pair(&CodePair::new_str(302, "LEADER{"))?;
+        writer.write_code_pair(&CodePair::new_bool(
+            290,
+            self.has_set_last_leader_line_point,
+        ))?;
+        writer.write_code_pair(&CodePair::new_bool(291, self.has_set_dogleg_vector))?;
+        writer.write_code_pair(&CodePair::new_f64(10, self.last_leader_line_point.x))?;
+        writer.write_code_pair(&CodePair::new_f64(20, self.last_leader_line_point.y))?;
+        writer.write_code_pair(&CodePair::new_f64(30, self.last_leader_line_point.z))?;
+        writer.write_code_pair(&CodePair::new_f64(11, self.dogleg_vector.x))?;
+        writer.write_code_pair(&CodePair::new_f64(21, self.dogleg_vector.y))?;
+        writer.write_code_pair(&CodePair::new_f64(31, self.dogleg_vector.z))?;
+        writer.write_code_pair(&CodePair::new_i32(90, self.leader_branch_index))?;
+        writer.write_code_pair(&CodePair::new_f64(40, self.dogleg_length))?;
+        for line in &self.lines {
+            line.write(writer)?;
+        }
+        writer.write_code_pair(&CodePair::new_i16(271, self.attachment_direction))?;
+        writer.write_code_pair(&CodePair::new_str(303, "}"))?;
+        Ok(())
+    }
+}
+
+// internal visibility only
+impl MLeaderLine {
+    pub(crate) fn read<I>(iter: &mut CodePairPutBack<I>) -> DxfResult<MLeaderLine>
+    where
+        I: Read,
+    {
+        let mut line = MLeaderLine::default();
+        loop {
+            let pair = match iter.next() {
+                Some(Ok(pair @ CodePair { code: 0, .. })) => {
+                    // unexpected end; put the pair back and return what we have
+                    iter.put_back(Ok(pair));
+                    break;
+                }
+                Some(Ok(pair)) => pair,
+                Some(Err(e)) => return Err(e),
+                None => break,
+            };
+            match pair.code {
+                305 => break, // end of LEADER_LINE{
+                10 => {
+                    // start a new vertex
+                    line.vertices.push(Point::new(pair.assert_f64()?, 0.0, 0.0));
+                }
+                20 => vec_last!(line.vertices).y = pair.assert_f64()?,
+                30 => vec_last!(line.vertices).z = pair.assert_f64()?,
+                91 => line.leader_line_index = pair.assert_i32()?,
+                _ => (), // unknown code, just ignore
+            }
+        }
+
+        Ok(line)
+    }
+    pub(crate) fn write<T>(&self, writer: &mut CodePairWriter<T>) -> DxfResult<()>
+    where
+        T: Write + ?Sized,
+    {
+        writer.write_code_pair(&CodePair::new_str(304, "LEADER_LINE{"))?;
+        for vertex in &self.vertices {
+            writer.write_code_pair(&CodePair::new_f64(10, vertex.x))?;
+            writer.write_code_pair(&CodePair::new_f64(20, vertex.y))?;
+            writer.write_code_pair(&CodePair::new_f64(30, vertex.z))?;
+        }
+        writer.write_code_pair(&CodePair::new_i32(91, self.leader_line_index))?;
+        writer.write_code_pair(&CodePair::new_str(305, "}"))?;
+        Ok(())
+    }
+}
@@ -9,12 +9,13 @@ extern crate chrono;
 use self::chrono::Duration;
 
 use crate::{
-    CodePair, Color, DataTableValue, DxfError, DxfResult, Point, SectionTypeSettings,
-    TableCellStyle, TransformationMatrix,
+    CodePair, CodePairValue, Color, DataTableValue, Drawing, DrawingItem, DxfError, DxfResult,
+    Point, SectionTypeSettings, TableCellStyle, TransformationMatrix, Vector, XDataItem,
 };
 
 use crate::code_pair_put_back::CodePairPutBack;
 use crate::code_pair_writer::CodePairWriter;
+use crate::entities::Entity;
 use crate::enums::*;
 use crate::helper_functions::*;
 use crate::objects::*;
@@ -70,6 +71,44 @@ impl DataTable {
     }
 }
 
+//------------------------------------------------------------------------------
+//                                                                        Layout
+//------------------------------------------------------------------------------
+impl Layout {
+    /// Returns the entities drawn on this `Layout`.  The model space layout holds the model space
+    /// entities, the active paper space layout (the one owned by `*Paper_Space`) holds the paper
+    /// space entities of the `ENTITIES` section, and any other layout holds the entities of its
+    /// `*Paper_Space<n>` block.
+    pub fn entities<'a>(&self, drawing: &'a Drawing) -> Vec<&'a Entity> {
+        let model_space = || drawing.model_space().collect::<Vec<_>>();
+        let paper_space = || {
+            drawing
+                .entities()
+                .filter(|e| e.common.is_in_paper_space)
+                .collect::<Vec<_>>()
+        };
+        match self.get_table_record(drawing) {
+            Some(DrawingItem::BlockRecord(record)) => {
+                if record.name.eq_ignore_ascii_case("*Model_Space") {
+                    model_space()
+                } else if record.name.eq_ignore_ascii_case("*Paper_Space") {
+                    paper_space()
+                } else {
+                    match drawing
+                        .blocks()
+                        .find(|b| b.name.eq_ignore_ascii_case(&record.name))
+                    {
+                        Some(block) => block.entities.iter().collect(),
+                        None => vec![],
+                    }
+                }
+            }
+            _ if self.layout_name.eq_ignore_ascii_case("Model") => model_space(),
+            _ => paper_space(),
+        }
+    }
+}
+
 //------------------------------------------------------------------------------
 //                                                                    VbaProject
 //------------------------------------------------------------------------------
@@ -88,6 +127,124 @@ impl VbaProject {
     }
 }
 
+//------------------------------------------------------------------------------
+//                                                                 XRecordObject
+//------------------------------------------------------------------------------
+impl XRecordObject {
+    /// Returns the data as typed items.  Strings use code 1, `ControlGroup`s are delimited by
+    /// code 102 `{` and `}` pairs, layer names use code 8, binary data codes 310-319, handles
+    /// codes 320-369 and 390-399, points codes 10-18 with their Y and Z values, and numbers the
+    /// `XDataItem` of the same type, e.g., code 40 is a `Real` and code 70 an `Integer`.
+    pub fn items(&self) -> DxfResult<Vec<XDataItem>> {
+        let mut groups = vec![vec![]];
+        let mut index = 0;
+        while index < self.data_pairs.len() {
+            let pair = &self.data_pairs[index];
+            index += 1;
+            let item = match (pair.code, &pair.value) {
+                (102, CodePairValue::Str(ref s)) if s.starts_with('{') => {
+                    groups.push(vec![]);
+                    continue;
+                }
+                (102, _) => {
+                    let items = match groups.pop() {
+                        Some(items) if !groups.is_empty() => items,
+                        _ => {
+                            return Err(DxfError::UnexpectedXData(String::from(
+                                "unmatched 102 `}` code pair",
+                            )))
+                        }
+                    };
+                    XDataItem::ControlGroup(items)
+                }
+                (8, _) => XDataItem::LayerName(pair.assert_string()?),
+                (310..=319, _) => {
+                    let mut data = vec![];
+                    parse_hex_string(&pair.assert_string()?, &mut data, pair.offset)?;
+                    XDataItem::BinaryData(data)
+                }
+                (320..=369, _) | (390..=399, _) => XDataItem::Handle(pair.as_handle()?),
+                (10..=18, CodePairValue::Double(x)) => {
+                    // the Y and Z values are optional
+                    let mut values = [*x, 0.0, 0.0];
+                    for (i, value) in values.iter_mut().enumerate().skip(1) {
+                        match self.data_pairs.get(index) {
+                            Some(next) if next.code == pair.code + 10 * i as i32 => {
+                                *value = next.assert_f64()?;
+                                index += 1;
+                            }
+                            _ => break,
+                        }
+                    }
+                    let point = Point::new(values[0], values[1], values[2]);
+                    match pair.code {
+                        11 => XDataItem::WorldSpacePosition(point),
+                        12 => XDataItem::WorldSpaceDisplacement(point),
+                        13 => XDataItem::WorldDirection(Vector::new(point.x, point.y, point.z)),
+                        _ => XDataItem::ThreeReals(point.x, point.y, point.z),
+                    }
+                }
+                (41, CodePairValue::Double(f)) => XDataItem::Distance(*f),
+                (42, CodePairValue::Double(f)) => XDataItem::ScaleFactor(*f),
+                (_, CodePairValue::Double(f)) => XDataItem::Real(*f),
+                (_, CodePairValue::Boolean(i)) | (_, CodePairValue::Short(i)) => {
+                    XDataItem::Integer(*i)
+                }
+                (_, CodePairValue::Integer(i)) => XDataItem::Long(*i),
+                (_, CodePairValue::Long(i)) => XDataItem::Long(*i as i32),
+                (_, CodePairValue::Str(ref s)) => XDataItem::Str(s.clone()),
+            };
+            groups.last_mut().unwrap().push(item);
+        }
+
+        match groups.pop() {
+            Some(items) if groups.is_empty() => Ok(items),
+            _ => Err(DxfError::UnexpectedXData(String::from(
+                "missing 102 `}` code pair",
+            ))),
+        }
+    }
+    /// Replaces the data with the typed items, using the codes described in `items()`.
+    pub fn set_items(&mut self, items: &[XDataItem]) {
+        self.data_pairs.clear();
+        for item in items {
+            XRecordObject::add_item_pairs(item, &mut self.data_pairs);
+        }
+    }
+    fn add_item_pairs(item: &XDataItem, pairs: &mut Vec<CodePair>) {
+        fn add_point(code: i32, x: f64, y: f64, z: f64, pairs: &mut Vec<CodePair>) {
+            pairs.push(CodePair::new_f64(code, x));
+            pairs.push(CodePair::new_f64(code + 10, y));
+            pairs.push(CodePair::new_f64(code + 20, z));
+        }
+        match item {
+            XDataItem::Str(ref s) => pairs.push(CodePair::new_string(1, s)),
+            XDataItem::ControlGroup(ref items) => {
+                pairs.push(CodePair::new_str(102, "{"));
+                for item in items {
+                    XRecordObject::add_item_pairs(item, pairs);
+                }
+                pairs.push(CodePair::new_str(102, "}"));
+            }
+            XDataItem::LayerName(ref l) => pairs.push(CodePair::new_string(8, l)),
+            XDataItem::BinaryData(ref data) => {
+                let line = data.iter().map(|b| format!("{:02X}", b)).join("");
+                pairs.push(CodePair::new_string(310, &line));
+            }
+            XDataItem::Handle(h) => pairs.push(CodePair::new_string(340, &as_handle(*h))),
+            XDataItem::ThreeReals(x, y, z) => add_point(10, *x, *y, *z, pairs),
+            XDataItem::WorldSpacePosition(ref p) => add_point(11, p.x, p.y, p.z, pairs),
+            XDataItem::WorldSpaceDisplacement(ref p) => add_point(12, p.x, p.y, p.z, pairs),
+            XDataItem::WorldDirection(ref v) => add_point(13, v.x, v.y, v.z, pairs),
+            XDataItem::Real(f) => pairs.push(CodePair::new_f64(40, *f)),
+            XDataItem::Distance(f) => pairs.push(CodePair::new_f64(41, *f)),
+            XDataItem::ScaleFactor(f) => pairs.push(CodePair::new_f64(42, *f)),
+            XDataItem::Integer(i) => pairs.push(CodePair::new_i16(70, *i)),
+            XDataItem::Long(i) => pairs.push(CodePair::new_i32(90, *i)),
+        }
+    }
+}
+
 //------------------------------------------------------------------------------
 //                                                                  ObjectCommon
 //------------------------------------------------------------------------------
@@ -118,65 +275,74 @@ impl Object {
     where
         I: Read,
     {
-        loop {
-            match iter.next() {
-                // first code pair must be 0/object-type
-                Some(Ok(pair @ CodePair { code: 0, .. })) => {
-                    let type_string = pair.assert_string()?;
-                    if type_string == "ENDSEC" || type_string == "ENDBLK" {
-                        iter.put_back(Ok(pair));
-                        return Ok(None);
-                    }
-
-                    match ObjectType::from_type_string(&type_string) {
-                        Some(e) => {
-                            let mut obj = Object::new(e);
-                            if !obj.apply_custom_reader(iter)? {
-                                // no custom reader, use the auto-generated one
-                                loop {
-                                    match iter.next() {
-                                        Some(Ok(pair @ CodePair { code: 0, .. })) => {
-                                            // new object or ENDSEC
-                                            iter.put_back(Ok(pair));
-                                            break;
-                                        }
-                                        Some(Ok(pair)) => obj.apply_code_pair(&pair, iter)?,
-                                        Some(Err(e)) => return Err(e),
-                                        None => return Err(DxfError::UnexpectedEndOfInput),
+        match iter.next() {
+            // first code pair must be 0/object-type
+            Some(Ok(pair @ CodePair { code: 0, .. })) => {
+                let type_string = pair.assert_string()?;
+                if type_string == "ENDSEC" || type_string == "ENDBLK" {
+                    iter.put_back(Ok(pair));
+                    return Ok(None);
+                }
+
+                match ObjectType::from_type_string(&type_string) {
+                    Some(e) => {
+                        let mut obj = Object::new(e);
+                        if !obj.apply_custom_reader(iter)? {
+                            // no custom reader, use the auto-generated one
+                            loop {
+                                match iter.next() {
+                                    Some(Ok(pair @ CodePair { code: 0, .. })) => {
+                                        // new object or ENDSEC
+                                        iter.put_back(Ok(pair));
+                                        break;
                                     }
+                                    Some(Ok(pair)) => obj.apply_code_pair(&pair, iter)?,
+                                    Some(Err(e)) => return Err(e),
+                                    None => return Err(DxfError::UnexpectedEndOfInput),
                                 }
-
-                                obj.post_parse(pair.offset)?;
                             }
 
-                            return Ok(Some(obj));
+                            obj.post_parse(pair.offset)?;
                         }
-                        None => {
-                            // swallow unsupported object
+
+                        Ok(Some(obj))
+                    }
+                    None => {
+                        // preserve unsupported object so it can be written back as-is
+                        let mut common = ObjectCommon::default();
+                        let pairs = iter.read_verbatim(|iter| {
+                            let mut pairs = vec![];
                             loop {
                                 match iter.next() {
                                     Some(Ok(pair @ CodePair { code: 0, .. })) => {
                                         // found another object or ENDSEC
                                         iter.put_back(Ok(pair));
-                                        break;
+                                        return Ok(pairs);
                                     }
-                                    Some(Ok(_)) => (), // part of the unsupported object
+                                    Some(Ok(pair)) => pairs.push(pair),
                                     Some(Err(e)) => return Err(e),
                                     None => return Err(DxfError::UnexpectedEndOfInput),
                                 }
                             }
-                        }
+                        })?;
+
+                        let (handle, owner) = get_handle_and_owner(&pairs)?;
+                        common.handle = handle;
+                        common.__owner_handle = owner;
+
+                        Ok(Some(Object {
+                            common,
+                            specific: ObjectType::Unknown { type_string, pairs },
+                        }))
                     }
                 }
-                Some(Ok(pair)) => {
-                    return Err(DxfError::UnexpectedCodePair(
-                        pair,
-                        String::from("expected 0/object-type or 0/ENDSEC"),
-                    ))
-                }
-                Some(Err(e)) => return Err(e),
-                None => return Err(DxfError::UnexpectedEndOfInput),
             }
+            Some(Ok(pair)) => Err(DxfError::UnexpectedCodePair(
+                pair,
+                String::from("expected 0/object-type or 0/ENDSEC"),
+            )),
+            Some(Err(e)) => Err(e),
+            None => Err(DxfError::UnexpectedEndOfInput),
         }
     }
     fn apply_code_pair<I>(
@@ -1550,6 +1716,18 @@ impl Object {
     where
         T: Write + ?Sized,
     {
+        if let ObjectType::Unknown { ref pairs, .. } = self.specific {
+            // unknown objects are written back as they were read with their current handle and owner
+            writer.write_code_pair(&CodePair::new_str(0, self.specific.to_type_string()))?;
+            write_unknown_pairs(
+                self.common.handle,
+                self.common.__owner_handle,
+                pairs,
+                writer,
+            )?;
+            return Ok(());
+        }
+
         if self.specific.is_supported_on_version(version) {
             writer.write_code_pair(&CodePair::new_str(0, self.specific.to_type_string()))?;
             self.common.write(version, writer)?;
@@ -1812,7 +1990,18 @@ mod tests {
             .join("\r\n")
             .as_str(),
         );
-        assert_eq!(0, drawing.objects().count());
+        let objects = drawing.objects().collect::<Vec<_>>();
+        assert_eq!(1, objects.len());
+        match objects[0].specific {
+            ObjectType::Unknown {
+                ref type_string,
+                ref pairs,
+            } => {
+                assert_eq!("UNSUPPORTED_OBJECT", type_string);
+                assert_eq!(vec![CodePair::new_str(1, "unsupported string")], *pairs);
+            }
+            _ => panic!("expected an unknown object"),
+        }
     }
 
     #[test]
@@ -1840,17 +2029,99 @@ mod tests {
             .as_str(),
         );
         let objects = drawing.objects().collect::<Vec<_>>();
-        assert_eq!(2, objects.len());
+        assert_eq!(3, objects.len());
         match objects[0].specific {
             ObjectType::DictionaryVariable(_) => (),
             _ => panic!("expected a dictionary variable"),
         }
         match objects[1].specific {
+            ObjectType::Unknown { .. } => (),
+            _ => panic!("expected an unknown object"),
+        }
+        match objects[2].specific {
             ObjectType::ImageDefinition(_) => (),
             _ => panic!("expected an image definition"),
         }
     }
 
+    #[test]
+    fn write_unsupported_object_verbatim() {
+        let drawing = from_section(
+            "OBJECTS",
+            vec![
+                "0",
+                "UNSUPPORTED_OBJECT",
+                "5",
+                "A1",
+                "330",
+                "C",
+                "100",
+                "AcDbUnsupported",
+                "90",
+                "42",
+            ]
+            .join("\r\n")
+            .as_str(),
+        );
+        assert_eq!(0xA1, drawing.objects().next().unwrap().common.handle);
+        assert_contains(
+            &drawing,
+            vec![
+                "  0",
+                "UNSUPPORTED_OBJECT",
+                "  5",
+                "A1",
+                "330",
+                "C",
+                "100",
+                "AcDbUnsupported",
+                " 90",
+                "       42",
+                "  0",
+                "ENDSEC",
+            ]
+            .join("\r\n"),
+        );
+    }
+
+    #[test]
+    fn write_unsupported_object_with_current_handle_and_owner() {
+        let mut drawing = from_section(
+            "OBJECTS",
+            [
+                "0",
+                "UNSUPPORTED_OBJECT",
+                "5",
+                "A1",
+                "330",
+                "C",
+                "100",
+                "AcDbUnsupported",
+            ]
+            .join("\r\n")
+            .as_str(),
+        );
+        for object in drawing.objects_mut() {
+            assert_eq!(0xC, object.common.__owner_handle);
+            object.common.handle = 0xA2;
+            object.common.__owner_handle = 0xD;
+        }
+        assert_contains(
+            &drawing,
+            [
+                "  0",
+                "UNSUPPORTED_OBJECT",
+                "  5",
+                "A2",
+                "330",
+                "D",
+                "100",
+                "AcDbUnsupported",
+            ]
+            .join("\r\n"),
+        );
+    }
+
     #[test]
     fn read_common_object_fields() {
         let obj = read_object("IMAGEDEF", vec!["5", "DEADBEEF"].join("\r\n"));
@@ -2370,4 +2641,105 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn read_x_record_items() {
+        let obj = read_object(
+            "XRECORD",
+            vec![
+                "100",
+                "AcDbXrecord",
+                "280",
+                "1",
+                "  1",
+                "name",
+                "102",
+                "{",
+                " 10",
+                "1.0",
+                " 20",
+                "2.0",
+                " 40",
+                "3.0",
+                "102",
+                "}",
+                " 11",
+                "4.0",
+                " 21",
+                "5.0",
+                " 31",
+                "6.0",
+                " 70",
+                "7",
+                " 90",
+                "8",
+                "340",
+                "A",
+                "  8",
+                "layer",
+            ]
+            .join("\r\n"),
+        );
+        let xr = match obj.specific {
+            ObjectType::XRecordObject(ref xr) => xr,
+            _ => panic!("expected an XRECORD"),
+        };
+        assert_eq!(
+            vec![
+                XDataItem::Str(String::from("name")),
+                XDataItem::ControlGroup(vec![
+                    XDataItem::ThreeReals(1.0, 2.0, 0.0),
+                    XDataItem::Real(3.0),
+                ]),
+                XDataItem::WorldSpacePosition(Point::new(4.0, 5.0, 6.0)),
+                XDataItem::Integer(7),
+                XDataItem::Long(8),
+                XDataItem::Handle(0xA),
+                XDataItem::LayerName(String::from("layer")),
+            ],
+            xr.items().unwrap()
+        );
+    }
+
+    #[test]
+    fn read_x_record_items_with_unmatched_control_group() {
+        let xr = XRecordObject {
+            data_pairs: vec![CodePair::new_str(102, "{")],
+            ..Default::default()
+        };
+        assert!(xr.items().is_err());
+        let xr = XRecordObject {
+            data_pairs: vec![CodePair::new_str(102, "}")],
+            ..Default::default()
+        };
+        assert!(xr.items().is_err());
+    }
+
+    #[test]
+    fn x_record_items_round_trip() {
+        let items = vec![
+            XDataItem::Str(String::from("name")),
+            XDataItem::ControlGroup(vec![
+                XDataItem::BinaryData(vec![0x01, 0x23]),
+                XDataItem::Distance(1.5),
+                XDataItem::ScaleFactor(2.0),
+            ]),
+            XDataItem::WorldDirection(Vector::new(0.0, 0.0, 1.0)),
+            XDataItem::WorldSpaceDisplacement(Point::new(1.0, 2.0, 3.0)),
+        ];
+        let mut xr = XRecordObject::default();
+        xr.set_items(&items);
+        let mut drawing = Drawing::new();
+        drawing.header.version = AcadVersion::R2000;
+        drawing.add_object(Object::new(ObjectType::XRecordObject(xr)));
+        let drawing = parse_drawing(&to_test_string(&drawing));
+        let xr = drawing
+            .objects()
+            .find_map(|o| match o.specific {
+                ObjectType::XRecordObject(ref xr) => Some(xr),
+                _ => None,
+            })
+            .unwrap();
+        assert_eq!(items, xr.items().unwrap());
+    }
 }
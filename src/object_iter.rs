@@ -11,9 +11,18 @@ impl<'a, I: 'a + Read> Iterator for ObjectIter<'a, I> {
     type Item = Object;
 
     fn next(&mut self) -> Option<Object> {
-        match Object::read(self.iter) {
-            Ok(Some(o)) => Some(o),
-            Ok(None) | Err(_) => None,
+        loop {
+            match Object::read(self.iter) {
+                Ok(Some(o)) => return Some(o),
+                Ok(None) => return None,
+                Err(e) => {
+                    if let Err(e) = self.iter.recover(e, "object", None) {
+                        // leave the error for the caller to report
+                        self.iter.put_back(Err(e));
+                        return None;
+                    }
+                }
+            }
         }
     }
 }
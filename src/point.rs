@@ -1,4 +1,4 @@
-use crate::{CodePair, DxfError, DxfResult};
+use crate::{CodePair, DxfError, DxfResult, Vector};
 
 /// Represents a simple point in Cartesian space.
 #[derive(Clone, Debug, Default, PartialEq)]
@@ -21,6 +21,10 @@ impl Point {
     pub fn origin() -> Point {
         Point::new(0.0, 0.0, 0.0)
     }
+    /// Returns this point moved by `vector`.
+    pub fn offset(&self, vector: &Vector) -> Point {
+        Point::new(self.x + vector.x, self.y + vector.y, self.z + vector.z)
+    }
     pub(crate) fn set(&mut self, pair: &CodePair) -> DxfResult<()> {
         match pair.code {
             10 => self.x = pair.assert_f64()?,
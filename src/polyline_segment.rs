@@ -0,0 +1,569 @@
+use crate::entities::{LwPolyline, Polyline};
+use crate::Point;
+
+/// Represents a single segment of a `LwPolyline` or `Polyline`.  Points are in the polyline's
+/// object coordinate system, except for 3D polylines which use world coordinates.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+pub enum PolylineSegment {
+    Line {
+        start: Point,
+        end: Point,
+    },
+    Arc {
+        start: Point,
+        end: Point,
+        center: Point,
+        radius: f64,
+        /// The angle of `start` around `center`, in degrees.
+        start_angle: f64,
+        /// The angle swept from `start` to `end`, in degrees.  Positive values are
+        /// counter-clockwise.
+        sweep_angle: f64,
+    },
+}
+
+impl PolylineSegment {
+    /// Creates the segment between `start` and `end` for the given bulge, the tangent of one
+    /// quarter of the arc's sweep angle.  A bulge of `0.0` (or coincident points) gives a line.
+    pub fn from_bulge(start: Point, end: Point, bulge: f64) -> PolylineSegment {
+        let dx = end.x - start.x;
+        let dy = end.y - start.y;
+        let chord = (dx * dx + dy * dy).sqrt();
+        if bulge == 0.0 || chord == 0.0 {
+            return PolylineSegment::Line { start, end };
+        }
+
+        // the center is offset from the middle of the chord along its left-hand normal
+        let offset = (1.0 - bulge * bulge) / (4.0 * bulge);
+        let center = Point::new(
+            (start.x + end.x) / 2.0 - dy * offset,
+            (start.y + end.y) / 2.0 + dx * offset,
+            start.z,
+        );
+        let radius = chord * (1.0 + bulge * bulge) / (4.0 * bulge.abs());
+        let start_angle = (start.y - center.y).atan2(start.x - center.x).to_degrees();
+        let sweep_angle = (4.0 * bulge.atan()).to_degrees();
+        PolylineSegment::Arc {
+            start,
+            end,
+            center,
+            radius,
+            start_angle,
+            sweep_angle,
+        }
+    }
+    pub fn start(&self) -> &Point {
+        match self {
+            PolylineSegment::Line { start, .. } | PolylineSegment::Arc { start, .. } => start,
+        }
+    }
+    pub fn end(&self) -> &Point {
+        match self {
+            PolylineSegment::Line { end, .. } | PolylineSegment::Arc { end, .. } => end,
+        }
+    }
+    pub fn length(&self) -> f64 {
+        match self {
+            PolylineSegment::Line { start, end } => distance(start, end),
+            PolylineSegment::Arc {
+                radius,
+                sweep_angle,
+                ..
+            } => radius * sweep_angle.to_radians().abs(),
+        }
+    }
+    /// Returns the point `distance` along the segment from its start, clamped to the segment.
+    pub fn point_at_distance(&self, distance: f64) -> Point {
+        let length = self.length();
+        let fraction = if length == 0.0 {
+            0.0
+        } else {
+            (distance / length).clamp(0.0, 1.0)
+        };
+        match self {
+            PolylineSegment::Line { start, end } => Point::new(
+                start.x + (end.x - start.x) * fraction,
+                start.y + (end.y - start.y) * fraction,
+                start.z + (end.z - start.z) * fraction,
+            ),
+            PolylineSegment::Arc {
+                start,
+                end,
+                center,
+                radius,
+                start_angle,
+                sweep_angle,
+            } => {
+                let angle = (start_angle + sweep_angle * fraction).to_radians();
+                Point::new(
+                    center.x + radius * angle.cos(),
+                    center.y + radius * angle.sin(),
+                    start.z + (end.z - start.z) * fraction,
+                )
+            }
+        }
+    }
+    /// Approximates the segment with points, starting with `start()` and ending with `end()`, such
+    /// that the chords deviate from an arc by no more than `chord_tolerance`.
+    pub fn tessellate(&self, chord_tolerance: f64) -> Vec<Point> {
+        match self {
+            PolylineSegment::Line { start, end } => vec![start.clone(), end.clone()],
+            PolylineSegment::Arc {
+                end,
+                radius,
+                sweep_angle,
+                ..
+            } => {
+                let sweep = sweep_angle.to_radians().abs();
+                let ratio = (1.0 - chord_tolerance.abs() / radius).max(-1.0);
+                let max_step = 2.0 * ratio.acos();
+                let pieces = if max_step > 0.0 {
+                    (sweep / max_step).ceil().max(1.0).min(u16::MAX as f64) as usize
+                } else {
+                    u16::MAX as usize
+                };
+                let length = self.length();
+                let mut points = (0..pieces)
+                    .map(|i| self.point_at_distance(length * i as f64 / pieces as f64))
+                    .collect::<Vec<_>>();
+                points.push(end.clone());
+                points
+            }
+        }
+    }
+    /// The signed area between the segment and the origin; summed over a closed path this gives the
+    /// enclosed area.
+    fn signed_area(&self) -> f64 {
+        let start = self.start();
+        let end = self.end();
+        let triangle = (start.x * end.y - end.x * start.y) / 2.0;
+        match self {
+            PolylineSegment::Line { .. } => triangle,
+            PolylineSegment::Arc {
+                radius,
+                sweep_angle,
+                ..
+            } => {
+                // add the circular segment between the chord and the arc
+                let sweep = sweep_angle.to_radians();
+                triangle + radius * radius * (sweep - sweep.sin()) / 2.0
+            }
+        }
+    }
+}
+
+//------------------------------------------------------------------------------
+//                                                                    LwPolyline
+//------------------------------------------------------------------------------
+impl LwPolyline {
+    /// Returns the segments between consecutive vertices, including the closing segment if the
+    /// polyline is closed.  The points are in the object coordinate system with a `z` value of
+    /// `0.0`; the elevation is stored with the entity's common values.
+    pub fn segments(&self) -> impl Iterator<Item = PolylineSegment> {
+        let vertices = self
+            .vertices
+            .iter()
+            .map(|v| (Point::new(v.x, v.y, 0.0), v.bulge))
+            .collect();
+        segments_from_vertices(vertices, self.get_is_closed()).into_iter()
+    }
+    /// Returns the total length of the `segments()`.
+    pub fn length(&self) -> f64 {
+        self.segments().map(|s| s.length()).sum()
+    }
+    /// Returns the signed area enclosed by the polyline; positive when the vertices run
+    /// counter-clockwise.  An open polyline is treated as if closed by a straight line.
+    pub fn area(&self) -> f64 {
+        area(self.segments())
+    }
+    /// Returns the point `distance` along the polyline from its first vertex, or `None` if
+    /// `distance` is negative or greater than the `length()`.
+    pub fn point_at_distance(&self, distance: f64) -> Option<Point> {
+        point_at_distance(self.segments(), distance)
+    }
+    /// Approximates the polyline with straight lines, replacing each arc with chords that deviate
+    /// from it by no more than `chord_tolerance`.  A closed polyline ends with its first point.
+    pub fn tessellate(&self, chord_tolerance: f64) -> Vec<Point> {
+        tessellate(self.segments(), chord_tolerance)
+    }
+}
+
+//------------------------------------------------------------------------------
+//                                                                      Polyline
+//------------------------------------------------------------------------------
+impl Polyline {
+    /// Returns the segments between consecutive vertices, including the closing segment if the
+    /// polyline is closed.  The frame control points of spline-fit polylines are skipped and
+    /// polygon and polyface meshes have no segments.  Bulges are only honored for 2D polylines,
+    /// whose segments are in the object coordinate system with `z` set to the elevation.
+    pub fn segments(&self) -> impl Iterator<Item = PolylineSegment> {
+        let vertices = if self.get_is_3d_polygon_mesh() || self.get_is_polyface_mesh() {
+            vec![]
+        } else {
+            let is_2d = self.is_2d();
+            self.vertices()
+                .filter(|v| !v.get_is_spline_frame_control_point())
+                .map(|v| {
+                    if is_2d {
+                        let location = Point::new(v.location.x, v.location.y, self.location.z);
+                        (location, v.bulge)
+                    } else {
+                        (v.location.clone(), 0.0)
+                    }
+                })
+                .collect()
+        };
+        segments_from_vertices(vertices, self.get_is_closed()).into_iter()
+    }
+    /// Returns the total length of the `segments()`.
+    pub fn length(&self) -> f64 {
+        self.segments().map(|s| s.length()).sum()
+    }
+    /// Returns the signed area enclosed by the polyline, projected onto its XY plane; positive
+    /// when the vertices run counter-clockwise.  An open polyline is treated as if closed by a
+    /// straight line.
+    pub fn area(&self) -> f64 {
+        area(self.segments())
+    }
+    /// Returns the point `distance` along the polyline from its first vertex, or `None` if
+    /// `distance` is negative or greater than the `length()`.
+    pub fn point_at_distance(&self, distance: f64) -> Option<Point> {
+        point_at_distance(self.segments(), distance)
+    }
+    /// Approximates the polyline with straight lines, replacing each arc with chords that deviate
+    /// from it by no more than `chord_tolerance`.  A closed polyline ends with its first point.
+    pub fn tessellate(&self, chord_tolerance: f64) -> Vec<Point> {
+        tessellate(self.segments(), chord_tolerance)
+    }
+}
+
+fn segments_from_vertices(vertices: Vec<(Point, f64)>, is_closed: bool) -> Vec<PolylineSegment> {
+    let count = vertices.len();
+    let segment_count = if is_closed && count > 1 {
+        count
+    } else {
+        count.saturating_sub(1)
+    };
+    (0..segment_count)
+        .map(|i| {
+            let (start, bulge) = &vertices[i];
+            let (end, _) = &vertices[(i + 1) % count];
+            PolylineSegment::from_bulge(start.clone(), end.clone(), *bulge)
+        })
+        .collect()
+}
+
+fn area<I>(segments: I) -> f64
+where
+    I: Iterator<Item = PolylineSegment>,
+{
+    let mut first = None;
+    let mut last = None;
+    let mut area = 0.0;
+    for segment in segments {
+        area += segment.signed_area();
+        if first.is_none() {
+            first = Some(segment.start().clone());
+        }
+        last = Some(segment.end().clone());
+    }
+    if let (Some(first), Some(last)) = (first, last) {
+        // close the path with a straight line; this is zero for closed polylines
+        area += (last.x * first.y - first.x * last.y) / 2.0;
+    }
+    area
+}
+
+fn point_at_distance<I>(segments: I, distance: f64) -> Option<Point>
+where
+    I: Iterator<Item = PolylineSegment>,
+{
+    if distance < 0.0 {
+        return None;
+    }
+    let mut remaining = distance;
+    let mut last = None;
+    for segment in segments {
+        let length = segment.length();
+        if remaining <= length {
+            return Some(segment.point_at_distance(remaining));
+        }
+        remaining -= length;
+        last = Some(segment);
+    }
+
+    // allow for rounding when asking for the very end of the polyline
+    match last {
+        Some(ref segment) if remaining <= 1e-9 * distance.max(1.0) => Some(segment.end().clone()),
+        _ => None,
+    }
+}
+
+fn tessellate<I>(segments: I, chord_tolerance: f64) -> Vec<Point>
+where
+    I: Iterator<Item = PolylineSegment>,
+{
+    let mut points = vec![];
+    for segment in segments {
+        let segment_points = segment.tessellate(chord_tolerance);
+        let skip = if points.is_empty() { 0 } else { 1 };
+        points.extend(segment_points.into_iter().skip(skip));
+    }
+    points
+}
+
+fn distance(a: &Point, b: &Point) -> f64 {
+    let dx = b.x - a.x;
+    let dy = b.y - a.y;
+    let dz = b.z - a.z;
+    (dx * dx + dy * dy + dz * dz).sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::PI;
+
+    use crate::entities::*;
+    use crate::*;
+
+    fn assert_close(expected: f64, actual: f64) {
+        assert!(
+            (expected - actual).abs() < 1e-9,
+            "expected {} but was {}",
+            expected,
+            actual
+        );
+    }
+
+    fn assert_point(expected: Point, actual: Point) {
+        assert!(
+            (expected.x - actual.x).abs() < 1e-9
+                && (expected.y - actual.y).abs() < 1e-9
+                && (expected.z - actual.z).abs() < 1e-9,
+            "expected {:?} but was {:?}",
+            expected,
+            actual
+        );
+    }
+
+    fn lw_polyline(vertices: &[(f64, f64, f64)], is_closed: bool) -> LwPolyline {
+        let mut poly = LwPolyline::default();
+        poly.set_is_closed(is_closed);
+        for (x, y, bulge) in vertices {
+            poly.vertices.push(LwPolylineVertex {
+                x: *x,
+                y: *y,
+                bulge: *bulge,
+                ..Default::default()
+            });
+        }
+        poly
+    }
+
+    #[test]
+    fn segment_from_bulge() {
+        // counter-clockwise half circle from (1, 0) to (-1, 0)
+        match PolylineSegment::from_bulge(
+            Point::new(1.0, 0.0, 0.0),
+            Point::new(-1.0, 0.0, 0.0),
+            1.0,
+        ) {
+            PolylineSegment::Arc {
+                center,
+                radius,
+                start_angle,
+                sweep_angle,
+                ..
+            } => {
+                assert_point(Point::origin(), center);
+                assert_close(1.0, radius);
+                assert_close(0.0, start_angle);
+                assert_close(180.0, sweep_angle);
+            }
+            _ => panic!("expected an arc"),
+        }
+
+        // clockwise quarter circle from (0, 1) to (1, 0)
+        let segment = PolylineSegment::from_bulge(
+            Point::new(0.0, 1.0, 0.0),
+            Point::new(1.0, 0.0, 0.0),
+            -(PI / 8.0).tan(),
+        );
+        match segment {
+            PolylineSegment::Arc {
+                ref center,
+                radius,
+                start_angle,
+                sweep_angle,
+                ..
+            } => {
+                assert_point(Point::origin(), center.clone());
+                assert_close(1.0, radius);
+                assert_close(90.0, start_angle);
+                assert_close(-90.0, sweep_angle);
+            }
+            _ => panic!("expected an arc"),
+        }
+        assert_close(PI / 2.0, segment.length());
+        let half = 0.5f64.sqrt();
+        assert_point(
+            Point::new(half, half, 0.0),
+            segment.point_at_distance(PI / 4.0),
+        );
+    }
+
+    #[test]
+    fn segment_from_zero_bulge_is_a_line() {
+        let segment = PolylineSegment::from_bulge(Point::origin(), Point::new(3.0, 4.0, 0.0), 0.0);
+        assert_eq!(
+            PolylineSegment::Line {
+                start: Point::origin(),
+                end: Point::new(3.0, 4.0, 0.0),
+            },
+            segment
+        );
+        assert_close(5.0, segment.length());
+    }
+
+    #[test]
+    fn lw_polyline_segments() {
+        let poly = lw_polyline(&[(0.0, 0.0, 0.0), (1.0, 0.0, 1.0), (1.0, 1.0, 0.0)], false);
+        assert_eq!(2, poly.segments().count());
+        let poly = lw_polyline(&[(0.0, 0.0, 0.0), (1.0, 0.0, 1.0), (1.0, 1.0, 0.0)], true);
+        let segments = poly.segments().collect::<Vec<_>>();
+        assert_eq!(3, segments.len());
+        assert_point(Point::origin(), segments[2].end().clone());
+        match segments[1] {
+            PolylineSegment::Arc { ref center, .. } => {
+                assert_point(Point::new(1.0, 0.5, 0.0), center.clone())
+            }
+            _ => panic!("expected an arc"),
+        }
+    }
+
+    #[test]
+    fn lw_polyline_length_and_area() {
+        // a square
+        let poly = lw_polyline(
+            &[
+                (0.0, 0.0, 0.0),
+                (2.0, 0.0, 0.0),
+                (2.0, 2.0, 0.0),
+                (0.0, 2.0, 0.0),
+            ],
+            true,
+        );
+        assert_close(8.0, poly.length());
+        assert_close(4.0, poly.area());
+
+        // the same square, clockwise
+        let poly = lw_polyline(
+            &[
+                (0.0, 0.0, 0.0),
+                (0.0, 2.0, 0.0),
+                (2.0, 2.0, 0.0),
+                (2.0, 0.0, 0.0),
+            ],
+            true,
+        );
+        assert_close(-4.0, poly.area());
+
+        // a circle made of two half circles
+        let poly = lw_polyline(&[(1.0, 0.0, 1.0), (-1.0, 0.0, 1.0)], true);
+        assert_close(2.0 * PI, poly.length());
+        assert_close(PI, poly.area());
+
+        // an open half circle is closed by its diameter
+        let poly = lw_polyline(&[(1.0, 0.0, 1.0), (-1.0, 0.0, 0.0)], false);
+        assert_close(PI, poly.length());
+        assert_close(PI / 2.0, poly.area());
+    }
+
+    #[test]
+    fn lw_polyline_point_at_distance() {
+        let poly = lw_polyline(&[(0.0, 0.0, 0.0), (2.0, 0.0, 1.0), (2.0, 2.0, 0.0)], false);
+        assert_point(
+            Point::new(1.0, 0.0, 0.0),
+            poly.point_at_distance(1.0).unwrap(),
+        );
+        // the top of the half circle
+        assert_point(
+            Point::new(3.0, 1.0, 0.0),
+            poly.point_at_distance(2.0 + PI / 2.0).unwrap(),
+        );
+        assert_point(
+            Point::new(2.0, 2.0, 0.0),
+            poly.point_at_distance(poly.length()).unwrap(),
+        );
+        assert_eq!(None, poly.point_at_distance(-1.0));
+        assert_eq!(None, poly.point_at_distance(poly.length() + 1.0));
+    }
+
+    #[test]
+    fn lw_polyline_tessellate() {
+        let poly = lw_polyline(&[(1.0, 0.0, 1.0), (-1.0, 0.0, 1.0)], true);
+        let points = poly.tessellate(0.01);
+        assert_point(Point::new(1.0, 0.0, 0.0), points[0].clone());
+        assert_point(Point::new(1.0, 0.0, 0.0), points[points.len() - 1].clone());
+        for pair in points.windows(2) {
+            let p = &pair[0];
+            assert_close(1.0, (p.x * p.x + p.y * p.y).sqrt());
+            let mx = (pair[0].x + pair[1].x) / 2.0;
+            let my = (pair[0].y + pair[1].y) / 2.0;
+            assert!(1.0 - (mx * mx + my * my).sqrt() <= 0.01);
+        }
+        assert!(poly.tessellate(0.1).len() < points.len());
+
+        let poly = lw_polyline(&[(0.0, 0.0, 0.0), (1.0, 0.0, 0.0), (1.0, 1.0, 0.0)], false);
+        assert_eq!(3, poly.tessellate(0.01).len());
+    }
+
+    #[test]
+    fn polyline_segments() {
+        let mut drawing = Drawing::new();
+        let mut poly = Polyline {
+            location: Point::new(0.0, 0.0, 5.0),
+            ..Default::default()
+        };
+        poly.set_is_closed(true);
+        let mut vertex = Vertex::new(Point::new(1.0, 0.0, 0.0));
+        vertex.bulge = 1.0;
+        poly.add_vertex(&mut drawing, vertex);
+        let mut vertex = Vertex::new(Point::new(-1.0, 0.0, 0.0));
+        vertex.bulge = 1.0;
+        poly.add_vertex(&mut drawing, vertex);
+        let mut frame_point = Vertex::new(Point::new(10.0, 10.0, 0.0));
+        frame_point.set_is_spline_frame_control_point(true);
+        poly.add_vertex(&mut drawing, frame_point);
+
+        let segments = poly.segments().collect::<Vec<_>>();
+        assert_eq!(2, segments.len());
+        assert_point(Point::new(1.0, 0.0, 5.0), segments[0].start().clone());
+        assert_close(2.0 * PI, poly.length());
+        assert_close(PI, poly.area());
+    }
+
+    #[test]
+    fn polyline_3d_ignores_bulges() {
+        let mut drawing = Drawing::new();
+        let mut poly = Polyline::default();
+        poly.set_is_3d_polyline(true);
+        let mut vertex = Vertex::new(Point::new(0.0, 0.0, 0.0));
+        vertex.bulge = 1.0;
+        poly.add_vertex(&mut drawing, vertex);
+        poly.add_vertex(&mut drawing, Vertex::new(Point::new(0.0, 3.0, 4.0)));
+        assert_eq!(
+            vec![PolylineSegment::Line {
+                start: Point::origin(),
+                end: Point::new(0.0, 3.0, 4.0),
+            }],
+            poly.segments().collect::<Vec<_>>()
+        );
+        assert_close(5.0, poly.length());
+        assert_point(
+            Point::new(0.0, 1.5, 2.0),
+            poly.point_at_distance(2.5).unwrap(),
+        );
+    }
+}
@@ -0,0 +1,151 @@
+use crate::enums::AcadVersion;
+
+/// How floating point values are written to text files.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum FloatFormat {
+    /// At most the given number of decimal places with trailing zeros removed, e.g., `1.5`.
+    Trimmed(usize),
+    /// Exactly the given number of decimal places, e.g., `1.500000`.
+    Fixed(usize),
+    /// The fewest digits that read back as the same value.
+    RoundTrip,
+}
+
+impl Default for FloatFormat {
+    fn default() -> Self {
+        FloatFormat::Trimmed(12)
+    }
+}
+
+impl FloatFormat {
+    pub(crate) fn format(self, val: f64) -> String {
+        let mut val = match self {
+            FloatFormat::Trimmed(decimals) => {
+                let mut val = format!("{:.*}", decimals, val);
+                if val.contains('.') {
+                    // trim trailing zeros
+                    while val.ends_with('0') {
+                        val.pop();
+                    }
+                }
+                val
+            }
+            FloatFormat::Fixed(decimals) => format!("{:.*}", decimals, val),
+            FloatFormat::RoundTrip => format!("{}", val),
+        };
+
+        // ensure it doesn't end with a decimal
+        if val.ends_with('.') {
+            val.push('0');
+        } else if !val.contains('.') && val.chars().all(|c| c == '-' || c.is_ascii_digit()) {
+            val.push_str(".0");
+        }
+
+        val
+    }
+}
+
+/// The line ending used when writing text files.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum LineEnding {
+    /// `\r\n`
+    #[default]
+    CrLf,
+    /// `\n`
+    Lf,
+}
+
+impl LineEnding {
+    pub(crate) fn as_str(self) -> &'static str {
+        match self {
+            LineEnding::CrLf => "\r\n",
+            LineEnding::Lf => "\n",
+        }
+    }
+}
+
+/// Options for writing a `Drawing`.  See `Drawing::save_with_options()`.
+///
+/// ```
+/// # use dxf::{LineEnding, SaveOptions};
+/// # use dxf::enums::AcadVersion;
+/// // stable output for source control
+/// let options = SaveOptions::new()
+///     .version(AcadVersion::R2000)
+///     .float_format(dxf::FloatFormat::Trimmed(6))
+///     .line_ending(LineEnding::Lf)
+///     .include_thumbnail(false);
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct SaveOptions {
+    pub(crate) version: Option<AcadVersion>,
+    pub(crate) is_binary: bool,
+    pub(crate) float_format: FloatFormat,
+    pub(crate) line_ending: LineEnding,
+    pub(crate) write_handles: Option<bool>,
+    pub(crate) text_as_ascii: Option<bool>,
+    pub(crate) exclude_thumbnail: bool,
+    pub(crate) build_object_graph: bool,
+    pub(crate) update_extents: bool,
+}
+
+impl SaveOptions {
+    /// Creates options that write a drawing the same way as `Drawing::save()`.
+    pub fn new() -> Self {
+        Default::default()
+    }
+    /// Writes the drawing as the specified version instead of `header.version`.  The drawing isn't
+    /// modified; entities and objects the version doesn't support are converted as by
+    /// `Drawing::convert_to_version()`, and `Drawing::save_with_options_report()` returns the notes
+    /// describing that conversion.
+    pub fn version(mut self, version: AcadVersion) -> Self {
+        self.version = Some(version);
+        self
+    }
+    /// When `true`, the drawing is written as binary DXF.  The default is `false`.
+    pub fn binary(mut self, is_binary: bool) -> Self {
+        self.is_binary = is_binary;
+        self
+    }
+    /// Sets how floating point values are written in text files.  The default is
+    /// `FloatFormat::Trimmed(12)`.
+    pub fn float_format(mut self, float_format: FloatFormat) -> Self {
+        self.float_format = float_format;
+        self
+    }
+    /// Sets the line ending used in text files.  The default is `LineEnding::CrLf`.
+    pub fn line_ending(mut self, line_ending: LineEnding) -> Self {
+        self.line_ending = line_ending;
+        self
+    }
+    /// Sets whether item handles are written.  By default they're written for R13 and later, or
+    /// when `header.handles_enabled` is set.
+    pub fn write_handles(mut self, write_handles: bool) -> Self {
+        self.write_handles = Some(write_handles);
+        self
+    }
+    /// When `true`, non-ASCII characters in text files are written as `\U+XXXX` escapes; when
+    /// `false`, they're written as UTF-8.  By default they're escaped for R2004 and earlier.
+    pub fn text_as_ascii(mut self, text_as_ascii: bool) -> Self {
+        self.text_as_ascii = Some(text_as_ascii);
+        self
+    }
+    /// Sets whether the `THUMBNAILIMAGE` section is written.  The default is `true`.
+    pub fn include_thumbnail(mut self, include_thumbnail: bool) -> Self {
+        self.exclude_thumbnail = !include_thumbnail;
+        self
+    }
+    /// When `true`, a drawing written as R2000 or later that has no object graph, e.g., an R12
+    /// drawing whose `header.version` was changed, is upgraded on a copy as by
+    /// `Drawing::convert_to_version()`.  The default is `false`.
+    pub fn build_object_graph(mut self, build_object_graph: bool) -> Self {
+        self.build_object_graph = build_object_graph;
+        self
+    }
+    /// When `true`, the header's `$EXTMIN` and `$EXTMAX` values are replaced with the extents of
+    /// the model space entities.  The drawing itself isn't modified.  The default is `false`.
+    pub fn update_extents(mut self, update_extents: bool) -> Self {
+        self.update_extents = update_extents;
+        self
+    }
+}
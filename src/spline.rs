@@ -0,0 +1,664 @@
+use crate::entities::Spline;
+use crate::{Point, Vector};
+
+/// The maximum number of times a piece of the curve is halved while tessellating.
+const MAX_SUBDIVISION_DEPTH: u32 = 16;
+
+// Spline
+impl Spline {
+    /// Returns the parameter range `(start, end)` over which the spline is defined, or `None` if
+    /// the spline has neither enough control points nor enough fit points to be evaluated.
+    pub fn domain(&self) -> Option<(f64, f64)> {
+        Nurbs::from_spline(self).map(|nurbs| nurbs.domain())
+    }
+    /// Evaluates the spline at parameter `t`, which is clamped to the spline's `domain()`.
+    ///
+    /// Per-control-point `weights` are honored when there is exactly one for every control point;
+    /// otherwise every weight is taken to be `1.0`.  If `knot_values` doesn't describe a valid knot
+    /// vector for the control points, a clamped uniform knot vector is used instead.  A spline
+    /// that only has fit points is evaluated through the control points that
+    /// `fit_points_to_control_points()` would compute.
+    pub fn point_at(&self, t: f64) -> Option<Point> {
+        Nurbs::from_spline(self).map(|nurbs| {
+            let p = nurbs.point_at(t);
+            Point::new(p[0], p[1], p[2])
+        })
+    }
+    /// Returns the first derivative of the spline with respect to its parameter at `t`, which is
+    /// clamped to the spline's `domain()`.  See `point_at()` for how the curve is defined.
+    pub fn derivative_at(&self, t: f64) -> Option<Vector> {
+        Nurbs::from_spline(self).map(|nurbs| {
+            let d = nurbs.derivative_at(t);
+            Vector::new(d[0], d[1], d[2])
+        })
+    }
+    /// Approximates the spline with a polyline whose segments deviate from the curve by no more
+    /// than (approximately) `chord_tolerance`.  The first and last points are the start and end of
+    /// the spline.  Returns an empty `Vec` if the spline can't be evaluated.
+    pub fn tessellate(&self, chord_tolerance: f64) -> Vec<Point> {
+        match Nurbs::from_spline(self) {
+            Some(nurbs) => nurbs
+                .tessellate(chord_tolerance.abs())
+                .iter()
+                .map(|p| Point::new(p[0], p[1], p[2]))
+                .collect(),
+            None => vec![],
+        }
+    }
+    /// Replaces the `control_points`, `knot_values`, and `weights` with a non-rational spline that
+    /// interpolates the `fit_points`, using chord-length parameterization.  When both
+    /// `start_tangent` and `end_tangent` are non-zero and the degree is 3, the curve also follows
+    /// the tangent directions at its ends.  The `fit_points` are left in place.  Returns `false`
+    /// and leaves the spline unchanged if there are fewer than two distinct fit points.
+    pub fn fit_points_to_control_points(&mut self) -> bool {
+        match Nurbs::interpolate(self) {
+            Some(nurbs) => {
+                self.degree_of_curve = nurbs.degree as i32;
+                self.knot_values = nurbs.knots;
+                self.control_points = nurbs
+                    .points
+                    .iter()
+                    .map(|p| Point::new(p[0], p[1], p[2]))
+                    .collect();
+                self.weights.clear();
+                self.set_is_rational(false);
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+/// A validated copy of the values needed to evaluate a `Spline`.
+struct Nurbs {
+    degree: usize,
+    knots: Vec<f64>,
+    points: Vec<[f64; 3]>,
+    weights: Vec<f64>,
+}
+
+impl Nurbs {
+    fn from_spline(spline: &Spline) -> Option<Nurbs> {
+        if spline.control_points.len() < 2 {
+            return Nurbs::interpolate(spline);
+        }
+
+        let points = spline
+            .control_points
+            .iter()
+            .map(|p| [p.x, p.y, p.z])
+            .collect::<Vec<_>>();
+        let count = points.len();
+        let degree = (spline.degree_of_curve.max(1) as usize).min(count - 1);
+        let weights = if spline.weights.len() == count && spline.weights.iter().all(|w| *w > 0.0) {
+            spline.weights.clone()
+        } else {
+            vec![1.0; count]
+        };
+        let knots = if is_valid_knot_vector(&spline.knot_values, count, degree) {
+            spline.knot_values.clone()
+        } else {
+            clamped_uniform_knots(count, degree)
+        };
+        Some(Nurbs {
+            degree,
+            knots,
+            points,
+            weights,
+        })
+    }
+    /// Global interpolation of the fit points (Piegl & Tiller, The NURBS Book, 9.2.1 and 9.2.2).
+    fn interpolate(spline: &Spline) -> Option<Nurbs> {
+        let fit_points = spline
+            .fit_points
+            .iter()
+            .map(|p| [p.x, p.y, p.z])
+            .collect::<Vec<_>>();
+        if fit_points.len() < 2 {
+            return None;
+        }
+
+        // chord-length parameters
+        let mut parameters = vec![0.0];
+        for pair in fit_points.windows(2) {
+            let last = parameters[parameters.len() - 1];
+            parameters.push(last + distance(&pair[0], &pair[1]));
+        }
+        let total_length = parameters[parameters.len() - 1];
+        if total_length == 0.0 {
+            return None;
+        }
+
+        let n = fit_points.len() - 1;
+        let start_tangent = unit(&[
+            spline.start_tangent.x,
+            spline.start_tangent.y,
+            spline.start_tangent.z,
+        ]);
+        let end_tangent = unit(&[
+            spline.end_tangent.x,
+            spline.end_tangent.y,
+            spline.end_tangent.z,
+        ]);
+        let tangents = match (start_tangent, end_tangent) {
+            (Some(start), Some(end)) if spline.degree_of_curve == 3 => Some((start, end)),
+            _ => None,
+        };
+
+        let (degree, knots) = match tangents {
+            Some(_) => {
+                // the parameters themselves make up the interior knots
+                let mut knots = vec![0.0; 4];
+                knots.extend_from_slice(&parameters[1..n]);
+                knots.extend_from_slice(&[total_length; 4]);
+                (3, knots)
+            }
+            None => {
+                // interior knots are averages of the parameters
+                let degree = (spline.degree_of_curve.max(1) as usize).min(n);
+                let mut knots = vec![0.0; degree + 1];
+                for j in 1..=(n - degree) {
+                    let sum: f64 = parameters[j..j + degree].iter().sum();
+                    knots.push(sum / degree as f64);
+                }
+                knots.extend(vec![total_length; degree + 1]);
+                (degree, knots)
+            }
+        };
+
+        let count = knots.len() - degree - 1;
+        let mut template = Nurbs {
+            degree,
+            knots,
+            points: vec![[0.0; 3]; count],
+            weights: vec![1.0; count],
+        };
+        let mut matrix = vec![];
+        let mut values = vec![];
+        for (parameter, point) in parameters.iter().zip(fit_points.iter()) {
+            matrix.push(template.basis_row(*parameter, false));
+            values.push(*point);
+        }
+        if let Some((start, end)) = tangents {
+            // the curve is parameterized by (approximate) arc length, so the derivative at either
+            // end is the unit tangent
+            matrix.push(template.basis_row(0.0, true));
+            values.push(start);
+            matrix.push(template.basis_row(total_length, true));
+            values.push(end);
+        }
+
+        template.points = solve(matrix, values)?;
+        Some(template)
+    }
+    fn domain(&self) -> (f64, f64) {
+        (self.knots[self.degree], self.knots[self.points.len()])
+    }
+    /// Finds the index of the knot span containing `t`.
+    fn find_span(&self, t: f64) -> usize {
+        let n = self.points.len() - 1;
+        let p = self.degree;
+        let (start, end) = self.domain();
+        if t >= end {
+            // the last non-empty span
+            let mut span = n;
+            while span > p && self.knots[span] >= end {
+                span -= 1;
+            }
+            return span;
+        }
+        if t <= start {
+            let mut span = p;
+            while span < n && self.knots[span + 1] <= start {
+                span += 1;
+            }
+            return span;
+        }
+
+        let mut low = p;
+        let mut high = n + 1;
+        while high - low > 1 {
+            let mid = (low + high) / 2;
+            if t < self.knots[mid] {
+                high = mid;
+            } else {
+                low = mid;
+            }
+        }
+        low
+    }
+    /// Computes the non-zero basis functions of the given `degree` at `t` in the knot span `span`
+    /// (Piegl & Tiller, A2.2).  The result holds `N[span - degree]..=N[span]`.
+    fn basis_functions(&self, span: usize, t: f64, degree: usize) -> Vec<f64> {
+        let mut values = vec![0.0; degree + 1];
+        let mut left = vec![0.0; degree + 1];
+        let mut right = vec![0.0; degree + 1];
+        values[0] = 1.0;
+        for j in 1..=degree {
+            left[j] = t - self.knots[span + 1 - j];
+            right[j] = self.knots[span + j] - t;
+            let mut saved = 0.0;
+            for r in 0..j {
+                let denominator = right[r + 1] + left[j - r];
+                let temp = if denominator == 0.0 {
+                    0.0
+                } else {
+                    values[r] / denominator
+                };
+                values[r] = saved + right[r + 1] * temp;
+                saved = left[j - r] * temp;
+            }
+            values[j] = saved;
+        }
+        values
+    }
+    /// Computes the first derivatives of the non-zero basis functions at `t` in the knot span
+    /// `span`.  The result holds `N'[span - degree]..=N'[span]`.
+    fn basis_derivatives(&self, span: usize, t: f64) -> Vec<f64> {
+        let p = self.degree;
+        let lower = self.basis_functions(span, t, p - 1);
+        let mut derivatives = vec![0.0; p + 1];
+        for (r, derivative) in derivatives.iter_mut().enumerate() {
+            let i = span - p + r;
+            let mut value = 0.0;
+            if r > 0 {
+                let denominator = self.knots[i + p] - self.knots[i];
+                if denominator != 0.0 {
+                    value += lower[r - 1] / denominator;
+                }
+            }
+            if r < p {
+                let denominator = self.knots[i + p + 1] - self.knots[i + 1];
+                if denominator != 0.0 {
+                    value -= lower[r] / denominator;
+                }
+            }
+            *derivative = p as f64 * value;
+        }
+        derivatives
+    }
+    /// Returns the value (or first derivative) of every basis function at `t`.
+    fn basis_row(&self, t: f64, derivative: bool) -> Vec<f64> {
+        let span = self.find_span(t);
+        let values = if derivative {
+            self.basis_derivatives(span, t)
+        } else {
+            self.basis_functions(span, t, self.degree)
+        };
+        let mut row = vec![0.0; self.points.len()];
+        for (r, value) in values.iter().enumerate() {
+            row[span - self.degree + r] = *value;
+        }
+        row
+    }
+    /// Returns the weighted sum of the control points and the sum of the weights for the given
+    /// basis function values.
+    fn weighted_sum(&self, span: usize, values: &[f64]) -> ([f64; 3], f64) {
+        let mut sum = [0.0; 3];
+        let mut weight = 0.0;
+        for (r, value) in values.iter().enumerate() {
+            let i = span - self.degree + r;
+            let w = value * self.weights[i];
+            for (s, c) in sum.iter_mut().zip(self.points[i].iter()) {
+                *s += w * c;
+            }
+            weight += w;
+        }
+        (sum, weight)
+    }
+    fn clamp(&self, t: f64) -> f64 {
+        let (start, end) = self.domain();
+        t.max(start).min(end)
+    }
+    fn point_at(&self, t: f64) -> [f64; 3] {
+        let t = self.clamp(t);
+        let span = self.find_span(t);
+        let values = self.basis_functions(span, t, self.degree);
+        let (sum, weight) = self.weighted_sum(span, &values);
+        [sum[0] / weight, sum[1] / weight, sum[2] / weight]
+    }
+    fn derivative_at(&self, t: f64) -> [f64; 3] {
+        let t = self.clamp(t);
+        let span = self.find_span(t);
+        let values = self.basis_functions(span, t, self.degree);
+        let derivatives = self.basis_derivatives(span, t);
+        let (sum, weight) = self.weighted_sum(span, &values);
+        let (sum_derivative, weight_derivative) = self.weighted_sum(span, &derivatives);
+
+        // quotient rule on C(t) = A(t) / w(t)
+        let mut result = [0.0; 3];
+        for (k, r) in result.iter_mut().enumerate() {
+            let point = sum[k] / weight;
+            *r = (sum_derivative[k] - weight_derivative * point) / weight;
+        }
+        result
+    }
+    fn tessellate(&self, chord_tolerance: f64) -> Vec<[f64; 3]> {
+        let (start, end) = self.domain();
+        let mut breaks = vec![start];
+        for knot in &self.knots {
+            if *knot > breaks[breaks.len() - 1] && *knot < end {
+                breaks.push(*knot);
+            }
+        }
+        breaks.push(end);
+
+        let mut result = vec![self.point_at(start)];
+        for span in breaks.windows(2) {
+            // split every span up front so that an s-bend doesn't look straight at its midpoint
+            let (a, b) = (span[0], span[1]);
+            let pieces = self.degree;
+            for i in 0..pieces {
+                let t0 = a + (b - a) * i as f64 / pieces as f64;
+                let t1 = a + (b - a) * (i + 1) as f64 / pieces as f64;
+                let p0 = self.point_at(t0);
+                let p1 = self.point_at(t1);
+                self.subdivide(t0, &p0, t1, &p1, chord_tolerance, 0, &mut result);
+            }
+        }
+        result
+    }
+    #[allow(clippy::too_many_arguments)]
+    fn subdivide(
+        &self,
+        t0: f64,
+        p0: &[f64; 3],
+        t1: f64,
+        p1: &[f64; 3],
+        chord_tolerance: f64,
+        depth: u32,
+        result: &mut Vec<[f64; 3]>,
+    ) {
+        let t = (t0 + t1) / 2.0;
+        let middle = self.point_at(t);
+        if depth < MAX_SUBDIVISION_DEPTH && distance_to_segment(&middle, p0, p1) > chord_tolerance {
+            self.subdivide(t0, p0, t, &middle, chord_tolerance, depth + 1, result);
+            self.subdivide(t, &middle, t1, p1, chord_tolerance, depth + 1, result);
+        } else {
+            result.push(*p1);
+        }
+    }
+}
+
+fn is_valid_knot_vector(knots: &[f64], count: usize, degree: usize) -> bool {
+    knots.len() == count + degree + 1
+        && knots.windows(2).all(|pair| pair[0] <= pair[1])
+        && knots[degree] < knots[count]
+}
+
+fn clamped_uniform_knots(count: usize, degree: usize) -> Vec<f64> {
+    let spans = count - degree;
+    let mut knots = vec![0.0; degree + 1];
+    for i in 1..spans {
+        knots.push(i as f64 / spans as f64);
+    }
+    knots.extend(vec![1.0; degree + 1]);
+    knots
+}
+
+fn distance(a: &[f64; 3], b: &[f64; 3]) -> f64 {
+    let dx = b[0] - a[0];
+    let dy = b[1] - a[1];
+    let dz = b[2] - a[2];
+    (dx * dx + dy * dy + dz * dz).sqrt()
+}
+
+fn unit(v: &[f64; 3]) -> Option<[f64; 3]> {
+    let length = distance(&[0.0; 3], v);
+    if length == 0.0 {
+        None
+    } else {
+        Some([v[0] / length, v[1] / length, v[2] / length])
+    }
+}
+
+fn distance_to_segment(point: &[f64; 3], start: &[f64; 3], end: &[f64; 3]) -> f64 {
+    let segment = [end[0] - start[0], end[1] - start[1], end[2] - start[2]];
+    let length_squared = segment.iter().map(|c| c * c).sum::<f64>();
+    if length_squared == 0.0 {
+        return distance(point, start);
+    }
+    let offset = [
+        point[0] - start[0],
+        point[1] - start[1],
+        point[2] - start[2],
+    ];
+    let t = (offset
+        .iter()
+        .zip(segment.iter())
+        .map(|(o, s)| o * s)
+        .sum::<f64>()
+        / length_squared)
+        .clamp(0.0, 1.0);
+    let closest = [
+        start[0] + segment[0] * t,
+        start[1] + segment[1] * t,
+        start[2] + segment[2] * t,
+    ];
+    distance(point, &closest)
+}
+
+/// Solves `matrix * x = values` with Gaussian elimination and partial pivoting, returning `None`
+/// if the matrix is singular.
+fn solve(mut matrix: Vec<Vec<f64>>, mut values: Vec<[f64; 3]>) -> Option<Vec<[f64; 3]>> {
+    let size = matrix.len();
+    for column in 0..size {
+        let pivot = (column..size).max_by(|a, b| {
+            matrix[*a][column]
+                .abs()
+                .partial_cmp(&matrix[*b][column].abs())
+                .unwrap()
+        })?;
+        if matrix[pivot][column].abs() < 1e-12 {
+            return None;
+        }
+        matrix.swap(column, pivot);
+        values.swap(column, pivot);
+        for row in column + 1..size {
+            let factor = matrix[row][column] / matrix[column][column];
+            if factor == 0.0 {
+                continue;
+            }
+            let (pivot_rows, rows) = matrix.split_at_mut(row);
+            for (value, pivot_value) in rows[0][column..]
+                .iter_mut()
+                .zip(pivot_rows[column][column..].iter())
+            {
+                *value -= factor * pivot_value;
+            }
+            let pivot_values = values[column];
+            for (value, pivot_value) in values[row].iter_mut().zip(pivot_values.iter()) {
+                *value -= factor * pivot_value;
+            }
+        }
+    }
+
+    let mut result = vec![[0.0; 3]; size];
+    for row in (0..size).rev() {
+        for k in 0..3 {
+            let mut value = values[row][k];
+            for (column, solved) in result.iter().enumerate().skip(row + 1) {
+                value -= matrix[row][column] * solved[k];
+            }
+            result[row][k] = value / matrix[row][row];
+        }
+    }
+    Some(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::entities::*;
+    use crate::*;
+
+    fn assert_point(expected: Point, actual: Point) {
+        assert!(
+            (expected.x - actual.x).abs() < 1e-9
+                && (expected.y - actual.y).abs() < 1e-9
+                && (expected.z - actual.z).abs() < 1e-9,
+            "expected {:?} but was {:?}",
+            expected,
+            actual
+        );
+    }
+
+    fn quarter_circle() -> Spline {
+        let mut spline = Spline {
+            degree_of_curve: 2,
+            knot_values: vec![0.0, 0.0, 0.0, 1.0, 1.0, 1.0],
+            control_points: vec![
+                Point::new(1.0, 0.0, 0.0),
+                Point::new(1.0, 1.0, 0.0),
+                Point::new(0.0, 1.0, 0.0),
+            ],
+            weights: vec![1.0, 0.5f64.sqrt(), 1.0],
+            ..Default::default()
+        };
+        spline.set_is_rational(true);
+        spline
+    }
+
+    fn distance_from_origin(p: &Point) -> f64 {
+        (p.x * p.x + p.y * p.y + p.z * p.z).sqrt()
+    }
+
+    #[test]
+    fn evaluate_linear_spline_without_knots() {
+        let spline = Spline {
+            control_points: vec![Point::new(0.0, 0.0, 0.0), Point::new(2.0, 4.0, 6.0)],
+            ..Default::default()
+        };
+        assert_eq!(Some((0.0, 1.0)), spline.domain());
+        assert_point(Point::new(1.0, 2.0, 3.0), spline.point_at(0.5).unwrap());
+        assert_eq!(Some(Vector::new(2.0, 4.0, 6.0)), spline.derivative_at(0.5));
+    }
+
+    #[test]
+    fn evaluate_rational_spline() {
+        let spline = quarter_circle();
+        assert_point(Point::new(1.0, 0.0, 0.0), spline.point_at(0.0).unwrap());
+        assert_point(Point::new(0.0, 1.0, 0.0), spline.point_at(1.0).unwrap());
+        for i in 0..=10 {
+            let p = spline.point_at(i as f64 / 10.0).unwrap();
+            assert!((1.0 - distance_from_origin(&p)).abs() < 1e-9);
+        }
+
+        // the tangent at the start points straight up
+        let d = spline.derivative_at(0.0).unwrap();
+        assert!(d.x.abs() < 1e-9 && d.y > 0.0);
+    }
+
+    #[test]
+    fn evaluate_ignores_mismatched_weights() {
+        let mut spline = quarter_circle();
+        spline.weights = vec![2.0];
+        // with uniform weights the midpoint is the midpoint of the quadratic bezier
+        assert_point(Point::new(0.75, 0.75, 0.0), spline.point_at(0.5).unwrap());
+    }
+
+    #[test]
+    fn evaluate_spline_with_too_few_points() {
+        let spline = Spline {
+            control_points: vec![Point::new(1.0, 2.0, 3.0)],
+            ..Default::default()
+        };
+        assert_eq!(None, spline.point_at(0.0));
+        assert!(spline.tessellate(0.01).is_empty());
+    }
+
+    #[test]
+    fn tessellate_within_chord_tolerance() {
+        let spline = quarter_circle();
+        let points = spline.tessellate(0.001);
+        assert!(points.len() > 2);
+        assert_point(Point::new(1.0, 0.0, 0.0), points[0].clone());
+        assert_point(Point::new(0.0, 1.0, 0.0), points[points.len() - 1].clone());
+        for pair in points.windows(2) {
+            assert!((1.0 - distance_from_origin(&pair[0])).abs() < 1e-9);
+            let middle = Point::new(
+                (pair[0].x + pair[1].x) / 2.0,
+                (pair[0].y + pair[1].y) / 2.0,
+                0.0,
+            );
+            assert!(1.0 - distance_from_origin(&middle) <= 0.001);
+        }
+
+        // a looser tolerance gives fewer points
+        assert!(spline.tessellate(0.1).len() < points.len());
+    }
+
+    #[test]
+    fn fit_points_to_control_points_interpolates() {
+        let fit_points = vec![
+            Point::new(0.0, 0.0, 0.0),
+            Point::new(1.0, 2.0, 0.0),
+            Point::new(3.0, 1.0, 0.0),
+            Point::new(4.0, 3.0, 1.0),
+            Point::new(6.0, 0.0, 0.0),
+        ];
+        let mut spline = Spline {
+            degree_of_curve: 3,
+            fit_points: fit_points.clone(),
+            ..Default::default()
+        };
+
+        // fit-point-only splines can be evaluated directly
+        assert_point(Point::new(6.0, 0.0, 0.0), spline.point_at(1e10).unwrap());
+
+        assert!(spline.fit_points_to_control_points());
+        assert_eq!(3, spline.degree_of_curve);
+        assert_eq!(5, spline.control_points.len());
+        assert_eq!(9, spline.knot_values.len());
+        assert_eq!(fit_points, spline.fit_points);
+
+        // the knots are chord lengths, so the curve passes through each fit point at those values
+        let mut parameter = 0.0;
+        for (i, fit_point) in fit_points.iter().enumerate() {
+            if i > 0 {
+                let previous = &fit_points[i - 1];
+                parameter += ((fit_point.x - previous.x).powi(2)
+                    + (fit_point.y - previous.y).powi(2)
+                    + (fit_point.z - previous.z).powi(2))
+                .sqrt();
+            }
+            assert_point(fit_point.clone(), spline.point_at(parameter).unwrap());
+        }
+    }
+
+    #[test]
+    fn fit_points_to_control_points_with_end_tangents() {
+        let mut spline = Spline {
+            degree_of_curve: 3,
+            fit_points: vec![
+                Point::new(0.0, 0.0, 0.0),
+                Point::new(2.0, 1.0, 0.0),
+                Point::new(4.0, 0.0, 0.0),
+            ],
+            start_tangent: Point::new(0.0, 1.0, 0.0),
+            end_tangent: Point::new(0.0, -2.0, 0.0),
+            ..Default::default()
+        };
+        assert!(spline.fit_points_to_control_points());
+        assert_eq!(5, spline.control_points.len());
+
+        let (start, end) = spline.domain().unwrap();
+        let d = spline.derivative_at(start).unwrap();
+        assert!(d.x.abs() < 1e-9 && (d.y - 1.0).abs() < 1e-9);
+        let d = spline.derivative_at(end).unwrap();
+        assert!(d.x.abs() < 1e-9 && (d.y + 1.0).abs() < 1e-9);
+        assert_point(
+            Point::new(2.0, 1.0, 0.0),
+            spline.point_at(5.0f64.sqrt()).unwrap(),
+        );
+    }
+
+    #[test]
+    fn fit_points_to_control_points_with_too_few_points() {
+        let mut spline = Spline {
+            fit_points: vec![Point::new(1.0, 1.0, 0.0), Point::new(1.0, 1.0, 0.0)],
+            ..Default::default()
+        };
+        assert!(!spline.fit_points_to_control_points());
+        assert!(spline.control_points.is_empty());
+    }
+}
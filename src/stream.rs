@@ -0,0 +1,479 @@
+//! Types for reading and writing drawings incrementally without holding every entity in memory.
+
+extern crate encoding_rs;
+use self::encoding_rs::Encoding;
+
+use std::io::{Read, Write};
+
+use itertools::{put_back, PutBack};
+
+use crate::code_pair_iter::CodePairIter;
+use crate::code_pair_put_back::CodePairPutBack;
+use crate::code_pair_writer::CodePairWriter;
+use crate::entities::Entity;
+use crate::entity_iter::next_gathered_entity;
+use crate::enums::AcadVersion;
+use crate::helper_functions::{is_dwg_signature, read_line};
+use crate::{CodePair, Drawing, DxfError, DxfResult, Header};
+
+/// Reads the entities of a DXF file one at a time.
+///
+/// Everything that precedes the `ENTITIES` section (header, classes, tables, and blocks) is read
+/// up front and is available via `drawing()`; entities are then parsed on demand and are never added
+/// to that `Drawing`.  Anything after the `ENTITIES` section is not read.
+///
+/// ```no_run
+/// # fn main() -> dxf::DxfResult<()> {
+/// use dxf::stream::EntityReader;
+/// let file = std::fs::File::open("survey.dxf")?;
+/// let reader = EntityReader::new(std::io::BufReader::new(file))?;
+/// println!("version: {:?}", reader.header().version);
+/// for entity in reader {
+///     println!("{:?}", entity?.common.layer);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub struct EntityReader<T: Read> {
+    drawing: Drawing,
+    iter: PutBack<RawEntityIter<T>>,
+}
+
+impl<T: Read> EntityReader<T> {
+    /// Creates a new `EntityReader` and reads everything that precedes the `ENTITIES` section.
+    pub fn new(reader: T) -> DxfResult<Self> {
+        EntityReader::new_with_encoding(reader, encoding_rs::WINDOWS_1252)
+    }
+    /// Creates a new `EntityReader` using the specified text encoding and reads everything that
+    /// precedes the `ENTITIES` section.
+    pub fn new_with_encoding(mut reader: T, encoding: &'static Encoding) -> DxfResult<Self> {
+        let first_line = match read_line(&mut reader, true, encoding) {
+            Some(Ok(line)) => line,
+            Some(Err(e)) => return Err(e),
+            None => return Err(DxfError::UnexpectedEndOfInput),
+        };
+        if is_dwg_signature(&first_line) {
+            return Err(DxfError::UnsupportedDwgFile);
+        }
+
+        let reader = CodePairIter::new(reader, encoding, first_line);
+        let mut iter = CodePairPutBack::from_code_pair_iter(reader);
+        let mut drawing = Drawing::new();
+        drawing.clear();
+        let is_at_entities = Drawing::read_sections(&mut drawing, &mut iter, true)?;
+        Ok(EntityReader {
+            drawing,
+            iter: put_back(RawEntityIter {
+                iter,
+                is_done: !is_at_entities,
+            }),
+        })
+    }
+    /// The drawing's header.
+    pub fn header(&self) -> &Header {
+        &self.drawing.header
+    }
+    /// The drawing as read up to the `ENTITIES` section.  This contains the header, classes, tables,
+    /// and blocks, but no entities.
+    pub fn drawing(&self) -> &Drawing {
+        &self.drawing
+    }
+    /// Consumes the reader and returns the drawing as read up to the `ENTITIES` section.
+    pub fn into_drawing(self) -> Drawing {
+        self.drawing
+    }
+}
+
+impl<T: Read> Iterator for EntityReader<T> {
+    type Item = DxfResult<Entity>;
+
+    fn next(&mut self) -> Option<DxfResult<Entity>> {
+        match next_gathered_entity(&mut self.iter) {
+            Ok(Some(e)) => Some(Ok(e)),
+            Ok(None) => None,
+            Err(e) => Some(Err(e)),
+        }
+    }
+}
+
+/// Reads individual entities, e.g., `POLYLINE` and `VERTEX` are reported separately.
+struct RawEntityIter<T: Read> {
+    iter: CodePairPutBack<T>,
+    is_done: bool,
+}
+
+impl<T: Read> Iterator for RawEntityIter<T> {
+    type Item = DxfResult<Entity>;
+
+    fn next(&mut self) -> Option<DxfResult<Entity>> {
+        if self.is_done {
+            return None;
+        }
+
+        match Entity::read(&mut self.iter) {
+            Ok(Some(e)) => Some(Ok(e)),
+            Ok(None) => {
+                // reached the end of the section
+                self.is_done = true;
+                None
+            }
+            Err(e) => {
+                self.is_done = true;
+                Some(Err(self.iter.add_context(e)))
+            }
+        }
+    }
+}
+
+/// Writes a DXF file one entity at a time.
+///
+/// The header, classes, tables, blocks, and any entities of the given `Drawing` are written when the
+/// writer is created; entities passed to `write_entity()` follow, and the remaining sections (e.g.,
+/// objects) are written by `finish()`.  Entities without a handle are assigned one as they're
+/// written, and entities without an owner are owned by the `*Model_Space` block record.
+///
+/// Since the header is written first, a range of handles is reserved for the streamed entities
+/// and the `$HANDSEED` value written is the first handle after that range.  `write_entity()` fails
+/// with `DxfError::HandlesExhausted` once the range is used up; use `new_with_handle_reserve()` to
+/// reserve more than `DEFAULT_HANDLE_RESERVE` handles.
+///
+/// ```no_run
+/// # fn main() -> dxf::DxfResult<()> {
+/// use dxf::entities::*;
+/// use dxf::stream::DrawingWriter;
+/// use dxf::{Drawing, Point};
+/// let mut file = std::io::BufWriter::new(std::fs::File::create("toolpath.dxf")?);
+/// let mut writer = DrawingWriter::new(Drawing::new(), &mut file)?;
+/// for i in 0..1000 {
+///     let p = Point::new(f64::from(i), 0.0, 0.0);
+///     writer.write_entity(&Entity::new(EntityType::ModelPoint(ModelPoint::new(p))))?;
+/// }
+/// writer.finish()?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct DrawingWriter<'a, T: Write + ?Sized + 'a> {
+    drawing: Drawing,
+    writer: CodePairWriter<'a, T>,
+    write_handles: bool,
+    model_space_handle: u32,
+    handle_reserve: u32,
+    handle_limit: u32,
+}
+
+/// The number of handles a `DrawingWriter` reserves for streamed entities by default.
+pub const DEFAULT_HANDLE_RESERVE: u32 = 0x10_0000;
+
+impl<'a, T: Write + ?Sized + 'a> DrawingWriter<'a, T> {
+    /// Creates a new `DrawingWriter` that writes text DXF and writes everything that precedes the
+    /// streamed entities.
+    pub fn new(drawing: Drawing, writer: &'a mut T) -> DxfResult<Self> {
+        DrawingWriter::new_with_handle_reserve(drawing, writer, true, DEFAULT_HANDLE_RESERVE)
+    }
+    /// Creates a new `DrawingWriter` that writes binary DXF and writes everything that precedes the
+    /// streamed entities.
+    pub fn new_binary(drawing: Drawing, writer: &'a mut T) -> DxfResult<Self> {
+        DrawingWriter::new_with_handle_reserve(drawing, writer, false, DEFAULT_HANDLE_RESERVE)
+    }
+    /// The drawing used for the header, tables, and other non-entity sections.
+    pub fn drawing(&self) -> &Drawing {
+        &self.drawing
+    }
+    /// Writes a single entity, assigning it and its children handles if they don't already have
+    /// them.  The handles are assigned to the written copy; `entity` itself isn't modified.
+    pub fn write_entity(&mut self, entity: &Entity) -> DxfResult<()> {
+        let mut entity = entity.clone();
+        if entity.common.handle == 0 {
+            entity.common.handle = self.drawing.next_handle();
+        }
+        if entity.common.__owner_handle == 0 {
+            entity.common.__owner_handle = self.model_space_handle;
+        }
+
+        self.drawing.assign_child_handles(&mut entity);
+        if self.drawing.header.next_available_handle > self.handle_limit {
+            return Err(DxfError::HandlesExhausted(self.handle_reserve));
+        }
+
+        entity.write(
+            self.drawing.header.version,
+            self.write_handles,
+            &mut self.writer,
+        )
+    }
+    /// Ends the `ENTITIES` section and writes the remaining sections.  The output is incomplete
+    /// until this is called.
+    pub fn finish(mut self) -> DxfResult<()> {
+        self.writer
+            .write_code_pair(&CodePair::new_str(0, "ENDSEC"))?;
+        self.drawing
+            .write_sections_after_entities(true, &mut self.writer)
+    }
+    /// Creates a new `DrawingWriter` that reserves `handle_reserve` handles for streamed entities
+    /// and writes everything that precedes them, as text DXF if `as_ascii` is `true`, otherwise as
+    /// binary DXF.  The drawing is converted first if its version requires it, as it would be by
+    /// `Drawing::save()`.
+    pub fn new_with_handle_reserve(
+        mut drawing: Drawing,
+        writer: &'a mut T,
+        as_ascii: bool,
+        handle_reserve: u32,
+    ) -> DxfResult<Self> {
+        let version = drawing.header.version;
+        if drawing.needs_conversion(version, false) {
+            drawing.convert_to_version(version);
+        }
+
+        let text_as_ascii = version <= AcadVersion::R2004;
+        let mut writer = CodePairWriter::new(writer, as_ascii, text_as_ascii, version);
+        let write_handles = version >= AcadVersion::R13 || drawing.header.handles_enabled;
+        let model_space_handle = if version >= AcadVersion::R2000 {
+            drawing
+                .block_records()
+                .find(|r| r.name.eq_ignore_ascii_case("*Model_Space"))
+                .map_or(0, |r| r.handle)
+        } else {
+            0
+        };
+
+        // the header can't be rewritten, so `$HANDSEED` is written past the reserved handles
+        let first_handle = drawing.header.next_available_handle.max(1);
+        let handle_limit = first_handle.saturating_add(handle_reserve);
+        drawing.header.next_available_handle = handle_limit;
+        writer.write_prelude()?;
+        drawing.write_sections_before_entities(write_handles, false, &mut writer)?;
+        writer.write_code_pair(&CodePair::new_str(0, "SECTION"))?;
+        writer.write_code_pair(&CodePair::new_str(2, "ENTITIES"))?;
+        drawing.write_entities(write_handles, &mut writer)?;
+        drawing.header.next_available_handle = first_handle;
+        Ok(DrawingWriter {
+            drawing,
+            writer,
+            write_handles,
+            model_space_handle,
+            handle_reserve,
+            handle_limit,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::entities::*;
+    use crate::enums::*;
+    use crate::helper_functions::tests::*;
+    use crate::objects::*;
+    use crate::stream::*;
+    use crate::*;
+    use std::io::Cursor;
+
+    fn reader(lines: Vec<&str>) -> EntityReader<Cursor<Vec<u8>>> {
+        let contents = lines.join("\r\n");
+        EntityReader::new(Cursor::new(contents.into_bytes())).unwrap()
+    }
+
+    #[test]
+    fn read_header_and_tables_before_entities() {
+        let reader = reader(vec![
+            "0",
+            "SECTION",
+            "2",
+            "HEADER",
+            "9",
+            "$ACADVER",
+            "1",
+            "AC1015",
+            "0",
+            "ENDSEC",
+            "0",
+            "SECTION",
+            "2",
+            "TABLES",
+            "0",
+            "TABLE",
+            "2",
+            "LAYER",
+            "0",
+            "LAYER",
+            "2",
+            "some-layer",
+            "0",
+            "ENDTAB",
+            "0",
+            "ENDSEC",
+            "0",
+            "SECTION",
+            "2",
+            "ENTITIES",
+            "0",
+            "LINE",
+            "0",
+            "ENDSEC",
+            "0",
+            "EOF",
+        ]);
+        assert_eq!(AcadVersion::R2000, reader.header().version);
+        assert!(reader.drawing().layers().any(|l| l.name == "some-layer"));
+        assert_eq!(0, reader.drawing().entities().count());
+    }
+
+    #[test]
+    fn read_entities_one_at_a_time() {
+        let mut reader = reader(vec![
+            "0", "SECTION", "2", "ENTITIES", "0", "LINE", "10", "1.0", "0", "POLYLINE", "0",
+            "VERTEX", "0", "VERTEX", "0", "SEQEND", "0", "CIRCLE", "0", "ENDSEC", "0", "SECTION",
+            "2", "OBJECTS", "0", "ENDSEC", "0", "EOF",
+        ]);
+        match reader.next() {
+            Some(Ok(Entity {
+                specific: EntityType::Line(ref line),
+                ..
+            })) => assert_eq!(1.0, line.p1.x),
+            _ => panic!("expected a line"),
+        }
+        match reader.next() {
+            Some(Ok(Entity {
+                specific: EntityType::Polyline(ref poly),
+                ..
+            })) => assert_eq!(2, poly.vertices().count()),
+            _ => panic!("expected a polyline"),
+        }
+        match reader.next() {
+            Some(Ok(Entity {
+                specific: EntityType::Circle(_),
+                ..
+            })) => (),
+            _ => panic!("expected a circle"),
+        }
+        assert!(reader.next().is_none());
+    }
+
+    #[test]
+    fn read_drawing_without_entities_section() {
+        let mut reader = reader(vec![
+            "0", "SECTION", "2", "OBJECTS", "0", "ENDSEC", "0", "EOF",
+        ]);
+        assert!(reader.next().is_none());
+    }
+
+    #[test]
+    fn report_error_while_reading_entities() {
+        let reader = reader(vec![
+            "0",
+            "SECTION",
+            "2",
+            "ENTITIES",
+            "0",
+            "LINE",
+            "10",
+            "not-a-number",
+            "0",
+            "ENDSEC",
+            "0",
+            "EOF",
+        ]);
+        let results = reader.collect::<Vec<_>>();
+        assert_eq!(1, results.len());
+        assert!(results[0].is_err());
+    }
+
+    fn write_lines(drawing: Drawing, as_text: bool) -> Vec<u8> {
+        let mut buf = vec![];
+        {
+            let mut writer = if as_text {
+                DrawingWriter::new(drawing, &mut buf).unwrap()
+            } else {
+                DrawingWriter::new_binary(drawing, &mut buf).unwrap()
+            };
+            for i in 0..3 {
+                let line = Line::new(Point::new(f64::from(i), 0.0, 0.0), Point::origin());
+                writer
+                    .write_entity(&Entity::new(EntityType::Line(line)))
+                    .unwrap();
+            }
+            writer.finish().unwrap();
+        }
+        buf
+    }
+
+    #[test]
+    fn write_entities_one_at_a_time() {
+        let mut drawing = Drawing::new();
+        drawing.header.version = AcadVersion::R2000;
+        drawing.add_entity(Entity::new(EntityType::Circle(Default::default())));
+        drawing.add_object(Object::new(ObjectType::PlaceHolder(Default::default())));
+
+        let written = unwrap_drawing(Drawing::load(&mut write_lines(drawing, true).as_slice()));
+        let entities = written.entities().collect::<Vec<_>>();
+        assert_eq!(4, entities.len());
+        match entities[0].specific {
+            EntityType::Circle(_) => (),
+            _ => panic!("expected a circle"),
+        }
+        match entities[3].specific {
+            EntityType::Line(ref line) => assert_eq!(2.0, line.p1.x),
+            _ => panic!("expected a line"),
+        }
+        assert!(written
+            .objects()
+            .any(|o| matches!(o.specific, ObjectType::PlaceHolder(_))));
+    }
+
+    #[test]
+    fn write_entities_with_unique_handles() {
+        let mut drawing = Drawing::new();
+        drawing.header.version = AcadVersion::R2000;
+        let written = unwrap_drawing(Drawing::load(&mut write_lines(drawing, true).as_slice()));
+        let mut handles = written
+            .entities()
+            .map(|e| e.common.handle)
+            .collect::<Vec<_>>();
+        handles.sort();
+        handles.dedup();
+        assert_eq!(3, handles.len());
+        assert!(!handles.contains(&0));
+    }
+
+    #[test]
+    fn write_entities_as_binary() {
+        let mut drawing = Drawing::new();
+        drawing.header.version = AcadVersion::R2000;
+        let buf = write_lines(drawing, false);
+        assert!(buf.starts_with(b"AutoCAD Binary DXF"));
+        let written = unwrap_drawing(Drawing::load(&mut buf.as_slice()));
+        assert_eq!(3, written.entities().count());
+    }
+
+    #[test]
+    fn write_handle_seed_past_streamed_entities() {
+        let mut drawing = Drawing::new();
+        drawing.header.version = AcadVersion::R2000;
+        let written = unwrap_drawing(Drawing::load(&mut write_lines(drawing, true).as_slice()));
+        let max_handle = written.entities().map(|e| e.common.handle).max().unwrap();
+        assert!(written.header.next_available_handle > max_handle);
+
+        let model_space = written
+            .block_records()
+            .find(|r| r.name.eq_ignore_ascii_case("*Model_Space"))
+            .unwrap();
+        assert!(written
+            .entities()
+            .all(|e| e.common.__owner_handle == model_space.handle));
+    }
+
+    #[test]
+    fn write_entities_past_handle_reserve() {
+        let mut drawing = Drawing::new();
+        drawing.header.version = AcadVersion::R2000;
+        let mut buf = vec![];
+        let mut writer =
+            DrawingWriter::new_with_handle_reserve(drawing, &mut buf, true, 2).unwrap();
+        let line = || Entity::new(EntityType::Line(Default::default()));
+        assert!(writer.write_entity(&line()).is_ok());
+        assert!(writer.write_entity(&line()).is_ok());
+        match writer.write_entity(&line()) {
+            Err(DxfError::HandlesExhausted(2)) => (),
+            _ => panic!("expected the handles to be exhausted"),
+        }
+    }
+}
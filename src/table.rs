@@ -1,6 +1,6 @@
 use crate::helper_functions::*;
 use crate::tables::*;
-use crate::Color;
+use crate::{Color, XDataItem};
 
 //------------------------------------------------------------------------------
 //                                                                         Layer
@@ -14,6 +14,20 @@ impl Layer {
             _ => (),
         }
     }
+    /// Returns the layer's transparency as an alpha value from `0` (fully transparent) to `255`
+    /// (opaque).  AutoCAD stores this in the `AcCmTransparency` extended data of the layer.
+    pub fn get_alpha(&self) -> u8 {
+        self.x_data
+            .iter()
+            .filter(|x| x.application_name == "AcCmTransparency")
+            .flat_map(|x| x.items.iter())
+            .filter_map(|item| match item {
+                XDataItem::Long(value) => transparency_to_alpha(*value),
+                _ => None,
+            })
+            .next()
+            .unwrap_or(255)
+    }
 }
 
 //------------------------------------------------------------------------------
@@ -221,6 +235,54 @@ mod tests {
         assert!(!layer.is_layer_on);
     }
 
+    #[test]
+    fn read_layer_true_color() {
+        let drawing = read_table("LAYER", vec!["0", "LAYER", "62", "5", "420", "16744448"]);
+        let layers = drawing.layers().collect::<Vec<_>>();
+        let layer = layers[0];
+        assert_eq!(Some(5), layer.color.index());
+        assert_eq!(0xFF_80_00, layer.color_24_bit);
+    }
+
+    #[test]
+    fn read_layer_flags_and_transparency() {
+        let drawing = read_table(
+            "LAYER",
+            vec![
+                "0",
+                "LAYER",
+                "70",
+                "5",
+                "1001",
+                "AcCmTransparency",
+                "1071",
+                "33554559",
+            ],
+        );
+        let layers = drawing.layers().collect::<Vec<_>>();
+        let layer = layers[0];
+        assert!(layer.get_is_frozen());
+        assert!(!layer.get_is_frozen_in_new_viewports());
+        assert!(layer.get_is_locked());
+        assert_eq!(127, layer.get_alpha());
+        assert_eq!(255, Layer::default().get_alpha());
+    }
+
+    #[test]
+    fn write_layer_flags() {
+        let mut drawing = Drawing::new();
+        let mut layer = Layer {
+            name: String::from("layer-name"),
+            ..Default::default()
+        };
+        layer.set_is_frozen(true);
+        drawing.add_layer(layer);
+        assert_contains(
+            &drawing,
+            vec!["  2", "layer-name", " 70", "     1", " 62", "     7"].join("\r\n"),
+        );
+    }
+
     #[test]
     fn write_layer() {
         let mut drawing = Drawing::new();
@@ -1,4 +1,9 @@
-/// Applies a transformation to a point.
+use std::ops::Mul;
+
+use crate::{Point, Vector};
+
+/// Applies a transformation to a point.  Points are treated as column vectors, so the translation
+/// is stored in `m14`, `m24`, and `m34`.
 #[derive(Clone, Copy, Debug, Default, PartialEq)]
 #[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
 pub struct TransformationMatrix {
@@ -31,6 +36,113 @@ impl TransformationMatrix {
             ..Default::default()
         }
     }
+    /// Returns a matrix that applies this transformation followed by `next`.
+    pub fn compose(&self, next: &TransformationMatrix) -> Self {
+        next.multiply(self)
+    }
+    /// Returns a matrix that applies this transformation followed by a translation.
+    pub fn translate(&self, offset: &Vector) -> Self {
+        self.compose(&TransformationMatrix {
+            m14: offset.x,
+            m24: offset.y,
+            m34: offset.z,
+            ..TransformationMatrix::identity()
+        })
+    }
+    /// Returns a matrix that applies this transformation followed by a counter-clockwise rotation
+    /// of `angle` degrees about `axis`, where the axis passes through the origin.
+    pub fn rotate(&self, axis: &Vector, angle: f64) -> Self {
+        let axis = axis.normalize();
+        let (sin, cos) = angle.to_radians().sin_cos();
+        let t = 1.0 - cos;
+        let (x, y, z) = (axis.x, axis.y, axis.z);
+        self.compose(&TransformationMatrix {
+            m11: t * x * x + cos,
+            m12: t * x * y - sin * z,
+            m13: t * x * z + sin * y,
+            m21: t * x * y + sin * z,
+            m22: t * y * y + cos,
+            m23: t * y * z - sin * x,
+            m31: t * x * z - sin * y,
+            m32: t * y * z + sin * x,
+            m33: t * z * z + cos,
+            m44: 1.0,
+            ..Default::default()
+        })
+    }
+    /// Returns a matrix that applies this transformation followed by a scale about the origin.
+    pub fn scale(&self, x: f64, y: f64, z: f64) -> Self {
+        self.compose(&TransformationMatrix {
+            m11: x,
+            m22: y,
+            m33: z,
+            m44: 1.0,
+            ..Default::default()
+        })
+    }
+    /// Returns a matrix that applies this transformation followed by a reflection across the plane
+    /// through the origin with the specified normal.
+    pub fn mirror(&self, normal: &Vector) -> Self {
+        let n = normal.normalize();
+        self.compose(&TransformationMatrix {
+            m11: 1.0 - 2.0 * n.x * n.x,
+            m12: -2.0 * n.x * n.y,
+            m13: -2.0 * n.x * n.z,
+            m21: -2.0 * n.x * n.y,
+            m22: 1.0 - 2.0 * n.y * n.y,
+            m23: -2.0 * n.y * n.z,
+            m31: -2.0 * n.x * n.z,
+            m32: -2.0 * n.y * n.z,
+            m33: 1.0 - 2.0 * n.z * n.z,
+            m44: 1.0,
+            ..Default::default()
+        })
+    }
+    /// Returns the determinant of the matrix.
+    pub fn determinant(&self) -> f64 {
+        let (_, det) = self.adjugate();
+        det
+    }
+    /// Returns the inverse of the matrix, or `None` if the matrix is singular.
+    pub fn invert(&self) -> Option<Self> {
+        let (adjugate, det) = self.adjugate();
+        if det == 0.0 || !det.is_finite() {
+            return None;
+        }
+
+        let values = adjugate.iter().map(|v| v / det).collect::<Vec<_>>();
+        Some(TransformationMatrix::from_vec(&values))
+    }
+    /// Applies the transformation to a point.
+    pub fn transform_point(&self, point: &Point) -> Point {
+        let x = self.m11 * point.x + self.m12 * point.y + self.m13 * point.z + self.m14;
+        let y = self.m21 * point.x + self.m22 * point.y + self.m23 * point.z + self.m24;
+        let z = self.m31 * point.x + self.m32 * point.y + self.m33 * point.z + self.m34;
+        let w = self.m41 * point.x + self.m42 * point.y + self.m43 * point.z + self.m44;
+        if w == 1.0 || w == 0.0 {
+            Point::new(x, y, z)
+        } else {
+            Point::new(x / w, y / w, z / w)
+        }
+    }
+    /// Applies the transformation to a vector.  Unlike points, vectors are not affected by
+    /// translation.
+    pub fn transform_vector(&self, vector: &Vector) -> Vector {
+        Vector::new(
+            self.m11 * vector.x + self.m12 * vector.y + self.m13 * vector.z,
+            self.m21 * vector.x + self.m22 * vector.y + self.m23 * vector.z,
+            self.m31 * vector.x + self.m32 * vector.y + self.m33 * vector.z,
+        )
+    }
+}
+
+impl Mul for TransformationMatrix {
+    type Output = TransformationMatrix;
+
+    /// Multiplies two matrices.  The resulting matrix applies `rhs` first, then `self`.
+    fn mul(self, rhs: TransformationMatrix) -> TransformationMatrix {
+        self.multiply(&rhs)
+    }
 }
 
 // internal visibility only
@@ -71,6 +183,41 @@ impl TransformationMatrix {
 
 // private implementation
 impl TransformationMatrix {
+    fn multiply(&self, other: &TransformationMatrix) -> Self {
+        let a = self.get_values();
+        let b = other.get_values();
+        let mut values = vec![0.0; 16];
+        for row in 0..4 {
+            for col in 0..4 {
+                values[row * 4 + col] = (0..4).map(|i| a[row * 4 + i] * b[i * 4 + col]).sum();
+            }
+        }
+
+        TransformationMatrix::from_vec(&values)
+    }
+    /// Returns the transposed cofactor matrix in row-major order, along with the determinant.
+    fn adjugate(&self) -> (Vec<f64>, f64) {
+        let m = self.get_values();
+        let mut adjugate = vec![0.0; 16];
+        for row in 0..4 {
+            for col in 0..4 {
+                // the 3x3 minor that excludes `row` and `col`
+                let minor = (0..4)
+                    .filter(|&r| r != row)
+                    .flat_map(|r| (0..4).filter(|&c| c != col).map(move |c| (r, c)))
+                    .map(|(r, c)| m[r * 4 + c])
+                    .collect::<Vec<_>>();
+                let minor_det = minor[0] * (minor[4] * minor[8] - minor[5] * minor[7])
+                    - minor[1] * (minor[3] * minor[8] - minor[5] * minor[6])
+                    + minor[2] * (minor[3] * minor[7] - minor[4] * minor[6]);
+                let sign = if (row + col) % 2 == 0 { 1.0 } else { -1.0 };
+                adjugate[col * 4 + row] = sign * minor_det;
+            }
+        }
+
+        let det = (0..4).map(|i| m[i] * adjugate[i * 4]).sum();
+        (adjugate, det)
+    }
     fn get_value_or_default(values: &[f64], index: usize) -> f64 {
         if values.len() > index {
             values[index]
@@ -79,3 +226,77 @@ impl TransformationMatrix {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    fn assert_point(expected: Point, actual: Point) {
+        assert!(
+            (expected.x - actual.x).abs() < 1e-9
+                && (expected.y - actual.y).abs() < 1e-9
+                && (expected.z - actual.z).abs() < 1e-9,
+            "expected {:?} but was {:?}",
+            expected,
+            actual
+        );
+    }
+
+    #[test]
+    fn compose_applies_in_order() {
+        let matrix = TransformationMatrix::identity()
+            .translate(&Vector::new(1.0, 0.0, 0.0))
+            .rotate(&Vector::z_axis(), 90.0)
+            .scale(2.0, 2.0, 2.0);
+        assert_point(
+            Point::new(0.0, 4.0, 0.0),
+            matrix.transform_point(&Point::new(1.0, 0.0, 0.0)),
+        );
+    }
+
+    #[test]
+    fn multiply_applies_right_hand_side_first() {
+        let translate = TransformationMatrix::identity().translate(&Vector::new(1.0, 0.0, 0.0));
+        let scale = TransformationMatrix::identity().scale(3.0, 3.0, 3.0);
+        assert_eq!(translate.compose(&scale), scale * translate);
+    }
+
+    #[test]
+    fn transform_vector_ignores_translation() {
+        let matrix = TransformationMatrix::identity().translate(&Vector::new(5.0, 5.0, 5.0));
+        assert_eq!(
+            Vector::new(1.0, 2.0, 3.0),
+            matrix.transform_vector(&Vector::new(1.0, 2.0, 3.0))
+        );
+    }
+
+    #[test]
+    fn invert() {
+        let matrix = TransformationMatrix::identity()
+            .scale(2.0, 3.0, 4.0)
+            .rotate(&Vector::new(1.0, 1.0, 0.0), 30.0)
+            .translate(&Vector::new(1.0, -2.0, 3.0));
+        let inverse = matrix.invert().unwrap();
+        let p = Point::new(4.0, 5.0, 6.0);
+        assert_point(
+            p.clone(),
+            inverse.transform_point(&matrix.transform_point(&p)),
+        );
+        assert!((24.0 - matrix.determinant()).abs() < 1e-9);
+    }
+
+    #[test]
+    fn invert_singular_matrix() {
+        let matrix = TransformationMatrix::identity().scale(1.0, 0.0, 1.0);
+        assert_eq!(None, matrix.invert());
+    }
+
+    #[test]
+    fn mirror() {
+        let matrix = TransformationMatrix::identity().mirror(&Vector::new(1.0, 1.0, 0.0));
+        assert_point(
+            Point::new(-2.0, -1.0, 0.0),
+            matrix.transform_point(&Point::new(1.0, 2.0, 0.0)),
+        );
+    }
+}
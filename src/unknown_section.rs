@@ -0,0 +1,80 @@
+use std::io::{Read, Write};
+
+use crate::{CodePair, DxfResult};
+
+use crate::code_pair_put_back::CodePairPutBack;
+use crate::code_pair_writer::CodePairWriter;
+
+/// Represents a drawing section not otherwise supported by this library, e.g., `ACDSDATA`.  The
+/// section's code pairs are preserved verbatim and written back in their original position.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+pub struct UnknownSection {
+    /// The name of the section.
+    pub name: String,
+    /// The name of the supported section that preceded this one when the drawing was read, or an
+    /// empty string if this section came first.  Used to write the section back in its original
+    /// position.
+    pub preceding_section: String,
+    /// The code pairs contained in the section, not including the surrounding `0/SECTION`,
+    /// `2/<name>`, and `0/ENDSEC` pairs.
+    pub pairs: Vec<CodePair>,
+}
+
+// public implementation
+impl UnknownSection {
+    pub fn new(name: &str) -> Self {
+        UnknownSection {
+            name: String::from(name),
+            preceding_section: String::new(),
+            pairs: vec![],
+        }
+    }
+}
+
+// internal visibility only
+impl UnknownSection {
+    pub(crate) fn read<I>(
+        name: String,
+        preceding_section: String,
+        iter: &mut CodePairPutBack<I>,
+    ) -> DxfResult<UnknownSection>
+    where
+        I: Read,
+    {
+        let mut section = UnknownSection {
+            name,
+            preceding_section,
+            pairs: vec![],
+        };
+        loop {
+            match iter.next() {
+                Some(Ok(pair)) => {
+                    if pair.code == 0 && pair.assert_string()? == "ENDSEC" {
+                        iter.put_back(Ok(pair));
+                        break;
+                    }
+
+                    section.pairs.push(pair);
+                }
+                Some(Err(e)) => return Err(e),
+                None => break,
+            }
+        }
+
+        Ok(section)
+    }
+    pub(crate) fn write<T>(&self, writer: &mut CodePairWriter<T>) -> DxfResult<()>
+    where
+        T: Write + ?Sized,
+    {
+        writer.write_code_pair(&CodePair::new_str(0, "SECTION"))?;
+        writer.write_code_pair(&CodePair::new_str(2, &self.name))?;
+        for pair in &self.pairs {
+            writer.write_code_pair(pair)?;
+        }
+
+        writer.write_code_pair(&CodePair::new_str(0, "ENDSEC"))?;
+        Ok(())
+    }
+}
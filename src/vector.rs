@@ -1,4 +1,4 @@
-use crate::{CodePair, DxfError, DxfResult};
+use crate::{CodePair, DxfError, DxfResult, TransformationMatrix};
 
 /// Represents a simple vector in Cartesian space.
 #[derive(Clone, Debug, Default, PartialEq)]
@@ -33,6 +33,75 @@ impl Vector {
     pub fn z_axis() -> Vector {
         Vector::new(0.0, 0.0, 1.0)
     }
+    /// Returns the dot product of this and another vector.
+    pub fn dot(&self, other: &Vector) -> f64 {
+        self.x * other.x + self.y * other.y + self.z * other.z
+    }
+    /// Returns the cross product of this and another vector.
+    pub fn cross(&self, other: &Vector) -> Vector {
+        Vector::new(
+            self.y * other.z - self.z * other.y,
+            self.z * other.x - self.x * other.z,
+            self.x * other.y - self.y * other.x,
+        )
+    }
+    /// Returns the sum of this and another vector.
+    pub fn add(&self, other: &Vector) -> Vector {
+        Vector::new(self.x + other.x, self.y + other.y, self.z + other.z)
+    }
+    /// Returns this vector multiplied by `factor`.
+    pub fn scale(&self, factor: f64) -> Vector {
+        Vector::new(self.x * factor, self.y * factor, self.z * factor)
+    }
+    /// Returns the length of the vector.
+    pub fn length(&self) -> f64 {
+        self.dot(self).sqrt()
+    }
+    /// Returns a vector with the same direction and a length of 1.  A zero vector is returned as-is.
+    pub fn normalize(&self) -> Vector {
+        let length = self.length();
+        if length == 0.0 {
+            self.clone()
+        } else {
+            Vector::new(self.x / length, self.y / length, self.z / length)
+        }
+    }
+    /// Returns the X, Y, and Z axes of the object coordinate system (OCS) that has this vector as its
+    /// normal, as calculated by the arbitrary axis algorithm.
+    pub(crate) fn ocs_axes(&self) -> (Vector, Vector, Vector) {
+        let normal = self.normalize();
+        if normal == Vector::zero() {
+            return (Vector::x_axis(), Vector::y_axis(), Vector::z_axis());
+        }
+
+        let limit = 1.0 / 64.0;
+        let x_axis = if normal.x.abs() < limit && normal.y.abs() < limit {
+            Vector::y_axis().cross(&normal)
+        } else {
+            Vector::z_axis().cross(&normal)
+        }
+        .normalize();
+        let y_axis = normal.cross(&x_axis).normalize();
+        (x_axis, y_axis, normal)
+    }
+    /// Returns the matrix that converts points from the object coordinate system (OCS) that has
+    /// this vector as its normal to world coordinates (WCS).  The axes of the OCS are calculated
+    /// with the arbitrary axis algorithm.  Use `invert()` on the result to convert from WCS to OCS.
+    pub fn ocs_matrix(&self) -> TransformationMatrix {
+        let (x, y, z) = self.ocs_axes();
+        TransformationMatrix {
+            m11: x.x,
+            m12: y.x,
+            m13: z.x,
+            m21: x.y,
+            m22: y.y,
+            m23: z.y,
+            m31: x.z,
+            m32: y.z,
+            m33: z.z,
+            ..TransformationMatrix::identity()
+        }
+    }
     pub(crate) fn set(&mut self, pair: &CodePair) -> DxfResult<()> {
         match pair.code {
             10 => self.x = pair.assert_f64()?,
@@ -49,3 +118,36 @@ impl Vector {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn ocs_matrix_of_z_axis_is_identity() {
+        assert_eq!(
+            TransformationMatrix::identity(),
+            Vector::z_axis().ocs_matrix()
+        );
+    }
+
+    #[test]
+    fn ocs_matrix_of_negative_z_axis() {
+        // the arbitrary axis algorithm uses Wy x N near the Z axis
+        let matrix = Vector::new(0.0, 0.0, -1.0).ocs_matrix();
+        assert_eq!(
+            Point::new(-1.0, 2.0, -3.0),
+            matrix.transform_point(&Point::new(1.0, 2.0, 3.0))
+        );
+    }
+
+    #[test]
+    fn ocs_matrix_of_x_axis() {
+        // Wz x N = Y, N x Y = Z
+        let matrix = Vector::x_axis().ocs_matrix();
+        assert_eq!(
+            Point::new(3.0, 1.0, 2.0),
+            matrix.transform_point(&Point::new(1.0, 2.0, 3.0))
+        );
+    }
+}
@@ -0,0 +1,1138 @@
+use std::collections::HashMap;
+use std::f64::consts::PI;
+use std::fmt;
+
+use crate::drawing::AUTO_REPLACE_HANDLE;
+use crate::entities::*;
+use crate::enums::*;
+use crate::helper_functions::strip_m_text_formatting;
+use crate::objects::*;
+use crate::tables::BlockRecord;
+use crate::{Block, BoundingBox, Class, Drawing, Point, TransformationMatrix, Vector};
+
+/// Curves are approximated to within this fraction of their size.
+const APPROXIMATION_TOLERANCE: f64 = 1.0e-3;
+
+/// The record name, C++ class name, and whether it's an entity, of the items that need a `Class`
+/// in R2000 and later.
+const CLASSES: [(&str, &str, bool); 15] = [
+    ("ACDBDICTIONARYWDFLT", "AcDbDictionaryWithDefault", false),
+    ("ACDBPLACEHOLDER", "AcDbPlaceHolder", false),
+    ("DICTIONARYVAR", "AcDbDictionaryVar", false),
+    ("IMAGE", "AcDbRasterImage", true),
+    ("IMAGEDEF", "AcDbRasterImageDef", false),
+    ("IMAGEDEF_REACTOR", "AcDbRasterImageDefReactor", false),
+    ("LAYOUT", "AcDbLayout", false),
+    ("MATERIAL", "AcDbMaterial", false),
+    ("MLEADERSTYLE", "AcDbMLeaderStyle", false),
+    ("MULTILEADER", "AcDbMLeader", true),
+    ("RASTERVARIABLES", "AcDbRasterVariables", false),
+    ("SORTENTSTABLE", "AcDbSortentsTable", false),
+    ("TABLESTYLE", "AcDbTableStyle", false),
+    ("VISUALSTYLE", "AcDbVisualStyle", false),
+    ("WIPEOUT", "AcDbWipeout", true),
+];
+
+/// A change made by `Drawing::convert_to_version()`.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+pub struct ConversionNote {
+    /// The handle of the entity or object that was converted or removed.
+    pub handle: u32,
+    /// A description of the change, e.g., `LWPOLYLINE converted to POLYLINE`.
+    pub description: String,
+    /// `true` if information was lost, e.g., a curve was approximated or an object was removed.
+    pub is_lossy: bool,
+}
+
+impl fmt::Display for ConversionNote {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        if self.handle != 0 {
+            write!(formatter, "[{:X}] ", self.handle)?;
+        }
+        write!(formatter, "{}", self.description)?;
+        if self.is_lossy {
+            write!(formatter, " (lossy)")?;
+        }
+        Ok(())
+    }
+}
+
+impl Drawing {
+    /// Returns `true` if the drawing needs to be upgraded, or if any entity or object needs to be
+    /// converted or removed, to be written as `version`.  A drawing is upgraded when it's written as
+    /// a newer version, or, if `build_object_graph` is set, as R2000 or later without an object
+    /// graph.
+    pub(crate) fn needs_conversion(&self, version: AcadVersion, build_object_graph: bool) -> bool {
+        let source_version = self.header.version;
+        let needs_conversion = |e: &Entity| !is_entity_supported(e, source_version, version);
+        let is_missing_object_graph = build_object_graph && self.root_dictionary_handle().is_none();
+        let is_upgrade = version >= AcadVersion::R2000
+            && (version > self.header.version || is_missing_object_graph);
+        is_upgrade
+            || self.entities().any(needs_conversion)
+            || self
+                .blocks()
+                .any(|b| b.entities.iter().any(needs_conversion))
+            || self
+                .objects()
+                .any(|o| !is_object_supported(o, source_version, version))
+    }
+    pub(crate) fn convert_entities(
+        &mut self,
+        entities: Vec<Entity>,
+        version: AcadVersion,
+        notes: &mut Vec<ConversionNote>,
+    ) -> Vec<Entity> {
+        let mut result = vec![];
+        for entity in entities {
+            if is_entity_supported(&entity, self.header.version, version) {
+                result.push(entity);
+                continue;
+            }
+
+            let type_string = entity.specific.to_type_string().to_string();
+            let (replacements, is_lossy) = convert_entity(&entity);
+            let description = match replacements.first() {
+                None => format!("{} removed", type_string),
+                Some(first) => {
+                    let count = if replacements.len() == 1 {
+                        String::new()
+                    } else {
+                        format!("{} ", replacements.len())
+                    };
+                    format!(
+                        "{} converted to {}{}",
+                        type_string,
+                        count,
+                        first.specific.to_type_string()
+                    )
+                }
+            };
+            notes.push(ConversionNote {
+                handle: entity.common.handle,
+                description,
+                is_lossy,
+            });
+            for (i, mut replacement) in replacements.into_iter().enumerate() {
+                // the first replacement takes the place of the original entity
+                replacement.common.handle = if i == 0 {
+                    entity.common.handle
+                } else {
+                    self.next_handle()
+                };
+                self.assign_child_handles(&mut replacement);
+                result.push(replacement);
+            }
+        }
+
+        result
+    }
+}
+
+impl Drawing {
+    /// Adds the items that R2000 and later expect, linking them to each other and to the existing
+    /// items.
+    pub(crate) fn build_object_graph(&mut self, notes: &mut Vec<ConversionNote>) {
+        // R12 names the model and paper space blocks `$MODEL_SPACE` and `$PAPER_SPACE`
+        for block in self.blocks_mut() {
+            if block.name.eq_ignore_ascii_case("$MODEL_SPACE") {
+                block.name = String::from("*Model_Space");
+            } else if block.name.eq_ignore_ascii_case("$PAPER_SPACE") {
+                block.name = String::from("*Paper_Space");
+            }
+        }
+        for name in &["*Model_Space", "*Paper_Space"] {
+            if !self.blocks().any(|b| b.name.eq_ignore_ascii_case(name)) {
+                let handle = self.next_handle();
+                self.add_block_no_handle_set(Block {
+                    handle,
+                    name: String::from(*name),
+                    ..Default::default()
+                });
+                notes.push(added(handle, format!("BLOCK {}", name)));
+            }
+        }
+
+        // every block is owned by the block record with the same name
+        let block_names = self.blocks().map(|b| b.name.clone()).collect::<Vec<_>>();
+        let mut record_handles = HashMap::new();
+        for name in block_names {
+            let handle = self.ensure_block_record(&name, notes);
+            record_handles.insert(name.to_uppercase(), handle);
+        }
+        for block in self.blocks_mut() {
+            let record_handle = record_handles[&block.name.to_uppercase()];
+            set_if_unset(&mut block.__owner_handle, record_handle);
+            for entity in block.entities.iter_mut() {
+                set_if_unset(&mut entity.common.__owner_handle, record_handle);
+            }
+        }
+        let model_space = record_handles["*MODEL_SPACE"];
+        let paper_space = record_handles["*PAPER_SPACE"];
+        for entity in self.entities_mut() {
+            let owner = if entity.common.is_in_paper_space {
+                paper_space
+            } else {
+                model_space
+            };
+            set_if_unset(&mut entity.common.__owner_handle, owner);
+        }
+
+        let root = self.ensure_root_dictionary(notes);
+        let groups = self.ensure_dictionary_entry(root, "ACAD_GROUP", notes);
+        let layouts = self.ensure_dictionary_entry(root, "ACAD_LAYOUT", notes);
+        self.ensure_layout(model_space, "Model", 0, layouts, notes);
+        self.ensure_layout(paper_space, "Layout1", 1, layouts, notes);
+
+        // existing layouts and groups are listed in their dictionaries
+        let mut unlisted = vec![];
+        for object in self.objects() {
+            let dictionary = match object.specific {
+                ObjectType::Layout(ref layout) => Some((layouts, layout.layout_name.clone())),
+                ObjectType::Group(_) => Some((groups, String::new())),
+                _ => None,
+            };
+            if let Some((dictionary, name)) = dictionary {
+                let is_listed = self
+                    .dictionary_entries(dictionary)
+                    .iter()
+                    .any(|entries| entries.values().any(|h| *h == object.common.handle));
+                if !is_listed {
+                    unlisted.push((object.common.handle, dictionary, name));
+                }
+            }
+        }
+        for (handle, dictionary, name) in unlisted {
+            self.add_dictionary_entry(dictionary, &name, handle);
+        }
+
+        self.ensure_classes(notes);
+    }
+    /// Returns the handle of the block record with the specified name, ignoring case, adding it if
+    /// necessary.
+    pub(crate) fn ensure_block_record(
+        &mut self,
+        name: &str,
+        notes: &mut Vec<ConversionNote>,
+    ) -> u32 {
+        if let Some(record) = self
+            .block_records()
+            .find(|r| r.name.eq_ignore_ascii_case(name))
+        {
+            return record.handle;
+        }
+
+        let handle = self.next_handle();
+        self.add_block_record_no_handle_set(BlockRecord {
+            name: String::from(name),
+            handle,
+            ..Default::default()
+        });
+        notes.push(added(handle, format!("BLOCK_RECORD {}", name)));
+        handle
+    }
+    /// Returns the handle of the root dictionary, which is the first object, adding it if
+    /// necessary.
+    pub(crate) fn ensure_root_dictionary(&mut self, notes: &mut Vec<ConversionNote>) -> u32 {
+        if let Some(handle) = self.root_dictionary_handle() {
+            return handle;
+        }
+
+        let handle = self.add_object_with_owner(ObjectType::Dictionary(Default::default()), 0);
+        self.move_object_to_front(handle);
+        notes.push(added(handle, String::from("root DICTIONARY")));
+        handle
+    }
+    /// Returns the handle of the dictionary stored in `parent` under `name`, adding it if
+    /// necessary.
+    pub(crate) fn ensure_dictionary_entry(
+        &mut self,
+        parent: u32,
+        name: &str,
+        notes: &mut Vec<ConversionNote>,
+    ) -> u32 {
+        let existing = self
+            .dictionary_entries(parent)
+            .and_then(|entries| entries.get(name).cloned());
+        if let Some(handle) = existing {
+            if self.objects().any(|o| o.common.handle == handle) {
+                return handle;
+            }
+        }
+
+        let handle = self.add_object_with_owner(ObjectType::Dictionary(Default::default()), parent);
+        self.add_dictionary_entry(parent, name, handle);
+        notes.push(added(handle, format!("DICTIONARY {}", name)));
+        handle
+    }
+    /// Returns the handle of the block record's `Layout`, adding one with the specified name if
+    /// necessary.
+    pub(crate) fn ensure_layout(
+        &mut self,
+        record_handle: u32,
+        name: &str,
+        tab_order: i32,
+        dictionary: u32,
+        notes: &mut Vec<ConversionNote>,
+    ) -> u32 {
+        let existing = self.objects().find_map(|o| match o.specific {
+            ObjectType::Layout(ref layout) if layout.__table_record_handle == record_handle => {
+                Some(o.common.handle)
+            }
+            _ => None,
+        });
+        let handle = match existing {
+            Some(handle) => handle,
+            None => {
+                let layout = Layout {
+                    layout_name: String::from(name),
+                    tab_order,
+                    __table_record_handle: record_handle,
+                    ..Default::default()
+                };
+                let handle = self.add_object_with_owner(ObjectType::Layout(layout), dictionary);
+                self.add_dictionary_entry(dictionary, name, handle);
+                notes.push(added(handle, format!("LAYOUT {}", name)));
+                handle
+            }
+        };
+        if let Some(record) = self.block_records_mut().find(|r| r.handle == record_handle) {
+            set_if_unset(&mut record.__layout_handle, handle);
+        }
+
+        handle
+    }
+    /// Adds a `Class` for each kind of item in the drawing that needs one.
+    fn ensure_classes(&mut self, notes: &mut Vec<ConversionNote>) {
+        let mut counts = HashMap::new();
+        let entity_type_strings = self
+            .entities()
+            .chain(self.blocks().flat_map(|b| b.entities.iter()))
+            .map(|e| e.specific.to_type_string());
+        let object_type_strings = self.objects().map(|o| o.specific.to_type_string());
+        for type_string in entity_type_strings.chain(object_type_strings) {
+            *counts.entry(type_string.to_string()).or_insert(0) += 1;
+        }
+
+        for (record_name, class_name, is_entity) in CLASSES.iter() {
+            let count = match counts.get(*record_name) {
+                Some(count) => *count,
+                None => continue,
+            };
+            if self
+                .classes
+                .iter()
+                .any(|c| c.record_name.eq_ignore_ascii_case(record_name))
+            {
+                continue;
+            }
+
+            self.classes.push(Class {
+                record_name: String::from(*record_name),
+                class_name: String::from(*class_name),
+                application_name: String::from("ObjectDBX Classes"),
+                instance_count: count,
+                is_entity: *is_entity,
+                ..Default::default()
+            });
+            notes.push(added(0, format!("CLASS {}", record_name)));
+        }
+    }
+    fn add_object_with_owner(&mut self, specific: ObjectType, owner: u32) -> u32 {
+        let handle = self.next_handle();
+        self.add_object_no_handle_set(Object {
+            common: ObjectCommon {
+                handle,
+                __owner_handle: owner,
+                ..Default::default()
+            },
+            specific,
+        });
+        handle
+    }
+}
+
+fn added(handle: u32, item: String) -> ConversionNote {
+    ConversionNote {
+        handle,
+        description: format!("{} added", item),
+        is_lossy: false,
+    }
+}
+
+fn set_if_unset(handle: &mut u32, value: u32) {
+    if *handle == 0 {
+        *handle = value;
+    }
+}
+
+/// Returns `true` if `entity`, read as part of a `source_version` drawing, can be written as
+/// `version`.  The versions that support an unknown entity can't be known, so it's only kept when
+/// the drawing isn't written as an older version.
+fn is_entity_supported(entity: &Entity, source_version: AcadVersion, version: AcadVersion) -> bool {
+    match entity.specific {
+        EntityType::Unknown { .. } => version >= source_version,
+        ref specific => specific.is_supported_on_version(version),
+    }
+}
+
+/// Returns `true` if `object`, read as part of a `source_version` drawing, can be written as
+/// `version`.  Unknown objects are treated as unknown entities are.
+pub(crate) fn is_object_supported(
+    object: &Object,
+    source_version: AcadVersion,
+    version: AcadVersion,
+) -> bool {
+    match object.specific {
+        ObjectType::Unknown { .. } => version >= source_version,
+        ref specific => specific.is_supported_on_version(version),
+    }
+}
+
+/// Returns the entities that replace an unsupported `entity`, and whether information
+/// was lost.  An empty result means the entity is removed.
+fn convert_entity(entity: &Entity) -> (Vec<Entity>, bool) {
+    let replace = |specific: EntityType| {
+        let mut common = entity.common.clone();
+        common.elevation = 0.0;
+        Entity { common, specific }
+    };
+    match entity.specific {
+        EntityType::LwPolyline(ref poly) => {
+            let polyline = from_lw_polyline(poly, entity.common.elevation);
+            (vec![replace(EntityType::Polyline(polyline))], false)
+        }
+        EntityType::Ellipse(ref ellipse) => match ellipse_as_circular_entity(ellipse) {
+            Some(specific) => (vec![replace(specific)], false),
+            None => {
+                let polyline = ellipse_as_polyline(ellipse);
+                (vec![replace(EntityType::Polyline(polyline))], true)
+            }
+        },
+        EntityType::Spline(ref spline) => match spline_as_polyline(spline) {
+            Some(polyline) => (vec![replace(EntityType::Polyline(polyline))], true),
+            None => (vec![], true),
+        },
+        EntityType::MText(ref mtext) => {
+            let lines = m_text_as_text(mtext)
+                .into_iter()
+                .map(|t| replace(EntityType::Text(t)))
+                .collect();
+            (lines, true)
+        }
+        _ => (vec![], true),
+    }
+}
+
+fn from_lw_polyline(poly: &LwPolyline, elevation: f64) -> Polyline {
+    let mut polyline = Polyline {
+        location: Point::new(0.0, 0.0, elevation),
+        thickness: poly.thickness,
+        default_starting_width: poly.constant_width,
+        default_ending_width: poly.constant_width,
+        normal: poly.extrusion_direction.clone(),
+        ..Default::default()
+    };
+    polyline.set_is_closed(poly.get_is_closed());
+    polyline.set_is_line_type_pattern_generated_continuously(poly.get_is_pline_gen());
+    for v in &poly.vertices {
+        let is_width_set = v.starting_width != 0.0 || v.ending_width != 0.0;
+        let vertex = Vertex {
+            location: Point::new(v.x, v.y, 0.0),
+            bulge: v.bulge,
+            starting_width: if is_width_set {
+                v.starting_width
+            } else {
+                poly.constant_width
+            },
+            ending_width: if is_width_set {
+                v.ending_width
+            } else {
+                poly.constant_width
+            },
+            ..Default::default()
+        };
+        polyline
+            .__vertices_and_handles
+            .push((vertex, AUTO_REPLACE_HANDLE));
+    }
+    polyline
+}
+
+/// Returns the sweep of the ellipse in radians, from 0 through 2π.
+fn ellipse_sweep(ellipse: &Ellipse) -> f64 {
+    let mut sweep = (ellipse.end_parameter - ellipse.start_parameter) % (2.0 * PI);
+    if sweep <= 0.0 {
+        sweep += 2.0 * PI;
+    }
+    sweep
+}
+
+fn is_full_turn(sweep: f64) -> bool {
+    (sweep - 2.0 * PI).abs() < 1.0e-9
+}
+
+/// Returns the equivalent `Circle` or `Arc` of an ellipse whose axes have the same length.
+fn ellipse_as_circular_entity(ellipse: &Ellipse) -> Option<EntityType> {
+    if (ellipse.minor_axis_ratio - 1.0).abs() > 1.0e-9 {
+        return None;
+    }
+
+    let to_ocs = ellipse.normal.ocs_matrix().invert()?;
+    let center = to_ocs.transform_point(&ellipse.center);
+    let major_axis = to_ocs.transform_vector(&ellipse.major_axis);
+    let radius = ellipse.major_axis.length();
+    let sweep = ellipse_sweep(ellipse);
+    if is_full_turn(sweep) {
+        let circle = Circle {
+            normal: ellipse.normal.clone(),
+            ..Circle::new(center, radius)
+        };
+        return Some(EntityType::Circle(circle));
+    }
+
+    let rotation = major_axis.y.atan2(major_axis.x);
+    let start = ellipse.start_parameter + rotation;
+    let arc = Arc {
+        normal: ellipse.normal.clone(),
+        ..Arc::new(
+            center,
+            radius,
+            start.to_degrees(),
+            (start + sweep).to_degrees(),
+        )
+    };
+    Some(EntityType::Arc(arc))
+}
+
+fn ellipse_as_polyline(ellipse: &Ellipse) -> Polyline {
+    let x_axis = ellipse.major_axis.clone();
+    let y_axis = ellipse.normal.normalize().cross(&x_axis);
+    let y_axis = Vector::new(
+        y_axis.x * ellipse.minor_axis_ratio,
+        y_axis.y * ellipse.minor_axis_ratio,
+        y_axis.z * ellipse.minor_axis_ratio,
+    );
+    let max_step = 2.0 * (1.0 - APPROXIMATION_TOLERANCE).acos();
+    let sweep = ellipse_sweep(ellipse);
+    let pieces = (sweep / max_step)
+        .max(sweep / (PI / 2.0))
+        .ceil()
+        .clamp(1.0, 4096.0) as usize;
+    let mut points = (0..=pieces)
+        .map(|i| {
+            let angle = ellipse.start_parameter + sweep * i as f64 / pieces as f64;
+            let (sin, cos) = angle.sin_cos();
+            Point::new(
+                ellipse.center.x + x_axis.x * cos + y_axis.x * sin,
+                ellipse.center.y + x_axis.y * cos + y_axis.y * sin,
+                ellipse.center.z + x_axis.z * cos + y_axis.z * sin,
+            )
+        })
+        .collect::<Vec<_>>();
+    let is_closed = is_full_turn(sweep);
+    if is_closed {
+        points.pop();
+    }
+
+    polyline_from_points(&points, &ellipse.normal, is_closed)
+}
+
+fn spline_as_polyline(spline: &Spline) -> Option<Polyline> {
+    let bounding_box =
+        BoundingBox::from_points(spline.control_points.iter().chain(spline.fit_points.iter()))?;
+    let size = bounding_box.size().length();
+    let tolerance = if size > 0.0 {
+        size * APPROXIMATION_TOLERANCE
+    } else {
+        APPROXIMATION_TOLERANCE
+    };
+    let mut points = spline.tessellate(tolerance);
+    if points.len() < 2 {
+        return None;
+    }
+
+    let is_closed = spline.get_is_closed() && points.first() == points.last();
+    if is_closed {
+        points.pop();
+    }
+
+    Some(polyline_from_points(&points, &spline.normal, is_closed))
+}
+
+/// Creates a 2D `Polyline` from world coordinate points that lie in the plane of `normal`, or a 3D
+/// `Polyline` if they don't.
+fn polyline_from_points(points: &[Point], normal: &Vector, is_closed: bool) -> Polyline {
+    let normal = if *normal == Vector::zero() {
+        Vector::z_axis()
+    } else {
+        normal.clone()
+    };
+    let to_ocs = normal
+        .ocs_matrix()
+        .invert()
+        .unwrap_or_else(TransformationMatrix::identity);
+    let ocs_points = points
+        .iter()
+        .map(|p| to_ocs.transform_point(p))
+        .collect::<Vec<_>>();
+    let elevation = ocs_points.first().map_or(0.0, |p| p.z);
+    let is_planar = ocs_points
+        .iter()
+        .all(|p| (p.z - elevation).abs() <= 1.0e-9 * (1.0 + elevation.abs()));
+
+    let mut polyline = Polyline::default();
+    polyline.set_is_closed(is_closed);
+    let vertices = if is_planar {
+        polyline.location = Point::new(0.0, 0.0, elevation);
+        polyline.normal = normal;
+        ocs_points
+            .into_iter()
+            .map(|p| Vertex::new(Point::new(p.x, p.y, 0.0)))
+            .collect::<Vec<_>>()
+    } else {
+        polyline.set_is_3d_polyline(true);
+        points.iter().map(|p| Vertex::new(p.clone())).collect()
+    };
+    for vertex in vertices {
+        polyline
+            .__vertices_and_handles
+            .push((vertex, AUTO_REPLACE_HANDLE));
+    }
+    polyline
+}
+
+/// Splits an `MText` into one `Text` per line, laid out as `MText` would.
+fn m_text_as_text(mtext: &MText) -> Vec<Text> {
+    let mut value = mtext.extended_text.join("");
+    value.push_str(&mtext.text);
+    let value = strip_m_text_formatting(&value);
+    let lines = value.split('\n').collect::<Vec<_>>();
+    let height = mtext.initial_text_height;
+    let line_spacing_factor = if mtext.line_spacing_factor > 0.0 {
+        mtext.line_spacing_factor
+    } else {
+        1.0
+    };
+    // successive lines are spaced 5/3 of the text height apart
+    let spacing = height * 5.0 / 3.0 * line_spacing_factor;
+    let total_height = height + spacing * (lines.len() - 1) as f64;
+
+    let normal = if mtext.extrusion_direction == Vector::zero() {
+        Vector::z_axis()
+    } else {
+        mtext.extrusion_direction.clone()
+    };
+    let to_wcs = normal.ocs_matrix();
+    let to_ocs = to_wcs
+        .invert()
+        .unwrap_or_else(TransformationMatrix::identity);
+    let x_axis = if mtext.x_axis_direction.length() > 0.0 {
+        to_ocs.transform_vector(&mtext.x_axis_direction)
+    } else {
+        let (sin, cos) = mtext.rotation_angle.sin_cos();
+        Vector::new(cos, sin, 0.0)
+    };
+    let x_axis = Vector::new(x_axis.x, x_axis.y, 0.0).normalize();
+    let y_axis = Vector::new(-x_axis.y, x_axis.x, 0.0);
+    let insertion_point = to_ocs.transform_point(&mtext.insertion_point);
+
+    let (horizontal_justification, top) = match mtext.attachment_point {
+        AttachmentPoint::TopLeft => (HorizontalTextJustification::Left, 0.0),
+        AttachmentPoint::TopCenter => (HorizontalTextJustification::Center, 0.0),
+        AttachmentPoint::TopRight => (HorizontalTextJustification::Right, 0.0),
+        AttachmentPoint::MiddleLeft => (HorizontalTextJustification::Left, -total_height / 2.0),
+        AttachmentPoint::MiddleCenter => (HorizontalTextJustification::Center, -total_height / 2.0),
+        AttachmentPoint::MiddleRight => (HorizontalTextJustification::Right, -total_height / 2.0),
+        AttachmentPoint::BottomLeft => (HorizontalTextJustification::Left, -total_height),
+        AttachmentPoint::BottomCenter => (HorizontalTextJustification::Center, -total_height),
+        AttachmentPoint::BottomRight => (HorizontalTextJustification::Right, -total_height),
+    };
+
+    let mut result = vec![];
+    for (i, line) in lines.iter().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        // the baseline of the line, measured downwards from the insertion point
+        let offset = top + height + spacing * i as f64;
+        let location = Point::new(
+            insertion_point.x - y_axis.x * offset,
+            insertion_point.y - y_axis.y * offset,
+            insertion_point.z,
+        );
+        let mut text = Text {
+            location: location.clone(),
+            text_height: height,
+            value: String::from(*line),
+            rotation: x_axis.y.atan2(x_axis.x).to_degrees(),
+            text_style_name: mtext.text_style_name.clone(),
+            horizontal_text_justification: horizontal_justification,
+            normal: normal.clone(),
+            ..Default::default()
+        };
+        if horizontal_justification != HorizontalTextJustification::Left {
+            text.second_alignment_point = location;
+        }
+        result.push(text);
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::entities::*;
+    use crate::enums::*;
+    use crate::objects::*;
+    use crate::*;
+    use std::collections::HashMap;
+    use std::f64::consts::PI;
+
+    fn assert_close(expected: f64, actual: f64) {
+        assert!(
+            (expected - actual).abs() < 1.0e-9,
+            "expected {} but was {}",
+            expected,
+            actual
+        );
+    }
+
+    fn converted(specific: EntityType) -> (Vec<Entity>, Vec<ConversionNote>) {
+        let mut drawing = Drawing::new();
+        drawing.header.version = AcadVersion::R2000;
+        drawing.add_entity(Entity::new(specific));
+        let notes = drawing.convert_to_version(AcadVersion::R12);
+        assert_eq!(AcadVersion::R12, drawing.header.version);
+        (drawing.entities().cloned().collect(), notes)
+    }
+
+    #[test]
+    fn lw_polyline_becomes_polyline() {
+        let mut poly = LwPolyline {
+            constant_width: 0.5,
+            ..Default::default()
+        };
+        poly.set_is_closed(true);
+        poly.vertices.push(LwPolylineVertex {
+            x: 1.0,
+            y: 2.0,
+            bulge: 1.0,
+            ..Default::default()
+        });
+        poly.vertices.push(LwPolylineVertex {
+            x: 3.0,
+            y: 4.0,
+            ..Default::default()
+        });
+        let mut entity = Entity::new(EntityType::LwPolyline(poly));
+        entity.common.elevation = 7.0;
+        entity.common.layer = String::from("outline");
+        let mut drawing = Drawing::new();
+        drawing.header.version = AcadVersion::R2000;
+        drawing.add_entity(entity);
+        let handle = drawing.entities().next().unwrap().common.handle;
+
+        let notes = drawing.convert_to_version(AcadVersion::R12);
+        assert_eq!(1, notes.len());
+        assert_eq!(handle, notes[0].handle);
+        assert_eq!("LWPOLYLINE converted to POLYLINE", notes[0].description);
+        assert!(!notes[0].is_lossy);
+
+        let entity = drawing.entities().next().unwrap();
+        assert_eq!(handle, entity.common.handle);
+        assert_eq!("outline", entity.common.layer);
+        match entity.specific {
+            EntityType::Polyline(ref poly) => {
+                assert!(poly.get_is_closed());
+                assert_eq!(7.0, poly.location.z);
+                let vertices = poly.vertices().collect::<Vec<_>>();
+                assert_eq!(2, vertices.len());
+                assert_eq!(Point::new(1.0, 2.0, 0.0), vertices[0].location);
+                assert_eq!(1.0, vertices[0].bulge);
+                assert_eq!(0.5, vertices[1].starting_width);
+                assert!(poly.__vertices_and_handles.iter().all(|v| v.1 != 0
+                    && v.1 != handle
+                    && v.1 != crate::drawing::AUTO_REPLACE_HANDLE));
+            }
+            _ => panic!("expected a polyline"),
+        }
+    }
+
+    #[test]
+    fn circular_ellipse_becomes_arc() {
+        let ellipse = Ellipse {
+            center: Point::new(1.0, 1.0, 0.0),
+            major_axis: Vector::new(0.0, 2.0, 0.0),
+            minor_axis_ratio: 1.0,
+            start_parameter: 0.0,
+            end_parameter: PI / 2.0,
+            ..Default::default()
+        };
+        let (entities, notes) = converted(EntityType::Ellipse(ellipse));
+        assert!(!notes[0].is_lossy);
+        match entities[0].specific {
+            EntityType::Arc(ref arc) => {
+                assert_eq!(Point::new(1.0, 1.0, 0.0), arc.center);
+                assert_close(2.0, arc.radius);
+                assert_close(90.0, arc.start_angle);
+                assert_close(180.0, arc.end_angle);
+            }
+            _ => panic!("expected an arc"),
+        }
+    }
+
+    #[test]
+    fn ellipse_is_approximated_with_polyline() {
+        let ellipse = Ellipse {
+            center: Point::new(0.0, 0.0, 3.0),
+            major_axis: Vector::new(4.0, 0.0, 0.0),
+            minor_axis_ratio: 0.5,
+            start_parameter: 0.0,
+            end_parameter: 2.0 * PI,
+            ..Default::default()
+        };
+        let (entities, notes) = converted(EntityType::Ellipse(ellipse));
+        assert_eq!("ELLIPSE converted to POLYLINE", notes[0].description);
+        assert!(notes[0].is_lossy);
+        match entities[0].specific {
+            EntityType::Polyline(ref poly) => {
+                assert!(poly.get_is_closed());
+                assert!(!poly.get_is_3d_polyline());
+                assert_eq!(3.0, poly.location.z);
+                for v in poly.vertices() {
+                    let (x, y) = (v.location.x / 4.0, v.location.y / 2.0);
+                    assert_close(1.0, x * x + y * y);
+                }
+                assert!(poly.vertices().count() > 16);
+            }
+            _ => panic!("expected a polyline"),
+        }
+    }
+
+    #[test]
+    fn spline_is_approximated_with_polyline() {
+        let spline = Spline {
+            degree_of_curve: 2,
+            control_points: vec![
+                Point::new(0.0, 0.0, 0.0),
+                Point::new(1.0, 1.0, 0.0),
+                Point::new(2.0, 0.0, 0.0),
+            ],
+            ..Default::default()
+        };
+        let (entities, notes) = converted(EntityType::Spline(spline));
+        assert!(notes[0].is_lossy);
+        match entities[0].specific {
+            EntityType::Polyline(ref poly) => {
+                let vertices = poly.vertices().collect::<Vec<_>>();
+                assert!(vertices.len() > 2);
+                assert_eq!(Point::new(0.0, 0.0, 0.0), vertices[0].location);
+                assert_eq!(
+                    Point::new(2.0, 0.0, 0.0),
+                    vertices[vertices.len() - 1].location
+                );
+            }
+            _ => panic!("expected a polyline"),
+        }
+    }
+
+    #[test]
+    fn m_text_becomes_text_lines() {
+        let mtext = MText {
+            insertion_point: Point::new(10.0, 20.0, 0.0),
+            initial_text_height: 3.0,
+            text: String::from("{\\fArial|b1;first}\\Psecond"),
+            attachment_point: AttachmentPoint::TopCenter,
+            ..Default::default()
+        };
+        let (entities, notes) = converted(EntityType::MText(mtext));
+        assert_eq!("MTEXT converted to 2 TEXT", notes[0].description);
+        assert!(notes[0].is_lossy);
+        assert_eq!(2, entities.len());
+        assert_ne!(entities[0].common.handle, entities[1].common.handle);
+        let texts = entities
+            .iter()
+            .map(|e| match e.specific {
+                EntityType::Text(ref t) => t.clone(),
+                _ => panic!("expected text"),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!("first", texts[0].value);
+        assert_eq!("second", texts[1].value);
+        assert_eq!(
+            HorizontalTextJustification::Center,
+            texts[0].horizontal_text_justification
+        );
+        assert_eq!(Point::new(10.0, 17.0, 0.0), texts[0].second_alignment_point);
+        assert_eq!(Point::new(10.0, 12.0, 0.0), texts[1].second_alignment_point);
+    }
+
+    #[test]
+    fn unsupported_entities_and_objects_are_removed() {
+        let mut drawing = Drawing::new();
+        drawing.header.version = AcadVersion::R2000;
+        drawing.add_entity(Entity::new(EntityType::Solid3D(Default::default())));
+        drawing.add_entity(Entity::new(EntityType::Line(Default::default())));
+        drawing.add_object(Object::new(ObjectType::PlaceHolder(Default::default())));
+        let mut block = Block::default();
+        block
+            .entities
+            .push(Entity::new(EntityType::LwPolyline(Default::default())));
+        drawing.add_block(block);
+
+        let notes = drawing.convert_to_version(AcadVersion::R12);
+        let descriptions = notes
+            .iter()
+            .map(|n| n.description.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            vec![
+                "3DSOLID removed",
+                "LWPOLYLINE converted to POLYLINE",
+                "ACDBPLACEHOLDER removed",
+            ],
+            descriptions
+        );
+        assert_eq!(1, drawing.entities().count());
+        assert_eq!(0, drawing.objects().count());
+        let block = drawing.blocks().next().unwrap();
+        match block.entities[0].specific {
+            EntityType::Polyline(_) => (),
+            _ => panic!("expected a polyline"),
+        }
+    }
+
+    #[test]
+    fn save_as_r12_converts_entities() {
+        let mut drawing = Drawing::new();
+        drawing.header.version = AcadVersion::R12;
+        let mut poly = LwPolyline::default();
+        poly.vertices.push(LwPolylineVertex::default());
+        poly.vertices.push(LwPolylineVertex {
+            x: 1.0,
+            ..Default::default()
+        });
+        drawing.add_entity(Entity::new(EntityType::LwPolyline(poly)));
+        let mut buf = vec![];
+        drawing.save(&mut buf).unwrap();
+        let text = String::from_utf8(buf).unwrap();
+        assert!(!text.contains("LWPOLYLINE"));
+        assert!(text.contains(&["  0", "POLYLINE"].join("\r\n")));
+        assert!(text.contains(&["  0", "SEQEND"].join("\r\n")));
+
+        // the drawing itself isn't changed
+        let entity = drawing.entities().next().unwrap();
+        match entity.specific {
+            EntityType::LwPolyline(_) => (),
+            _ => panic!("expected an lw polyline"),
+        }
+    }
+
+    fn dictionary_entries(drawing: &Drawing, handle: u32) -> HashMap<String, u32> {
+        match drawing.get_item_by_handle(handle) {
+            Some(DrawingItem::Object(Object {
+                specific: ObjectType::Dictionary(ref d),
+                ..
+            })) => d.value_handles.clone(),
+            _ => panic!("expected a dictionary"),
+        }
+    }
+
+    fn r12_drawing() -> Drawing {
+        let mut drawing = Drawing::new();
+        drawing.add_entity(Entity::new(EntityType::Line(Default::default())));
+        let mut paper_space_line = Entity::new(EntityType::Line(Default::default()));
+        paper_space_line.common.is_in_paper_space = true;
+        drawing.add_entity(paper_space_line);
+        let mut block = Block {
+            name: String::from("b"),
+            ..Default::default()
+        };
+        block
+            .entities
+            .push(Entity::new(EntityType::Circle(Default::default())));
+        drawing.add_block(block);
+        drawing
+    }
+
+    #[test]
+    fn upgrade_builds_object_graph() {
+        let mut drawing = r12_drawing();
+        let notes = drawing.convert_to_version(AcadVersion::R2000);
+        assert!(notes.iter().all(|n| !n.is_lossy));
+        assert_eq!(AcadVersion::R2000, drawing.header.version);
+
+        let record = |name: &str| {
+            drawing
+                .block_records()
+                .find(|r| r.name.eq_ignore_ascii_case(name))
+                .unwrap_or_else(|| panic!("expected block record {}", name))
+                .clone()
+        };
+        let model_space = record("*Model_Space");
+        let paper_space = record("*Paper_Space");
+        let b = record("b");
+        for name in &["*Model_Space", "*Paper_Space", "b"] {
+            let block = drawing.blocks().find(|b| b.name == *name).unwrap();
+            assert_eq!(record(name).handle, block.__owner_handle);
+        }
+        let block = drawing.blocks().find(|b| b.name == "b").unwrap();
+        assert_eq!(b.handle, block.entities[0].common.__owner_handle);
+        let owners = drawing
+            .entities()
+            .map(|e| e.common.__owner_handle)
+            .collect::<Vec<_>>();
+        assert_eq!(vec![model_space.handle, paper_space.handle], owners);
+
+        // the root dictionary is the first object
+        let root = drawing.objects().next().unwrap();
+        assert_eq!(0, root.common.__owner_handle);
+        let root_entries = dictionary_entries(&drawing, root.common.handle);
+        assert!(root_entries.contains_key("ACAD_GROUP"));
+        let layouts = dictionary_entries(&drawing, root_entries["ACAD_LAYOUT"]);
+        for (name, record) in &[("Model", &model_space), ("Layout1", &paper_space)] {
+            let handle = layouts[*name];
+            assert_eq!(handle, record.__layout_handle);
+            match drawing.get_item_by_handle(handle) {
+                Some(DrawingItem::Object(Object {
+                    common,
+                    specific: ObjectType::Layout(ref layout),
+                })) => {
+                    assert_eq!(root_entries["ACAD_LAYOUT"], common.__owner_handle);
+                    assert_eq!(record.handle, layout.__table_record_handle);
+                }
+                _ => panic!("expected a layout"),
+            }
+        }
+
+        assert!(drawing.classes.iter().any(|c| c.record_name == "LAYOUT"));
+    }
+
+    #[test]
+    fn save_builds_object_graph_when_requested() {
+        let mut drawing = r12_drawing();
+        drawing.header.version = AcadVersion::R2018;
+        let mut buf = vec![];
+        drawing.save(&mut buf).unwrap();
+        let text = String::from_utf8(buf).unwrap();
+        assert!(!text.contains("ACAD_LAYOUT"));
+
+        let mut buf = vec![];
+        let options = SaveOptions::new().build_object_graph(true);
+        drawing.save_with_options(&mut buf, &options).unwrap();
+        let drawing = Drawing::load(&mut buf.as_slice()).unwrap();
+        let root = drawing.named_object_dictionary().unwrap();
+        assert!(root.names().contains(&"ACAD_LAYOUT"));
+        assert!(drawing
+            .block_records()
+            .any(|r| r.name.eq_ignore_ascii_case("*Model_Space")));
+    }
+
+    #[test]
+    fn downgrade_removes_unknown_items() {
+        let mut drawing = Drawing::new();
+        drawing.header.version = AcadVersion::R2018;
+        let entity = drawing.add_entity(Entity::new(EntityType::Unknown {
+            type_string: String::from("UNSUPPORTED_ENTITY"),
+            pairs: vec![],
+        }));
+        let entity_handle = entity.common.handle;
+        let object = drawing.add_object(Object::new(ObjectType::Unknown {
+            type_string: String::from("UNSUPPORTED_OBJECT"),
+            pairs: vec![],
+        }));
+        let object_handle = object.common.handle;
+        assert!(!drawing.needs_conversion(AcadVersion::R2018, false));
+        assert!(drawing.needs_conversion(AcadVersion::R14, false));
+
+        let notes = drawing.convert_to_version(AcadVersion::R14);
+        assert_eq!(0, drawing.entities().count());
+        assert!(!drawing
+            .objects()
+            .any(|o| matches!(o.specific, ObjectType::Unknown { .. })));
+        assert_eq!(
+            vec![
+                ConversionNote {
+                    handle: entity_handle,
+                    description: String::from("UNSUPPORTED_ENTITY removed"),
+                    is_lossy: true,
+                },
+                ConversionNote {
+                    handle: object_handle,
+                    description: String::from("UNSUPPORTED_OBJECT removed"),
+                    is_lossy: true,
+                },
+            ],
+            notes
+        );
+    }
+
+    #[test]
+    fn upgrade_keeps_unknown_items() {
+        let mut drawing = Drawing::new();
+        drawing.header.version = AcadVersion::R2000;
+        drawing.add_entity(Entity::new(EntityType::Unknown {
+            type_string: String::from("UNSUPPORTED_ENTITY"),
+            pairs: vec![],
+        }));
+        drawing.convert_to_version(AcadVersion::R2018);
+        assert_eq!(1, drawing.entities().count());
+    }
+
+    #[test]
+    fn upgrade_keeps_existing_items() {
+        let mut drawing = r12_drawing();
+        drawing.convert_to_version(AcadVersion::R2000);
+        let object_count = drawing.objects().count();
+        let notes = drawing.convert_to_version(AcadVersion::R2018);
+        assert!(notes.is_empty());
+        assert_eq!(object_count, drawing.objects().count());
+    }
+
+    #[test]
+    fn upgrade_renames_r12_model_space_block() {
+        let mut drawing = Drawing::new();
+        drawing.add_block(Block {
+            name: String::from("$MODEL_SPACE"),
+            ..Default::default()
+        });
+        drawing.convert_to_version(AcadVersion::R2000);
+        let names = drawing
+            .blocks()
+            .map(|b| b.name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(vec!["*Model_Space", "*Paper_Space"], names);
+    }
+
+    #[test]
+    fn upgrade_adds_groups_to_group_dictionary() {
+        let mut drawing = Drawing::new();
+        let group = drawing.add_object(Object::new(ObjectType::Group(Default::default())));
+        let group_handle = group.common.handle;
+        drawing.convert_to_version(AcadVersion::R2000);
+        let root = drawing.objects().next().unwrap().common.handle;
+        let groups = dictionary_entries(&drawing, root)["ACAD_GROUP"];
+        assert_eq!(
+            Some(&group_handle),
+            dictionary_entries(&drawing, groups).get("*A1")
+        );
+    }
+
+    #[test]
+    fn save_as_newer_version_upgrades() {
+        let drawing = r12_drawing();
+        let mut buf = vec![];
+        let options = SaveOptions::new().version(AcadVersion::R2000);
+        drawing.save_with_options(&mut buf, &options).unwrap();
+        let text = String::from_utf8(buf).unwrap();
+        assert!(text.contains("ACAD_LAYOUT"));
+        assert!(text.contains(&["  0", "LAYOUT"].join("\r\n")));
+        assert!(text.contains("*Model_Space"));
+
+        // the drawing itself isn't changed
+        assert_eq!(0, drawing.objects().count());
+    }
+}
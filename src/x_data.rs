@@ -52,6 +52,90 @@ pub enum XDataItem {
 }
 
 impl XData {
+    /// Creates an empty `XData` for the specified application.  The application name is added to
+    /// the drawing's `AppId` table when the owning entity or object is added.
+    pub fn new(application_name: &str) -> Self {
+        XData {
+            application_name: String::from(application_name),
+            items: vec![],
+        }
+    }
+    /// Appends an `XDataItem::Str`.
+    pub fn push_str(&mut self, value: &str) -> &mut Self {
+        self.push(XDataItem::Str(String::from(value)))
+    }
+    /// Appends an `XDataItem::LayerName`.
+    pub fn push_layer_name(&mut self, layer_name: &str) -> &mut Self {
+        self.push(XDataItem::LayerName(String::from(layer_name)))
+    }
+    /// Appends an `XDataItem::BinaryData`.
+    pub fn push_binary_data(&mut self, data: &[u8]) -> &mut Self {
+        self.push(XDataItem::BinaryData(data.to_vec()))
+    }
+    /// Appends an `XDataItem::Handle`.
+    pub fn push_handle(&mut self, handle: u32) -> &mut Self {
+        self.push(XDataItem::Handle(handle))
+    }
+    /// Appends a point as an `XDataItem::ThreeReals`.
+    pub fn push_point(&mut self, point: &Point) -> &mut Self {
+        self.push(XDataItem::ThreeReals(point.x, point.y, point.z))
+    }
+    /// Appends an `XDataItem::WorldSpacePosition`.
+    pub fn push_world_space_position(&mut self, point: &Point) -> &mut Self {
+        self.push(XDataItem::WorldSpacePosition(point.clone()))
+    }
+    /// Appends an `XDataItem::WorldSpaceDisplacement`.
+    pub fn push_world_space_displacement(&mut self, point: &Point) -> &mut Self {
+        self.push(XDataItem::WorldSpaceDisplacement(point.clone()))
+    }
+    /// Appends an `XDataItem::WorldDirection`.
+    pub fn push_world_direction(&mut self, direction: &Vector) -> &mut Self {
+        self.push(XDataItem::WorldDirection(direction.clone()))
+    }
+    /// Appends an `XDataItem::Real`.
+    pub fn push_real(&mut self, value: f64) -> &mut Self {
+        self.push(XDataItem::Real(value))
+    }
+    /// Appends an `XDataItem::Distance`.
+    pub fn push_distance(&mut self, value: f64) -> &mut Self {
+        self.push(XDataItem::Distance(value))
+    }
+    /// Appends an `XDataItem::ScaleFactor`.
+    pub fn push_scale_factor(&mut self, value: f64) -> &mut Self {
+        self.push(XDataItem::ScaleFactor(value))
+    }
+    /// Appends an `XDataItem::Integer`.
+    pub fn push_integer(&mut self, value: i16) -> &mut Self {
+        self.push(XDataItem::Integer(value))
+    }
+    /// Appends an `XDataItem::Long`.
+    pub fn push_long(&mut self, value: i32) -> &mut Self {
+        self.push(XDataItem::Long(value))
+    }
+    /// Appends an `XDataItem::ControlGroup` with the items added by `build`, e.g.,
+    /// `xdata.push_list(|list| { list.push_str("name").push_real(1.0); })`.
+    pub fn push_list<F>(&mut self, build: F) -> &mut Self
+    where
+        F: FnOnce(&mut XData),
+    {
+        let mut list = XData::new("");
+        build(&mut list);
+        self.push(XDataItem::ControlGroup(list.items))
+    }
+    /// Appends an item.
+    pub fn push(&mut self, item: XDataItem) -> &mut Self {
+        self.items.push(item);
+        self
+    }
+    /// Returns the item at the specified path of indices, where each index after the first is into
+    /// the `ControlGroup` found by the previous one, e.g., `&[2, 0]` is the first item of the
+    /// group that's the third item.
+    pub fn get(&self, path: &[usize]) -> Option<&XDataItem> {
+        let (first, rest) = path.split_first()?;
+        let item = self.items.get(*first)?;
+        rest.iter()
+            .try_fold(item, |item, index| item.as_list()?.get(*index))
+    }
     pub(crate) fn read_item<I>(
         application_name: String,
         iter: &mut CodePairPutBack<I>,
@@ -104,6 +188,81 @@ impl XData {
 }
 
 impl XDataItem {
+    /// Returns the value of a `Str`.
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            XDataItem::Str(ref s) => Some(s),
+            _ => None,
+        }
+    }
+    /// Returns the value of a `LayerName`.
+    pub fn as_layer_name(&self) -> Option<&str> {
+        match self {
+            XDataItem::LayerName(ref l) => Some(l),
+            _ => None,
+        }
+    }
+    /// Returns the value of a `BinaryData`.
+    pub fn as_binary_data(&self) -> Option<&[u8]> {
+        match self {
+            XDataItem::BinaryData(ref data) => Some(data),
+            _ => None,
+        }
+    }
+    /// Returns the value of a `Handle`.
+    pub fn as_handle(&self) -> Option<u32> {
+        match self {
+            XDataItem::Handle(h) => Some(*h),
+            _ => None,
+        }
+    }
+    /// Returns the value of a `ThreeReals`, `WorldSpacePosition`, or `WorldSpaceDisplacement` as
+    /// a `Point`.
+    pub fn as_point(&self) -> Option<Point> {
+        match self {
+            XDataItem::ThreeReals(x, y, z) => Some(Point::new(*x, *y, *z)),
+            XDataItem::WorldSpacePosition(ref p) | XDataItem::WorldSpaceDisplacement(ref p) => {
+                Some(p.clone())
+            }
+            _ => None,
+        }
+    }
+    /// Returns the value of a `WorldDirection`.
+    pub fn as_vector(&self) -> Option<Vector> {
+        match self {
+            XDataItem::WorldDirection(ref v) => Some(v.clone()),
+            _ => None,
+        }
+    }
+    /// Returns the value of a `Real`, `Distance`, or `ScaleFactor`.
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            XDataItem::Real(f) | XDataItem::Distance(f) | XDataItem::ScaleFactor(f) => Some(*f),
+            _ => None,
+        }
+    }
+    /// Returns the value of an `Integer`.
+    pub fn as_i16(&self) -> Option<i16> {
+        match self {
+            XDataItem::Integer(i) => Some(*i),
+            _ => None,
+        }
+    }
+    /// Returns the value of a `Long` or an `Integer`.
+    pub fn as_i32(&self) -> Option<i32> {
+        match self {
+            XDataItem::Integer(i) => Some(i32::from(*i)),
+            XDataItem::Long(i) => Some(*i),
+            _ => None,
+        }
+    }
+    /// Returns the items of a `ControlGroup`.
+    pub fn as_list(&self) -> Option<&[XDataItem]> {
+        match self {
+            XDataItem::ControlGroup(ref items) => Some(items),
+            _ => None,
+        }
+    }
     fn read_item<I>(pair: &CodePair, iter: &mut CodePairPutBack<I>) -> DxfResult<XDataItem>
     where
         I: Read,
@@ -274,3 +433,65 @@ impl XDataItem {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::entities::*;
+    use crate::*;
+
+    #[test]
+    fn build_x_data() {
+        let mut xdata = XData::new("IXMILIA");
+        xdata
+            .push_str("name")
+            .push_point(&Point::new(1.0, 2.0, 3.0))
+            .push_list(|list| {
+                list.push_integer(4).push_list(|inner| {
+                    inner.push_real(5.0);
+                });
+            })
+            .push_long(6);
+        assert_eq!(
+            vec![
+                XDataItem::Str(String::from("name")),
+                XDataItem::ThreeReals(1.0, 2.0, 3.0),
+                XDataItem::ControlGroup(vec![
+                    XDataItem::Integer(4),
+                    XDataItem::ControlGroup(vec![XDataItem::Real(5.0)]),
+                ]),
+                XDataItem::Long(6),
+            ],
+            xdata.items
+        );
+    }
+
+    #[test]
+    fn get_x_data_items_by_path() {
+        let mut xdata = XData::new("IXMILIA");
+        xdata.push_str("name").push_list(|list| {
+            list.push_integer(4).push_list(|inner| {
+                inner.push_distance(5.0);
+            });
+        });
+        assert_eq!(Some("name"), xdata.get(&[0]).and_then(|i| i.as_str()));
+        assert_eq!(Some(4), xdata.get(&[1, 0]).and_then(|i| i.as_i16()));
+        assert_eq!(Some(4), xdata.get(&[1, 0]).and_then(|i| i.as_i32()));
+        assert_eq!(Some(5.0), xdata.get(&[1, 1, 0]).and_then(|i| i.as_f64()));
+        assert_eq!(2, xdata.get(&[1]).and_then(|i| i.as_list()).unwrap().len());
+        assert!(xdata.get(&[0, 0]).is_none());
+        assert!(xdata.get(&[2]).is_none());
+        assert!(xdata.get(&[]).is_none());
+        assert!(xdata.get(&[0]).and_then(|i| i.as_f64()).is_none());
+    }
+
+    #[test]
+    fn x_data_application_name_is_added_as_app_id() {
+        let mut drawing = Drawing::new();
+        let mut entity = Entity::new(EntityType::Line(Default::default()));
+        let mut xdata = XData::new("IXMILIA");
+        xdata.push_str("name");
+        entity.common.x_data.push(xdata);
+        drawing.add_entity(entity);
+        assert!(drawing.app_ids().any(|a| a.name == "IXMILIA"));
+    }
+}
@@ -0,0 +1,832 @@
+// maps serde values to and from `XData` and `XRecordObject` items
+
+use std::fmt::Display;
+
+use serde::de::{
+    self, DeserializeOwned, DeserializeSeed, EnumAccess, IntoDeserializer, MapAccess, SeqAccess,
+    VariantAccess, Visitor,
+};
+use serde::ser::{self, Serialize};
+
+use crate::objects::XRecordObject;
+use crate::{DxfError, DxfResult, XData, XDataItem};
+
+impl XData {
+    /// Creates an `XData` for the application from a serializable value.  Structs and maps are
+    /// stored as alternating keys and values, sequences and tuples as `ControlGroup`s, strings as
+    /// `Str`, floats as `Real`, and integers as `Integer` or `Long`; the items of a top-level
+    /// struct, map, or sequence are stored directly instead of in a `ControlGroup`.
+    pub fn from_value<T>(application_name: &str, value: &T) -> DxfResult<XData>
+    where
+        T: Serialize + ?Sized,
+    {
+        Ok(XData {
+            application_name: String::from(application_name),
+            items: to_items(value)?,
+        })
+    }
+    /// Creates a value from the items, as stored by `from_value()`.
+    pub fn to_value<T>(&self) -> DxfResult<T>
+    where
+        T: DeserializeOwned,
+    {
+        from_items(&self.items)
+    }
+}
+
+impl XRecordObject {
+    /// Creates an `XRecordObject` from a serializable value, stored as described in
+    /// `XData::from_value()` and `XRecordObject::set_items()`.
+    pub fn from_value<T>(value: &T) -> DxfResult<XRecordObject>
+    where
+        T: Serialize + ?Sized,
+    {
+        let mut xrecord = XRecordObject::default();
+        xrecord.set_items(&to_items(value)?);
+        Ok(xrecord)
+    }
+    /// Creates a value from the data, as stored by `from_value()`.
+    pub fn to_value<T>(&self) -> DxfResult<T>
+    where
+        T: DeserializeOwned,
+    {
+        from_items(&self.items()?)
+    }
+}
+
+fn to_items<T>(value: &T) -> DxfResult<Vec<XDataItem>>
+where
+    T: Serialize + ?Sized,
+{
+    match value.serialize(ItemSerializer)? {
+        XDataItem::ControlGroup(items) => Ok(items),
+        item => Ok(vec![item]),
+    }
+}
+
+fn from_items<T>(items: &[XDataItem]) -> DxfResult<T>
+where
+    T: DeserializeOwned,
+{
+    T::deserialize(ItemDeserializer {
+        item: XDataItem::ControlGroup(items.to_vec()),
+        is_root: true,
+    })
+}
+
+fn unexpected<T>(message: T) -> DxfError
+where
+    T: Display,
+{
+    DxfError::UnexpectedXData(message.to_string())
+}
+
+impl ser::Error for DxfError {
+    fn custom<T>(message: T) -> Self
+    where
+        T: Display,
+    {
+        unexpected(message)
+    }
+}
+
+impl de::Error for DxfError {
+    fn custom<T>(message: T) -> Self
+    where
+        T: Display,
+    {
+        unexpected(message)
+    }
+}
+
+//------------------------------------------------------------------------------
+//                                                                    Serializer
+//------------------------------------------------------------------------------
+struct ItemSerializer;
+
+impl ItemSerializer {
+    fn long<T>(value: T) -> DxfResult<XDataItem>
+    where
+        T: Copy + Display + std::convert::TryInto<i32>,
+    {
+        match value.try_into() {
+            Ok(i) => Ok(XDataItem::Long(i)),
+            Err(_) => Err(unexpected(format!("{} doesn't fit in a Long", value))),
+        }
+    }
+}
+
+impl ser::Serializer for ItemSerializer {
+    type Ok = XDataItem;
+    type Error = DxfError;
+    type SerializeSeq = ListSerializer;
+    type SerializeTuple = ListSerializer;
+    type SerializeTupleStruct = ListSerializer;
+    type SerializeTupleVariant = ListSerializer;
+    type SerializeMap = ListSerializer;
+    type SerializeStruct = ListSerializer;
+    type SerializeStructVariant = ListSerializer;
+
+    fn serialize_bool(self, v: bool) -> DxfResult<XDataItem> {
+        Ok(XDataItem::Integer(if v { 1 } else { 0 }))
+    }
+    fn serialize_i8(self, v: i8) -> DxfResult<XDataItem> {
+        Ok(XDataItem::Integer(i16::from(v)))
+    }
+    fn serialize_i16(self, v: i16) -> DxfResult<XDataItem> {
+        Ok(XDataItem::Integer(v))
+    }
+    fn serialize_i32(self, v: i32) -> DxfResult<XDataItem> {
+        Ok(XDataItem::Long(v))
+    }
+    fn serialize_i64(self, v: i64) -> DxfResult<XDataItem> {
+        ItemSerializer::long(v)
+    }
+    fn serialize_u8(self, v: u8) -> DxfResult<XDataItem> {
+        Ok(XDataItem::Integer(i16::from(v)))
+    }
+    fn serialize_u16(self, v: u16) -> DxfResult<XDataItem> {
+        Ok(XDataItem::Long(i32::from(v)))
+    }
+    fn serialize_u32(self, v: u32) -> DxfResult<XDataItem> {
+        ItemSerializer::long(v)
+    }
+    fn serialize_u64(self, v: u64) -> DxfResult<XDataItem> {
+        ItemSerializer::long(v)
+    }
+    fn serialize_f32(self, v: f32) -> DxfResult<XDataItem> {
+        Ok(XDataItem::Real(f64::from(v)))
+    }
+    fn serialize_f64(self, v: f64) -> DxfResult<XDataItem> {
+        Ok(XDataItem::Real(v))
+    }
+    fn serialize_char(self, v: char) -> DxfResult<XDataItem> {
+        Ok(XDataItem::Str(v.to_string()))
+    }
+    fn serialize_str(self, v: &str) -> DxfResult<XDataItem> {
+        Ok(XDataItem::Str(String::from(v)))
+    }
+    fn serialize_bytes(self, v: &[u8]) -> DxfResult<XDataItem> {
+        Ok(XDataItem::BinaryData(v.to_vec()))
+    }
+    fn serialize_none(self) -> DxfResult<XDataItem> {
+        Ok(XDataItem::ControlGroup(vec![]))
+    }
+    fn serialize_some<T>(self, value: &T) -> DxfResult<XDataItem>
+    where
+        T: Serialize + ?Sized,
+    {
+        Ok(XDataItem::ControlGroup(vec![value.serialize(self)?]))
+    }
+    fn serialize_unit(self) -> DxfResult<XDataItem> {
+        Ok(XDataItem::ControlGroup(vec![]))
+    }
+    fn serialize_unit_struct(self, _name: &'static str) -> DxfResult<XDataItem> {
+        self.serialize_unit()
+    }
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> DxfResult<XDataItem> {
+        self.serialize_str(variant)
+    }
+    fn serialize_newtype_struct<T>(self, _name: &'static str, value: &T) -> DxfResult<XDataItem>
+    where
+        T: Serialize + ?Sized,
+    {
+        value.serialize(self)
+    }
+    fn serialize_newtype_variant<T>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> DxfResult<XDataItem>
+    where
+        T: Serialize + ?Sized,
+    {
+        Ok(XDataItem::ControlGroup(vec![
+            XDataItem::Str(String::from(variant)),
+            value.serialize(self)?,
+        ]))
+    }
+    fn serialize_seq(self, _len: Option<usize>) -> DxfResult<ListSerializer> {
+        Ok(ListSerializer::new(None))
+    }
+    fn serialize_tuple(self, _len: usize) -> DxfResult<ListSerializer> {
+        Ok(ListSerializer::new(None))
+    }
+    fn serialize_tuple_struct(self, _name: &'static str, _len: usize) -> DxfResult<ListSerializer> {
+        Ok(ListSerializer::new(None))
+    }
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        _len: usize,
+    ) -> DxfResult<ListSerializer> {
+        Ok(ListSerializer::new(Some(variant)))
+    }
+    fn serialize_map(self, _len: Option<usize>) -> DxfResult<ListSerializer> {
+        Ok(ListSerializer::new(None))
+    }
+    fn serialize_struct(self, _name: &'static str, _len: usize) -> DxfResult<ListSerializer> {
+        Ok(ListSerializer::new(None))
+    }
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        _len: usize,
+    ) -> DxfResult<ListSerializer> {
+        Ok(ListSerializer::new(Some(variant)))
+    }
+}
+
+/// Collects the items of a `ControlGroup`, which is wrapped with the name of the enum variant, if
+/// any.
+struct ListSerializer {
+    items: Vec<XDataItem>,
+    variant: Option<&'static str>,
+}
+
+impl ListSerializer {
+    fn new(variant: Option<&'static str>) -> Self {
+        ListSerializer {
+            items: vec![],
+            variant,
+        }
+    }
+    fn push<T>(&mut self, value: &T) -> DxfResult<()>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.items.push(value.serialize(ItemSerializer)?);
+        Ok(())
+    }
+    fn finish(self) -> DxfResult<XDataItem> {
+        let list = XDataItem::ControlGroup(self.items);
+        match self.variant {
+            Some(variant) => Ok(XDataItem::ControlGroup(vec![
+                XDataItem::Str(String::from(variant)),
+                list,
+            ])),
+            None => Ok(list),
+        }
+    }
+}
+
+impl ser::SerializeSeq for ListSerializer {
+    type Ok = XDataItem;
+    type Error = DxfError;
+    fn serialize_element<T>(&mut self, value: &T) -> DxfResult<()>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.push(value)
+    }
+    fn end(self) -> DxfResult<XDataItem> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeTuple for ListSerializer {
+    type Ok = XDataItem;
+    type Error = DxfError;
+    fn serialize_element<T>(&mut self, value: &T) -> DxfResult<()>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.push(value)
+    }
+    fn end(self) -> DxfResult<XDataItem> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeTupleStruct for ListSerializer {
+    type Ok = XDataItem;
+    type Error = DxfError;
+    fn serialize_field<T>(&mut self, value: &T) -> DxfResult<()>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.push(value)
+    }
+    fn end(self) -> DxfResult<XDataItem> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeTupleVariant for ListSerializer {
+    type Ok = XDataItem;
+    type Error = DxfError;
+    fn serialize_field<T>(&mut self, value: &T) -> DxfResult<()>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.push(value)
+    }
+    fn end(self) -> DxfResult<XDataItem> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeMap for ListSerializer {
+    type Ok = XDataItem;
+    type Error = DxfError;
+    fn serialize_key<T>(&mut self, key: &T) -> DxfResult<()>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.push(key)
+    }
+    fn serialize_value<T>(&mut self, value: &T) -> DxfResult<()>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.push(value)
+    }
+    fn end(self) -> DxfResult<XDataItem> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeStruct for ListSerializer {
+    type Ok = XDataItem;
+    type Error = DxfError;
+    fn serialize_field<T>(&mut self, key: &'static str, value: &T) -> DxfResult<()>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.push(key)?;
+        self.push(value)
+    }
+    fn end(self) -> DxfResult<XDataItem> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeStructVariant for ListSerializer {
+    type Ok = XDataItem;
+    type Error = DxfError;
+    fn serialize_field<T>(&mut self, key: &'static str, value: &T) -> DxfResult<()>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.push(key)?;
+        self.push(value)
+    }
+    fn end(self) -> DxfResult<XDataItem> {
+        self.finish()
+    }
+}
+
+//------------------------------------------------------------------------------
+//                                                                  Deserializer
+//------------------------------------------------------------------------------
+struct ItemDeserializer {
+    item: XDataItem,
+    /// The top-level items, which hold a single scalar value as the only item.
+    is_root: bool,
+}
+
+impl ItemDeserializer {
+    fn new(item: XDataItem) -> Self {
+        ItemDeserializer {
+            item,
+            is_root: false,
+        }
+    }
+    /// Returns the items of a `ControlGroup`, or the coordinates of a point as `Real`s.
+    fn list(self) -> DxfResult<Vec<XDataItem>> {
+        match self.item {
+            XDataItem::ControlGroup(items) => Ok(items),
+            XDataItem::ThreeReals(x, y, z) => Ok(vec![
+                XDataItem::Real(x),
+                XDataItem::Real(y),
+                XDataItem::Real(z),
+            ]),
+            XDataItem::WorldSpacePosition(p) | XDataItem::WorldSpaceDisplacement(p) => Ok(vec![
+                XDataItem::Real(p.x),
+                XDataItem::Real(p.y),
+                XDataItem::Real(p.z),
+            ]),
+            XDataItem::WorldDirection(v) => Ok(vec![
+                XDataItem::Real(v.x),
+                XDataItem::Real(v.y),
+                XDataItem::Real(v.z),
+            ]),
+            item => Err(unexpected(format!(
+                "expected a ControlGroup, found {:?}",
+                item
+            ))),
+        }
+    }
+    /// Returns the item, or the only item of the top-level items.
+    fn scalar(self) -> DxfResult<XDataItem> {
+        match self.item {
+            XDataItem::ControlGroup(mut items) if self.is_root && items.len() == 1 => {
+                Ok(items.remove(0))
+            }
+            item => Ok(item),
+        }
+    }
+}
+
+impl<'de> de::Deserializer<'de> for ItemDeserializer {
+    type Error = DxfError;
+
+    fn deserialize_any<V>(self, visitor: V) -> DxfResult<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        match self.scalar()? {
+            XDataItem::Str(s) | XDataItem::LayerName(s) => visitor.visit_string(s),
+            XDataItem::BinaryData(data) => visitor.visit_byte_buf(data),
+            XDataItem::Handle(h) => visitor.visit_u32(h),
+            XDataItem::Real(f) | XDataItem::Distance(f) | XDataItem::ScaleFactor(f) => {
+                visitor.visit_f64(f)
+            }
+            XDataItem::Integer(i) => visitor.visit_i16(i),
+            XDataItem::Long(i) => visitor.visit_i32(i),
+            item => visitor.visit_seq(ListAccess::new(ItemDeserializer::new(item).list()?)),
+        }
+    }
+    fn deserialize_bool<V>(self, visitor: V) -> DxfResult<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        match self.scalar()? {
+            XDataItem::Integer(i) => visitor.visit_bool(i != 0),
+            XDataItem::Long(i) => visitor.visit_bool(i != 0),
+            item => Err(unexpected(format!("expected a boolean, found {:?}", item))),
+        }
+    }
+    fn deserialize_option<V>(self, visitor: V) -> DxfResult<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        let mut items = self.list()?;
+        match items.len() {
+            0 => visitor.visit_none(),
+            1 => visitor.visit_some(ItemDeserializer::new(items.remove(0))),
+            _ => Err(unexpected("expected an optional value")),
+        }
+    }
+    fn deserialize_unit<V>(self, visitor: V) -> DxfResult<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        if self.list()?.is_empty() {
+            visitor.visit_unit()
+        } else {
+            Err(unexpected("expected an empty ControlGroup"))
+        }
+    }
+    fn deserialize_unit_struct<V>(self, _name: &'static str, visitor: V) -> DxfResult<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_unit(visitor)
+    }
+    fn deserialize_newtype_struct<V>(self, _name: &'static str, visitor: V) -> DxfResult<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_newtype_struct(self)
+    }
+    fn deserialize_seq<V>(self, visitor: V) -> DxfResult<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_seq(ListAccess::new(self.list()?))
+    }
+    fn deserialize_tuple<V>(self, _len: usize, visitor: V) -> DxfResult<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_seq(visitor)
+    }
+    fn deserialize_tuple_struct<V>(
+        self,
+        _name: &'static str,
+        _len: usize,
+        visitor: V,
+    ) -> DxfResult<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_seq(visitor)
+    }
+    fn deserialize_map<V>(self, visitor: V) -> DxfResult<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_map(ListAccess::new(self.list()?))
+    }
+    fn deserialize_struct<V>(
+        self,
+        _name: &'static str,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> DxfResult<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_map(visitor)
+    }
+    fn deserialize_enum<V>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> DxfResult<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        match self.scalar()? {
+            XDataItem::Str(variant) => visitor.visit_enum(VariantAccessor {
+                variant,
+                value: None,
+            }),
+            XDataItem::ControlGroup(items) => {
+                let mut items = items.into_iter();
+                match (items.next(), items.next(), items.next()) {
+                    (Some(XDataItem::Str(variant)), Some(value), None) => {
+                        visitor.visit_enum(VariantAccessor {
+                            variant,
+                            value: Some(value),
+                        })
+                    }
+                    _ => Err(unexpected("expected an enum variant name and value")),
+                }
+            }
+            item => Err(unexpected(format!(
+                "expected an enum variant, found {:?}",
+                item
+            ))),
+        }
+    }
+    fn deserialize_ignored_any<V>(self, visitor: V) -> DxfResult<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_unit()
+    }
+
+    serde::forward_to_deserialize_any! {
+        i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string bytes byte_buf
+        identifier
+    }
+}
+
+/// Visits the items of a `ControlGroup` as a sequence, or as alternating map keys and values.
+struct ListAccess {
+    items: std::vec::IntoIter<XDataItem>,
+}
+
+impl ListAccess {
+    fn new(items: Vec<XDataItem>) -> Self {
+        ListAccess {
+            items: items.into_iter(),
+        }
+    }
+}
+
+impl<'de> SeqAccess<'de> for ListAccess {
+    type Error = DxfError;
+
+    fn next_element_seed<T>(&mut self, seed: T) -> DxfResult<Option<T::Value>>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        match self.items.next() {
+            Some(item) => seed.deserialize(ItemDeserializer::new(item)).map(Some),
+            None => Ok(None),
+        }
+    }
+}
+
+impl<'de> MapAccess<'de> for ListAccess {
+    type Error = DxfError;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> DxfResult<Option<K::Value>>
+    where
+        K: DeserializeSeed<'de>,
+    {
+        self.next_element_seed(seed)
+    }
+    fn next_value_seed<V>(&mut self, seed: V) -> DxfResult<V::Value>
+    where
+        V: DeserializeSeed<'de>,
+    {
+        match self.next_element_seed(seed)? {
+            Some(value) => Ok(value),
+            None => Err(unexpected("expected a value after the key")),
+        }
+    }
+}
+
+/// Visits an enum variant stored as its name, or as its name and value in a `ControlGroup`.
+struct VariantAccessor {
+    variant: String,
+    value: Option<XDataItem>,
+}
+
+impl VariantAccessor {
+    fn value(self) -> DxfResult<ItemDeserializer> {
+        match self.value {
+            Some(value) => Ok(ItemDeserializer::new(value)),
+            None => Err(unexpected(format!(
+                "expected a value for the enum variant {}",
+                self.variant
+            ))),
+        }
+    }
+}
+
+impl<'de> EnumAccess<'de> for VariantAccessor {
+    type Error = DxfError;
+    type Variant = Self;
+
+    fn variant_seed<V>(self, seed: V) -> DxfResult<(V::Value, Self)>
+    where
+        V: DeserializeSeed<'de>,
+    {
+        let deserializer: de::value::StrDeserializer<DxfError> =
+            self.variant.as_str().into_deserializer();
+        let variant = seed.deserialize(deserializer)?;
+        Ok((variant, self))
+    }
+}
+
+impl<'de> VariantAccess<'de> for VariantAccessor {
+    type Error = DxfError;
+
+    fn unit_variant(self) -> DxfResult<()> {
+        match self.value {
+            None => Ok(()),
+            Some(_) => Err(unexpected(format!(
+                "expected no value for the enum variant {}",
+                self.variant
+            ))),
+        }
+    }
+    fn newtype_variant_seed<T>(self, seed: T) -> DxfResult<T::Value>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        seed.deserialize(self.value()?)
+    }
+    fn tuple_variant<V>(self, _len: usize, visitor: V) -> DxfResult<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        de::Deserializer::deserialize_seq(self.value()?, visitor)
+    }
+    fn struct_variant<V>(self, _fields: &'static [&'static str], visitor: V) -> DxfResult<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        de::Deserializer::deserialize_map(self.value()?, visitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::enums::*;
+    use crate::helper_functions::tests::*;
+    use crate::objects::*;
+    use crate::*;
+
+    #[derive(Debug, Deserialize, PartialEq, Serialize)]
+    enum Shape {
+        Empty,
+        Circle(f64),
+        Rectangle { width: f64, height: f64 },
+    }
+
+    #[derive(Debug, Deserialize, PartialEq, Serialize)]
+    struct Metadata {
+        name: String,
+        revision: i32,
+        is_approved: bool,
+        tags: Vec<String>,
+        origin: (f64, f64, f64),
+        comment: Option<String>,
+        shapes: Vec<Shape>,
+    }
+
+    fn metadata() -> Metadata {
+        Metadata {
+            name: String::from("part"),
+            revision: 3,
+            is_approved: true,
+            tags: vec![String::from("a"), String::from("b")],
+            origin: (1.0, 2.0, 3.0),
+            comment: None,
+            shapes: vec![
+                Shape::Empty,
+                Shape::Circle(1.5),
+                Shape::Rectangle {
+                    width: 2.0,
+                    height: 3.0,
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn struct_to_x_data_items() {
+        let xdata = XData::from_value("IXMILIA", &metadata()).unwrap();
+        assert_eq!("IXMILIA", xdata.application_name);
+        assert_eq!(14, xdata.items.len());
+        assert_eq!(XDataItem::Str(String::from("name")), xdata.items[0]);
+        assert_eq!(XDataItem::Str(String::from("part")), xdata.items[1]);
+        assert_eq!(XDataItem::Long(3), xdata.items[3]);
+        assert_eq!(XDataItem::Integer(1), xdata.items[5]);
+        assert_eq!(XDataItem::ControlGroup(vec![]), xdata.items[11]);
+        assert_eq!(
+            XDataItem::ControlGroup(vec![
+                XDataItem::Str(String::from("Empty")),
+                XDataItem::ControlGroup(vec![
+                    XDataItem::Str(String::from("Circle")),
+                    XDataItem::Real(1.5),
+                ]),
+                XDataItem::ControlGroup(vec![
+                    XDataItem::Str(String::from("Rectangle")),
+                    XDataItem::ControlGroup(vec![
+                        XDataItem::Str(String::from("width")),
+                        XDataItem::Real(2.0),
+                        XDataItem::Str(String::from("height")),
+                        XDataItem::Real(3.0),
+                    ]),
+                ]),
+            ]),
+            xdata.items[13]
+        );
+        assert_eq!(metadata(), xdata.to_value::<Metadata>().unwrap());
+    }
+
+    #[test]
+    fn scalar_x_data() {
+        let xdata = XData::from_value("IXMILIA", "some string").unwrap();
+        assert_eq!(
+            vec![XDataItem::Str(String::from("some string"))],
+            xdata.items
+        );
+        assert_eq!("some string", xdata.to_value::<String>().unwrap());
+        let xdata = XData::from_value("IXMILIA", &Some(2.5)).unwrap();
+        assert_eq!(Some(2.5), xdata.to_value::<Option<f64>>().unwrap());
+    }
+
+    #[test]
+    fn points_and_handles_are_read_as_numbers() {
+        let mut xdata = XData::new("IXMILIA");
+        xdata
+            .push_world_space_position(&Point::new(1.0, 2.0, 3.0))
+            .push_handle(0xA);
+        let value = xdata.to_value::<((f64, f64, f64), u32)>().unwrap();
+        assert_eq!(((1.0, 2.0, 3.0), 0xA), value);
+    }
+
+    #[test]
+    fn mismatched_x_data_fails() {
+        let mut xdata = XData::new("IXMILIA");
+        xdata.push_str("name").push_str("part");
+        match xdata.to_value::<Metadata>() {
+            Err(DxfError::UnexpectedXData(_)) => (),
+            _ => panic!("expected an UnexpectedXData error"),
+        }
+        let xdata = XData::from_value("IXMILIA", &u64::MAX);
+        assert!(xdata.is_err());
+    }
+
+    #[test]
+    fn struct_to_x_record_round_trip() {
+        let mut drawing = Drawing::new();
+        drawing.header.version = AcadVersion::R2000;
+        let xr = XRecordObject::from_value(&metadata()).unwrap();
+        drawing
+            .add_named_object("IXMILIA", Object::new(ObjectType::XRecordObject(xr)))
+            .unwrap();
+        let drawing = parse_drawing(&to_test_string(&drawing));
+        let xr = match drawing
+            .named_object_dictionary()
+            .unwrap()
+            .get("IXMILIA")
+            .unwrap()
+            .specific
+        {
+            ObjectType::XRecordObject(ref xr) => xr,
+            _ => panic!("expected an XRECORD"),
+        };
+        assert_eq!(metadata(), xr.to_value::<Metadata>().unwrap());
+    }
+}